@@ -0,0 +1,466 @@
+//! What editors show of a panda program without running it: where it fails
+//! to parse or compile, what its names refer to, and what it declares.
+
+mod resolver;
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashSet, path::Path};
+
+pub use resolver::DefinitionKind;
+
+use self::resolver::{resolve, Definition, Resolution, Target};
+use crate::{
+    compiler::Compiler,
+    lexer::Lexer,
+    module::ModuleLoader,
+    object::builtins::{BUILTINS, BUILTIN_METHODS},
+    parser::Parser,
+    token::{Kind, Span},
+};
+
+// The kinds of values with builtin methods, in the order of their lists.
+const METHOD_RECEIVERS: [&str; 7] = ["INT", "FLOAT", "STR", "CHAR", "ARRAY", "DICT", "ITER"];
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Diagnostic {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Hover {
+    /// The name hovered over.
+    pub span: Span,
+    /// Markdown describing what it refers to.
+    pub contents: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompletionKind {
+    Builtin,
+    Method,
+    Definition(DefinitionKind),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: Option<String>,
+}
+
+/// A function, class, enum or interface, with what it declares inside.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OutlineItem {
+    pub name: String,
+    pub kind: DefinitionKind,
+    pub detail: String,
+    pub span: Span,
+    pub extent: Span,
+    pub children: Vec<Self>,
+}
+
+/// A program as an editor has it, analysed once for each version of its
+/// source. Offsets and spans count `char`s.
+///
+/// ```
+/// use panda::analysis::Document;
+///
+/// let document = Document::new("var x = 1;\nx + 1", None);
+/// assert!(document.diagnostics().is_empty());
+/// assert_eq!(Some(4), document.definition(11).map(|span| span.start));
+/// ```
+pub struct Document {
+    source: String,
+    diagnostics: Vec<Diagnostic>,
+    resolution: Resolution,
+}
+
+impl Document {
+    /// Analyses `source`, resolving its imports relative to `path` when it
+    /// has one.
+    #[must_use]
+    pub fn new(source: &str, path: Option<&Path>) -> Self {
+        Self {
+            source: source.to_string(),
+            diagnostics: diagnostics(source, path),
+            resolution: resolve(source),
+        }
+    }
+
+    /// The errors the parser finds, or else the first one the compiler does.
+    #[must_use]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Where the name at `offset` is declared.
+    #[must_use]
+    pub fn definition(&self, offset: usize) -> Option<Span> {
+        let def = self.definition_at(offset)?;
+        Some(self.resolution.definitions[def].span)
+    }
+
+    /// Everywhere the name at `offset` is used, declarations included.
+    #[must_use]
+    pub fn references(&self, offset: usize) -> Vec<Span> {
+        let Some(reference) = self.reference_at(offset) else {
+            return Vec::new();
+        };
+
+        let references = self.resolution.references.iter().filter(|other| {
+            other.target == reference.target
+                && (reference.target != Target::Builtin || other.name == reference.name)
+        });
+
+        match reference.target {
+            Target::Unresolved => vec![reference.span],
+            _ => references.map(|other| other.span).collect(),
+        }
+    }
+
+    /// How the name at `offset` is declared, with what `type()` tells of it
+    /// where that's known and the `#` comments right above its declaration.
+    #[must_use]
+    pub fn hover(&self, offset: usize) -> Option<Hover> {
+        let reference = self.reference_at(offset)?;
+
+        let contents = match reference.target {
+            Target::Definition(def) => {
+                let definition = &self.resolution.definitions[def];
+
+                let mut sections = vec![format!("```panda\n{}\n```", definition.signature)];
+                sections.extend(self.type_of(definition).map(|kind| format!("`{kind}`")));
+
+                let docs = self.docs(definition);
+                if !docs.is_empty() {
+                    sections.push(docs);
+                }
+
+                sections.join("\n\n")
+            }
+            Target::Builtin => format!("```panda\nfn {}\n```\n\n`BUILTIN`", reference.name),
+            Target::Unresolved => return None,
+        };
+
+        Some(Hover {
+            span: reference.span,
+            contents,
+        })
+    }
+
+    /// What can be written at `offset`: the methods of the value before a
+    /// `.`, the members of the enum or class before a `::`, and otherwise
+    /// the builtins and the names declared so far in scope.
+    #[must_use]
+    pub fn completions(&self, offset: usize) -> Vec<Completion> {
+        let chars = self.source.chars().take(offset).collect::<Vec<_>>();
+
+        // The start of the name being written.
+        let start = chars
+            .iter()
+            .rposition(|&ch| !(ch.is_alphanumeric() || ch == '_'))
+            .map_or(0, |i| i + 1);
+
+        match &chars[..start] {
+            [.., '.'] => self.methods(&chars[..start - 1]),
+            [.., ':', ':'] => self.members(&chars[..start - 2]),
+            _ => self.names(start),
+        }
+    }
+
+    /// The functions, classes, enums and interfaces declared at the top level,
+    /// with their members.
+    #[must_use]
+    pub fn outline(&self) -> Vec<OutlineItem> {
+        self.outline_items(None)
+    }
+
+    fn outline_items(&self, parent: Option<usize>) -> Vec<OutlineItem> {
+        let definitions = self.resolution.definitions.iter().enumerate();
+
+        definitions
+            .filter(|(_, definition)| definition.parent == parent)
+            .filter(|(_, definition)| match definition.kind {
+                DefinitionKind::Function
+                | DefinitionKind::Class
+                | DefinitionKind::Enum
+                | DefinitionKind::Interface => true,
+                DefinitionKind::Method | DefinitionKind::Variant | DefinitionKind::Field => {
+                    parent.is_some()
+                }
+                _ => false,
+            })
+            .map(|(def, definition)| OutlineItem {
+                name: definition.name.clone(),
+                kind: definition.kind,
+                detail: definition.signature.clone(),
+                span: definition.span,
+                extent: definition.extent,
+                children: self.outline_items(Some(def)),
+            })
+            .collect()
+    }
+
+    fn methods(&self, receiver: &[char]) -> Vec<Completion> {
+        let kind = self.receiver_kind(receiver);
+
+        // Instances have the members of their class.
+        let class = kind.as_ref().and_then(|kind| {
+            self.resolution
+                .definitions
+                .iter()
+                .position(|def| def.kind == DefinitionKind::Class && &def.name == kind)
+        });
+
+        if let Some(class) = class {
+            return self.children(class);
+        }
+
+        let receivers = METHOD_RECEIVERS.iter().zip(BUILTIN_METHODS);
+
+        receivers
+            .filter(|(receiver, _)| kind.as_deref().is_none_or(|kind| kind == **receiver))
+            .flat_map(|(receiver, methods)| {
+                methods.iter().map(|(name, _)| Completion {
+                    label: (*name).to_string(),
+                    kind: CompletionKind::Method,
+                    detail: Some((*receiver).to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn members(&self, receiver: &[char]) -> Vec<Completion> {
+        let Some(span) = last_token(receiver).map(|(_, span)| span) else {
+            return Vec::new();
+        };
+
+        self.definition_at(span.start)
+            .map(|def| self.children(def))
+            .unwrap_or_default()
+    }
+
+    fn children(&self, parent: usize) -> Vec<Completion> {
+        let definitions = self.resolution.definitions.iter();
+
+        definitions
+            .filter(|definition| definition.parent == Some(parent))
+            .filter(|definition| definition.kind != DefinitionKind::Parameter)
+            .map(completion)
+            .collect()
+    }
+
+    // What `type()` would tell of the value before a `.`.
+    fn receiver_kind(&self, receiver: &[char]) -> Option<String> {
+        let (kind, span) = last_token(receiver)?;
+
+        let kind = match kind {
+            Kind::IntLiteral => "INT",
+            Kind::FloatLiteral => "FLOAT",
+            Kind::StrLiteral | Kind::FStrLiteral => "STR",
+            Kind::CharLiteral => "CHAR",
+            Kind::RBracket => "ARRAY",
+            Kind::Ident => {
+                let def = self.definition_at(span.start)?;
+                return self.resolution.definitions[def].value_kind.clone();
+            }
+            _ => return None,
+        };
+
+        Some(kind.to_string())
+    }
+
+    fn names(&self, offset: usize) -> Vec<Completion> {
+        let scopes = self.scopes_at(offset);
+        let mut seen = HashSet::new();
+
+        let mut completions = self
+            .resolution
+            .definitions
+            .iter()
+            .rev()
+            .filter(|definition| definition.span.end <= offset)
+            .filter(|definition| scopes.contains(&definition.scope))
+            .filter(|definition| {
+                !matches!(
+                    definition.kind,
+                    DefinitionKind::Method | DefinitionKind::Variant
+                )
+            })
+            .filter(|definition| seen.insert(definition.name.as_str()))
+            .map(completion)
+            .collect::<Vec<_>>();
+
+        completions.extend(
+            BUILTINS
+                .iter()
+                .filter(|(name, _)| !seen.contains(name))
+                .map(|(name, _)| Completion {
+                    label: (*name).to_string(),
+                    kind: CompletionKind::Builtin,
+                    detail: Some("BUILTIN".to_string()),
+                }),
+        );
+
+        completions
+    }
+
+    // The scopes visible at `offset`, innermost first.
+    fn scopes_at(&self, offset: usize) -> Vec<usize> {
+        let scopes = &self.resolution.scopes;
+
+        let innermost = scopes
+            .iter()
+            .rposition(|scope| scope.extent.start <= offset && offset <= scope.extent.end)
+            .unwrap_or(0);
+
+        std::iter::successors(Some(innermost), |&scope| scopes[scope].outer).collect()
+    }
+
+    fn type_of(&self, definition: &Definition) -> Option<String> {
+        let kind = match definition.kind {
+            DefinitionKind::Function | DefinitionKind::Method => "FUNCTION",
+            DefinitionKind::Class => "CLASS",
+            DefinitionKind::Enum => "ENUM",
+            DefinitionKind::Interface => "INTERFACE",
+            // Variants have their enum for a type.
+            DefinitionKind::Variant => {
+                let parent = definition.parent?;
+                return Some(self.resolution.definitions[parent].name.clone());
+            }
+            _ => return definition.value_kind.clone(),
+        };
+
+        Some(kind.to_string())
+    }
+
+    // The comments on the lines right above a declaration.
+    fn docs(&self, definition: &Definition) -> String {
+        let line = self
+            .source
+            .chars()
+            .take(definition.extent.start)
+            .filter(|&ch| ch == '\n')
+            .count();
+
+        let mut comments = self
+            .source
+            .lines()
+            .take(line)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .map(str::trim_start)
+            .take_while(|line| line.starts_with('#'))
+            .map(|line| {
+                let comment = line.trim_start_matches('#');
+                comment.strip_prefix(' ').unwrap_or(comment)
+            })
+            .collect::<Vec<_>>();
+
+        comments.reverse();
+        comments.join("\n")
+    }
+
+    fn reference_at(&self, offset: usize) -> Option<&resolver::Reference> {
+        self.resolution
+            .references
+            .iter()
+            .find(|reference| reference.span.start <= offset && offset <= reference.span.end)
+    }
+
+    fn definition_at(&self, offset: usize) -> Option<usize> {
+        match self.reference_at(offset)?.target {
+            Target::Definition(def) => Some(def),
+            _ => None,
+        }
+    }
+}
+
+fn completion(definition: &Definition) -> Completion {
+    Completion {
+        label: definition.name.clone(),
+        kind: CompletionKind::Definition(definition.kind),
+        detail: Some(definition.signature.clone()),
+    }
+}
+
+fn diagnostics(source: &str, path: Option<&Path>) -> Vec<Diagnostic> {
+    let mut lexer = Lexer::new(source);
+    let mut parser = Parser::new(&mut lexer).with_lines();
+
+    let program = parser.parse_program();
+
+    if !parser.errors.is_empty() {
+        let errors = parser.errors.iter().zip(&parser.error_spans);
+
+        return errors
+            .map(|(message, &span)| Diagnostic {
+                span,
+                message: message.clone(),
+            })
+            .collect();
+    }
+
+    let Some(program) = program else {
+        return Vec::new();
+    };
+
+    let mut compiler = Compiler::new_with_modules(ModuleLoader::from_env());
+
+    if let Some(path) = path {
+        // Imports fail to resolve without a root, which the compiler reports.
+        let _ = compiler.set_root(path);
+    }
+
+    match compiler.compile(program) {
+        Ok(()) => Vec::new(),
+        Err(message) => vec![Diagnostic {
+            span: line_span(source, compiler.line()),
+            message,
+        }],
+    }
+}
+
+// The span of a line counted from 1, without its indentation.
+fn line_span(source: &str, line: usize) -> Span {
+    let (mut start, mut byte_start) = (0, 0);
+
+    for (i, text) in source.split('\n').enumerate() {
+        let len = text.chars().count();
+
+        if i + 1 == line.max(1) {
+            let indent = text.len() - text.trim_start().len();
+            let trimmed = text.trim();
+
+            return Span {
+                start: start + text[..indent].chars().count(),
+                end: start + text[..indent].chars().count() + trimmed.chars().count(),
+                byte_start: byte_start + indent,
+                byte_end: byte_start + indent + trimmed.len(),
+            };
+        }
+
+        start += len + 1;
+        byte_start += text.len() + 1;
+    }
+
+    Span::default()
+}
+
+// The last token of some source, if it has any.
+fn last_token(source: &[char]) -> Option<(Kind, Span)> {
+    let mut lexer = Lexer::new(&String::from_iter(source));
+    let mut last = None;
+
+    loop {
+        let token = lexer.next_token();
+        if token.tok_type == Kind::Eol {
+            return last;
+        }
+
+        last = Some((token.tok_type, token.span));
+    }
+}
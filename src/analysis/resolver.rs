@@ -0,0 +1,813 @@
+use std::collections::HashMap;
+
+use crate::{
+    compiler::symbol_table::{SymbolScope, SymbolTable},
+    lexer::Lexer,
+    module::module_name,
+    object::builtins::BUILTINS,
+    parser::find_interpolation_end,
+    token::{Kind, Span, Token},
+};
+
+/// What a name is declared as.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DefinitionKind {
+    Variable,
+    Constant,
+    Parameter,
+    Function,
+    Class,
+    Field,
+    Method,
+    Enum,
+    Variant,
+    Interface,
+    Module,
+}
+
+/// A name declared in a document.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    /// Where the name is declared.
+    pub span: Span,
+    /// The whole declaration, up to the end of the body of functions,
+    /// classes, enums and interfaces.
+    pub extent: Span,
+    /// How it's declared, _e.g._ `fn add(a, b)`.
+    pub signature: String,
+    /// What `type()` tells of the literal a variable is declared with.
+    pub value_kind: Option<String>,
+    /// The scope it's visible in.
+    pub scope: usize,
+    /// The function, class, enum or interface it belongs to.
+    pub parent: Option<usize>,
+}
+
+/// What a name used in a document refers to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+    Definition(usize),
+    Builtin,
+    Unresolved,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Reference {
+    pub name: String,
+    pub span: Span,
+    pub target: Target,
+}
+
+/// A scope opened by a function or class, with the scope it's nested in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Scope {
+    pub extent: Span,
+    pub outer: Option<usize>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Resolution {
+    pub definitions: Vec<Definition>,
+    pub references: Vec<Reference>,
+    pub scopes: Vec<Scope>,
+}
+
+/// Finds the declarations of a document and what each name in it refers
+/// to. Scopes follow the compiler's, whose symbol tables resolve variables,
+/// so it works on the tokens alone and copes with code that doesn't parse.
+pub fn resolve(source: &str) -> Resolution {
+    let mut lexer = Lexer::new(source);
+    let mut tokens = Vec::new();
+
+    loop {
+        let token = lexer.next_token();
+        if token.tok_type == Kind::Eol {
+            break;
+        }
+
+        tokens.push(token);
+    }
+
+    let mut table = SymbolTable::new();
+    for (i, (name, _)) in BUILTINS.iter().enumerate() {
+        table.define_builtin(name, i);
+    }
+
+    let mut resolver = Resolver {
+        tokens,
+        pos: 0,
+        table,
+        scopes: Vec::new(),
+        resolution: Resolution::default(),
+    };
+
+    let end = source.chars().count();
+    resolver.resolution.scopes.push(Scope {
+        extent: Span {
+            start: 0,
+            end,
+            byte_start: 0,
+            byte_end: source.len(),
+        },
+        outer: None,
+    });
+    resolver.scopes.push(ScopeState::new(0, None));
+
+    resolver.code(None);
+
+    resolver.resolution
+}
+
+struct Resolver {
+    tokens: Vec<Token>,
+    pos: usize,
+    table: SymbolTable,
+    scopes: Vec<ScopeState>,
+    resolution: Resolution,
+}
+
+// The scopes being walked, innermost last.
+struct ScopeState {
+    id: usize,
+    // The definitions of the symbols of the scope's table, by index.
+    symbols: Vec<usize>,
+    // The function whose body the scope is, which its name refers to.
+    function: Option<usize>,
+    // Classes and modules, which aren't symbols.
+    types: HashMap<String, usize>,
+}
+
+impl ScopeState {
+    fn new(id: usize, function: Option<usize>) -> Self {
+        Self {
+            id,
+            symbols: Vec::new(),
+            function,
+            types: HashMap::new(),
+        }
+    }
+}
+
+impl Resolver {
+    // Walks code up to the `}` closing the block it's in.
+    fn code(&mut self, parent: Option<usize>) {
+        while let Some(token) = self.next() {
+            match token.tok_type {
+                Kind::RBrace => return,
+                Kind::LBrace => self.code(parent),
+                Kind::Var | Kind::Const => self.declaration(&token, parent),
+                Kind::Function | Kind::Generator => self.function(&token, parent),
+                Kind::For => self.for_loop(parent),
+                Kind::Class => self.class(parent),
+                Kind::Enum => self.enumeration(parent),
+                Kind::Interface => self.interface(parent),
+                Kind::Import => self.import(parent),
+                Kind::Delete => {
+                    if let Some(ident) = self.eat(Kind::Ident) {
+                        self.reference(&ident);
+                        self.table.delete(&ident.tok_lit);
+                    }
+                }
+                Kind::Ident => self.reference(&token),
+                Kind::FStrLiteral => self.format_string(&token),
+                _ => self.member(&token),
+            }
+        }
+    }
+
+    fn declaration(&mut self, keyword: &Token, parent: Option<usize>) {
+        let Some(ident) = self.eat(Kind::Ident) else {
+            return;
+        };
+
+        let (kind, mutable) = if keyword.tok_type == Kind::Var {
+            (DefinitionKind::Variable, true)
+        } else {
+            (DefinitionKind::Constant, false)
+        };
+
+        let value_kind = if self.peek_is(0, Kind::Assign) {
+            self.peek(1).and_then(|value| self.value_kind(value))
+        } else {
+            None
+        };
+
+        let def = self.define(&ident, kind, parent);
+        let symbol = self.table.define(&ident.tok_lit, mutable);
+        self.bind(symbol.index, def);
+
+        let definition = &mut self.resolution.definitions[def];
+        definition.signature = format!("{} {}", keyword.tok_lit, ident.tok_lit);
+        definition.value_kind = value_kind;
+
+        // Lambdas are named after the variable they're assigned to.
+        let lambda = self.peek_is(1, Kind::Function) || self.peek_is(1, Kind::Generator);
+
+        if self.peek_is(0, Kind::Assign) && lambda && self.peek_is(2, Kind::LParen) {
+            self.pos += 2;
+            self.function_body(Some(def), parent);
+        }
+    }
+
+    fn function(&mut self, keyword: &Token, parent: Option<usize>) {
+        let Some(ident) = self.eat(Kind::Ident) else {
+            // A lambda.
+            self.function_body(None, parent);
+            return;
+        };
+
+        let def = self.define(&ident, DefinitionKind::Function, parent);
+        let symbol = self.table.define(&ident.tok_lit, false);
+        self.bind(symbol.index, def);
+
+        let parameters = self.function_body(Some(def), Some(def));
+        self.finish(def, keyword.span);
+
+        self.resolution.definitions[def].signature = format!(
+            "{} {}({})",
+            keyword.tok_lit,
+            ident.tok_lit,
+            parameters.join(", ")
+        );
+    }
+
+    // Walks the parameters and body of a function named by `function`,
+    // returning the names of the parameters.
+    fn function_body(&mut self, function: Option<usize>, parent: Option<usize>) -> Vec<String> {
+        let parameters = self.parameters();
+        let name = function.map(|def| self.resolution.definitions[def].name.clone());
+
+        self.enter_scope(function);
+        if let Some(name) = name {
+            self.table.define_function_name(&name);
+        }
+
+        for parameter in &parameters {
+            let def = self.define(parameter, DefinitionKind::Parameter, function.or(parent));
+            let symbol = self.table.define(&parameter.tok_lit, false);
+            self.bind(symbol.index, def);
+        }
+
+        if self.eat(Kind::LBrace).is_some() {
+            self.code(function.or(parent));
+        }
+
+        self.leave_scope();
+
+        parameters.into_iter().map(|token| token.tok_lit).collect()
+    }
+
+    // The identifiers between parentheses, if the next token opens them.
+    fn parameters(&mut self) -> Vec<Token> {
+        let mut parameters = Vec::new();
+
+        if self.eat(Kind::LParen).is_none() {
+            return parameters;
+        }
+
+        while let Some(token) = self.next() {
+            match token.tok_type {
+                Kind::RParen => break,
+                Kind::Ident => parameters.push(token),
+                _ => {}
+            }
+        }
+
+        parameters
+    }
+
+    fn for_loop(&mut self, parent: Option<usize>) {
+        if !(self.peek_is(0, Kind::LParen) && self.peek_is(1, Kind::Ident)) {
+            return;
+        }
+
+        self.pos += 1;
+        let ident = self.next().unwrap_or_else(|| unreachable!());
+
+        let def = self.define(&ident, DefinitionKind::Variable, parent);
+        let symbol = self.table.define(&ident.tok_lit, false);
+        self.bind(symbol.index, def);
+
+        self.resolution.definitions[def].signature = format!("for {}", ident.tok_lit);
+    }
+
+    // Fields are visible to the methods of their class, so classes get a
+    // scope of their own for them.
+    fn class(&mut self, parent: Option<usize>) {
+        let Some(ident) = self.eat(Kind::Ident) else {
+            return;
+        };
+
+        let def = self.define(&ident, DefinitionKind::Class, parent);
+        self.define_type(&ident.tok_lit, def);
+
+        let fields = self.parameters();
+
+        if self.peek_is(0, Kind::Ident)
+            && self.peek(0).is_some_and(|tok| tok.tok_lit == "implements")
+        {
+            self.pos += 1;
+
+            while let Some(interface) = self.eat(Kind::Ident) {
+                self.reference(&interface);
+
+                if self.eat(Kind::Comma).is_none() {
+                    break;
+                }
+            }
+        }
+
+        self.resolution.definitions[def].signature = if fields.is_empty() {
+            format!("class {}", ident.tok_lit)
+        } else {
+            let fields = fields.iter().map(|field| field.tok_lit.as_str());
+            format!(
+                "class {}({})",
+                ident.tok_lit,
+                fields.collect::<Vec<_>>().join(", ")
+            )
+        };
+
+        if self.eat(Kind::LBrace).is_none() {
+            return;
+        }
+
+        self.enter_scope(None);
+
+        for field in &fields {
+            self.field(field, def);
+        }
+
+        self.class_body(def);
+        self.leave_scope();
+        self.finish(def, ident.span);
+    }
+
+    fn class_body(&mut self, class: usize) {
+        while let Some(token) = self.next() {
+            let member = self.at_member();
+
+            match token.tok_type {
+                Kind::RBrace => return,
+                Kind::LBrace => self.code(Some(class)),
+                Kind::Static => {}
+                Kind::Var | Kind::Const if member => {
+                    if let Some(ident) = self.eat(Kind::Ident) {
+                        self.define(&ident, DefinitionKind::Field, Some(class));
+                    }
+                }
+                Kind::Function | Kind::Generator if member && self.peek_is(0, Kind::Ident) => {
+                    let ident = self.next().unwrap_or_else(|| unreachable!());
+                    self.method(&ident, class);
+                }
+                Kind::Ident if member && self.peek_is(0, Kind::LParen) => {
+                    self.method(&token, class);
+                }
+                Kind::Ident
+                    if member
+                        && matches!(token.tok_lit.as_str(), "get" | "set")
+                        && self.peek_is(0, Kind::Ident) =>
+                {
+                    let ident = self.next().unwrap_or_else(|| unreachable!());
+                    self.method(&ident, class);
+                }
+                Kind::Ident if member && self.peek_is(0, Kind::Assign) => {
+                    self.field(&token, class);
+                }
+                Kind::Function | Kind::Generator => self.function(&token, Some(class)),
+                Kind::Ident => self.reference(&token),
+                Kind::FStrLiteral => self.format_string(&token),
+                _ => self.member(&token),
+            }
+        }
+    }
+
+    fn field(&mut self, ident: &Token, class: usize) {
+        let def = self.define(ident, DefinitionKind::Field, Some(class));
+        let symbol = self.table.define(&ident.tok_lit, true);
+        self.bind(symbol.index, def);
+    }
+
+    fn method(&mut self, ident: &Token, parent: usize) {
+        let def = self.define(ident, DefinitionKind::Method, Some(parent));
+        let parameters = self.function_body(Some(def), Some(def));
+
+        self.finish(def, ident.span);
+        self.resolution.definitions[def].signature =
+            format!("{}({})", ident.tok_lit, parameters.join(", "));
+    }
+
+    fn enumeration(&mut self, parent: Option<usize>) {
+        let Some(ident) = self.eat(Kind::Ident) else {
+            return;
+        };
+
+        let def = self.define(&ident, DefinitionKind::Enum, parent);
+        let symbol = self.table.define(&ident.tok_lit, false);
+        self.bind(symbol.index, def);
+
+        self.resolution.definitions[def].signature = format!("enum {}", ident.tok_lit);
+
+        if self.eat(Kind::LBrace).is_none() {
+            return;
+        }
+
+        while let Some(token) = self.next() {
+            match token.tok_type {
+                Kind::RBrace => break,
+                Kind::Ident if self.peek_is(0, Kind::LParen) => {
+                    // Variants with fields look like methods up to their body.
+                    let start = self.pos;
+                    let fields = self.parameters();
+
+                    if self.peek_is(0, Kind::LBrace) {
+                        self.pos = start;
+                        self.method(&token, def);
+                    } else {
+                        let variant = self.define(&token, DefinitionKind::Variant, Some(def));
+                        let fields = fields.iter().map(|field| field.tok_lit.as_str());
+
+                        self.resolution.definitions[variant].signature = format!(
+                            "{}({})",
+                            token.tok_lit,
+                            fields.collect::<Vec<_>>().join(", ")
+                        );
+                    }
+                }
+                Kind::Ident if self.peek_is(0, Kind::LBrace) => self.method(&token, def),
+                Kind::Ident => {
+                    let variant = self.define(&token, DefinitionKind::Variant, Some(def));
+                    self.resolution.definitions[variant]
+                        .signature
+                        .clone_from(&token.tok_lit);
+                }
+                _ => {}
+            }
+        }
+
+        self.finish(def, ident.span);
+    }
+
+    fn interface(&mut self, parent: Option<usize>) {
+        let Some(ident) = self.eat(Kind::Ident) else {
+            return;
+        };
+
+        let def = self.define(&ident, DefinitionKind::Interface, parent);
+        let symbol = self.table.define(&ident.tok_lit, false);
+        self.bind(symbol.index, def);
+
+        self.resolution.definitions[def].signature = format!("interface {}", ident.tok_lit);
+
+        if self.eat(Kind::LBrace).is_none() {
+            return;
+        }
+
+        while let Some(token) = self.next() {
+            match token.tok_type {
+                Kind::RBrace => break,
+                Kind::Ident if self.peek_is(0, Kind::LParen) => self.method(&token, def),
+                _ => {}
+            }
+        }
+
+        self.finish(def, ident.span);
+    }
+
+    fn import(&mut self, parent: Option<usize>) {
+        if self.eat(Kind::LBrace).is_some() {
+            while let Some(name) = self.eat(Kind::Ident) {
+                let binding = if self.eat(Kind::As).is_some() {
+                    self.eat(Kind::Ident).unwrap_or(name)
+                } else {
+                    name
+                };
+
+                let def = self.define(&binding, DefinitionKind::Variable, parent);
+                let symbol = self.table.define(&binding.tok_lit, false);
+                self.bind(symbol.index, def);
+
+                self.resolution.definitions[def].signature = format!("import {}", binding.tok_lit);
+
+                if self.eat(Kind::Comma).is_none() {
+                    break;
+                }
+            }
+
+            self.import_source();
+            return;
+        }
+
+        if self.eat(Kind::Asterisk).is_some() {
+            self.import_source();
+            return;
+        }
+
+        self.eat(Kind::Class);
+
+        let Some(path) = self.eat(Kind::StrLiteral) else {
+            return;
+        };
+
+        let (name, span) = if self.eat(Kind::As).is_some() {
+            match self.eat(Kind::Ident) {
+                Some(alias) => (alias.tok_lit, alias.span),
+                None => return,
+            }
+        } else {
+            (module_name(&path.tok_lit), path.span)
+        };
+
+        let module = Token {
+            tok_type: Kind::Ident,
+            tok_lit: name,
+            span,
+        };
+
+        let def = self.define(&module, DefinitionKind::Module, parent);
+        self.define_type(&module.tok_lit, def);
+
+        self.resolution.definitions[def].signature = format!("import \"{}\"", path.tok_lit);
+    }
+
+    // Skips the `} from "path"` after imported names.
+    fn import_source(&mut self) {
+        self.eat(Kind::RBrace);
+
+        if self.peek(0).is_some_and(|tok| tok.tok_lit == "from") {
+            self.pos += 1;
+            self.eat(Kind::StrLiteral);
+        }
+    }
+
+    // The names used in the expressions of a format string.
+    fn format_string(&mut self, token: &Token) {
+        let chars = token.tok_lit.chars().collect::<Vec<_>>();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                ch @ ('{' | '}') if chars.get(i + 1) == Some(&ch) => i += 2,
+                '{' => {
+                    let Some((end, colon)) = find_interpolation_end(&chars, i + 1) else {
+                        return;
+                    };
+
+                    // After the `f"` and the `{`.
+                    let start = token.span.start + 2 + i + 1;
+                    let byte_start = token.span.byte_start
+                        + 2
+                        + chars[..=i].iter().map(|ch| ch.len_utf8()).sum::<usize>();
+
+                    let source = String::from_iter(&chars[i + 1..colon.unwrap_or(end)]);
+                    let mut lexer = Lexer::new(&source);
+                    let mut previous = Kind::Illegal;
+
+                    loop {
+                        let mut token = lexer.next_token();
+                        if token.tok_type == Kind::Eol {
+                            break;
+                        }
+
+                        token.span.start += start;
+                        token.span.end += start;
+                        token.span.byte_start += byte_start;
+                        token.span.byte_end += byte_start;
+
+                        if token.tok_type == Kind::Ident && !is_member_access(previous) {
+                            self.reference(&token);
+                        }
+
+                        previous = token.tok_type;
+                    }
+
+                    i = end + 1;
+                }
+                _ => i += 1,
+            }
+        }
+    }
+
+    // Skips the name of a member after `.`, `?.` or `::`.
+    fn member(&mut self, token: &Token) {
+        if is_member_access(token.tok_type) {
+            self.eat(Kind::Ident);
+        }
+    }
+
+    fn reference(&mut self, token: &Token) {
+        let target = match self.table.resolve(&token.tok_lit) {
+            Some(symbol) if symbol.scope == SymbolScope::Builtin => Target::Builtin,
+            Some(symbol) => self
+                .definition(symbol.scope, symbol.index)
+                .map_or(Target::Unresolved, Target::Definition),
+            None => self
+                .scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.types.get(&token.tok_lit))
+                .map_or(Target::Unresolved, |&def| Target::Definition(def)),
+        };
+
+        self.resolution.references.push(Reference {
+            name: token.tok_lit.clone(),
+            span: token.span,
+            target,
+        });
+    }
+
+    // Follows a symbol of the current scope to its definition, through the
+    // free symbols of the scopes it was captured by.
+    fn definition(&self, scope: SymbolScope, index: usize) -> Option<usize> {
+        let mut table = &self.table;
+        let mut level = self.scopes.len() - 1;
+        let (mut scope, mut index) = (scope, index);
+
+        loop {
+            match scope {
+                SymbolScope::Global => return self.scopes[0].symbols.get(index).copied(),
+                SymbolScope::Local => return self.scopes[level].symbols.get(index).copied(),
+                SymbolScope::Function => return self.scopes[level].function,
+                SymbolScope::Builtin => return None,
+                SymbolScope::Free => {
+                    let original = table.free_symbols.get(index)?;
+                    (scope, index) = (original.scope, original.index);
+
+                    table = table.outer.as_deref()?;
+                    level = level.checked_sub(1)?;
+                }
+            }
+        }
+    }
+
+    fn define(&mut self, ident: &Token, kind: DefinitionKind, parent: Option<usize>) -> usize {
+        let scope = self.scopes.last().map_or(0, |scope| scope.id);
+
+        self.resolution.definitions.push(Definition {
+            name: ident.tok_lit.clone(),
+            kind,
+            span: ident.span,
+            extent: ident.span,
+            signature: ident.tok_lit.clone(),
+            value_kind: None,
+            scope,
+            parent,
+        });
+
+        let def = self.resolution.definitions.len() - 1;
+
+        self.resolution.references.push(Reference {
+            name: ident.tok_lit.clone(),
+            span: ident.span,
+            target: Target::Definition(def),
+        });
+
+        def
+    }
+
+    fn bind(&mut self, index: usize, def: usize) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+
+        if scope.symbols.len() <= index {
+            scope.symbols.resize(index + 1, def);
+        }
+
+        scope.symbols[index] = def;
+    }
+
+    fn define_type(&mut self, name: &str, def: usize) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.types.insert(name.to_string(), def);
+        }
+    }
+
+    // Extends a declaration from `start` to the last token walked.
+    fn finish(&mut self, def: usize, start: Span) {
+        let end = self.last_span().unwrap_or(start);
+
+        self.resolution.definitions[def].extent = Span {
+            start: start.start,
+            end: end.end,
+            byte_start: start.byte_start,
+            byte_end: end.byte_end,
+        };
+    }
+
+    fn enter_scope(&mut self, function: Option<usize>) {
+        let outer = std::mem::replace(&mut self.table, SymbolTable::new());
+        self.table = SymbolTable::new_enclosed(outer);
+
+        let start = self
+            .peek(0)
+            .or_else(|| self.tokens.last())
+            .map(|tok| tok.span);
+
+        self.resolution.scopes.push(Scope {
+            extent: start.unwrap_or_default(),
+            outer: self.scopes.last().map(|scope| scope.id),
+        });
+
+        let id = self.resolution.scopes.len() - 1;
+        self.scopes.push(ScopeState::new(id, function));
+    }
+
+    fn leave_scope(&mut self) {
+        if let Some(outer) = self.table.outer.take() {
+            self.table = *outer;
+        }
+
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+
+        if let Some(end) = self.last_span() {
+            let extent = &mut self.resolution.scopes[scope.id].extent;
+            extent.end = end.end;
+            extent.byte_end = end.byte_end;
+        }
+    }
+
+    // Whether the token just walked starts a member of a class body, rather
+    // than continuing the value of a field.
+    fn at_member(&self) -> bool {
+        let previous = self
+            .pos
+            .checked_sub(2)
+            .and_then(|pos| self.tokens.get(pos))
+            .map(|tok| tok.tok_type);
+
+        matches!(
+            previous,
+            None | Some(
+                Kind::LBrace
+                    | Kind::RBrace
+                    | Kind::Semicolon
+                    | Kind::Static
+                    | Kind::Ident
+                    | Kind::IntLiteral
+                    | Kind::FloatLiteral
+                    | Kind::StrLiteral
+                    | Kind::FStrLiteral
+                    | Kind::CharLiteral
+                    | Kind::True
+                    | Kind::False
+                    | Kind::Nil
+                    | Kind::RParen
+                    | Kind::RBracket
+            )
+        )
+    }
+
+    fn value_kind(&self, value: &Token) -> Option<String> {
+        let kind = match value.tok_type {
+            Kind::IntLiteral => "INT",
+            Kind::FloatLiteral => "FLOAT",
+            Kind::StrLiteral | Kind::FStrLiteral => "STR",
+            Kind::CharLiteral => "CHAR",
+            Kind::True | Kind::False => "BOOLEAN",
+            Kind::LBracket => "ARRAY",
+            Kind::LBrace => "DICT",
+            Kind::Function | Kind::Generator => "FUNCTION",
+            // Instances have their class for a type.
+            Kind::New => return self.peek(2).map(|class| class.tok_lit.clone()),
+            _ => return None,
+        };
+
+        Some(kind.to_string())
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, kind: Kind) -> Option<Token> {
+        if self.peek_is(0, kind) {
+            self.next()
+        } else {
+            None
+        }
+    }
+
+    fn peek(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn peek_is(&self, n: usize, kind: Kind) -> bool {
+        self.peek(n).is_some_and(|tok| tok.tok_type == kind)
+    }
+
+    fn last_span(&self) -> Option<Span> {
+        let last = self.pos.min(self.tokens.len()).checked_sub(1)?;
+        self.tokens.get(last).map(|tok| tok.span)
+    }
+}
+
+const fn is_member_access(kind: Kind) -> bool {
+    matches!(kind, Kind::Dot | Kind::OptionalDot | Kind::Scope)
+}
@@ -0,0 +1,239 @@
+use super::*;
+
+// The offset of the `n`th occurrence of `needle` in `source`, in `char`s.
+fn offset(source: &str, needle: &str, n: usize) -> usize {
+    let (byte, _) = source.match_indices(needle).nth(n).unwrap();
+    source[..byte].chars().count()
+}
+
+fn text(source: &str, span: Span) -> &str {
+    &source[span.byte_start..span.byte_end]
+}
+
+fn labels(completions: &[Completion]) -> Vec<&str> {
+    completions
+        .iter()
+        .map(|completion| completion.label.as_str())
+        .collect()
+}
+
+#[test]
+fn test_diagnostics() {
+    let source = "var x = 1;\nvar = 2;";
+    let document = Document::new(source, None);
+
+    let diagnostics = document
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| (diagnostic.message.as_str(), text(source, diagnostic.span)))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec![(
+            "expected next token to be IDENT, got ASSIGN instead.",
+            "var ="
+        ),],
+        diagnostics
+    );
+
+    let source = "var x = 1;\nfn f() {\n  y + x\n}";
+    let document = Document::new(source, None);
+
+    let diagnostics = document.diagnostics();
+    assert_eq!(1, diagnostics.len());
+    assert_eq!("undefined variable y", diagnostics[0].message);
+    assert_eq!("y + x", text(source, diagnostics[0].span));
+
+    assert!(Document::new("# A comment.\nvar x = 1;", None)
+        .diagnostics()
+        .is_empty());
+}
+
+#[test]
+fn test_definitions_and_references() {
+    let source = "var x = 1;
+fn add(a, b) {
+    fn inner() { a + x }
+    inner() + b
+}
+class Point(x, y) {
+    norm() { x * x + y * y }
+}
+var p = new Point(1, 2);
+println(add(x, f\"{x}\"));
+println(1);";
+    let document = Document::new(source, None);
+
+    let definition = |needle, n| document.definition(offset(source, needle, n));
+    let references = |needle, n| {
+        document
+            .references(offset(source, needle, n))
+            .into_iter()
+            .map(|span| span.start)
+            .collect::<Vec<_>>()
+    };
+
+    // Globals, captured through a closure.
+    let x = offset(source, "x", 0);
+    assert_eq!(Some(x), definition("x }", 0).map(|span| span.start));
+    assert_eq!(
+        vec![
+            x,
+            offset(source, "x }", 0),
+            offset(source, "x, f", 0),
+            offset(source, "x}", 0)
+        ],
+        references("x", 0)
+    );
+
+    // Free variables and the functions they're declared in.
+    assert_eq!(
+        Some(offset(source, "a,", 0)),
+        definition("a + x", 0).map(|span| span.start)
+    );
+    assert_eq!(
+        Some(offset(source, "inner", 0)),
+        definition("inner()", 1).map(|span| span.start)
+    );
+
+    // Fields are visible to the methods of their class, and classes where
+    // they're constructed.
+    assert_eq!(
+        Some(offset(source, "x, y)", 0)),
+        definition("x * x", 0).map(|span| span.start)
+    );
+    assert_eq!(
+        Some(offset(source, "Point", 0)),
+        definition("Point", 1).map(|span| span.start)
+    );
+
+    // Builtins are found wherever they're used.
+    assert_eq!(None, definition("println", 0));
+    assert_eq!(
+        vec![offset(source, "println", 0), offset(source, "println", 1)],
+        references("println", 0)
+    );
+}
+
+#[test]
+fn test_hover() {
+    let source = "# Adds two numbers.
+# Both of them.
+fn add(a, b) { a + b }
+var name = \"panda\";
+enum Shape { Circle(r), Square }
+var p = new Point();
+add(1, 2);
+str(name);
+Shape::Circle(1);";
+    let document = Document::new(source, None);
+
+    let hover = |needle, n| {
+        document
+            .hover(offset(source, needle, n))
+            .map(|hover| hover.contents)
+    };
+
+    assert_eq!(
+        Some(
+            "```panda\nfn add(a, b)\n```\n\n`FUNCTION`\n\nAdds two numbers.\nBoth of them."
+                .to_string()
+        ),
+        hover("add", 1)
+    );
+    assert_eq!(
+        Some("```panda\nvar name\n```\n\n`STR`".to_string()),
+        hover("name", 1)
+    );
+    assert_eq!(
+        Some("```panda\nvar p\n```\n\n`Point`".to_string()),
+        hover("p ", 0)
+    );
+    assert_eq!(
+        Some("```panda\nfn str\n```\n\n`BUILTIN`".to_string()),
+        hover("str", 0)
+    );
+    assert_eq!(
+        Some("```panda\nenum Shape\n```\n\n`ENUM`".to_string()),
+        hover("Shape", 1)
+    );
+    assert_eq!(None, hover("Circle", 1));
+}
+
+#[test]
+fn test_completions() {
+    let source = "var s = \"panda\";
+var xs = [1, 2];
+fn f(a) {
+    var inner = 1;
+    s.
+}
+enum Shape { Circle(r), Square, area() { 0 } }
+xs.l
+Shape::
+var y = ";
+    let document = Document::new(source, None);
+
+    let methods = document.completions(offset(source, "s.", 0) + 2);
+    assert!(labels(&methods).contains(&"split"), "{methods:?}");
+    assert!(methods
+        .iter()
+        .all(|method| method.detail.as_deref() == Some("STR")));
+
+    let methods = document.completions(offset(source, "xs.l", 0) + 4);
+    assert!(labels(&methods).contains(&"len"), "{methods:?}");
+    assert!(methods
+        .iter()
+        .all(|method| method.detail.as_deref() == Some("ARRAY")));
+
+    assert_eq!(
+        vec!["Circle", "Square", "area"],
+        labels(&document.completions(offset(source, "Shape::", 0) + 7))
+    );
+
+    let names = document.completions(offset(source, "    s.", 0));
+    let names = labels(&names);
+    assert_eq!(vec!["inner", "a", "f", "xs", "s"], names[..5], "{names:?}");
+    assert!(names.contains(&"println"));
+
+    let names = document.completions(source.chars().count());
+    assert!(labels(&names).contains(&"Shape"));
+    assert!(!labels(&names).contains(&"inner"));
+}
+
+fn outline_names(items: &[OutlineItem]) -> Vec<(&str, DefinitionKind, Vec<&str>)> {
+    items
+        .iter()
+        .map(|item| {
+            let children = item.children.iter().map(|child| child.name.as_str());
+            (item.name.as_str(), item.kind, children.collect())
+        })
+        .collect()
+}
+
+#[test]
+fn test_outline() {
+    let source = "fn add(a, b) { a + b }
+class Point(x, y) {
+    norm() { x * x + y * y }
+}
+enum Shape { Circle(r), Square }
+interface Draw { fn draw(); }
+var x = fn(a) { a };";
+    let document = Document::new(source, None);
+
+    assert_eq!(
+        vec![
+            ("add", DefinitionKind::Function, vec![]),
+            ("Point", DefinitionKind::Class, vec!["x", "y", "norm"]),
+            ("Shape", DefinitionKind::Enum, vec!["Circle", "Square"]),
+            ("Draw", DefinitionKind::Interface, vec!["draw"]),
+        ],
+        outline_names(&document.outline())
+    );
+
+    let point = &document.outline()[1];
+    assert_eq!("class Point(x, y)", point.detail);
+    assert!(text(source, point.extent).starts_with("Point(x, y) {"));
+    assert!(text(source, point.extent).ends_with('}'));
+}
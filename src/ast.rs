@@ -1,4 +1,7 @@
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    iter,
+};
 
 use crate::token::Kind;
 
@@ -42,6 +45,11 @@ pub struct Return {
     pub return_value: Expression,
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct Yield {
+    pub value: Expression,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Delete {
     pub delete_ident: Ident,
@@ -58,6 +66,7 @@ pub struct Function {
     pub ident: Ident,
     pub parameters: Vec<Ident>,
     pub body: BlockStatement,
+    pub generator: bool,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -77,7 +86,160 @@ pub struct For {
 pub struct ClassDecl {
     pub ident: Ident,
     pub initializers: Vec<Ident>,
+    pub interfaces: Vec<Ident>,
     pub body: Vec<ClassStatement>,
+    pub statics: Vec<Statement>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct InterfaceDecl {
+    pub ident: Ident,
+    pub methods: Vec<InterfaceMethod>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct InterfaceMethod {
+    pub name: Ident,
+    pub parameters: Vec<Ident>,
+    pub body: Option<BlockStatement>,
+}
+
+impl ClassDecl {
+    /// The `static` declarations and functions of the class, renamed to the
+    /// `Class::name` variables they're stored in.
+    pub fn static_members(&self) -> Vec<Statement> {
+        let qualify = |name: &str| format!("{}::{name}", self.ident);
+
+        self.statics
+            .iter()
+            .cloned()
+            .map(|stmt| match stmt {
+                Statement::Declaration(decl) => Statement::Declaration(Declaration {
+                    name: qualify(&decl.name),
+                    ..decl
+                }),
+                Statement::Function(func) => Statement::Function(Function {
+                    ident: qualify(&func.ident),
+                    ..func
+                }),
+                stmt => stmt,
+            })
+            .collect()
+    }
+
+    /// Adds the default methods of `interface` the class doesn't define, failing
+    /// when it's missing a method without one.
+    pub fn implement(&mut self, interface: &InterfaceDecl) -> Result<(), String> {
+        let defaults = interface.check(self)?;
+        self.body
+            .extend(defaults.into_iter().map(ClassStatement::Method));
+
+        Ok(())
+    }
+}
+
+impl InterfaceDecl {
+    /// Checks that `class` defines every method of the interface that has no
+    /// default, returning the defaults `class` doesn't define itself.
+    pub fn check(&self, class: &ClassDecl) -> Result<Vec<ClassMethod>, String> {
+        let mut defaults = Vec::new();
+
+        for method in &self.methods {
+            let defined = class.body.iter().find_map(|stmt| match stmt {
+                ClassStatement::Method(defined) if defined.name == method.name => Some(defined),
+                _ => None,
+            });
+
+            match (defined, &method.body) {
+                (Some(defined), _) if defined.parameters.len() != method.parameters.len() => {
+                    return Err(format!(
+                        "method \"{}\" of class \"{}\" takes {} parameters, but interface \"{}\" expects {}",
+                        method.name,
+                        class.ident,
+                        defined.parameters.len(),
+                        self.ident,
+                        method.parameters.len()
+                    ));
+                }
+                (Some(_), _) => {}
+                (None, Some(body)) => defaults.push(ClassMethod {
+                    name: method.name.clone(),
+                    parameters: method.parameters.clone(),
+                    body: body.clone(),
+                }),
+                (None, None) => {
+                    return Err(format!(
+                        "class \"{}\" does not implement method \"{}\" of interface \"{}\"",
+                        class.ident, method.name, self.ident
+                    ));
+                }
+            }
+        }
+
+        Ok(defaults)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct EnumDecl {
+    pub ident: Ident,
+    pub variants: Vec<EnumVariant>,
+    pub methods: Vec<ClassMethod>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EnumVariant {
+    pub name: Ident,
+    pub fields: Vec<Ident>,
+}
+
+impl Display for EnumDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "enum {} {{ {}",
+            self.ident,
+            self.variants
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+
+        for ClassMethod {
+            name, parameters, ..
+        } in &self.methods
+        {
+            write!(f, " {name}({}) {{}}", parameters.join(", "))?;
+        }
+
+        write!(f, " }}")
+    }
+}
+
+impl Display for InterfaceDecl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "interface {} {{ ", self.ident)?;
+
+        for InterfaceMethod {
+            name, parameters, ..
+        } in &self.methods
+        {
+            write!(f, "fn {name}({}); ", parameters.join(", "))?;
+        }
+
+        write!(f, "}}")
+    }
+}
+
+impl Display for EnumVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.fields.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{}({})", self.name, self.fields.join(", "))
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -85,6 +247,62 @@ pub struct Import {
     pub path: String,
     pub class: bool,
     pub alias: Option<Ident>,
+    pub items: Option<ImportItems>,
+}
+
+/// The names `import { .. } from` and `import * from` bind in the importer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ImportItems {
+    All,
+    Names(Vec<ImportName>),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ImportName {
+    pub name: Ident,
+    pub alias: Option<Ident>,
+}
+
+impl Display for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = &self.path;
+
+        match &self.items {
+            Some(ImportItems::All) => write!(f, "import * from \"{path}\""),
+            Some(ImportItems::Names(names)) => write!(
+                f,
+                "import {{ {} }} from \"{path}\"",
+                names
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            None => write!(
+                f,
+                "import{} \"{path}\"{}",
+                if self.class { " class" } else { "" },
+                self.alias
+                    .clone()
+                    .map_or_else(String::new, |alias| format!(" as {alias}"))
+            ),
+        }
+    }
+}
+
+impl ImportName {
+    pub fn binding(&self) -> &Ident {
+        self.alias.as_ref().unwrap_or(&self.name)
+    }
+}
+
+impl Display for ImportName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.alias {
+            Some(alias) => write!(f, "{} as {alias}", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -104,6 +322,8 @@ pub struct ClassMethod {
 pub enum ClassStatement {
     Variable(ClassVariable),
     Method(ClassMethod),
+    Getter(ClassMethod),
+    Setter(ClassMethod),
 }
 
 impl ToString for ClassStatement {
@@ -116,15 +336,40 @@ impl ToString for ClassStatement {
 pub enum Statement {
     Declaration(Declaration),
     Return(Return),
+    Yield(Yield),
     Delete(Delete),
     ExpressionStmt(ExpressionStmt),
     Function(Function),
     While(While),
     For(For),
     ClassDecl(ClassDecl),
+    EnumDecl(EnumDecl),
+    InterfaceDecl(InterfaceDecl),
     Import(Import),
+    Export(Box<Self>),
     Break,
     Continue,
+    /// Where the next statement starts, which the parser only records for
+    /// debuggers.
+    Line(usize),
+}
+
+impl Statement {
+    /// The names an exported statement adds to the interface of its module,
+    /// including the static members of a class.
+    pub fn declared_names(&self) -> Vec<Ident> {
+        match self {
+            Self::Declaration(Declaration { name, .. }) => Vec::from([name.clone()]),
+            Self::Function(Function { ident, .. })
+            | Self::EnumDecl(EnumDecl { ident, .. })
+            | Self::InterfaceDecl(InterfaceDecl { ident, .. }) => Vec::from([ident.clone()]),
+            Self::ClassDecl(decl) => iter::once(decl.ident.clone())
+                .chain(decl.static_members().iter().flat_map(Self::declared_names))
+                .collect(),
+            Self::Export(stmt) => stmt.declared_names(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl Display for Statement {
@@ -181,24 +426,23 @@ impl Display for Statement {
                 ident,
                 parameters,
                 body,
-                ..
+                generator,
             }) => write!(
                 f,
-                "fn {}({}) {}",
+                "fn{} {}({}) {}",
+                if *generator { "*" } else { "" },
                 ident,
                 parameters.join(", "),
                 body.iter().map(ToString::to_string).collect::<String>()
             ),
 
-            Self::Import(Import { path, alias, class }) => write!(
-                f,
-                "import{} \"{}\"{}",
-                if *class { " class" } else { "" },
-                path,
-                alias
-                    .clone()
-                    .map_or_else(String::new, |alias| format!(" as {alias}"))
-            ),
+            Self::EnumDecl(decl) => write!(f, "{decl}"),
+
+            Self::InterfaceDecl(decl) => write!(f, "{decl}"),
+
+            Self::Import(import) => write!(f, "{import}"),
+
+            Self::Export(stmt) => write!(f, "export {stmt}"),
 
             Self::Return(Return { return_value }) => write!(
                 f,
@@ -210,6 +454,16 @@ impl Display for Statement {
                 }
             ),
 
+            Self::Yield(Yield { value }) => write!(
+                f,
+                "yield {};",
+                if matches!(value, Expression::Literal(Literal { lit: Lit::Nil })) {
+                    String::new()
+                } else {
+                    value.to_string()
+                }
+            ),
+
             Self::While(While { condition, body }) => write!(
                 f,
                 "while ({}) {}",
@@ -221,6 +475,8 @@ impl Display for Statement {
 
             Self::Continue => write!(f, "continue"),
 
+            Self::Line(_) => Ok(()),
+
             Self::Delete(Delete { delete_ident }) => write!(f, "delete {delete_ident};"),
         }
     }
@@ -231,6 +487,26 @@ pub struct Method {
     pub left: Box<Expression>,
     pub name: Ident,
     pub arguments: Option<Vec<Expression>>,
+    pub optional: bool,
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dot = if self.optional { "?." } else { "." };
+
+        write!(f, "{}{dot}{}", self.left, self.name)?;
+
+        if let Some(arguments) = &self.arguments {
+            let arguments = arguments
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            write!(f, "({})", arguments.join(", "))?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -253,9 +529,19 @@ pub struct Identifier {
 #[derive(Clone, PartialEq, Debug)]
 pub struct Assign {
     pub to: Assignable,
+    pub operator: Option<Operator>,
     pub value: Box<Expression>,
 }
 
+impl Display for Assign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.operator {
+            Some(operator) => write!(f, "{} {operator}= {};", self.to, self.value),
+            None => write!(f, "{} = {};", self.to, self.value),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct Prefix {
     pub operator: Operator,
@@ -281,6 +567,7 @@ pub struct Lambda {
     pub parameters: Vec<Ident>,
     pub body: BlockStatement,
     pub name: Ident,
+    pub generator: bool,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -289,10 +576,12 @@ pub struct Call {
     pub arguments: Vec<Expression>,
 }
 
+#[allow(clippy::struct_field_names)]
 #[derive(Clone, PartialEq, Debug)]
 pub struct Index {
     pub left: Box<Expression>,
     pub index: Box<Expression>,
+    pub optional: bool,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -306,6 +595,41 @@ pub struct Scope {
     pub member: Box<Expression>,
 }
 
+impl Scope {
+    /// Splits a member like `b` or `b(1, 2)` into its name and call arguments.
+    pub fn split_member(member: Expression) -> Result<(Ident, Option<Vec<Expression>>), String> {
+        match member {
+            Expression::Identifier(Identifier { value }) => Ok((value, None)),
+            Expression::Call(Call {
+                function,
+                arguments,
+                ..
+            }) => {
+                let Expression::Identifier(Identifier { value }) = *function else {
+                    return Err("expected Identifier in scope expression".to_string());
+                };
+
+                Ok((value, Some(arguments)))
+            }
+            _ => Err("invalid scope expression".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum InterpolationPart {
+    Str(String),
+    Expr {
+        value: Expression,
+        spec: Option<String>,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Interpolation {
+    pub parts: Vec<InterpolationPart>,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum Expression {
     Method(Method),
@@ -321,12 +645,13 @@ pub enum Expression {
     Index(Index),
     Literal(Literal),
     Scope(Scope),
+    Interpolation(Interpolation),
 }
 
 impl Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Assign(Assign { to, value }) => write!(f, "{to} = {value};"),
+            Self::Assign(assign) => write!(f, "{assign}"),
 
             Self::Call(Call {
                 function,
@@ -350,15 +675,16 @@ impl Display for Expression {
                 parameters,
                 body,
                 name,
-                ..
+                generator,
             }) => write!(
                 f,
-                "{}fn({}) {}",
+                "{}fn{}({}) {}",
                 if name.is_empty() {
                     String::new()
                 } else {
                     format!("<{name}>")
                 },
+                if *generator { "*" } else { "" },
                 parameters.join(", "),
                 body.iter().map(ToString::to_string).collect::<String>()
             ),
@@ -384,7 +710,11 @@ impl Display for Expression {
                 ))
             ),
 
-            Self::Index(Index { left, index }) => write!(f, "({left}[{index}])"),
+            Self::Index(Index {
+                left,
+                index,
+                optional,
+            }) => write!(f, "({left}{}[{index}])", if *optional { "?" } else { "" }),
 
             Self::Infix(Infix {
                 left,
@@ -395,27 +725,7 @@ impl Display for Expression {
 
             Self::Literal(Literal { lit }) => write!(f, "{lit}"),
 
-            Self::Method(Method {
-                left,
-                name: method,
-                arguments,
-                ..
-            }) => write!(
-                f,
-                "{}.{}{}",
-                left,
-                method,
-                arguments
-                    .as_ref()
-                    .map_or_else(String::new, |arguments| format!(
-                        "({})",
-                        arguments
-                            .iter()
-                            .map(ToString::to_string)
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    ))
-            ),
+            Self::Method(method) => write!(f, "{method}"),
 
             Self::Prefix(Prefix { operator, right }) => {
                 write!(f, "({operator}{right})")
@@ -429,6 +739,24 @@ impl Display for Expression {
             ),
 
             Self::Scope(Scope { module, member }) => write!(f, "{module}::{member}"),
+
+            Self::Interpolation(Interpolation { parts }) => write!(
+                f,
+                "f\"{}\"",
+                parts.iter().map(ToString::to_string).collect::<String>()
+            ),
+        }
+    }
+}
+
+impl Display for InterpolationPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Str(value) => write!(f, "{}", value.replace('{', "{{").replace('}', "}}")),
+            Self::Expr { value, spec } => match spec {
+                Some(spec) => write!(f, "{{{value}:{spec}}}"),
+                None => write!(f, "{{{value}}}"),
+            },
         }
     }
 }
@@ -518,6 +846,21 @@ pub enum Operator {
     Lt,
     GtEq,
     LtEq,
+    NilCoalesce,
+    Pow,
+    FloorDiv,
+    BitNot,
+    In,
+    NotIn,
+}
+
+impl Operator {
+    pub const fn is_bitwise(self) -> bool {
+        matches!(
+            self,
+            Self::BitXor | Self::BitAnd | Self::BitOr | Self::Shr | Self::Shl | Self::BitNot
+        )
+    }
 }
 
 impl TryFrom<Kind> for Operator {
@@ -544,6 +887,11 @@ impl TryFrom<Kind> for Operator {
             Kind::Lt => Ok(Self::Lt),
             Kind::GtEq => Ok(Self::GtEq),
             Kind::LtEq => Ok(Self::LtEq),
+            Kind::NilCoalesce => Ok(Self::NilCoalesce),
+            Kind::Power => Ok(Self::Pow),
+            Kind::FloorDiv => Ok(Self::FloorDiv),
+            Kind::Tilde => Ok(Self::BitNot),
+            Kind::In => Ok(Self::In),
             _ => Err(()),
         }
     }
@@ -571,6 +919,12 @@ impl Display for Operator {
             Self::Lt => "<",
             Self::GtEq => ">=",
             Self::LtEq => "<=",
+            Self::NilCoalesce => "??",
+            Self::Pow => "**",
+            Self::FloorDiv => "//",
+            Self::BitNot => "~",
+            Self::In => "in",
+            Self::NotIn => "not in",
         };
         write!(f, "{out}")
     }
@@ -612,36 +966,19 @@ pub enum Assignable {
     Identifier(Identifier),
     Method(Method),
     Index(Index),
+    Scope(Scope),
 }
 
 impl Display for Assignable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Method(Method {
-                left,
-                name: method,
-                arguments,
-                ..
-            }) => write!(
-                f,
-                "{}.{}{}",
-                left,
-                method,
-                arguments
-                    .as_ref()
-                    .map_or_else(String::new, |arguments| format!(
-                        "({})",
-                        arguments
-                            .iter()
-                            .map(ToString::to_string)
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    ))
-            ),
+            Self::Method(method) => write!(f, "{method}"),
 
-            Self::Index(Index { left, index }) => write!(f, "{left}[{index}]"),
+            Self::Index(Index { left, index, .. }) => write!(f, "{left}[{index}]"),
 
             Self::Identifier(Identifier { value }) => write!(f, "{value}"),
+
+            Self::Scope(Scope { module, member }) => write!(f, "{module}::{member}"),
         }
     }
 }
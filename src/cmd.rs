@@ -1,15 +1,7 @@
 use std::path::PathBuf;
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
-
-#[derive(
-    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, strum::EnumString, strum::Display,
-)]
-#[strum(serialize_all = "lowercase")]
-pub enum Engine {
-    Eval,
-    VM,
-}
+use panda::Engine;
 
 #[derive(Parser)]
 #[command(author, about, long_about = None)]
@@ -28,6 +20,15 @@ pub enum Commands {
 
     /// Outputs debug outputs for testing
     Debug(DebugArgs),
+
+    /// Run the provided file in the interactive debugger
+    Dbg(DbgArgs),
+
+    /// Serve the Debug Adapter Protocol over stdio, for editors to debug with
+    Dap,
+
+    /// Serve the Language Server Protocol over stdio, for editors to check and navigate code with
+    Lsp,
 }
 
 #[derive(Args)]
@@ -40,6 +41,20 @@ pub struct RunArgs {
     pub engine: Engine,
 }
 
+#[derive(Args)]
+pub struct DbgArgs {
+    /// The input file path
+    pub file_name: String,
+
+    /// The engine to use as the interpreter
+    #[arg(long, short, default_value_t = Engine::VM)]
+    pub engine: Engine,
+
+    /// Lines to pause at. Without any, the debugger pauses at the first statement
+    #[arg(long = "break", short)]
+    pub breakpoints: Vec<usize>,
+}
+
 #[derive(Args)]
 pub struct ReplArgs {
     /// The engine to use as the interpreter
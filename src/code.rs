@@ -14,6 +14,7 @@ pub enum Opcode {
     Pop,
     PopNoRet,
     Dup,
+    DupTwo,
 
     // Infix binary operators
     Add,
@@ -21,6 +22,8 @@ pub enum Opcode {
     Mul,
     Div,
     Mod,
+    Pow,
+    FloorDiv,
 
     // Infix bitwise operators
     BitXor,
@@ -39,18 +42,25 @@ pub enum Opcode {
     NotEqual,
     GreaterThan,
     GreaterThanEqual,
+    LessThan,
+    LessThanEqual,
 
     // Prefix operators
     Minus,
     Bang,
+    BitNot,
 
     // Infix boolean operators
     And,
     Or,
 
+    // Membership operator
+    In,
+
     // Conditional Jumps
     Jump,
     JumpNotTruthy,
+    JumpNil,
 
     // Bindings to names
     GetGlobal,
@@ -60,6 +70,7 @@ pub enum Opcode {
     Array,
     Dict,
     Index,
+    SetIndex,
     Range,
 
     // Function Opcodes
@@ -71,21 +82,32 @@ pub enum Opcode {
     GetBuiltin,
     Closure,
     GetFree,
+    SetFree,
     CurrentClosure,
     Method,
 
     Scope,
     Constructor,
-    ClassMember,
+    SetMember,
     Delete,
+    Enum,
+    Variant,
 
     // Iterator
     Next,
     Start,
     JumpEnd,
+    Yield,
+
+    // Format String
+    Concat,
+    Format,
 
     // Method Name
     String,
+
+    // Debugging
+    Line,
 }
 
 #[allow(dead_code)]
@@ -112,6 +134,10 @@ const DEFINITIONS: &[Definition] = &[
         name: "Dup",
         operand_widths: &[],
     },
+    Definition {
+        name: "DupTwo",
+        operand_widths: &[],
+    },
     Definition {
         name: "Add",
         operand_widths: &[],
@@ -132,6 +158,14 @@ const DEFINITIONS: &[Definition] = &[
         name: "Mod",
         operand_widths: &[],
     },
+    Definition {
+        name: "Pow",
+        operand_widths: &[],
+    },
+    Definition {
+        name: "FloorDiv",
+        operand_widths: &[],
+    },
     Definition {
         name: "BitXor",
         operand_widths: &[],
@@ -180,6 +214,14 @@ const DEFINITIONS: &[Definition] = &[
         name: "GreaterThanEqual",
         operand_widths: &[],
     },
+    Definition {
+        name: "LessThan",
+        operand_widths: &[],
+    },
+    Definition {
+        name: "LessThanEqual",
+        operand_widths: &[],
+    },
     Definition {
         name: "Minus",
         operand_widths: &[],
@@ -188,6 +230,10 @@ const DEFINITIONS: &[Definition] = &[
         name: "Bang",
         operand_widths: &[],
     },
+    Definition {
+        name: "BitNot",
+        operand_widths: &[],
+    },
     Definition {
         name: "And",
         operand_widths: &[],
@@ -196,6 +242,10 @@ const DEFINITIONS: &[Definition] = &[
         name: "Or",
         operand_widths: &[],
     },
+    Definition {
+        name: "In",
+        operand_widths: &[],
+    },
     Definition {
         name: "Jump",
         operand_widths: &[2],
@@ -204,6 +254,10 @@ const DEFINITIONS: &[Definition] = &[
         name: "JumpNotTruthy",
         operand_widths: &[2],
     },
+    Definition {
+        name: "JumpNil",
+        operand_widths: &[2],
+    },
     Definition {
         name: "GetGlobal",
         operand_widths: &[2],
@@ -224,6 +278,10 @@ const DEFINITIONS: &[Definition] = &[
         name: "Index",
         operand_widths: &[],
     },
+    Definition {
+        name: "SetIndex",
+        operand_widths: &[],
+    },
     Definition {
         name: "Range",
         operand_widths: &[1],
@@ -250,7 +308,7 @@ const DEFINITIONS: &[Definition] = &[
     },
     Definition {
         name: "GetBuiltin",
-        operand_widths: &[1],
+        operand_widths: &[2],
     },
     Definition {
         name: "Closure",
@@ -260,13 +318,17 @@ const DEFINITIONS: &[Definition] = &[
         name: "GetFree",
         operand_widths: &[1],
     },
+    Definition {
+        name: "SetFree",
+        operand_widths: &[1],
+    },
     Definition {
         name: "CurrentClosure",
         operand_widths: &[],
     },
     Definition {
         name: "Method",
-        operand_widths: &[8, 1, 1],
+        operand_widths: &[2, 1, 1, 1],
     },
     Definition {
         name: "Scope",
@@ -274,16 +336,24 @@ const DEFINITIONS: &[Definition] = &[
     },
     Definition {
         name: "Constructor",
-        operand_widths: &[1],
+        operand_widths: &[2, 1],
     },
     Definition {
-        name: "ClassMember",
-        operand_widths: &[8, 1],
+        name: "SetMember",
+        operand_widths: &[2],
     },
     Definition {
         name: "Delete",
         operand_widths: &[2],
     },
+    Definition {
+        name: "Enum",
+        operand_widths: &[2, 1],
+    },
+    Definition {
+        name: "Variant",
+        operand_widths: &[2, 1],
+    },
     Definition {
         name: "Next",
         operand_widths: &[],
@@ -296,10 +366,26 @@ const DEFINITIONS: &[Definition] = &[
         name: "JumpEnd",
         operand_widths: &[2, 2],
     },
+    Definition {
+        name: "Yield",
+        operand_widths: &[],
+    },
+    Definition {
+        name: "Concat",
+        operand_widths: &[2],
+    },
+    Definition {
+        name: "Format",
+        operand_widths: &[2],
+    },
     Definition {
         name: "String",
         operand_widths: &[1],
     },
+    Definition {
+        name: "Line",
+        operand_widths: &[2],
+    },
 ];
 
 pub fn make(op: Opcode, operands: &[usize]) -> Instructions {
@@ -420,6 +506,10 @@ fn fmt_instruction(def: &Definition, operands: &[usize]) -> String {
             "{:<16} {:>5} {:>5} {:>5}",
             def.name, operands[0], operands[1], operands[2]
         ),
+        4 => format!(
+            "{:<16} {:>5} {:>5} {:>5} {:>5}",
+            def.name, operands[0], operands[1], operands[2], operands[3]
+        ),
         _ => format!("ERROR: unhandled operand_count for {}\n", def.name),
     }
 }
@@ -5,28 +5,50 @@ mod tests;
 
 pub mod symbol_table;
 
-use std::path::PathBuf;
+use std::{collections::HashMap, iter, path::Path, sync::Arc};
 
 pub use symbol_table::*;
 
 use self::symbol_table::SymbolTable;
 use crate::{
     ast::{
-        Assign, Assignable, BlockStatement, Call, ClassStatement, Constructable, Constructor,
-        Declaration, Delete, Expression, ExpressionStmt, For, Function, Identifier, If, Import,
-        Index, Infix, Lambda, Lit, Literal, Method, Node, Operator, Prefix, Range, Return, Scope,
-        Statement, While,
+        Assign, Assignable, BlockStatement, Call, ClassDecl, ClassMethod, ClassStatement,
+        ClassVariable, Constructable, Constructor, Declaration, Delete, EnumDecl, Expression,
+        ExpressionStmt, For, Function, Identifier, If, Import, ImportItems, Index, Infix,
+        InterfaceDecl, Interpolation, InterpolationPart, Lambda, Lit, Literal, Method, Node,
+        Operator, Prefix, Range, Return, Scope, Statement, While, Yield,
     },
     code::{make, Instructions, Opcode},
-    lexer::Lexer,
-    object::{builtins::BUILTINS, hash_method_name, CompiledModule, Object, Str, DIR_ENV_VAR_NAME},
-    parser::Parser,
+    module::{module_name, ModuleLoader},
+    native::{std_modules, NativeModule},
+    object::{
+        builtins::BUILTINS, getter_name, setter_name, CompiledFunction, CompiledModule, Enum,
+        FunctionInfo, NativeFunction, Object, Str,
+    },
 };
 
+#[derive(Clone)]
 pub struct CompilationScope {
     instructions: Instructions,
     last_instruction: EmittedInstruction,
     previous_instruction: EmittedInstruction,
+    generator: bool,
+}
+
+impl CompilationScope {
+    const fn new(opcode: Opcode) -> Self {
+        let emitted = EmittedInstruction {
+            opcode,
+            position: 0,
+        };
+
+        Self {
+            instructions: Vec::new(),
+            last_instruction: emitted,
+            previous_instruction: emitted,
+            generator: false,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -35,21 +57,16 @@ pub struct EmittedInstruction {
     position: usize,
 }
 
-pub fn new_with_state(s: SymbolTable, constants: &[Object]) -> Compiler {
-    let mut comp = Compiler::new();
-    comp.symbol_table = s;
-    comp.constants = constants.to_vec();
+type ExportedDecls = (Vec<(String, ClassDecl)>, Vec<(String, InterfaceDecl)>);
 
-    comp
-}
-
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct LoopState {
     in_loop: bool,
     continues: Vec<usize>,
     breaks: Vec<usize>,
 }
 
+#[derive(Clone)]
 pub struct Compiler {
     constants: Vec<Object>,
     symbol_table: SymbolTable,
@@ -58,41 +75,137 @@ pub struct Compiler {
     scope_index: usize,
 
     loop_state: LoopState,
+
+    modules: ModuleLoader<CompiledModule>,
+    // The names the program exports when it's imported as a module.
+    exports: Vec<String>,
+    // The symbol tables of the programs importing the module being compiled,
+    // outermost first.
+    importers: Vec<SymbolTable>,
+    // The scope and name of the module being compiled, if any.
+    module: Option<(usize, String)>,
+    // The functions of registered native modules, qualified by their module.
+    // They're builtins numbered after `BUILTINS`.
+    natives: Vec<(String, NativeFunction)>,
+    // Whether functions keep the names of their variables for debuggers.
+    debug: bool,
+    line: usize,
 }
 
 impl Compiler {
     pub fn new() -> Self {
-        let main_scope = CompilationScope {
-            instructions: Vec::new(),
-            last_instruction: EmittedInstruction {
-                opcode: Opcode::Pop,
-                position: 0,
-            },
-            previous_instruction: EmittedInstruction {
-                opcode: Opcode::Pop,
-                position: 0,
-            },
+        Self::new_with_modules(ModuleLoader::default())
+    }
+
+    pub fn new_with_modules(modules: ModuleLoader<CompiledModule>) -> Self {
+        let mut compiler = Self {
+            constants: Vec::new(),
+            symbol_table: SymbolTable::new(),
+
+            scopes: Vec::from([CompilationScope::new(Opcode::Pop)]),
+            scope_index: 0,
+
+            loop_state: LoopState::default(),
+
+            modules,
+            exports: Vec::new(),
+            importers: Vec::new(),
+            module: None,
+            natives: Vec::new(),
+            debug: false,
+            line: 0,
         };
 
+        compiler.symbol_table = compiler.builtin_symbol_table();
+
+        for module in std_modules() {
+            compiler.register(&module);
+        }
+
+        compiler
+    }
+
+    /// Makes the functions of `module` importable from its name and callable
+    /// as `name::function()`.
+    pub fn register(&mut self, module: &NativeModule) {
+        for function in module.functions() {
+            let name = format!("{}::{}", module.name(), function.name);
+
+            self.symbol_table
+                .define_builtin(&name, BUILTINS.len() + self.natives.len());
+            self.natives.push((name, function.clone()));
+        }
+
+        self.modules.embed(module.name());
+    }
+
+    fn builtin_symbol_table(&self) -> SymbolTable {
         let mut symbol_table = SymbolTable::new();
 
-        for (i, (name, _)) in BUILTINS.iter().enumerate() {
+        let builtins = BUILTINS.iter().map(|(name, _)| *name);
+        let natives = self.natives.iter().map(|(name, _)| name.as_str());
+
+        for (i, name) in builtins.chain(natives).enumerate() {
             symbol_table.define_builtin(name, i);
         }
 
-        Self {
-            constants: Vec::new(),
-            symbol_table,
+        symbol_table
+    }
 
-            scopes: Vec::from([main_scope]),
-            scope_index: 0,
+    /// Compiles a program fed in pieces, like the lines of the REPL, into
+    /// the bytecode of `program` alone. Its definitions carry over to the next
+    /// pieces unless it fails to compile.
+    pub fn compile_chunk(&mut self, program: Node) -> Result<Bytecode, String> {
+        let snapshot = self.clone();
 
-            loop_state: LoopState::default(),
+        self.scopes = Vec::from([CompilationScope::new(Opcode::Pop)]);
+        self.scope_index = 0;
+
+        if let Err(err) = self.compile(program) {
+            *self = snapshot;
+            return Err(err);
         }
+
+        Ok(self.bytecode())
+    }
+
+    pub fn set_root(&mut self, file: &Path) -> Result<(), String> {
+        self.modules.set_root(file)
+    }
+
+    /// Makes the functions compiled from now on keep the names of their
+    /// variables, which debuggers show.
+    pub const fn set_debug(&mut self) {
+        self.debug = true;
+    }
+
+    /// The line of the statement compiled last, which is only known for
+    /// programs parsed with their lines.
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The names of the globals, by slot.
+    pub fn global_names(&self) -> Vec<String> {
+        self.symbol_table.names()
+    }
+
+    pub const fn num_globals(&self) -> usize {
+        self.symbol_table.num_definitions
+    }
+
+    pub fn resolve_global(&self, name: &str) -> Option<usize> {
+        self.symbol_table
+            .get(name)
+            .filter(|symbol| symbol.scope == SymbolScope::Global)
+            .map(|symbol| symbol.index)
     }
 
-    pub fn get_symbol_table(&self) -> SymbolTable {
-        self.symbol_table.clone()
+    /// The slot of the global `name`, which is defined as a mutable variable
+    /// if the program doesn't define it.
+    pub fn global_slot(&mut self, name: &str) -> usize {
+        self.resolve_global(name)
+            .unwrap_or_else(|| self.symbol_table.define(name, true).index)
     }
 
     pub fn compile(&mut self, node: Node) -> Result<(), String> {
@@ -123,7 +236,7 @@ impl Compiler {
                     value,
                     ..
                 }) => {
-                    let symbol = self.symbol_table.define(&name, mutable);
+                    let symbol = self.define(&name, mutable);
 
                     if let Some(value) = value {
                         self.compile(Node::Expr(value))?;
@@ -143,6 +256,15 @@ impl Compiler {
                     self.emit_op(Opcode::ReturnValue);
                 }
 
+                Statement::Yield(Yield { value }) => {
+                    if !self.scopes[self.scope_index].generator {
+                        return Err("cannot use `yield` outside generator functions".to_string());
+                    }
+
+                    self.compile(Node::Expr(value))?;
+                    self.emit_op(Opcode::Yield);
+                }
+
                 Statement::Delete(Delete { delete_ident }) => {
                     let Some(symbol) = self.symbol_table.delete(&delete_ident) else {
                         return Err(format!("undefined variable `{delete_ident}`"));
@@ -155,11 +277,12 @@ impl Compiler {
                     ident,
                     parameters,
                     body,
-                    ..
+                    generator,
                 }) => {
-                    let symbol = self.symbol_table.define(&ident, false);
+                    let symbol = self.define(&ident, false);
 
                     self.enter_scope();
+                    self.scopes[self.scope_index].generator = generator;
 
                     self.symbol_table.define_function_name(&ident);
 
@@ -169,9 +292,11 @@ impl Compiler {
                         self.symbol_table.define(&p, false);
                     }
 
+                    let trailing_loop = ends_with_loop(&body);
+
                     self.compile_block_statements(body)?;
 
-                    if self.last_instruction_is(Opcode::Pop) {
+                    if self.last_instruction_is(Opcode::Pop) && !trailing_loop {
                         self.replace_last_pop_with(Opcode::ReturnValue);
                     }
 
@@ -187,13 +312,20 @@ impl Compiler {
 
                     let free_symbols = self.symbol_table.free_symbols.clone();
                     let num_locals = self.symbol_table.num_definitions;
+                    let info = self.function_info(&ident);
                     let instructions = self.leave_scope();
 
                     for symbol in &free_symbols {
                         self.load_symbol(symbol);
                     }
 
-                    let compiled_fn = Object::compiled_fn(instructions, num_locals, num_parameters);
+                    let compiled_fn = Object::CompiledFunction(CompiledFunction {
+                        instructions,
+                        num_locals,
+                        num_parameters,
+                        generator,
+                        info,
+                    });
 
                     let idx = self.add_constant(compiled_fn);
                     self.emit(Opcode::Closure, &[idx, free_symbols.len()]);
@@ -241,6 +373,11 @@ impl Compiler {
                     }
                 }
 
+                Statement::Line(line) => {
+                    self.line = line;
+                    self.emit(Opcode::Line, &[line]);
+                }
+
                 Statement::Continue => {
                     if self.loop_state.in_loop {
                         let pos = self.emit(Opcode::Jump, &[9999]);
@@ -250,58 +387,166 @@ impl Compiler {
                     }
                 }
 
-                Statement::ClassDecl(decl) => {
-                    self.symbol_table.define_type(decl.ident.clone(), decl);
-                }
+                Statement::ClassDecl(mut decl) => {
+                    for name in decl.interfaces.clone() {
+                        let interface = self
+                            .symbol_table
+                            .resolve_interface(&name)
+                            .ok_or_else(|| format!("no interface named \"{name}\" found"))?;
 
-                Statement::Import(Import { path, alias, class }) => {
-                    let path_buf = PathBuf::from(&path);
-                    let start_dir =
-                        std::env::var(DIR_ENV_VAR_NAME).map_err(|err| err.to_string())?;
+                        decl.implement(&interface)?;
+                    }
 
-                    if let Some(ext) = path_buf.extension() {
-                        if ext != "pd" {
-                            return Err("cannot import non panda files".to_string());
-                        }
-                        let import_file =
-                            std::fs::read_to_string(PathBuf::from(start_dir).join(&path_buf))
-                                .map_err(|err| err.to_string())?;
+                    let statics = decl.static_members();
+                    let ident = decl.ident.clone();
+                    let methods: Vec<_> = decl
+                        .body
+                        .iter()
+                        .filter(|stmt| !matches!(stmt, ClassStatement::Variable(_)))
+                        .cloned()
+                        .collect();
+
+                    self.symbol_table.define_type(ident.clone(), decl);
+
+                    // Methods may refer to the statics, so their names are
+                    // defined before any method is compiled.
+                    let statics: Vec<_> = statics
+                        .into_iter()
+                        .map(|stmt| {
+                            let symbol = match &stmt {
+                                Statement::Declaration(decl) => {
+                                    Some(self.define(&decl.name, decl.mutable))
+                                }
+                                Statement::Function(func) => Some(self.define(&func.ident, false)),
+                                _ => None,
+                            };
+
+                            (stmt, symbol)
+                        })
+                        .collect();
 
-                        let mut lexer = Lexer::new(&import_file);
-                        let mut parser = Parser::new(&mut lexer);
+                    // The methods are compiled once into an instance without
+                    // fields, which constructing the class extends.
+                    let symbol = self.define(&prototype_name(&ident), false);
+                    self.emit_op(Opcode::Nil);
+                    let num_methods = methods.len();
+
+                    for stmt in methods {
+                        let (member, method) = match stmt {
+                            ClassStatement::Method(method) => (method.name.clone(), method),
+                            ClassStatement::Getter(method) => (getter_name(&method.name), method),
+                            ClassStatement::Setter(method) => (setter_name(&method.name), method),
+                            ClassStatement::Variable(_) => unreachable!(),
+                        };
+
+                        let member = self.add_constant(Object::str(member));
+                        self.emit(Opcode::Constant, &[member]);
+
+                        let ClassMethod {
+                            name,
+                            parameters,
+                            body,
+                        } = method;
+
+                        let lambda = Lambda {
+                            parameters: iter::once("self".to_string()).chain(parameters).collect(),
+                            body,
+                            name,
+                            generator: false,
+                        };
 
-                        let program = parser.parse_program();
+                        self.compile_function(lambda, true)?;
+                    }
+
+                    let name = self.add_constant(Object::str(ident));
+                    self.emit(Opcode::Constructor, &[name, num_methods]);
+                    self.store_symbol(&symbol);
+
+                    for (stmt, symbol) in statics {
+                        let Some(symbol) = symbol else {
+                            self.compile(Node::Stmt(stmt))?;
+                            continue;
+                        };
 
-                        if !parser.errors.is_empty() {
-                            println!("parser errors:");
-                            for msg in &parser.errors {
-                                println!("\t{msg}");
+                        match stmt {
+                            Statement::Declaration(Declaration {
+                                value: Some(value), ..
+                            }) => self.compile(Node::Expr(value))?,
+                            Statement::Function(Function {
+                                ident,
+                                parameters,
+                                body,
+                                generator,
+                            }) => self.compile_function(
+                                Lambda {
+                                    parameters,
+                                    body,
+                                    name: ident,
+                                    generator,
+                                },
+                                false,
+                            )?,
+                            _ => {
+                                self.emit_op(Opcode::Nil);
                             }
-                            return Err(format!("could not import \"{path}\" as it had errors."));
                         }
 
-                        let mut comp = Self::new();
-                        if let Err(err) = comp.compile(program.unwrap()) {
-                            println!("compiler error:\n\t{err}");
-                            return Ok(());
-                        };
+                        self.store_symbol(&symbol);
+                    }
+                }
 
-                        let module_symbol_table = comp.symbol_table;
+                Statement::InterfaceDecl(decl) => {
+                    let symbol = self.define(&decl.ident, false);
+                    self.symbol_table
+                        .define_interface(decl.ident.clone(), decl.clone());
 
-                        let module_name = alias.unwrap_or_else(|| {
-                            path_buf.file_stem().unwrap().to_str().unwrap().to_string()
-                        });
+                    let idx = self.add_constant(Object::Interface(Arc::new(decl)));
+                    self.emit(Opcode::Constant, &[idx]);
+                    self.store_symbol(&symbol);
+                }
 
-                        self.symbol_table.define_import(
-                            module_name.clone(),
-                            CompiledModule {
-                                symbol_table: module_symbol_table,
-                                name: module_name,
-                                constants: comp.constants,
-                                class,
-                            },
-                        );
+                // Methods are compiled to closures taking `self` as their first
+                // parameter, which `Enum` pops along with their names.
+                Statement::EnumDecl(EnumDecl {
+                    ident,
+                    variants,
+                    methods,
+                }) => {
+                    let symbol = self.define(&ident, false);
+                    let num_methods = methods.len();
+
+                    for ClassMethod {
+                        name,
+                        parameters,
+                        body,
+                    } in methods
+                    {
+                        let name = self.add_constant(Object::str(name));
+                        self.emit(Opcode::Constant, &[name]);
+
+                        self.compile(Node::Expr(Expression::Lambda(Lambda {
+                            parameters: iter::once("self".to_string()).chain(parameters).collect(),
+                            body,
+                            name: String::new(),
+                            generator: false,
+                        })))?;
                     }
+
+                    let decl = self.add_constant(Object::Enum(Arc::new(Enum {
+                        name: ident,
+                        variants,
+                        methods: HashMap::new(),
+                    })));
+
+                    self.emit(Opcode::Enum, &[decl, num_methods]);
+                    self.store_symbol(&symbol);
+                }
+
+                Statement::Import(import) => self.compile_import(import)?,
+
+                Statement::Export(stmt) => {
+                    self.exports.extend(stmt.declared_names());
+                    self.compile(Node::Stmt(*stmt))?;
                 }
 
                 Statement::For(For {
@@ -366,18 +611,20 @@ impl Compiler {
                     right,
                     ..
                 }) => {
-                    if operator == Operator::Lt {
-                        self.compile(Node::Expr(*right))?;
+                    if operator == Operator::NilCoalesce {
                         self.compile(Node::Expr(*left))?;
 
-                        self.emit_op(Opcode::GreaterThan);
+                        let jump_nil_pos = self.emit(Opcode::JumpNil, &[9999]);
+                        let jump_pos = self.emit(Opcode::Jump, &[9999]);
 
-                        return Ok(());
-                    } else if operator == Operator::LtEq {
+                        let after_left_pos = self.current_instructions().len();
+                        self.change_operand(jump_nil_pos, after_left_pos);
+
+                        self.emit_op(Opcode::Pop);
                         self.compile(Node::Expr(*right))?;
-                        self.compile(Node::Expr(*left))?;
 
-                        self.emit_op(Opcode::GreaterThanEqual);
+                        let after_right_pos = self.current_instructions().len();
+                        self.change_operand(jump_pos, after_right_pos);
 
                         return Ok(());
                     }
@@ -385,25 +632,7 @@ impl Compiler {
                     self.compile(Node::Expr(*left))?;
                     self.compile(Node::Expr(*right))?;
 
-                    match operator {
-                        Operator::Add => self.emit_op(Opcode::Add),
-                        Operator::Sub => self.emit_op(Opcode::Sub),
-                        Operator::Mul => self.emit_op(Opcode::Mul),
-                        Operator::Div => self.emit_op(Opcode::Div),
-                        Operator::Mod => self.emit_op(Opcode::Mod),
-                        Operator::BitXor => self.emit_op(Opcode::BitXor),
-                        Operator::BitAnd => self.emit_op(Opcode::BitAnd),
-                        Operator::BitOr => self.emit_op(Opcode::BitOr),
-                        Operator::Shr => self.emit_op(Opcode::Shr),
-                        Operator::Shl => self.emit_op(Opcode::Shl),
-                        Operator::Gt => self.emit_op(Opcode::GreaterThan),
-                        Operator::GtEq => self.emit_op(Opcode::GreaterThanEqual),
-                        Operator::Eq => self.emit_op(Opcode::Equal),
-                        Operator::NotEq => self.emit_op(Opcode::NotEqual),
-                        Operator::And => self.emit_op(Opcode::And),
-                        Operator::Or => self.emit_op(Opcode::Or),
-                        _ => return Err(format!("unknown operator: \"{operator}\"")),
-                    };
+                    self.emit_operator(operator)?;
                 }
 
                 Expression::Prefix(Prefix { operator, right }) => {
@@ -412,6 +641,7 @@ impl Compiler {
                     match operator {
                         Operator::Bang => self.emit_op(Opcode::Bang),
                         Operator::Sub => self.emit_op(Opcode::Minus),
+                        Operator::BitNot => self.emit_op(Opcode::BitNot),
                         _ => return Err(format!("unknown operator: {operator}")),
                     };
                 }
@@ -474,6 +704,30 @@ impl Compiler {
                     }
                 },
 
+                Expression::Interpolation(Interpolation { parts }) => {
+                    let n = parts.len();
+
+                    for part in parts {
+                        match part {
+                            InterpolationPart::Str(value) => {
+                                let operand = self.add_constant(Object::str(value));
+                                self.emit(Opcode::Constant, &[operand]);
+                            }
+
+                            InterpolationPart::Expr { value, spec } => {
+                                self.compile(Node::Expr(value))?;
+
+                                if let Some(spec) = spec {
+                                    let operand = self.add_constant(Object::str(spec));
+                                    self.emit(Opcode::Format, &[operand]);
+                                }
+                            }
+                        }
+                    }
+
+                    self.emit(Opcode::Concat, &[n]);
+                }
+
                 Expression::If(If {
                     condition,
                     consequence,
@@ -487,6 +741,8 @@ impl Compiler {
 
                     if self.last_instruction_is(Opcode::Pop) {
                         self.remove_last_pop();
+                    } else {
+                        self.emit_op(Opcode::Nil);
                     }
 
                     let jump_pos = self.emit(Opcode::Jump, &[9999]);
@@ -499,6 +755,8 @@ impl Compiler {
 
                         if self.last_instruction_is(Opcode::Pop) {
                             self.remove_last_pop();
+                        } else {
+                            self.emit_op(Opcode::Nil);
                         }
                     } else {
                         self.emit_op(Opcode::Nil);
@@ -517,246 +775,813 @@ impl Compiler {
                     self.load_symbol(&symbol);
                 }
 
-                Expression::Index(Index { left, index }) => {
-                    self.compile(Node::Expr(*left))?;
-                    self.compile(Node::Expr(*index))?;
+                link @ (Expression::Call(_) | Expression::Index(_) | Expression::Method(_)) => {
+                    let mut nil_jumps = vec![];
+                    self.compile_link(link, &mut nil_jumps)?;
 
-                    self.emit_op(Opcode::Index);
+                    let after_chain_pos = self.current_instructions().len();
+                    for pos in nil_jumps {
+                        self.change_operand(pos, after_chain_pos);
+                    }
                 }
 
                 Expression::Range(Range { start, end, step }) => {
+                    self.compile(Node::Expr(*start))?;
+                    self.compile(Node::Expr(*end))?;
+
+                    let has_step = step.is_some();
                     if let Some(step) = step {
                         self.compile(Node::Expr(*step))?;
-                        self.emit(Opcode::Range, &[3]);
-                        return Ok(());
                     }
 
-                    self.compile(Node::Expr(*start))?;
-                    self.compile(Node::Expr(*end))?;
-                    self.emit(Opcode::Range, &[2]);
+                    self.emit(Opcode::Range, &[usize::from(has_step)]);
                 }
 
-                Expression::Lambda(Lambda {
-                    parameters,
-                    body,
-                    name,
-                    ..
-                }) => {
-                    self.enter_scope();
+                Expression::Lambda(lambda) => self.compile_function(lambda, false)?,
 
-                    if !name.is_empty() {
-                        self.symbol_table.define_function_name(&name);
+                Expression::Assign(Assign {
+                    to,
+                    operator,
+                    value,
+                }) => match to {
+                    Assignable::Identifier(Identifier { value: name }) => {
+                        let symbol = self.resolve_assignable(&name)?;
+
+                        if let Some(operator) = operator {
+                            self.load_symbol(&symbol);
+                            self.compile(Node::Expr(*value))?;
+                            self.emit_operator(operator)?;
+                        } else {
+                            self.compile(Node::Expr(*value))?;
+                        }
+
+                        self.emit_op(Opcode::Dup);
+                        self.store_symbol(&symbol);
                     }
 
-                    let num_parameters = parameters.len();
+                    // The target is evaluated once: `DupTwo` keeps the container and index
+                    // around for `SetIndex` while `Index` reads the current value.
+                    Assignable::Index(Index { left, index, .. }) => {
+                        let Expression::Identifier(Identifier { value: name }) = *left else {
+                            return Err(format!("cannot assign to {left}"));
+                        };
 
-                    for p in parameters {
-                        self.symbol_table.define(&p, false);
+                        let symbol = self.resolve_assignable(&name)?;
+
+                        self.load_symbol(&symbol);
+                        self.compile(Node::Expr(*index))?;
+
+                        if let Some(operator) = operator {
+                            self.emit_op(Opcode::DupTwo);
+                            self.emit_op(Opcode::Index);
+                            self.compile(Node::Expr(*value))?;
+                            self.emit_operator(operator)?;
+                        } else {
+                            self.compile(Node::Expr(*value))?;
+                        }
+
+                        self.emit_op(Opcode::SetIndex);
+                        self.store_symbol(&symbol);
                     }
 
-                    self.compile_block_statements(body)?;
+                    // `SetMember` pushes the assigned value and the updated instance,
+                    // which is stored back to its variable.
+                    Assignable::Method(Method {
+                        left, name: member, ..
+                    }) => {
+                        let Expression::Identifier(Identifier { value: name }) = *left else {
+                            return Err(format!("cannot assign to method '{member}'"));
+                        };
 
-                    if self.last_instruction_is(Opcode::Pop) {
-                        self.replace_last_pop_with(Opcode::ReturnValue);
+                        let symbol = self.resolve_assignable(&name)?;
+
+                        if !symbol.mutable {
+                            return Err(format!("identifier is not mutable: {name}"));
+                        }
+
+                        let member = self.add_constant(Object::str(member));
+                        self.load_symbol(&symbol);
+
+                        if let Some(operator) = operator {
+                            self.emit_op(Opcode::Dup);
+                            self.emit(Opcode::Method, &[member, 0, 0, 0]);
+                            self.compile(Node::Expr(*value))?;
+                            self.emit_operator(operator)?;
+                        } else {
+                            self.compile(Node::Expr(*value))?;
+                        }
+
+                        self.emit(Opcode::SetMember, &[member]);
+                        self.store_symbol(&symbol);
                     }
 
-                    if self.last_instruction_is(Opcode::PopNoRet) {
-                        self.replace_last_pop_with(Opcode::Return);
+                    Assignable::Scope(Scope { module, member }) => {
+                        let Expression::Identifier(Identifier { value: name }) = *member else {
+                            return Err(format!("cannot assign to {module}::{member}"));
+                        };
+
+                        let qualified = format!("{module}::{name}");
+
+                        let Some(symbol) = self.symbol_table.resolve(&qualified) else {
+                            return Err(format!(
+                                "no static member `{name}` found in class \"{module}\""
+                            ));
+                        };
+
+                        if !symbol.mutable {
+                            return Err(format!("identifier is not mutable: {qualified}"));
+                        }
+
+                        return self.compile(Node::Expr(Expression::Assign(Assign {
+                            to: Assignable::Identifier(Identifier { value: qualified }),
+                            operator,
+                            value,
+                        })));
                     }
+                },
 
-                    if !self.last_instruction_is(Opcode::ReturnValue)
-                        && !self.last_instruction_is(Opcode::Return)
-                    {
-                        self.emit_op(Opcode::Return);
+                Expression::Scope(Scope { module, member }) => {
+                    if self.symbol_table.resolve_type(&module).is_some() {
+                        return self.compile_static_member(&module, *member);
                     }
 
-                    let free_symbols = self.symbol_table.free_symbols.clone();
-                    let num_locals = self.symbol_table.num_definitions;
-                    let instructions = self.leave_scope();
+                    if let Some(symbol) = self.symbol_table.resolve(&module) {
+                        return self.compile_enum_variant(&symbol, *member);
+                    }
 
-                    for symbol in &free_symbols {
-                        self.load_symbol(symbol);
+                    return self.compile_module_member(&module, *member);
+                }
+
+                Expression::Constructor(Constructor { constructable }) => {
+                    self.compile_constructor(constructable)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    // Compiles a call, index or member. A `?.` on `nil` jumps past the whole
+    // chain the link is part of, to where the caller patches `nil_jumps`.
+    fn compile_link(&mut self, link: Expression, nil_jumps: &mut Vec<usize>) -> Result<(), String> {
+        match link {
+            Expression::Call(Call {
+                function,
+                arguments,
+                ..
+            }) => {
+                self.compile_link(*function, nil_jumps)?;
+
+                let n = arguments.len();
+
+                for arg in arguments {
+                    self.compile(Node::Expr(arg))?;
+                }
+
+                self.emit(Opcode::Call, &[n]);
+            }
+
+            Expression::Index(Index {
+                left,
+                index,
+                optional,
+            }) => {
+                self.compile_link(*left, nil_jumps)?;
+
+                if optional {
+                    nil_jumps.push(self.emit(Opcode::JumpNil, &[9999]));
+                }
+
+                self.compile(Node::Expr(*index))?;
+
+                self.emit_op(Opcode::Index);
+            }
+
+            // Methods can change the class instance they're called on. When it's
+            // held by a variable, `Method` also pushes the instance to store back.
+            Expression::Method(Method {
+                left,
+                name: method,
+                arguments,
+                optional,
+            }) => {
+                let receiver = match left.as_ref() {
+                    Expression::Identifier(Identifier { value }) => {
+                        self.symbol_table.resolve(value).filter(|symbol| {
+                            symbol.mutable
+                                && matches!(symbol.scope, SymbolScope::Global | SymbolScope::Local)
+                        })
                     }
+                    _ => None,
+                };
 
-                    let compiled_fn = Object::compiled_fn(instructions, num_locals, num_parameters);
+                self.compile_link(*left, nil_jumps)?;
 
-                    let idx = self.add_constant(compiled_fn);
-                    self.emit(Opcode::Closure, &[idx, free_symbols.len()]);
+                if optional {
+                    nil_jumps.push(self.emit(Opcode::JumpNil, &[9999]));
                 }
 
-                Expression::Call(Call {
-                    function,
-                    arguments,
-                    ..
-                }) => {
-                    self.compile(Node::Expr(*function))?;
+                let method = self.add_constant(Object::str(method));
+                let has_arguments = arguments.is_some();
 
+                let n = if let Some(arguments) = arguments {
                     let n = arguments.len();
 
                     for arg in arguments {
                         self.compile(Node::Expr(arg))?;
                     }
 
-                    self.emit(Opcode::Call, &[n]);
+                    n
+                } else {
+                    0
+                };
+
+                self.emit(
+                    Opcode::Method,
+                    &[
+                        method,
+                        usize::from(has_arguments),
+                        n,
+                        usize::from(receiver.is_some()),
+                    ],
+                );
+
+                if let Some(symbol) = receiver {
+                    self.store_symbol(&symbol);
                 }
+            }
 
-                Expression::Assign(Assign { to, value }) => match to {
-                    Assignable::Identifier(Identifier { value: name }) => {
-                        self.compile(Node::Expr(*value))?;
+            // The start of the chain.
+            expr => self.compile(Node::Expr(expr))?,
+        }
 
-                        let symbol = self
-                            .symbol_table
-                            .resolve(&name)
-                            .ok_or_else(|| format!("undefined variable {name}"))?;
+        Ok(())
+    }
 
-                        self.emit_op(Opcode::Dup);
+    // Instances extend the class's methods with the names and values of their
+    // fields, pushed in the order they're declared: the values it's initialized
+    // with, then the fields of the class body.
+    fn compile_constructor(&mut self, constructable: Constructable) -> Result<(), String> {
+        let (class, arguments) = match constructable {
+            Constructable::Identifier(Identifier { value }) => {
+                let class = self
+                    .symbol_table
+                    .resolve_type(&value)
+                    .ok_or_else(|| format!("no class named \"{value}\" found."))?;
+
+                self.load_prototype(&value);
+                (class, None)
+            }
 
-                        if symbol.scope == SymbolScope::Global {
-                            self.emit(Opcode::SetGlobal, &[symbol.index]);
-                        } else {
-                            self.emit(Opcode::SetLocal, &[symbol.index]);
-                        }
-                    }
+            Constructable::Call(call) => {
+                let (name, arguments) = Scope::split_member(Expression::Call(call))?;
 
-                    _ => todo!(),
-                },
+                let class = self
+                    .symbol_table
+                    .resolve_type(&name)
+                    .ok_or_else(|| format!("no class named \"{name}\" found."))?;
 
-                Expression::Method(Method {
-                    left,
-                    name: method,
-                    arguments,
-                    ..
-                }) => {
-                    self.compile(Node::Expr(*left))?;
+                self.load_prototype(&name);
+                (class, arguments)
+            }
 
-                    let method_hash = hash_method_name(&method);
-                    let has_arguments = arguments.is_some();
+            Constructable::Scope(Scope { module, member }) => {
+                let imported = self
+                    .symbol_table
+                    .resolve_import(&module)
+                    .ok_or_else(|| format!("no module named '{module}' found"))?;
 
-                    let n = if let Some(arguments) = arguments {
-                        let n = arguments.len();
+                let (name, arguments) = Scope::split_member(*member)?;
+                let global = imported.global;
 
-                        for arg in arguments {
-                            self.compile(Node::Expr(arg))?;
-                        }
+                let class = imported
+                    .types
+                    .into_iter()
+                    .find_map(|(class, decl)| (class == name).then_some(decl))
+                    .ok_or_else(|| format!("no class named '{name}' found in module '{module}'"))?;
 
-                        n
-                    } else {
-                        0
-                    };
+                self.emit(Opcode::GetGlobal, &[global]);
+                let key = self.add_constant(Object::str(prototype_name(&name)));
+                self.emit(Opcode::Constant, &[key]);
+                self.emit_op(Opcode::Index);
 
-                    self.emit(
-                        Opcode::Method,
-                        &[method_hash, usize::from(has_arguments), n],
-                    );
+                (class, arguments)
+            }
+        };
+
+        // Aliased classes keep the name they were declared with.
+        let name = self.add_constant(Object::str(class.ident));
+
+        let arguments = match arguments {
+            None if !class.initializers.is_empty() => {
+                return Err(format!(
+                    "cannot initialize class with 0 variables. required: {}",
+                    class.initializers.len()
+                ));
+            }
+            Some(arguments) if arguments.len() != class.initializers.len() => {
+                return Err(format!(
+                    "invalid length of initializers. required: {}, got: {}",
+                    class.initializers.len(),
+                    arguments.len()
+                ));
+            }
+            arguments => arguments.unwrap_or_default(),
+        };
+
+        let fields: Vec<_> = class
+            .body
+            .into_iter()
+            .filter_map(|stmt| match stmt {
+                ClassStatement::Variable(field) => Some(field),
+                _ => None,
+            })
+            .collect();
+        let num_members = fields.len() + arguments.len();
+
+        for (initializer, argument) in class.initializers.into_iter().zip(arguments) {
+            let initializer = self.add_constant(Object::str(initializer));
+            self.emit(Opcode::Constant, &[initializer]);
+            self.compile(Node::Expr(argument))?;
+        }
+
+        for ClassVariable { name, value, .. } in fields {
+            let member = self.add_constant(Object::str(name));
+            self.emit(Opcode::Constant, &[member]);
+
+            match value {
+                Some(value) => self.compile(Node::Expr(value))?,
+                None => {
+                    self.emit_op(Opcode::Nil);
                 }
+            }
+        }
 
-                Expression::Scope(Scope { module, .. }) => {
-                    let (_, pos) = self
-                        .symbol_table
-                        .resolve_import(&module)
-                        .ok_or_else(|| format!("no module named {module} found."))?;
+        self.emit(Opcode::Constructor, &[name, num_members]);
+
+        Ok(())
+    }
+
+    fn load_prototype(&mut self, class: &str) {
+        let symbol = self
+            .symbol_table
+            .resolve(&prototype_name(class))
+            .expect("classes are defined with their prototype");
+
+        self.load_symbol(&symbol);
+    }
+
+    // Methods are compiled like functions taking `self` as their first
+    // parameter, which they can assign.
+    fn compile_function(
+        &mut self,
+        Lambda {
+            parameters,
+            body,
+            name,
+            generator,
+        }: Lambda,
+        bound: bool,
+    ) -> Result<(), String> {
+        self.enter_scope();
+        self.scopes[self.scope_index].generator = generator;
+
+        if !name.is_empty() {
+            self.symbol_table.define_function_name(&name);
+        }
+
+        let num_parameters = parameters.len();
+
+        for (i, p) in parameters.iter().enumerate() {
+            self.symbol_table.define(p, bound && i == 0);
+        }
+
+        let trailing_loop = ends_with_loop(&body);
+
+        self.compile_block_statements(body)?;
+
+        if self.last_instruction_is(Opcode::Pop) && !trailing_loop {
+            self.replace_last_pop_with(Opcode::ReturnValue);
+        }
+
+        if self.last_instruction_is(Opcode::PopNoRet) {
+            self.replace_last_pop_with(Opcode::Return);
+        }
+
+        if !self.last_instruction_is(Opcode::ReturnValue)
+            && !self.last_instruction_is(Opcode::Return)
+        {
+            self.emit_op(Opcode::Return);
+        }
+
+        let free_symbols = self.symbol_table.free_symbols.clone();
+        let num_locals = self.symbol_table.num_definitions;
+        let info = self.function_info(&name);
+        let instructions = self.leave_scope();
+
+        for symbol in &free_symbols {
+            self.load_symbol(symbol);
+        }
+
+        let compiled_fn = Object::CompiledFunction(CompiledFunction {
+            instructions,
+            num_locals,
+            num_parameters,
+            generator,
+            info,
+        });
+
+        let idx = self.add_constant(compiled_fn);
+        self.emit(Opcode::Closure, &[idx, free_symbols.len()]);
+
+        Ok(())
+    }
+
+    fn emit_operator(&mut self, operator: Operator) -> Result<(), String> {
+        match operator {
+            Operator::Add => self.emit_op(Opcode::Add),
+            Operator::Sub => self.emit_op(Opcode::Sub),
+            Operator::Mul => self.emit_op(Opcode::Mul),
+            Operator::Div => self.emit_op(Opcode::Div),
+            Operator::Mod => self.emit_op(Opcode::Mod),
+            Operator::Pow => self.emit_op(Opcode::Pow),
+            Operator::FloorDiv => self.emit_op(Opcode::FloorDiv),
+            Operator::BitXor => self.emit_op(Opcode::BitXor),
+            Operator::BitAnd => self.emit_op(Opcode::BitAnd),
+            Operator::BitOr => self.emit_op(Opcode::BitOr),
+            Operator::Shr => self.emit_op(Opcode::Shr),
+            Operator::Shl => self.emit_op(Opcode::Shl),
+            Operator::Gt => self.emit_op(Opcode::GreaterThan),
+            Operator::GtEq => self.emit_op(Opcode::GreaterThanEqual),
+            Operator::Lt => self.emit_op(Opcode::LessThan),
+            Operator::LtEq => self.emit_op(Opcode::LessThanEqual),
+            Operator::Eq => self.emit_op(Opcode::Equal),
+            Operator::NotEq => self.emit_op(Opcode::NotEqual),
+            Operator::And => self.emit_op(Opcode::And),
+            Operator::Or => self.emit_op(Opcode::Or),
+            Operator::In => self.emit_op(Opcode::In),
+            Operator::NotIn => {
+                self.emit_op(Opcode::In);
+                self.emit_op(Opcode::Bang)
+            }
+            _ => return Err(format!("unknown operator: \"{operator}\"")),
+        };
+
+        Ok(())
+    }
+
+    fn compile_enum_variant(&mut self, symbol: &Symbol, member: Expression) -> Result<(), String> {
+        let (name, arguments) = Scope::split_member(member)?;
+        let arguments = arguments.unwrap_or_default();
+
+        self.load_symbol(symbol);
+
+        let n = arguments.len();
+
+        for arg in arguments {
+            self.compile(Node::Expr(arg))?;
+        }
+
+        let name = self.add_constant(Object::str(name));
+        self.emit(Opcode::Variant, &[name, n]);
+
+        Ok(())
+    }
+
+    fn compile_static_member(&mut self, class: &str, member: Expression) -> Result<(), String> {
+        let (name, arguments) = Scope::split_member(member)?;
 
-                    self.emit(Opcode::Scope, &[pos]);
+        let symbol = self
+            .symbol_table
+            .resolve(&format!("{class}::{name}"))
+            .ok_or_else(|| format!("no static member `{name}` found in class \"{class}\""))?;
 
-                    todo!();
+        self.load_symbol(&symbol);
+
+        if let Some(arguments) = arguments {
+            let n = arguments.len();
+
+            for arg in arguments {
+                self.compile(Node::Expr(arg))?;
+            }
+
+            self.emit(Opcode::Call, &[n]);
+        }
+
+        Ok(())
+    }
+
+    fn compile_import(
+        &mut self,
+        Import {
+            path,
+            class,
+            alias,
+            items,
+        }: Import,
+    ) -> Result<(), String> {
+        let mut modules = std::mem::take(&mut self.modules);
+        let mut compiled = false;
+
+        let module = modules.load(&path, |modules, program| {
+            compiled = true;
+
+            std::mem::swap(&mut self.modules, modules);
+            let module = self.compile_module(&path, program);
+            std::mem::swap(&mut self.modules, modules);
+
+            module
+        });
+
+        self.modules = modules;
+        let module = module?;
+
+        // Cached modules already ran where they were first imported.
+        if compiled {
+            self.emit(Opcode::Closure, &[module.function, 0]);
+            self.emit(Opcode::Call, &[0]);
+            self.emit(Opcode::SetGlobal, &[module.global]);
+        }
+
+        match items {
+            None => {
+                let name = alias.unwrap_or_else(|| module_name(&path));
+
+                self.symbol_table.define_import(
+                    name.clone(),
+                    CompiledModule {
+                        name,
+                        class,
+                        ..module
+                    },
+                );
+            }
+            Some(ImportItems::All) => {
+                // Static members come along with their class.
+                for export in module.exports.iter().filter(|name| !name.contains("::")) {
+                    self.import_name(&module, export, export)?;
                 }
+            }
+            Some(ImportItems::Names(names)) => {
+                for item in names {
+                    self.import_name(&module, &item.name, item.binding())?;
+                }
+            }
+        }
 
-                Expression::Constructor(Constructor { constructable }) => {
-                    match constructable {
-                        Constructable::Identifier(Identifier { ref value }) => {
-                            let class = self
-                                .symbol_table
-                                .resolve_type(value)
-                                .ok_or_else(|| format!("no class named \"{value}\" found."))?;
-
-                            (!class.initializers.is_empty())
-                                .then_some(())
-                                .ok_or_else(|| {
-                                    format!(
-                                        "cannot initialize class with 0 variables. required: {}",
-                                        class.initializers.len()
-                                    )
-                                })?;
-
-                            for stmt in class.body {
-                                match stmt {
-                                    ClassStatement::Variable(decl) => {
-                                        if let Some(value) = decl.value {
-                                            self.compile(Node::Expr(value))?;
-                                        } else {
-                                            self.emit_op(Opcode::Nil);
-                                        }
-
-                                        self.emit(
-                                            Opcode::ClassMember,
-                                            &[hash_method_name(&decl.name), 0],
-                                        );
-                                    }
-
-                                    ClassStatement::Method(func) => {
-                                        self.enter_scope();
-
-                                        self.symbol_table.define_function_name(&func.name);
-
-                                        let num_parameters = func.parameters.len();
-
-                                        for p in func.parameters {
-                                            self.symbol_table.define(&p, false);
-                                        }
-
-                                        self.compile_block_statements(func.body)?;
-
-                                        if self.last_instruction_is(Opcode::Pop) {
-                                            self.replace_last_pop_with(Opcode::ReturnValue);
-                                        }
-
-                                        if self.last_instruction_is(Opcode::PopNoRet) {
-                                            self.replace_last_pop_with(Opcode::Return);
-                                        }
-
-                                        if !self.last_instruction_is(Opcode::ReturnValue)
-                                            && !self.last_instruction_is(Opcode::Return)
-                                        {
-                                            self.emit_op(Opcode::Return);
-                                        }
-
-                                        let free_symbols = self.symbol_table.free_symbols.clone();
-                                        let num_locals = self.symbol_table.num_definitions;
-                                        let instructions = self.leave_scope();
-
-                                        for symbol in &free_symbols {
-                                            self.load_symbol(symbol);
-                                        }
-
-                                        let compiled_fn = Object::compiled_fn(
-                                            instructions,
-                                            num_locals,
-                                            num_parameters,
-                                        );
-
-                                        let idx = self.add_constant(compiled_fn);
-                                        self.emit(Opcode::Closure, &[idx, free_symbols.len()]);
-
-                                        self.emit(
-                                            Opcode::ClassMember,
-                                            &[hash_method_name(&func.name), 1],
-                                        );
-                                    }
-                                }
-                            }
-                        }
+        Ok(())
+    }
 
-                        Constructable::Call(_) | Constructable::Scope(_) => {}
-                    };
+    // Modules compile to a function of their own, run once where they're first
+    // imported. Their top level bindings are globals of their own, which the
+    // importer can only reach through the returned exports.
+    fn compile_module(&mut self, path: &str, program: Node) -> Result<CompiledModule, String> {
+        let name = module_name(path);
+
+        let builtins = self.builtin_symbol_table();
+        let importer = std::mem::replace(&mut self.symbol_table, builtins);
+        self.importers.push(importer);
+        let importer_exports = std::mem::take(&mut self.exports);
+
+        self.enter_scope();
+        let importer_module = self.module.replace((self.scope_index, name.clone()));
+
+        let prefix = format!("{path}::");
+        let natives = self
+            .natives
+            .iter()
+            .filter_map(|(name, _)| {
+                name.strip_prefix(&prefix)
+                    .map(|native| (native.to_string(), name.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        for (native, qualified) in natives {
+            let builtin = self.symbol_table.resolve(&qualified).unwrap();
+            self.load_symbol(&builtin);
+
+            let symbol = self.define(&native, false);
+            self.store_symbol(&symbol);
+            self.exports.push(native);
+        }
+
+        let compiled = self.compile(program).and_then(|()| self.compile_exports());
+
+        let num_locals = self.symbol_table.num_definitions;
+        let info = self.function_info(&format!("module {name}"));
+        let instructions = self.leave_scope();
+        self.module = importer_module;
+
+        self.symbol_table = self.importers.pop().unwrap();
+        let exports = std::mem::replace(&mut self.exports, importer_exports);
+
+        let (types, interfaces) = compiled?;
+
+        let global = self
+            .importers
+            .first_mut()
+            .unwrap_or(&mut self.symbol_table)
+            .define_global(&format!("module {name}"))
+            .index;
+
+        let function = self.add_constant(Object::CompiledFunction(CompiledFunction {
+            instructions,
+            num_locals,
+            num_parameters: 0,
+            generator: false,
+            info,
+        }));
+
+        Ok(CompiledModule {
+            name,
+            function,
+            global,
+            exports,
+            types,
+            interfaces,
+            class: false,
+        })
+    }
+
+    // Returns the exports as a dict keyed by name. Classes are exported as their
+    // declarations, and their prototypes under an unnameable key.
+    fn compile_exports(&mut self) -> Result<ExportedDecls, String> {
+        let mut types = Vec::new();
+        let mut interfaces = Vec::new();
+        let mut n = 0;
+
+        for name in self.exports.clone() {
+            if let Some(decl) = self.symbol_table.resolve_type(&name) {
+                let prototype = prototype_name(&name);
+                let symbol = self.symbol_table.resolve(&prototype).unwrap();
+
+                let key = self.add_constant(Object::str(prototype));
+                self.emit(Opcode::Constant, &[key]);
+                self.load_symbol(&symbol);
+                n += 1;
+
+                types.push((name, decl));
+                continue;
+            }
+
+            if let Some(decl) = self.symbol_table.resolve_interface(&name) {
+                interfaces.push((name.clone(), decl));
+            }
+
+            let symbol = self
+                .symbol_table
+                .resolve(&name)
+                .ok_or_else(|| format!("undefined variable {name}"))?;
+
+            let key = self.add_constant(Object::Str(Str { value: name }));
+            self.emit(Opcode::Constant, &[key]);
+            self.load_symbol(&symbol);
+            n += 1;
+        }
+
+        self.emit(Opcode::Dict, &[n]);
+        self.emit_op(Opcode::ReturnValue);
 
-                    todo!();
+        Ok((types, interfaces))
+    }
+
+    fn import_name(
+        &mut self,
+        module: &CompiledModule,
+        name: &str,
+        binding: &str,
+    ) -> Result<(), String> {
+        if !module.exports.iter().any(|export| export == name) {
+            return Err(format!(
+                "module \"{}\" does not export \"{name}\"",
+                module.name
+            ));
+        }
+
+        if let Some((_, decl)) = module.types.iter().find(|(class, _)| class == name) {
+            self.symbol_table
+                .define_type(binding.to_string(), decl.clone());
+            self.bind_export(module, &prototype_name(name), &prototype_name(binding));
+
+            let prefix = format!("{name}::");
+
+            for export in &module.exports {
+                if let Some(member) = export.strip_prefix(&prefix) {
+                    self.bind_export(module, export, &format!("{binding}::{member}"));
                 }
-            },
+            }
+
+            return Ok(());
+        }
+
+        if let Some((_, decl)) = module.interfaces.iter().find(|(i, _)| i == name) {
+            self.symbol_table
+                .define_interface(binding.to_string(), decl.clone());
+        }
+
+        self.bind_export(module, name, binding);
+
+        Ok(())
+    }
+
+    fn bind_export(&mut self, module: &CompiledModule, name: &str, binding: &str) {
+        self.emit(Opcode::GetGlobal, &[module.global]);
+
+        let key = self.add_constant(Object::Str(Str {
+            value: name.to_string(),
+        }));
+        self.emit(Opcode::Constant, &[key]);
+        self.emit_op(Opcode::Index);
+
+        let symbol = self.define(binding, false);
+        self.store_symbol(&symbol);
+    }
+
+    fn compile_module_member(&mut self, module: &str, member: Expression) -> Result<(), String> {
+        let (name, arguments) = Scope::split_member(member)?;
+
+        if let Some(imported) = self.symbol_table.resolve_import(module) {
+            if !imported.exports.contains(&name) {
+                return Err(format!("member '{name}' not found in module '{module}'"));
+            }
+
+            self.emit(Opcode::GetGlobal, &[imported.global]);
+
+            let key = self.add_constant(Object::Str(Str { value: name }));
+            self.emit(Opcode::Constant, &[key]);
+            self.emit_op(Opcode::Index);
+        } else {
+            let prefix = format!("{module}::");
+
+            if !self
+                .natives
+                .iter()
+                .any(|(native, _)| native.starts_with(&prefix))
+            {
+                return Err(format!("no module named {module} found."));
+            }
+
+            let symbol = self
+                .symbol_table
+                .resolve(&format!("{prefix}{name}"))
+                .filter(|symbol| symbol.scope == SymbolScope::Builtin)
+                .ok_or_else(|| format!("member '{name}' not found in module '{module}'"))?;
+
+            self.load_symbol(&symbol);
+        }
+
+        if let Some(arguments) = arguments {
+            let n = arguments.len();
+
+            for arg in arguments {
+                self.compile(Node::Expr(arg))?;
+            }
+
+            self.emit(Opcode::Call, &[n]);
         }
 
         Ok(())
     }
 
+    // Defines `name` in the current scope. At the top level of a module it gets
+    // a slot among the globals, so that the module's functions can assign it.
+    fn define(&mut self, name: &str, mutable: bool) -> Symbol {
+        match &self.module {
+            Some((scope, module)) if *scope == self.scope_index => {
+                let global = self
+                    .importers
+                    .first_mut()
+                    .unwrap()
+                    .define_global(&format!("{module} {name}"));
+
+                self.symbol_table.define_alias(name, global.index, mutable)
+            }
+            _ => self.symbol_table.define(name, mutable),
+        }
+    }
+
+    // A function's own name has no slot to store to, and isn't mutable anyway.
+    fn resolve_assignable(&mut self, name: &str) -> Result<Symbol, String> {
+        let symbol = self
+            .symbol_table
+            .resolve(name)
+            .ok_or_else(|| format!("undefined variable {name}"))?;
+
+        if symbol.scope == SymbolScope::Function {
+            return Err(format!("identifier is not mutable: {name}"));
+        }
+
+        Ok(symbol)
+    }
+
+    fn store_symbol(&mut self, symbol: &Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Opcode::SetGlobal, &[symbol.index]),
+            SymbolScope::Free => self.emit(Opcode::SetFree, &[symbol.index]),
+            _ => self.emit(Opcode::SetLocal, &[symbol.index]),
+        };
+    }
+
     fn load_symbol(&mut self, symbol: &Symbol) {
         match symbol.scope {
             SymbolScope::Global => self.emit(Opcode::GetGlobal, &[symbol.index]),
@@ -771,6 +1596,11 @@ impl Compiler {
         Bytecode {
             instructions: self.current_instructions().clone(),
             constants: self.constants.clone(),
+            natives: self
+                .natives
+                .iter()
+                .map(|(_, native)| native.clone())
+                .collect(),
         }
     }
 
@@ -867,20 +1697,25 @@ impl Compiler {
         self.scopes[self.scope_index].instructions.as_mut()
     }
 
-    fn enter_scope(&mut self) {
-        let scope = CompilationScope {
-            instructions: Vec::new(),
-            last_instruction: EmittedInstruction {
-                opcode: Opcode::Constant,
-                position: 0,
-            },
-            previous_instruction: EmittedInstruction {
-                opcode: Opcode::Constant,
-                position: 0,
-            },
-        };
+    // The names of the variables of the function being compiled, which only
+    // debuggers need.
+    fn function_info(&self, name: &str) -> Option<Arc<FunctionInfo>> {
+        self.debug.then(|| {
+            Arc::new(FunctionInfo {
+                name: name.to_string(),
+                locals: self.symbol_table.names(),
+                free: self
+                    .symbol_table
+                    .free_symbols
+                    .iter()
+                    .map(|symbol| symbol.name.clone())
+                    .collect(),
+            })
+        })
+    }
 
-        self.scopes.push(scope);
+    fn enter_scope(&mut self) {
+        self.scopes.push(CompilationScope::new(Opcode::Constant));
         self.scope_index += 1;
 
         self.symbol_table = SymbolTable::new_enclosed(self.symbol_table.clone());
@@ -905,7 +1740,19 @@ impl Compiler {
     }
 }
 
+// Loops end with an unreachable `Pop`, which must not be mistaken for the
+// value of a trailing expression statement.
+fn ends_with_loop(body: &BlockStatement) -> bool {
+    matches!(body.last(), Some(Statement::While(_) | Statement::For(_)))
+}
+
+// The symbol holding the methods of `class`, which isn't a valid identifier.
+fn prototype_name(class: &str) -> String {
+    format!("class {class}")
+}
+
 pub struct Bytecode {
     pub instructions: Instructions,
     pub constants: Vec<Object>,
+    pub natives: Vec<NativeFunction>,
 }
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use crate::{ast::ClassDecl, object::CompiledModule};
+use crate::{
+    ast::{ClassDecl, InterfaceDecl},
+    object::CompiledModule,
+};
 
 #[derive(strum::Display, Clone, Copy, PartialEq, Eq, Debug)]
 #[strum(serialize_all = "UPPERCASE")]
@@ -41,6 +44,7 @@ pub struct SymbolTable {
     pub free_symbols: Vec<Symbol>,
 
     types: Vec<(String, ClassDecl)>,
+    interfaces: Vec<(String, InterfaceDecl)>,
     imports: Vec<(String, CompiledModule)>,
 }
 
@@ -54,6 +58,7 @@ impl SymbolTable {
             free_symbols: Vec::new(),
 
             types: Vec::new(),
+            interfaces: Vec::new(),
             imports: Vec::new(),
         }
     }
@@ -67,6 +72,7 @@ impl SymbolTable {
             free_symbols: Vec::new(),
 
             types: Vec::new(),
+            interfaces: Vec::new(),
             imports: Vec::new(),
         }
     }
@@ -84,6 +90,15 @@ impl SymbolTable {
         symbol
     }
 
+    /// Defines `name` as the global in slot `index`, which an outer table
+    /// allocated.
+    pub fn define_alias(&mut self, name: &str, index: usize, mutable: bool) -> Symbol {
+        let symbol = Symbol::new(name, SymbolScope::Global, index, mutable);
+        self.store.insert(name.to_string(), symbol.clone());
+
+        symbol
+    }
+
     pub fn define_builtin(&mut self, name: &str, index: usize) -> Symbol {
         let symbol = Symbol::new(name, SymbolScope::Builtin, index, false);
         self.store.insert(name.to_string(), symbol.clone());
@@ -131,6 +146,27 @@ impl SymbolTable {
         }
     }
 
+    /// The names of the variables defined in this table, by index. Slots whose
+    /// variable was deleted or redefined are left blank.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![String::new(); self.num_definitions];
+
+        for symbol in self.store.values() {
+            if matches!(symbol.scope, SymbolScope::Global | SymbolScope::Local) {
+                if let Some(name) = names.get_mut(symbol.index) {
+                    name.clone_from(&symbol.name);
+                }
+            }
+        }
+
+        names
+    }
+
+    /// Looks `name` up in this table alone, unlike `resolve`.
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        self.store.get(name)
+    }
+
     pub fn delete(&mut self, name: &str) -> Option<Symbol> {
         if let Some(symbol) = self.store.remove(name) {
             Some(symbol)
@@ -148,6 +184,13 @@ impl SymbolTable {
         pos
     }
 
+    pub fn define_interface(&mut self, name: String, decl: InterfaceDecl) -> usize {
+        let pos = self.interfaces.len();
+        self.interfaces.push((name, decl));
+
+        pos
+    }
+
     pub fn define_import(&mut self, name: String, module: CompiledModule) -> usize {
         let pos = self.imports.len();
         self.imports.push((name, module));
@@ -155,6 +198,15 @@ impl SymbolTable {
         pos
     }
 
+    /// Defines `name` in the outermost table, where it's always a global.
+    pub fn define_global(&mut self, name: &str) -> Symbol {
+        if let Some(outer) = self.outer.as_mut() {
+            return outer.define_global(name);
+        }
+
+        self.define(name, false)
+    }
+
     pub fn resolve_type(&self, name: &str) -> Option<ClassDecl> {
         for (class_name, node) in &self.types {
             if class_name == name {
@@ -162,16 +214,30 @@ impl SymbolTable {
             }
         }
 
+        self.outer
+            .as_ref()
+            .and_then(|outer| outer.resolve_type(name))
+    }
+
+    pub fn resolve_interface(&self, name: &str) -> Option<InterfaceDecl> {
+        for (interface_name, node) in &self.interfaces {
+            if interface_name == name {
+                return Some(node.clone());
+            }
+        }
+
         None
     }
 
-    pub fn resolve_import(&self, name: &str) -> Option<(CompiledModule, usize)> {
-        for (pos, (import, node)) in self.imports.iter().enumerate() {
+    pub fn resolve_import(&self, name: &str) -> Option<CompiledModule> {
+        for (import, node) in &self.imports {
             if import == name {
-                return Some((node.clone(), pos));
+                return Some(node.clone());
             }
         }
 
-        None
+        self.outer
+            .as_ref()
+            .and_then(|outer| outer.resolve_import(name))
     }
 }
@@ -1,7 +1,7 @@
 use pretty_assertions::assert_eq;
 
 use super::*;
-use crate::{code::*, lexer::Lexer, parser::Parser};
+use crate::{ast::EnumVariant, code::*, lexer::Lexer, parser::Parser};
 
 struct TestCase {
     input: String,
@@ -148,6 +148,39 @@ fn test_integer_arithmetic() {
                 make(Opcode::Pop, &[]),
             ]),
         },
+        TestCase {
+            input: "2 ** 3 // 4".to_string(),
+            expected_constants: Vec::from([Object::int(2), Object::int(3), Object::int(4)]),
+            expected_instructions: Vec::from([
+                make(Opcode::Constant, &[0]),
+                make(Opcode::Constant, &[1]),
+                make(Opcode::Pow, &[]),
+                make(Opcode::Constant, &[2]),
+                make(Opcode::FloorDiv, &[]),
+                make(Opcode::Pop, &[]),
+            ]),
+        },
+        TestCase {
+            input: "~1".to_string(),
+            expected_constants: Vec::from([Object::int(1)]),
+            expected_instructions: Vec::from([
+                make(Opcode::Constant, &[0]),
+                make(Opcode::BitNot, &[]),
+                make(Opcode::Pop, &[]),
+            ]),
+        },
+        TestCase {
+            input: "1 not in [2]".to_string(),
+            expected_constants: Vec::from([Object::int(1), Object::int(2)]),
+            expected_instructions: Vec::from([
+                make(Opcode::Constant, &[0]),
+                make(Opcode::Constant, &[1]),
+                make(Opcode::Array, &[1]),
+                make(Opcode::In, &[]),
+                make(Opcode::Bang, &[]),
+                make(Opcode::Pop, &[]),
+            ]),
+        },
     ]);
 }
 
@@ -166,11 +199,11 @@ fn test_boolean_expressions() {
         },
         TestCase {
             input: "1 < 2".to_string(),
-            expected_constants: Vec::from([Object::int(2), Object::int(1)]),
+            expected_constants: Vec::from([Object::int(1), Object::int(2)]),
             expected_instructions: Vec::from([
                 make(Opcode::Constant, &[0]),
                 make(Opcode::Constant, &[1]),
-                make(Opcode::GreaterThan, &[]),
+                make(Opcode::LessThan, &[]),
                 make(Opcode::Pop, &[]),
             ]),
         },
@@ -186,11 +219,11 @@ fn test_boolean_expressions() {
         },
         TestCase {
             input: "1 <= 2".to_string(),
-            expected_constants: Vec::from([Object::int(2), Object::int(1)]),
+            expected_constants: Vec::from([Object::int(1), Object::int(2)]),
             expected_instructions: Vec::from([
                 make(Opcode::Constant, &[0]),
                 make(Opcode::Constant, &[1]),
-                make(Opcode::GreaterThanEqual, &[]),
+                make(Opcode::LessThanEqual, &[]),
                 make(Opcode::Pop, &[]),
             ]),
         },
@@ -299,6 +332,83 @@ fn test_conditionals() {
     ]);
 }
 
+#[test]
+fn test_nil_safe_operators() {
+    run_compiler_tests(&[
+        TestCase {
+            input: "nil ?? 1;".to_string(),
+            expected_constants: Vec::from([Object::int(1)]),
+            expected_instructions: Vec::from([
+                make(Opcode::Nil, &[]),
+                make(Opcode::JumpNil, &[7]),
+                make(Opcode::Jump, &[11]),
+                make(Opcode::Pop, &[]),
+                make(Opcode::Constant, &[0]),
+                make(Opcode::PopNoRet, &[]),
+            ]),
+        },
+        TestCase {
+            input: "[1]?[0];".to_string(),
+            expected_constants: Vec::from([Object::int(1), Object::int(0)]),
+            expected_instructions: Vec::from([
+                make(Opcode::Constant, &[0]),
+                make(Opcode::Array, &[1]),
+                make(Opcode::JumpNil, &[13]),
+                make(Opcode::Constant, &[1]),
+                make(Opcode::Index, &[]),
+                make(Opcode::PopNoRet, &[]),
+            ]),
+        },
+        TestCase {
+            input: "[1]?[0][1];".to_string(),
+            expected_constants: Vec::from([Object::int(1), Object::int(0), Object::int(1)]),
+            expected_instructions: Vec::from([
+                make(Opcode::Constant, &[0]),
+                make(Opcode::Array, &[1]),
+                make(Opcode::JumpNil, &[17]),
+                make(Opcode::Constant, &[1]),
+                make(Opcode::Index, &[]),
+                make(Opcode::Constant, &[2]),
+                make(Opcode::Index, &[]),
+                make(Opcode::PopNoRet, &[]),
+            ]),
+        },
+    ]);
+}
+
+#[test]
+fn test_enums() {
+    run_compiler_tests(&[TestCase {
+        input: "enum A { B(x), C }; A::B(1);".to_string(),
+        expected_constants: Vec::from([
+            Object::Enum(Arc::new(Enum {
+                name: "A".to_string(),
+                variants: Vec::from([
+                    EnumVariant {
+                        name: "B".to_string(),
+                        fields: Vec::from(["x".to_string()]),
+                    },
+                    EnumVariant {
+                        name: "C".to_string(),
+                        fields: Vec::new(),
+                    },
+                ]),
+                methods: HashMap::new(),
+            })),
+            Object::int(1),
+            Object::str("B".to_string()),
+        ]),
+        expected_instructions: Vec::from([
+            make(Opcode::Enum, &[0, 0]),
+            make(Opcode::SetGlobal, &[0]),
+            make(Opcode::GetGlobal, &[0]),
+            make(Opcode::Constant, &[1]),
+            make(Opcode::Variant, &[2, 1]),
+            make(Opcode::PopNoRet, &[]),
+        ]),
+    }]);
+}
+
 #[test]
 fn test_while_loop() {
     run_compiler_tests(&[TestCase {
@@ -939,6 +1049,37 @@ fn test_builtins() {
 #[test]
 fn test_closures() {
     run_compiler_tests(&[
+        TestCase {
+            input: "fn(a) { fn() { a = 2 } }".to_string(),
+            expected_constants: Vec::from([
+                Object::int(2),
+                Object::compiled_fn(
+                    [
+                        make(Opcode::Constant, &[0]),
+                        make(Opcode::Dup, &[]),
+                        make(Opcode::SetFree, &[0]),
+                        make(Opcode::ReturnValue, &[]),
+                    ]
+                    .concat(),
+                    0,
+                    0,
+                ),
+                Object::compiled_fn(
+                    [
+                        make(Opcode::GetLocal, &[0]),
+                        make(Opcode::Closure, &[1, 1]),
+                        make(Opcode::ReturnValue, &[]),
+                    ]
+                    .concat(),
+                    1,
+                    1,
+                ),
+            ]),
+            expected_instructions: Vec::from([
+                make(Opcode::Closure, &[2, 0]),
+                make(Opcode::Pop, &[]),
+            ]),
+        },
         TestCase {
             input: "fn(a) { fn(b) { a + b } }".to_string(),
             expected_constants: Vec::from([
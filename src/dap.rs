@@ -0,0 +1,533 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread,
+};
+
+use panda::{
+    DebugHook, Debugger, Engine, Error, Interpreter, InterruptHandle, Io, Resume, Snapshot,
+};
+use serde_json::{json, Value};
+
+use crate::protocol::{read_message, write_message};
+
+// Programs run on a single thread, which is the only one editors are told of.
+const THREAD_ID: u64 = 1;
+
+// The scopes of each frame, numbered after it in `variablesReference`s.
+const SCOPES: [&str; 3] = ["Locals", "Free variables", "Globals"];
+
+/// Serves the Debug Adapter Protocol, running the program an editor launches
+/// on a thread of its own so requests are answered while it runs.
+pub fn serve(mut input: impl BufRead, output: impl Write + Send + 'static) -> io::Result<()> {
+    let mut session = Session::new(Client::new(output));
+
+    while let Some(request) = read_message(&mut input)? {
+        let command = request["command"].as_str().unwrap_or_default().to_string();
+        let result = session.handle(&command, &request["arguments"]);
+
+        session.client.respond(&request, result);
+
+        match command.as_str() {
+            "initialize" => session.client.event("initialized", json!({})),
+            "disconnect" => break,
+            _ => session.start(),
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends messages to the editor, both from the thread answering its requests
+/// and from the one running the program.
+#[derive(Clone)]
+struct Client {
+    seq: Arc<AtomicU64>,
+    output: Arc<Mutex<dyn Write + Send>>,
+}
+
+impl Client {
+    fn new(output: impl Write + Send + 'static) -> Self {
+        Self {
+            seq: Arc::new(AtomicU64::new(0)),
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+
+    fn send(&self, mut message: Value) {
+        message["seq"] = json!(self.seq.fetch_add(1, Ordering::Relaxed) + 1);
+
+        // The editor has gone if this fails, which the next read finds out.
+        let _ = write_message(&mut *lock(&self.output), &message);
+    }
+
+    fn respond(&self, request: &Value, result: Result<Value, String>) {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+
+        self.send(response);
+    }
+
+    fn event(&self, event: &str, body: Value) {
+        let mut message = json!({ "type": "event", "event": event });
+        message["body"] = body;
+
+        self.send(message);
+    }
+}
+
+struct Launch {
+    program: String,
+    engine: Engine,
+    stop_on_entry: bool,
+}
+
+struct Session {
+    client: Client,
+    // The lines to break at by the source they're in, of which the engines
+    // only know the program's own.
+    breakpoints: BTreeMap<PathBuf, BTreeSet<usize>>,
+    // Programs start once they're launched and the editor has set its
+    // breakpoints, whichever comes last.
+    launch: Option<Launch>,
+    configured: bool,
+    running: Option<Running>,
+}
+
+/// The program that was started, and the way to reach it on its thread.
+struct Running {
+    program: String,
+    paused: Arc<Mutex<Option<Snapshot>>>,
+    resume: Sender<Resume>,
+    breakpoints: Sender<BTreeSet<usize>>,
+    interrupt: InterruptHandle,
+}
+
+impl Session {
+    const fn new(client: Client) -> Self {
+        Self {
+            client,
+            breakpoints: BTreeMap::new(),
+            launch: None,
+            configured: false,
+            running: None,
+        }
+    }
+
+    fn handle(&mut self, command: &str, args: &Value) -> Result<Value, String> {
+        match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsEvaluateForHovers": true,
+                "supportsTerminateRequest": true,
+            })),
+            "launch" => self.launch(args),
+            "setBreakpoints" => self.set_breakpoints(args),
+            "configurationDone" => {
+                self.configured = true;
+                Ok(json!({}))
+            }
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "stackTrace" => self.stack_trace(),
+            "scopes" => scopes(args),
+            "variables" => self.variables(args),
+            "evaluate" => self.evaluate(args),
+            "continue" => self
+                .resume(Resume::Continue)
+                .map(|()| json!({ "allThreadsContinued": true })),
+            "next" => self.resume(Resume::StepOver).map(|()| json!({})),
+            "stepIn" => self.resume(Resume::StepInto).map(|()| json!({})),
+            "stepOut" => self.resume(Resume::StepOut).map(|()| json!({})),
+            "terminate" | "disconnect" => {
+                if let Some(running) = &self.running {
+                    running.interrupt.interrupt();
+                    let _ = self.resume(Resume::Continue);
+                }
+
+                Ok(json!({}))
+            }
+            _ => Err(format!("unsupported request `{command}`")),
+        }
+    }
+
+    fn launch(&mut self, args: &Value) -> Result<Value, String> {
+        let Some(program) = args["program"].as_str() else {
+            return Err("launch needs the path of a `program`".to_string());
+        };
+
+        let engine = args["engine"].as_str().map_or(Ok(Engine::VM), |engine| {
+            Engine::from_str(engine)
+                .map_err(|_| format!("unknown engine `{engine}`. expected vm or eval"))
+        })?;
+
+        self.launch = Some(Launch {
+            program: program.to_string(),
+            engine,
+            stop_on_entry: args["stopOnEntry"].as_bool().unwrap_or(false),
+        });
+
+        Ok(json!({}))
+    }
+
+    fn set_breakpoints(&mut self, args: &Value) -> Result<Value, String> {
+        let source = args["source"]["path"]
+            .as_str()
+            .map(source_path)
+            .ok_or("setBreakpoints needs the `path` of its `source`")?;
+
+        // Older editors send the lines on their own.
+        let lines: Vec<&Value> = args["breakpoints"].as_array().map_or_else(
+            || args["lines"].as_array().into_iter().flatten().collect(),
+            |breakpoints| breakpoints.iter().map(|bp| &bp["line"]).collect(),
+        );
+
+        let lines = lines
+            .into_iter()
+            .filter_map(Value::as_u64)
+            .filter_map(|line| usize::try_from(line).ok())
+            .collect::<BTreeSet<_>>();
+
+        let program = self
+            .launch
+            .as_ref()
+            .map(|launch| &launch.program)
+            .or_else(|| self.running.as_ref().map(|running| &running.program));
+
+        let debuggee = program.is_none_or(|program| source_path(program) == source);
+
+        let breakpoints = lines
+            .iter()
+            .map(|line| {
+                let mut breakpoint = json!({ "verified": debuggee, "line": line });
+
+                if !debuggee {
+                    breakpoint["message"] =
+                        json!("breakpoints can only be set in the program being debugged");
+                }
+
+                breakpoint
+            })
+            .collect::<Vec<_>>();
+
+        if debuggee {
+            if let Some(running) = &self.running {
+                let _ = running.breakpoints.send(lines.clone());
+            }
+        }
+
+        self.breakpoints.insert(source, lines);
+
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn stack_trace(&self) -> Result<Value, String> {
+        let snapshot = self.paused()?;
+        let program = self.running.as_ref().map(|running| &running.program);
+
+        let frames = snapshot
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(id, frame)| {
+                json!({
+                    "id": id,
+                    "name": frame.name,
+                    "line": frame.line,
+                    "column": 1,
+                    "source": { "path": program },
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+    }
+
+    fn variables(&self, args: &Value) -> Result<Value, String> {
+        let snapshot = self.paused()?;
+
+        let reference = args["variablesReference"]
+            .as_u64()
+            .and_then(|reference| usize::try_from(reference).ok())
+            .and_then(|reference| reference.checked_sub(1))
+            .ok_or("expected a `variablesReference`")?;
+
+        let frame = snapshot
+            .frames
+            .get(reference / SCOPES.len())
+            .ok_or("no such frame")?;
+
+        let variables = match reference % SCOPES.len() {
+            0 => &frame.locals,
+            1 => &frame.free,
+            _ => &snapshot.globals,
+        };
+
+        let variables = variables
+            .iter()
+            .map(|(name, value)| {
+                json!({
+                    "name": name,
+                    "value": value.inspect(),
+                    "type": value.kind(),
+                    "variablesReference": 0,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn evaluate(&self, args: &Value) -> Result<Value, String> {
+        let snapshot = self.paused()?;
+        let expression = args["expression"].as_str().unwrap_or_default();
+
+        // Expressions run in the frame the editor has selected.
+        let frame = if args["frameId"].is_null() {
+            0
+        } else {
+            frame_id(args)?
+        };
+
+        let snapshot = Snapshot {
+            frames: snapshot.frames.get(frame..).unwrap_or_default().to_vec(),
+            globals: snapshot.globals,
+        };
+
+        let value = snapshot
+            .evaluate(expression)
+            .map_err(|err| err.to_string())?;
+
+        Ok(json!({ "result": value.inspect(), "variablesReference": 0 }))
+    }
+
+    /// Starts the launched program once the editor is done configuring.
+    fn start(&mut self) {
+        if !self.configured || self.running.is_some() {
+            return;
+        }
+
+        let Some(launch) = self.launch.take() else {
+            return;
+        };
+
+        let (resume, resumes) = mpsc::channel();
+        let (breakpoints, updates) = mpsc::channel();
+        let paused = Arc::new(Mutex::new(None));
+
+        let mut panda = Interpreter::new(launch.engine);
+
+        panda.set_io(
+            Io::default()
+                .output(Output::new(&self.client, "stdout"))
+                .error(Output::new(&self.client, "stderr"))
+                .sandboxed(),
+        );
+
+        let mut debugger = frontend(
+            self.client.clone(),
+            Arc::clone(&paused),
+            resumes,
+            launch.stop_on_entry,
+        );
+        if let Some(lines) = self.breakpoints.get(&source_path(&launch.program)) {
+            debugger.set_breakpoints(lines.iter().copied());
+        }
+
+        panda.set_debugger(Hook { debugger, updates });
+
+        self.running = Some(Running {
+            program: launch.program.clone(),
+            paused,
+            resume,
+            breakpoints,
+            interrupt: panda.interrupt_handle(),
+        });
+
+        let client = self.client.clone();
+
+        // The evaluator needs as much stack as the main thread gets.
+        let program = thread::Builder::new().stack_size(8 << 20).spawn(move || {
+            let code = match panda.eval_file(&launch.program) {
+                Ok(_) => 0,
+                Err(Error::Exit(code)) => code,
+                Err(err) => {
+                    client.event(
+                        "output",
+                        json!({ "category": "stderr", "output": format!("{err}\n") }),
+                    );
+                    1
+                }
+            };
+
+            client.event("exited", json!({ "exitCode": code }));
+            client.event("terminated", json!({}));
+        });
+
+        if let Err(err) = program {
+            self.client.event(
+                "output",
+                json!({ "category": "stderr", "output": format!("{err}\n") }),
+            );
+            self.client.event("terminated", json!({}));
+        }
+    }
+
+    fn paused(&self) -> Result<Snapshot, String> {
+        self.running
+            .as_ref()
+            .and_then(|running| lock(&running.paused).clone())
+            .ok_or_else(|| "the program isn't paused".to_string())
+    }
+
+    fn resume(&self, resume: Resume) -> Result<(), String> {
+        let running = self.running.as_ref().ok_or("the program isn't running")?;
+
+        if lock(&running.paused).take().is_none() {
+            return Err("the program isn't paused".to_string());
+        }
+
+        running
+            .resume
+            .send(resume)
+            .map_err(|_| "the program has ended".to_string())
+    }
+}
+
+/// Reports each pause to the editor, then waits for it to say how the
+/// program goes on.
+fn frontend(
+    client: Client,
+    paused: Arc<Mutex<Option<Snapshot>>>,
+    resumes: Receiver<Resume>,
+    stop_on_entry: bool,
+) -> Debugger {
+    let mut entry = stop_on_entry;
+
+    let debugger = Debugger::new(move |snapshot, breakpoints| {
+        let reason = if std::mem::take(&mut entry) {
+            "entry"
+        } else if breakpoints.contains(&snapshot.line()) {
+            "breakpoint"
+        } else {
+            "step"
+        };
+
+        *lock(&paused) = Some(snapshot.clone());
+
+        client.event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+
+        // The session is gone if this fails, so the program may as well end.
+        resumes.recv().unwrap_or(Resume::Continue)
+    });
+
+    if stop_on_entry {
+        debugger.stop_on_entry()
+    } else {
+        debugger
+    }
+}
+
+// Sources are told apart by their canonical paths, where they exist.
+fn source_path(path: &str) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// Applies the breakpoints the editor sets while the program runs.
+struct Hook {
+    debugger: Debugger,
+    updates: Receiver<BTreeSet<usize>>,
+}
+
+impl DebugHook for Hook {
+    fn should_pause(&mut self, line: usize, depth: usize) -> bool {
+        if let Some(breakpoints) = self.updates.try_iter().last() {
+            self.debugger.set_breakpoints(breakpoints);
+        }
+
+        self.debugger.should_pause(line, depth)
+    }
+
+    fn pause(&mut self, snapshot: &Snapshot) {
+        self.debugger.pause(snapshot);
+    }
+}
+
+/// Sends what the program prints to the editor as `output` events, since
+/// stdout carries the protocol.
+struct Output {
+    client: Client,
+    category: &'static str,
+}
+
+impl Output {
+    fn new(client: &Client, category: &'static str) -> Self {
+        Self {
+            client: client.clone(),
+            category,
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.client.event(
+            "output",
+            json!({ "category": self.category, "output": String::from_utf8_lossy(buf) }),
+        );
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn scopes(args: &Value) -> Result<Value, String> {
+    let frame = frame_id(args)?;
+
+    let scopes = SCOPES
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            json!({
+                "name": name,
+                "variablesReference": frame * SCOPES.len() + i + 1,
+                "expensive": false,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "scopes": scopes }))
+}
+
+fn frame_id(args: &Value) -> Result<usize, String> {
+    args["frameId"]
+        .as_u64()
+        .and_then(|id| usize::try_from(id).ok())
+        .ok_or_else(|| "expected a `frameId`".to_string())
+}
+
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
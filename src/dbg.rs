@@ -0,0 +1,127 @@
+use std::{
+    collections::BTreeSet,
+    io::{stdin, stdout, Write},
+    process::exit,
+};
+
+use panda::{Debugger, Object, Resume, Snapshot};
+
+const HELP: &str = "\
+c, continue      run until the next breakpoint
+s, step          step into the next statement
+n, next          step over calls to the next statement
+o, out           run until the current function returns
+b, break [LINE]  set a breakpoint, or list them
+d, delete LINE   remove a breakpoint
+l, locals        print the locals of the current function
+f, free          print the free variables of the current function
+g, globals       print the globals
+bt, backtrace    print the call stack
+p, print EXPR    evaluate an expression
+w, watch EXPR    evaluate an expression at every pause
+u, unwatch N     remove the Nth watch expression
+q, quit          stop the program";
+
+/// The interactive frontend of `panda dbg`, which reads commands from stdin
+/// each time the program pauses.
+pub fn debugger(source: &str, breakpoints: &[usize]) -> Debugger {
+    let lines = source.lines().map(str::to_string).collect::<Vec<_>>();
+    let mut watches: Vec<String> = Vec::new();
+
+    let debugger = Debugger::new(move |snapshot, breakpoints| {
+        let line = snapshot.line();
+        let frame = &snapshot.frames[0];
+        let text = line
+            .checked_sub(1)
+            .and_then(|idx| lines.get(idx))
+            .map_or("", |text| text.trim());
+
+        println!("{} at line {line}: {text}", frame.name);
+
+        for (i, watch) in watches.iter().enumerate() {
+            println!("  {}: {watch} = {}", i + 1, evaluate(snapshot, watch));
+        }
+
+        loop {
+            print!("(dbg) ");
+            stdout().flush().unwrap();
+
+            let mut input = String::new();
+            if stdin().read_line(&mut input).unwrap_or(0) == 0 {
+                exit(0);
+            }
+
+            let input = input.trim();
+            let (command, arg) = input
+                .split_once(' ')
+                .map_or((input, ""), |(command, arg)| (command, arg.trim()));
+
+            match command {
+                "c" | "continue" => return Resume::Continue,
+                "s" | "step" => return Resume::StepInto,
+                "n" | "next" => return Resume::StepOver,
+                "o" | "out" => return Resume::StepOut,
+                "b" | "break" if arg.is_empty() => print_breakpoints(breakpoints),
+                "b" | "break" | "d" | "delete" => match arg.parse() {
+                    Ok(line) if command.starts_with('b') => {
+                        breakpoints.insert(line);
+                    }
+                    Ok(line) => {
+                        breakpoints.remove(&line);
+                    }
+                    Err(_) => println!("expected a line number, got `{arg}`"),
+                },
+                "l" | "locals" => print_variables(&frame.locals),
+                "f" | "free" => print_variables(&frame.free),
+                "g" | "globals" => print_variables(&snapshot.globals),
+                "bt" | "backtrace" => {
+                    for (i, frame) in snapshot.frames.iter().enumerate() {
+                        println!("#{i} {} at line {}", frame.name, frame.line);
+                    }
+                }
+                "p" | "print" => println!("{}", evaluate(snapshot, arg)),
+                "w" | "watch" => {
+                    println!("{}", evaluate(snapshot, arg));
+                    watches.push(arg.to_string());
+                }
+                "u" | "unwatch" => match arg.parse::<usize>() {
+                    Ok(n) if (1..=watches.len()).contains(&n) => {
+                        watches.remove(n - 1);
+                    }
+                    _ => println!("no watch expression numbered `{arg}`"),
+                },
+                "q" | "quit" => exit(0),
+                "h" | "help" => println!("{HELP}"),
+                "" => {}
+                _ => println!("unknown command `{command}`. type `help` for the commands"),
+            }
+        }
+    });
+
+    if breakpoints.is_empty() {
+        return debugger.stop_on_entry();
+    }
+
+    breakpoints
+        .iter()
+        .fold(debugger, |debugger, &line| debugger.breakpoint(line))
+}
+
+fn evaluate(snapshot: &Snapshot, expression: &str) -> String {
+    match snapshot.evaluate(expression) {
+        Ok(value) => value.inspect(),
+        Err(err) => err.to_string(),
+    }
+}
+
+fn print_variables(variables: &[(String, Object)]) {
+    for (name, value) in variables {
+        println!("{name} = {}", value.inspect());
+    }
+}
+
+fn print_breakpoints(breakpoints: &BTreeSet<usize>) {
+    for line in breakpoints {
+        println!("breakpoint at line {line}");
+    }
+}
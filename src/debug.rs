@@ -0,0 +1,40 @@
+//! The internals the CLI's `debug` command prints.
+
+use crate::{
+    code::instructions_to_string, compiler::Compiler, error::Error, interpreter::parse,
+    interpreters::vm::VM,
+};
+
+/// # Errors
+///
+/// Fails if `source` doesn't parse.
+pub fn ast(source: &str) -> Result<String, Error> {
+    Ok(format!("{:#?}", parse(source)?))
+}
+
+/// # Errors
+///
+/// Fails if `source` doesn't parse or compile.
+pub fn bytecode(source: &str) -> Result<String, Error> {
+    let mut comp = Compiler::new();
+    comp.compile(parse(source)?).map_err(Error::Compile)?;
+
+    Ok(instructions_to_string(&comp.bytecode().instructions))
+}
+
+/// The stack of the VM once it ran `source`, which is printed even when the
+/// VM fails.
+///
+/// # Errors
+///
+/// Fails if `source` doesn't parse or compile.
+pub fn stack(source: &str) -> Result<(String, Option<Error>), Error> {
+    let mut comp = Compiler::new();
+    comp.compile(parse(source)?).map_err(Error::Compile)?;
+
+    let byte_code = comp.bytecode();
+    let mut machine = VM::new(&byte_code);
+    let result = machine.run().err().map(Error::Runtime);
+
+    Ok((format!("{:#?}", machine.get_stack()), result))
+}
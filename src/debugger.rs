@@ -0,0 +1,289 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Debug},
+};
+
+use crate::{
+    error::Error,
+    interpreter::parse,
+    interpreters::eval::Evaluator,
+    module::ModuleLoader,
+    object::{Error as ErrorObj, Object},
+};
+
+/// Called by the engines before each statement of a program run under a
+/// debugger, with the line it starts on and how many calls deep it runs.
+///
+/// Deciding whether to pause is kept apart from pausing, so the engines only
+/// gather what the program can see when a debugger stops it.
+pub trait DebugHook: Send {
+    fn should_pause(&mut self, line: usize, depth: usize) -> bool;
+
+    fn pause(&mut self, snapshot: &Snapshot);
+}
+
+impl Debug for dyn DebugHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DebugHook").finish_non_exhaustive()
+    }
+}
+
+/// A frame of the call stack of a paused program.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StackFrame {
+    pub name: String,
+    pub line: usize,
+    pub locals: Vec<(String, Object)>,
+    pub free: Vec<(String, Object)>,
+}
+
+/// What a paused program can see, with the innermost frame first.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Snapshot {
+    pub frames: Vec<StackFrame>,
+    pub globals: Vec<(String, Object)>,
+}
+
+impl Snapshot {
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.frames.first().map_or(0, |frame| frame.line)
+    }
+
+    /// Evaluates `expression` with the variables of the innermost frame in
+    /// scope. It runs on copies of their values, so it can't change them.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `expression` doesn't parse or fails to run.
+    pub fn evaluate(&self, expression: &str) -> Result<Object, Error> {
+        let mut evaluator = Evaluator::new_with_modules(ModuleLoader::default());
+
+        let frame = self.frames.first();
+        let variables = self
+            .globals
+            .iter()
+            .chain(frame.iter().flat_map(|frame| &frame.free))
+            .chain(frame.iter().flat_map(|frame| &frame.locals));
+
+        for (name, value) in variables {
+            evaluator.set_global(name, value.clone());
+        }
+
+        match evaluator.eval(parse(expression)?) {
+            Some(Object::Error(ErrorObj { value })) => Err(Error::Runtime(value)),
+            value => Ok(value.unwrap_or(Object::Nil)),
+        }
+    }
+}
+
+/// Pairs the values of variables with their names, leaving out the blank
+/// names of unnamed slots and the hidden ones the compiler defines.
+pub fn variables(names: &[String], values: &[Object]) -> Vec<(String, Object)> {
+    names
+        .iter()
+        .zip(values)
+        .filter(|(name, _)| !name.is_empty() && !name.contains(' '))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// The name a frame is shown with, given the name of its function.
+pub fn frame_name(function: Option<&str>) -> String {
+    match function {
+        None => "main".to_string(),
+        Some("") => "<lambda>".to_string(),
+        Some(name) => name.to_string(),
+    }
+}
+
+/// How a paused program goes on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Resume {
+    /// Runs until the next breakpoint.
+    Continue,
+    /// Stops at the next statement, inside the functions it calls too.
+    StepInto,
+    /// Stops at the next statement of the current function or its callers.
+    StepOver,
+    /// Stops once the current function returns.
+    StepOut,
+}
+
+type Frontend = dyn FnMut(&Snapshot, &mut BTreeSet<usize>) -> Resume + Send;
+
+/// Line breakpoints and stepping, for a frontend that's handed the program
+/// each time it pauses and decides how it resumes. The frontend can change
+/// the breakpoints meanwhile.
+///
+/// ```
+/// use panda::{Debugger, Engine, Interpreter, Resume};
+///
+/// let mut panda = Interpreter::new(Engine::VM);
+/// panda.set_debugger(Debugger::new(|snapshot, _| {
+///     println!("line {}: {:?}", snapshot.line(), snapshot.evaluate("x"));
+///     Resume::Continue
+/// }).breakpoint(2));
+///
+/// panda.eval_str("var x = 1;\nx = x + 1;\nx").unwrap();
+/// ```
+pub struct Debugger {
+    breakpoints: BTreeSet<usize>,
+    // How the program last resumed, and from which depth.
+    resume: Resume,
+    depth: usize,
+    frontend: Box<Frontend>,
+}
+
+impl Debugger {
+    pub fn new(
+        frontend: impl FnMut(&Snapshot, &mut BTreeSet<usize>) -> Resume + Send + 'static,
+    ) -> Self {
+        Self {
+            breakpoints: BTreeSet::new(),
+            resume: Resume::Continue,
+            depth: 0,
+            frontend: Box::new(frontend),
+        }
+    }
+
+    #[must_use]
+    pub fn breakpoint(mut self, line: usize) -> Self {
+        self.breakpoints.insert(line);
+        self
+    }
+
+    /// Replaces the breakpoints, for frontends that change them while the
+    /// program runs rather than while it's paused.
+    pub fn set_breakpoints(&mut self, lines: impl IntoIterator<Item = usize>) {
+        self.breakpoints = lines.into_iter().collect();
+    }
+
+    /// Pauses the program before its first statement.
+    #[must_use]
+    pub const fn stop_on_entry(mut self) -> Self {
+        self.resume = Resume::StepInto;
+        self
+    }
+}
+
+impl DebugHook for Debugger {
+    fn should_pause(&mut self, line: usize, depth: usize) -> bool {
+        let stepped = match self.resume {
+            Resume::Continue => false,
+            Resume::StepInto => true,
+            Resume::StepOver => depth <= self.depth,
+            Resume::StepOut => depth < self.depth,
+        };
+
+        if stepped || self.breakpoints.contains(&line) {
+            self.depth = depth;
+            return true;
+        }
+
+        false
+    }
+
+    fn pause(&mut self, snapshot: &Snapshot) {
+        self.resume = (self.frontend)(snapshot, &mut self.breakpoints);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::interpreter::{Engine, Interpreter};
+
+    const PROGRAM: &str = "var total = 0;
+fn add(x) {
+    var sum = total + x;
+    sum
+}
+fn outer(n) {
+    var y = add(n);
+    y * 2
+}
+total = outer(3);
+total";
+
+    #[test]
+    fn test_debugger() {
+        let steps = [
+            Resume::StepInto,
+            Resume::StepOut,
+            Resume::StepOver,
+            Resume::Continue,
+        ];
+
+        for engine in [Engine::Eval, Engine::VM] {
+            let pauses = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&pauses);
+            let mut steps = steps.into_iter();
+
+            let debugger = Debugger::new(move |snapshot, breakpoints| {
+                let frame = &snapshot.frames[0];
+                let names = snapshot.frames.iter().map(|frame| frame.name.clone());
+
+                recorded.lock().unwrap().push((
+                    snapshot.line(),
+                    names.collect::<Vec<_>>(),
+                    frame.locals.clone(),
+                    snapshot.evaluate("total + 100").unwrap(),
+                ));
+
+                breakpoints.remove(&7);
+                steps.next().unwrap()
+            })
+            .breakpoint(7);
+
+            let mut panda = Interpreter::new(engine);
+            panda.set_debugger(debugger);
+
+            assert_eq!(Object::from(6), panda.eval_str(PROGRAM).unwrap());
+
+            let pauses = pauses.lock().unwrap().clone();
+            let lines = pauses.iter().map(|pause| pause.0).collect::<Vec<_>>();
+
+            assert_eq!(vec![7, 3, 8, 11], lines, "{engine}");
+            assert_eq!(vec!["add", "outer", "main"], pauses[1].1, "{engine}");
+            assert_eq!(
+                vec![("x".to_string(), Object::from(3))],
+                pauses[1]
+                    .2
+                    .iter()
+                    .filter(|(_, value)| *value != Object::Nil)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                "{engine}"
+            );
+            assert_eq!(vec!["main"], pauses[3].1, "{engine}");
+            assert_eq!(Object::from(100), pauses[0].3, "{engine}");
+            assert_eq!(Object::from(106), pauses[3].3, "{engine}");
+        }
+    }
+
+    #[test]
+    fn test_debugger_in_generators() {
+        for engine in [Engine::Eval, Engine::VM] {
+            let lines = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&lines);
+
+            let debugger = Debugger::new(move |snapshot, _| {
+                recorded.lock().unwrap().push(snapshot.line());
+                Resume::Continue
+            })
+            .breakpoint(3);
+
+            let mut panda = Interpreter::new(engine);
+            panda.set_debugger(debugger);
+
+            panda
+                .eval_str("fn* count() {\n    yield 1;\n    yield 2;\n}\ncount().collect()")
+                .unwrap();
+
+            assert_eq!(vec![3], *lines.lock().unwrap(), "{engine}");
+        }
+    }
+}
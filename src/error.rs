@@ -0,0 +1,75 @@
+use std::fmt::{self, Display};
+
+use crate::limits::LimitError;
+
+/// The ways running panda code through an [`Interpreter`](crate::Interpreter)
+/// can fail.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse(Vec<String>),
+    Compile(String),
+    Runtime(String),
+    /// The program called `exit` with this code.
+    Exit(i32),
+    /// The program went over one of the [`Limits`](crate::Limits) of the
+    /// interpreter, or was interrupted.
+    Limit(LimitError),
+    /// [`Interpreter::call_function`](crate::Interpreter::call_function) was
+    /// given a name the program doesn't define.
+    Undefined(String),
+    Conversion(ConversionError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error:\n\t{err}"),
+            Self::Parse(errors) => write!(f, "parser errors:\n\t{}", errors.join("\n\t")),
+            Self::Compile(err) => write!(f, "compiler error:\n\t{err}"),
+            Self::Runtime(err) => write!(f, "runtime error:\n\t{err}"),
+            Self::Exit(code) => write!(f, "exited with code {code}"),
+            Self::Limit(err) => write!(f, "execution stopped:\n\t{err}"),
+            Self::Undefined(name) => write!(f, "undefined variable {name}"),
+            Self::Conversion(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Conversion(err) => Some(err),
+            Self::Limit(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ConversionError> for Error {
+    fn from(err: ConversionError) -> Self {
+        Self::Conversion(err)
+    }
+}
+
+/// A value converted from an [`Object`](crate::Object) of another type.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ConversionError {
+    pub expected: &'static str,
+    pub found: String,
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected {}, got {}", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for ConversionError {}
@@ -0,0 +1,586 @@
+use std::path::Path;
+
+use crate::{
+    ast::Node,
+    compiler::Compiler,
+    debugger::DebugHook,
+    error::Error,
+    interpreters::{eval::Evaluator, vm::VM},
+    io::Io,
+    lexer::Lexer,
+    limits::{Budget, InterruptHandle, Limits},
+    module::ModuleLoader,
+    native::NativeModule,
+    object::{Error as ErrorObj, Object},
+    parser::Parser,
+};
+
+#[derive(
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Debug,
+    clap::ValueEnum,
+    strum::EnumString,
+    strum::Display,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Engine {
+    Eval,
+    VM,
+}
+
+/// Runs panda code with either engine, keeping the definitions of everything
+/// it ran for the code that follows.
+///
+/// ```
+/// use panda::{Engine, Interpreter, Object};
+///
+/// let mut panda = Interpreter::new(Engine::VM);
+/// panda.eval_str("fn add(a, b) { a + b }").unwrap();
+///
+/// let sum = panda.call_function("add", &[1.into(), 2.into()]).unwrap();
+/// assert_eq!(sum, Object::from(3));
+/// ```
+pub struct Interpreter {
+    state: State,
+    io: Io,
+    budget: Budget,
+    // The debugger of the VM, which the machine of each run borrows. The
+    // evaluator keeps its own.
+    debugger: Option<Box<dyn DebugHook>>,
+    debugging: bool,
+}
+
+enum State {
+    Eval(Box<Evaluator>),
+    VM {
+        compiler: Box<Compiler>,
+        globals: Vec<Object>,
+    },
+}
+
+impl Interpreter {
+    /// Creates an interpreter whose imports are also searched for in the
+    /// directories listed in `PANDA_PATH`.
+    #[must_use]
+    pub fn new(engine: Engine) -> Self {
+        let io = Io::default();
+
+        let state = match engine {
+            Engine::Eval => {
+                let mut evaluator = Evaluator::new_with_modules(ModuleLoader::from_env());
+                evaluator.set_io(io.clone());
+
+                State::Eval(Box::new(evaluator))
+            }
+            Engine::VM => State::VM {
+                compiler: Box::new(Compiler::new_with_modules(ModuleLoader::from_env())),
+                globals: Vec::new(),
+            },
+        };
+
+        Self {
+            state,
+            io,
+            budget: Budget::default(),
+            debugger: None,
+            debugging: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn engine(&self) -> Engine {
+        match self.state {
+            State::Eval(_) => Engine::Eval,
+            State::VM { .. } => Engine::VM,
+        }
+    }
+
+    /// Replaces the streams the program prints to and reads from.
+    pub fn set_io(&mut self, io: Io) {
+        if let State::Eval(evaluator) = &mut self.state {
+            evaluator.set_io(io.clone());
+        }
+
+        self.io = io;
+    }
+
+    /// Bounds the work each run of a program may do from now on.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.budget = self.budget.with_limits(limits);
+
+        if let State::Eval(evaluator) = &mut self.state {
+            evaluator.set_budget(self.budget.clone());
+        }
+    }
+
+    /// Pauses the programs run from now on where `debugger` asks to, which
+    /// are parsed with the lines of their statements for it. Imported modules
+    /// aren't.
+    pub fn set_debugger(&mut self, debugger: impl DebugHook + 'static) {
+        match &mut self.state {
+            State::Eval(evaluator) => evaluator.set_debugger(Box::new(debugger)),
+            State::VM { compiler, .. } => {
+                compiler.set_debug();
+                self.debugger = Some(Box::new(debugger));
+            }
+        }
+
+        self.debugging = true;
+    }
+
+    /// A handle other threads can stop the running program with.
+    #[must_use]
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.budget.interrupt_handle()
+    }
+
+    /// Makes the functions of `module` importable from its name and callable
+    /// as `name::function()`. Registering a name again adds to the module,
+    /// replacing the functions with the same name.
+    pub fn register(&mut self, module: NativeModule) {
+        match &mut self.state {
+            State::Eval(evaluator) => evaluator.register(module),
+            State::VM { compiler, .. } => compiler.register(&module),
+        }
+    }
+
+    /// Runs `source` and returns the value of its last expression.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `source` doesn't parse or compile, or if running it fails.
+    pub fn eval_str(&mut self, source: &str) -> Result<Object, Error> {
+        let program = parse_program(source, self.debugging)?;
+        self.budget.start();
+
+        match &mut self.state {
+            State::Eval(evaluator) => match evaluator.eval(program) {
+                Some(Object::Error(ErrorObj { value })) => {
+                    Err(runtime_error(&self.io, &self.budget, value))
+                }
+                value => Ok(value.unwrap_or(Object::Nil)),
+            },
+
+            State::VM { compiler, globals } => {
+                let bytecode = compiler.compile_chunk(program).map_err(Error::Compile)?;
+
+                // Globals defined by code that failed to run are left unset.
+                if globals.len() < compiler.num_globals() {
+                    globals.resize(compiler.num_globals(), Object::Nil);
+                }
+
+                let mut machine = VM::new_with_global_store(&bytecode, globals);
+                machine.set_io(self.io.clone());
+                machine.set_budget(self.budget.clone());
+
+                if let Some(debugger) = self.debugger.take() {
+                    machine.set_debugger(debugger, compiler.global_names());
+                }
+
+                let result = machine.run();
+                *globals = machine.get_globals();
+                self.debugger = machine.take_debugger();
+
+                result.map_err(|err| runtime_error(&self.io, &self.budget, err))?;
+
+                Ok(machine.last_popped_stack_elem.unwrap_or(Object::Nil))
+            }
+        }
+    }
+
+    /// Runs the file at `path`, which the imports it makes are relative to.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file can't be read, or as [`Interpreter::eval_str`] does.
+    pub fn eval_file(&mut self, path: impl AsRef<Path>) -> Result<Object, Error> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+
+        let root = match &mut self.state {
+            State::Eval(evaluator) => evaluator.set_root(path),
+            State::VM { compiler, .. } => compiler.set_root(path),
+        };
+        root.map_err(std::io::Error::other)?;
+
+        self.eval_str(&source)
+    }
+
+    /// Calls the function the program defined as `name`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `name` isn't defined, or if the call fails.
+    pub fn call_function(&mut self, name: &str, args: &[Object]) -> Result<Object, Error> {
+        let func = self
+            .get_global(name)
+            .ok_or_else(|| Error::Undefined(name.to_string()))?;
+        self.budget.start();
+
+        match &mut self.state {
+            State::Eval(evaluator) => match evaluator.eval_call_expression(&func, args) {
+                Object::Error(ErrorObj { value }) => {
+                    Err(runtime_error(&self.io, &self.budget, value))
+                }
+                value => Ok(value),
+            },
+
+            State::VM { compiler, globals } => {
+                let bytecode = compiler.bytecode();
+
+                let mut machine = VM::new_with_global_store(&bytecode, globals);
+                machine.set_io(self.io.clone());
+                machine.set_budget(self.budget.clone());
+
+                if let Some(debugger) = self.debugger.take() {
+                    machine.set_debugger(debugger, compiler.global_names());
+                }
+
+                let result = machine.call(func, args);
+                *globals = machine.get_globals();
+                self.debugger = machine.take_debugger();
+
+                result.map_err(|err| runtime_error(&self.io, &self.budget, err))
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        match &self.state {
+            State::Eval(evaluator) => evaluator.get_global(name),
+            State::VM { compiler, globals } => compiler
+                .resolve_global(name)
+                .and_then(|idx| globals.get(idx).cloned()),
+        }
+    }
+
+    /// Sets the global variable `name`, defining it if the program didn't.
+    pub fn set_global(&mut self, name: &str, value: impl Into<Object>) {
+        match &mut self.state {
+            State::Eval(evaluator) => evaluator.set_global(name, value.into()),
+            State::VM { compiler, globals } => {
+                let idx = compiler.global_slot(name);
+
+                if globals.len() <= idx {
+                    globals.resize(idx + 1, Object::Nil);
+                }
+
+                globals[idx] = value.into();
+            }
+        }
+    }
+
+    /// Formats `value` as the REPL prints it, which classes can customize.
+    pub fn inspect(&mut self, value: &Object) -> String {
+        match &mut self.state {
+            State::Eval(evaluator) => evaluator.inspect(value),
+            State::VM { compiler, globals } => {
+                let bytecode = compiler.bytecode();
+
+                let mut machine = VM::new_with_global_store(&bytecode, globals);
+                machine.set_io(self.io.clone());
+                machine.set_budget(self.budget.clone());
+
+                let repr = machine.inspect(value);
+                *globals = machine.get_globals();
+
+                repr
+            }
+        }
+    }
+}
+
+// Errors unwind the program after `exit` and when it runs out of budget too,
+// which record why it stopped.
+fn runtime_error(io: &Io, budget: &Budget, err: String) -> Error {
+    if let Some(err) = budget.take_error() {
+        return Error::Limit(err);
+    }
+
+    io.take_exit_code().map_or(Error::Runtime(err), Error::Exit)
+}
+
+pub fn parse(source: &str) -> Result<Node, Error> {
+    parse_program(source, false)
+}
+
+fn parse_program(source: &str, lines: bool) -> Result<Node, Error> {
+    let mut lexer = Lexer::new(source);
+    let mut parser = Parser::new(&mut lexer);
+
+    if lines {
+        parser = parser.with_lines();
+    }
+
+    let program = parser.parse_program();
+
+    match program {
+        Some(program) if parser.errors.is_empty() => Ok(program),
+        _ => Err(Error::Parse(parser.errors)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::{error::ConversionError, io::OutputBuffer, limits::LimitError};
+
+    #[test]
+    fn test_interpreter() {
+        for engine in [Engine::Eval, Engine::VM] {
+            let mut panda = Interpreter::new(engine);
+
+            panda.register(
+                NativeModule::new("host").function("twice", |args| Ok(args.get::<isize>(0)? * 2)),
+            );
+            panda.set_global("base", 10);
+
+            assert_eq!(
+                Object::from(10),
+                panda.eval_str("var total = base; total").unwrap()
+            );
+            panda
+                .eval_str("fn add(x) { total + host::twice(x) }")
+                .unwrap();
+
+            assert_eq!(
+                Object::from(16),
+                panda.call_function("add", &[3.into()]).unwrap()
+            );
+            assert_eq!(Some(Object::from(10)), panda.get_global("total"));
+            assert_eq!(Ok(10), isize::try_from(&panda.get_global("total").unwrap()));
+            assert_eq!(
+                Err(ConversionError {
+                    expected: "STR",
+                    found: "INT".to_string(),
+                }),
+                String::try_from(&panda.get_global("total").unwrap())
+            );
+
+            assert!(matches!(
+                panda.eval_str("[1, 2"),
+                Err(Error::Parse(errors)) if !errors.is_empty()
+            ));
+            assert!(matches!(
+                panda.call_function("missing", &[]),
+                Err(Error::Undefined(name)) if name == "missing"
+            ));
+            assert!(matches!(
+                panda.eval_str("add(\"a\")"),
+                Err(Error::Runtime(_))
+            ));
+            assert!(matches!(
+                panda.eval_file("does/not/exist.pnd"),
+                Err(Error::Io(_))
+            ));
+
+            assert_eq!(Object::from(14), panda.eval_str("add(2)").unwrap());
+
+            panda.register(
+                NativeModule::new("host")
+                    .function("twice", |args| Ok(args.get::<isize>(0)? * 20))
+                    .function("neg", |args| Ok(-args.get::<isize>(0)?)),
+            );
+            assert_eq!(
+                Object::from(37),
+                panda.eval_str("host::twice(2) + host::neg(3)").unwrap()
+            );
+            assert_eq!(Object::Nil, panda.eval_str("").unwrap());
+            assert!(matches!(panda.eval_str("exit(2)"), Err(Error::Exit(2))));
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        for engine in [Engine::Eval, Engine::VM] {
+            let mut panda = Interpreter::new(engine);
+            panda.eval_str("fn down(n) { down(n + 1) }").unwrap();
+
+            panda.set_limits(Limits::default().steps(1000));
+            assert!(matches!(
+                panda.eval_str("while (true) { 1 }"),
+                Err(Error::Limit(LimitError::Steps(1000)))
+            ));
+            assert_eq!(Object::from(2), panda.eval_str("1 + 1").unwrap());
+
+            panda.set_limits(Limits::default().depth(8));
+            assert!(matches!(
+                panda.call_function("down", &[0.into()]),
+                Err(Error::Limit(LimitError::Depth(8)))
+            ));
+            assert!(matches!(
+                panda.eval_str(
+                    "fn* g(n) { yield n; for (x in g(n + 1)) { yield x; } } g(0).collect()"
+                ),
+                Err(Error::Limit(LimitError::Depth(8)))
+            ));
+
+            // Calls can't nest forever without a depth limit either.
+            panda.set_limits(Limits::default().steps(1_000_000));
+            assert!(matches!(
+                panda.call_function("down", &[0.into()]),
+                Err(Error::Runtime(err)) if err == "stack overflow"
+            ));
+
+            panda.set_limits(Limits::default().collection_len(100));
+            assert!(matches!(
+                panda.eval_str("var a = []; while (true) { a = a.push(0) }"),
+                Err(Error::Limit(LimitError::CollectionLen(100)))
+            ));
+            assert!(matches!(
+                panda.eval_str("var s = \"ab\"; while (true) { s = s + s }"),
+                Err(Error::Limit(LimitError::CollectionLen(100)))
+            ));
+
+            // Builtins check what they're about to allocate up front.
+            for source in [
+                "f\"{1.5:>50000}\"",
+                "f\"{1.5:.50000}\"",
+                "fn* n() { var i = 0; while (true) { yield i; i += 1; } } n().collect()",
+                "import { repeat } from \"std/string\"; repeat(\"ab\", 1000000000000)",
+            ] {
+                assert!(
+                    matches!(
+                        panda.eval_str(source),
+                        Err(Error::Limit(LimitError::CollectionLen(100)))
+                    ),
+                    "{source}"
+                );
+            }
+
+            let timeout = Duration::from_millis(20);
+            panda.set_limits(Limits::default().timeout(timeout));
+            assert!(matches!(
+                panda.eval_str("while (true) { 1 }"),
+                Err(Error::Limit(LimitError::Timeout(t))) if t == timeout
+            ));
+
+            // Sleeping doesn't outlast the timeout or an interrupt.
+            let sleep = "import { sleep } from \"std/time\"; sleep(3000); 1";
+            let started = Instant::now();
+            assert!(matches!(
+                panda.eval_str(sleep),
+                Err(Error::Limit(LimitError::Timeout(t))) if t == timeout
+            ));
+            assert!(started.elapsed() < Duration::from_secs(1));
+
+            panda.set_limits(Limits::default());
+            let handle = panda.interrupt_handle();
+            let interrupter = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                handle.interrupt();
+            });
+
+            let started = Instant::now();
+            assert!(matches!(
+                panda.eval_str(sleep),
+                Err(Error::Limit(LimitError::Interrupted))
+            ));
+            assert!(started.elapsed() < Duration::from_secs(1));
+            interrupter.join().unwrap();
+
+            let handle = panda.interrupt_handle();
+            let interrupter = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                handle.interrupt();
+            });
+
+            assert!(matches!(
+                panda.eval_str("while (true) { 1 }"),
+                Err(Error::Limit(LimitError::Interrupted))
+            ));
+            interrupter.join().unwrap();
+
+            panda.set_limits(Limits::default().steps(1000));
+            assert!(matches!(
+                panda.eval_str("fn* ones() { while (true) { yield 1 } } for (x in ones()) { x }"),
+                Err(Error::Limit(LimitError::Steps(1000)))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_io() {
+        for engine in [Engine::Eval, Engine::VM] {
+            let out = OutputBuffer::default();
+            let err = OutputBuffer::default();
+
+            let mut panda = Interpreter::new(engine);
+            panda.set_io(
+                Io::default()
+                    .output(out.clone())
+                    .error(err.clone())
+                    .input(Cursor::new("bob\nline\n")),
+            );
+
+            panda
+                .eval_str(r#"var name = input("name? "); print(1, 2.5); println(" hi", name)"#)
+                .unwrap();
+            panda
+                .eval_str(r#"import { readLine, eprintln } from "std/io"; eprintln(readLine(), readLine())"#)
+                .unwrap();
+
+            assert_eq!("name? 1 2.5 hi bob\n", out.contents());
+            assert_eq!("line nil\n", err.contents());
+
+            assert_eq!(
+                Object::bool(true),
+                panda
+                    .eval_str(r#"import { exists } from "std/io"; exists("Cargo.toml")"#)
+                    .unwrap()
+            );
+
+            panda
+                .eval_str(
+                    "fn* gen() { println(\"gen\"); yield 1; } for (x in gen()) { println(x) }",
+                )
+                .unwrap();
+            assert_eq!("name? 1 2.5 hi bob\ngen\n1\n", out.contents());
+
+            assert!(matches!(
+                panda.eval_str("println(1); exit(3); println(2)"),
+                Err(Error::Exit(3))
+            ));
+            assert!(matches!(panda.eval_str("exit()"), Err(Error::Exit(0))));
+            assert!(matches!(
+                panda.eval_str("exit(\"a\")"),
+                Err(Error::Runtime(err)) if err == "cannot use STR as exit code. expected INT"
+            ));
+            assert_eq!("name? 1 2.5 hi bob\ngen\n1\n1\n", out.contents());
+
+            panda.set_io(Io::default().output(out.clone()).sandboxed());
+
+            assert!(matches!(
+                panda.eval_str("input(\"> \")"),
+                Err(Error::Runtime(err)) if err == "cannot read input in a sandbox"
+            ));
+            assert!(matches!(
+                panda.eval_str("readLine()"),
+                Err(Error::Runtime(err)) if err == "cannot read input in a sandbox"
+            ));
+
+            for input in [
+                r#"import { readFile } from "std/io"; readFile("Cargo.toml")"#,
+                r#"import { writeFile } from "std/io"; writeFile("out.txt", "a")"#,
+                r#"import { exists } from "std/io"; exists("Cargo.toml")"#,
+            ] {
+                assert!(matches!(
+                    panda.eval_str(input),
+                    Err(Error::Runtime(err)) if err == "cannot access files in a sandbox"
+                ));
+            }
+        }
+    }
+}
@@ -1,13 +1,45 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
 
 use super::{EvaluatedModule, Object};
 use crate::ast::ClassDecl;
 
+/// The static members of a class. Environments are copied into the functions
+/// they define, so the members are stored once and shared by every copy.
+#[derive(Clone, Default)]
+pub struct Statics(Arc<Mutex<HashMap<String, (Object, bool)>>>);
+
+impl Statics {
+    pub fn get(&self, name: &str) -> Option<(Object, bool)> {
+        self.0.lock().unwrap().get(name).cloned()
+    }
+
+    pub fn set(&self, name: String, val: Object, mutable: bool) {
+        self.0.lock().unwrap().insert(name, (val, mutable));
+    }
+}
+
+impl PartialEq for Statics {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Debug for Statics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Statics")
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct Environment {
     pub store: HashMap<String, (Object, bool)>,
     pub outer: Option<Box<Environment>>,
     pub types: HashMap<String, ClassDecl>,
+    pub statics: HashMap<String, Statics>,
     pub imports: HashMap<String, EvaluatedModule>,
 }
 
@@ -17,6 +49,7 @@ impl Environment {
             store: HashMap::new(),
             outer: None,
             types: HashMap::new(),
+            statics: HashMap::new(),
             imports: HashMap::new(),
         }
     }
@@ -26,6 +59,7 @@ impl Environment {
             store: HashMap::new(),
             outer: Some(Box::new(outer)),
             types: HashMap::new(),
+            statics: HashMap::new(),
             imports: HashMap::new(),
         }
     }
@@ -70,6 +104,21 @@ impl Environment {
         self.types.insert(name, class);
     }
 
+    pub fn get_statics(&self, class: &str) -> Option<Statics> {
+        self.statics.get(class).cloned().map_or_else(
+            || {
+                self.outer
+                    .as_ref()
+                    .map_or_else(|| None, |outer| outer.get_statics(class))
+            },
+            Some,
+        )
+    }
+
+    pub fn set_statics(&mut self, class: String, statics: Statics) {
+        self.statics.insert(class, statics);
+    }
+
     pub fn get_import(&self, name: &str) -> Option<EvaluatedModule> {
         self.imports.get(name).cloned().map_or_else(
             || {
@@ -84,4 +133,49 @@ impl Environment {
     pub fn set_import(&mut self, name: String, class: EvaluatedModule) {
         self.imports.insert(name, class);
     }
+
+    /// The interface of a module, made of its bindings and classes named in
+    /// `exports`. Imported bindings can't be reassigned.
+    pub fn exported(&self, exports: &[String]) -> Self {
+        let mut interface = Self::new();
+
+        for name in exports {
+            if let Some((value, _)) = self.store.get(name) {
+                interface.set(name.clone(), value.clone(), false);
+            }
+
+            if let Some(class) = self.types.get(name) {
+                interface.set_type(name.clone(), class.clone());
+            }
+
+            if let Some(statics) = self.statics.get(name) {
+                interface.set_statics(name.clone(), statics.clone());
+            }
+        }
+
+        interface
+    }
+
+    /// Binds `name` from the interface of a module as `binding`, along with
+    /// the static members of a class. Returns false if it isn't exported.
+    pub fn import(&mut self, interface: &Self, name: &str, binding: &str) -> bool {
+        let value = interface.store.get(name).cloned();
+        let class = interface.types.get(name).cloned();
+
+        if let Some(value) = value {
+            self.store.insert(binding.to_string(), value);
+        }
+
+        let Some(class) = class else {
+            return interface.store.contains_key(name);
+        };
+
+        if let Some(statics) = interface.statics.get(name) {
+            self.set_statics(binding.to_string(), statics.clone());
+        }
+
+        self.set_type(binding.to_string(), class);
+
+        true
+    }
 }
@@ -1,28 +1,55 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
-    path::PathBuf,
+    collections::HashMap,
+    iter,
+    ops::ControlFlow,
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver, SendError, Sender},
+        Arc, Mutex,
+    },
+    thread,
 };
 
+use indexmap::IndexMap;
+
 pub mod environment;
-use environment::Environment;
+use environment::{Environment, Statics};
 
 use crate::{
     ast::{
-        Assign, Assignable, BlockStatement, Call, ClassStatement, Constructable, Constructor,
-        Declaration, Delete, Expression, ExpressionStmt, For, Function, Identifier, If, Import,
-        Index, Infix, Lambda, Lit, Literal, Method, Node, Operator, Prefix, Range, Return, Scope,
-        Statement, While,
+        Assign, Assignable, BlockStatement, Call, ClassMethod, ClassStatement, Constructable,
+        Constructor, Declaration, Delete, EnumDecl, Expression, ExpressionStmt, For, Function,
+        Ident, Identifier, If, Import, ImportItems, Index, Infix, Interpolation, InterpolationPart,
+        Lambda, Lit, Literal, Method, Node, Operator, Prefix, Range, Return, Scope, Statement,
+        While, Yield,
     },
-    lexer::Lexer,
+    debugger::{frame_name, DebugHook, Snapshot, StackFrame},
+    io::Io,
+    limits::Budget,
+    module::{module_name, ModuleLoader},
+    native::{std_modules, NativeModule},
     object::{
-        allowed_in_array, builtins::get_builtin_by_name, hash_method_name, Array, Bool, Builtin,
-        Char, Class, ClassMember, Dict, DictPair, Error, EvaluatedFunction, EvaluatedModule, Float,
-        Hashable, Int, Iterable, Object, Range as RangeObj, ReturnValue, Str, Type,
-        DIR_ENV_VAR_NAME,
+        allowed_in_array,
+        builtins::get_builtin_by_name,
+        contains,
+        format::format_value,
+        getter_name, grapheme_at, grapheme_count, grapheme_object, hash_method_name, int_op,
+        int_pow, int_to_float, is_private,
+        iter::{Driver, EvaluatedGenerator, Generator, Iter, IterState, Resume, Yielded},
+        set_index, setter_name, slice_str, Array, Bool, Builtin, Char, Class, ClassMember, Dict,
+        DictPair, Enum, EnumValue, Error, EvaluatedFunction, EvaluatedModule, Float, Hashable, Int,
+        Object, Range as RangeObj, ReturnValue, Str, Type,
     },
-    parser::Parser,
 };
 
+// Programs recurse about as deeply as the VM lets them.
+const MAX_DEPTH: usize = 1024;
+
+// Each call of a program nests calls of `eval`, which move to a new stack of
+// `STACK_SIZE` bytes when less than `RED_ZONE` are left.
+const RED_ZONE: usize = 1024 * 1024;
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
 #[derive(Default, Debug)]
 struct LoopState {
     in_loop: bool,
@@ -30,20 +57,159 @@ struct LoopState {
     continue_loop: bool,
 }
 
+struct GeneratorChannel {
+    yielded: Sender<Yielded>,
+    resume: Receiver<Resume>,
+}
+
 pub struct Evaluator {
     environment: Environment,
     loop_state: LoopState,
+    generator: Option<GeneratorChannel>,
+    // Instances whose `toString`/`inspect` method is being evaluated.
+    representing: Vec<Object>,
+    // The classes whose methods are running, innermost last. Accessors also
+    // record their property, which they read and write as a plain field.
+    running: Vec<(String, Option<String>)>,
+    // Shared with the generators the program creates, which run on evaluators
+    // of their own.
+    modules: Arc<Mutex<ModuleLoader<EvaluatedModule>>>,
+    // The names the program exports when it's imported as a module.
+    exports: Vec<String>,
+    natives: Vec<NativeModule>,
+    io: Io,
+    budget: Budget,
+    debugger: Option<Box<dyn DebugHook>>,
+    // The functions running, outermost first, and the line of the statement
+    // running if the program was parsed with line information.
+    calls: Vec<CallFrame>,
+    line: usize,
+    // The depth generators are resumed at, which their own calls nest under.
+    depth: usize,
+}
+
+struct CallFrame {
+    // The name of the function, which is only kept for debuggers.
+    name: Option<String>,
+    line: usize,
+    environment: Environment,
 }
 
 impl Evaluator {
+    #[cfg(test)]
     pub fn new() -> Self {
-        Self {
+        Self::new_with_modules(ModuleLoader::default())
+    }
+
+    pub fn new_with_modules(modules: ModuleLoader<EvaluatedModule>) -> Self {
+        Self::new_with_natives(modules, std_modules())
+    }
+
+    fn new_with_natives(
+        modules: ModuleLoader<EvaluatedModule>,
+        natives: Vec<NativeModule>,
+    ) -> Self {
+        let mut evaluator = Self {
             environment: Environment::new(),
             loop_state: LoopState::default(),
+            generator: None,
+            representing: Vec::new(),
+            running: Vec::new(),
+            modules: Arc::new(Mutex::new(modules)),
+            exports: Vec::new(),
+            natives: Vec::new(),
+            io: Io::default(),
+            budget: Budget::default(),
+            debugger: None,
+            calls: Vec::new(),
+            line: 0,
+            depth: 0,
+        };
+
+        for module in natives {
+            evaluator.register(module);
+        }
+
+        evaluator
+    }
+
+    /// Makes the functions of `module` importable from its name and callable
+    /// as `name::function()`.
+    pub fn register(&mut self, module: NativeModule) {
+        self.modules.lock().unwrap().embed(module.name());
+        self.natives.push(module);
+    }
+
+    pub fn set_root(&mut self, file: &Path) -> Result<(), String> {
+        self.modules.lock().unwrap().set_root(file)
+    }
+
+    pub fn set_io(&mut self, io: Io) {
+        self.io = io;
+    }
+
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
+    }
+
+    /// Hands `debugger` the lines of the programs parsed with line
+    /// information. Generators run on threads of their own, so their snapshots
+    /// only have their own frames.
+    pub fn set_debugger(&mut self, debugger: Box<dyn DebugHook>) {
+        self.debugger = Some(debugger);
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.environment
+            .get(name.to_string())
+            .map(|(value, _)| value)
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Object) {
+        self.environment.set(name.to_string(), value, true);
+    }
+
+    // Modules registered more than once under a name are merged, later
+    // functions replacing the earlier ones of the same name.
+    fn native_module(&self, name: &str) -> Option<EvaluatedModule> {
+        let mut modules = self
+            .natives
+            .iter()
+            .filter(|module| module.name() == name)
+            .peekable();
+        modules.peek()?;
+
+        let mut environment = Environment::new();
+
+        for function in modules.flat_map(NativeModule::functions) {
+            environment.set(
+                function.name.clone(),
+                Object::Native(function.clone()),
+                false,
+            );
         }
+
+        Some(EvaluatedModule {
+            name: name.to_string(),
+            environment,
+            class: false,
+        })
     }
 
     pub fn eval(&mut self, node: Node) -> Option<Object> {
+        if let Err(err) = self.budget.step() {
+            return Some(Object::error(err));
+        }
+
+        let value = self.eval_node(node)?;
+
+        match self.budget.check_len(&value) {
+            Ok(()) => Some(value),
+            Err(err) => Some(Object::error(err)),
+        }
+    }
+
+    fn eval_node(&mut self, node: Node) -> Option<Object> {
         match node {
             Node::Program { statements } => {
                 return self.eval_program(&statements);
@@ -74,6 +240,24 @@ impl Evaluator {
                     }));
                 }
 
+                Statement::Yield(Yield { value }) => {
+                    let value = self.eval(Node::Expr(value))?;
+
+                    if is_error(&value) {
+                        return Some(value);
+                    }
+
+                    if self.generator.is_none() {
+                        return Some(Object::error(
+                            "cannot use `yield` outside generator functions".to_string(),
+                        ));
+                    }
+
+                    if let Err(err) = self.yield_value(value) {
+                        return Some(Object::error(err));
+                    }
+                }
+
                 Statement::Declaration(Declaration {
                     name,
                     mutable,
@@ -101,7 +285,7 @@ impl Evaluator {
                     ident,
                     parameters,
                     body,
-                    ..
+                    generator,
                 }) => {
                     self.environment.set(
                         ident.clone(),
@@ -110,6 +294,7 @@ impl Evaluator {
                             parameters,
                             environment: self.environment.clone(),
                             body,
+                            generator,
                         }),
                         false,
                     );
@@ -161,7 +346,13 @@ impl Evaluator {
                         return Some(obj);
                     }
 
-                    let Some(iterator) = Iterable::from_object(obj.clone()) else {
+                    if let Object::Range(range) = &obj {
+                        if let Err(err) = validate_range(range) {
+                            return Some(Object::Error(err));
+                        }
+                    }
+
+                    let Some(iterator) = Iter::from_object(obj.clone()) else {
                         return Some(Object::error(format!("{} is not iterable", obj.kind())));
                     };
 
@@ -170,64 +361,131 @@ impl Evaluator {
                     return self.eval_for_statement(&iterator, &ident, &body);
                 }
 
-                Statement::ClassDecl(ast_node) => {
-                    self.environment.set_type(ast_node.ident.clone(), ast_node);
-                }
-
-                Statement::Import(Import { path, alias, class }) => {
-                    let path_buf = PathBuf::from(&path);
-                    let start_dir = std::env::var(DIR_ENV_VAR_NAME).ok()?;
+                Statement::ClassDecl(mut ast_node) => {
+                    for name in ast_node.interfaces.clone() {
+                        let Some((Object::Interface(interface), _)) =
+                            self.environment.get(name.clone())
+                        else {
+                            return Some(Object::error(format!(
+                                "no interface named \"{name}\" found"
+                            )));
+                        };
 
-                    if let Some(ext) = path_buf.extension() {
-                        if ext != "pnd" {
-                            return Some(Object::error(
-                                "cannot import non panda files".to_string(),
-                            ));
+                        if let Err(err) = ast_node.implement(&interface) {
+                            return Some(Object::error(err));
                         }
+                    }
 
-                        let import_file =
-                            std::fs::read_to_string(PathBuf::from(start_dir).join(&path_buf))
-                                .ok()?;
+                    let statics = Statics::default();
+                    let ident = ast_node.ident.clone();
+                    let members = ast_node.statics.clone();
+
+                    self.environment.set_type(ident.clone(), ast_node);
+                    self.environment.set_statics(ident, statics.clone());
+
+                    for stmt in members {
+                        match stmt {
+                            Statement::Declaration(Declaration {
+                                name,
+                                mutable,
+                                value,
+                                ..
+                            }) => {
+                                let value = match value {
+                                    Some(value) => self.eval(Node::Expr(value))?,
+                                    None => Object::Nil,
+                                };
+
+                                if is_error(&value) {
+                                    return Some(value);
+                                }
 
-                        let mut lexer = Lexer::new(&import_file);
-                        let mut parser = Parser::new(&mut lexer);
+                                statics.set(name, value, mutable);
+                            }
 
-                        let program = parser.parse_program();
+                            Statement::Function(Function {
+                                ident,
+                                parameters,
+                                body,
+                                generator,
+                            }) => {
+                                let func = Object::EvaluatedFunction(EvaluatedFunction {
+                                    name: ident.clone(),
+                                    parameters,
+                                    body,
+                                    environment: self.environment.clone(),
+                                    generator,
+                                });
+
+                                statics.set(ident, func, false);
+                            }
 
-                        if !parser.errors.is_empty() {
-                            println!("parser errors:");
-                            for msg in &parser.errors {
-                                println!("\t{msg}");
+                            stmt => {
+                                if let Some(err @ Object::Error(_)) = self.eval(Node::Stmt(stmt)) {
+                                    return Some(err);
+                                }
                             }
-                            return Some(Object::error(format!(
-                                "could not import \"{path}\" as it had errors."
-                            )));
                         }
+                    }
+                }
 
-                        let mut evaluator = Self::new();
+                Statement::InterfaceDecl(ast_node) => {
+                    self.environment.set(
+                        ast_node.ident.clone(),
+                        Object::Interface(Arc::new(ast_node)),
+                        false,
+                    );
+                }
 
-                        let evaluated = evaluator.eval(program.unwrap());
+                Statement::EnumDecl(EnumDecl {
+                    ident,
+                    variants,
+                    methods,
+                }) => {
+                    let methods = methods
+                        .into_iter()
+                        .map(
+                            |ClassMethod {
+                                 name,
+                                 parameters,
+                                 body,
+                             }| {
+                                let func = Object::EvaluatedFunction(EvaluatedFunction {
+                                    name: name.clone(),
+                                    parameters,
+                                    body,
+                                    environment: self.environment.clone(),
+                                    generator: false,
+                                });
+
+                                (hash_method_name(&name), ClassMember::new(name, func))
+                            },
+                        )
+                        .collect();
 
-                        if let Some(evaluated) = evaluated {
-                            if matches!(evaluated, Object::Error { .. }) {
-                                println!("{}", evaluated.inspect());
-                                return None;
-                            }
-                        }
+                    let decl = Object::Enum(Arc::new(Enum {
+                        name: ident.clone(),
+                        variants,
+                        methods,
+                    }));
 
-                        let module_name = alias.unwrap_or_else(|| {
-                            path_buf.file_stem().unwrap().to_str().unwrap().to_string()
-                        });
+                    self.environment.set(ident, decl, false);
+                }
 
-                        self.environment.set_import(
-                            module_name.clone(),
-                            EvaluatedModule {
-                                environment: evaluator.environment,
-                                name: module_name,
-                                class,
-                            },
-                        );
+                Statement::Import(import) => {
+                    if let Err(err) = self.eval_import(import) {
+                        return Some(Object::error(err));
+                    }
+                }
+
+                Statement::Export(stmt) => {
+                    let names = stmt.declared_names();
+
+                    if let Some(err @ Object::Error(_)) = self.eval(Node::Stmt(*stmt)) {
+                        return Some(err);
                     }
+
+                    self.exports.extend(names);
                 }
 
                 Statement::Break => {
@@ -248,6 +506,11 @@ impl Evaluator {
                     self.loop_state.continue_loop = true;
                 }
 
+                Statement::Line(line) => {
+                    self.line = line;
+                    self.on_line(line);
+                }
+
                 Statement::Delete(Delete { delete_ident }) => {
                     return self.environment.delete(&delete_ident).map_or_else(
                         || {
@@ -261,6 +524,38 @@ impl Evaluator {
             },
 
             Node::Expr(expr) => match expr {
+                Expression::Interpolation(Interpolation { parts }) => {
+                    let mut out = String::new();
+
+                    for part in parts {
+                        match part {
+                            InterpolationPart::Str(value) => out.push_str(&value),
+
+                            InterpolationPart::Expr { value, spec } => {
+                                let value = self.eval(Node::Expr(value))?;
+                                let value = self.eval_to_string(value);
+
+                                if is_error(&value) {
+                                    return Some(value);
+                                }
+
+                                match spec {
+                                    Some(spec) => match self
+                                        .budget
+                                        .run_builtin(|| format_value(&value, &spec))
+                                    {
+                                        Ok(formatted) => out.push_str(&formatted),
+                                        Err(err) => return Some(Object::error(err)),
+                                    },
+                                    None => out.push_str(&value.to_string()),
+                                }
+                            }
+                        }
+                    }
+
+                    return Some(Object::str(out));
+                }
+
                 Expression::Prefix(Prefix { right, operator }) => {
                     let right = self.eval(Node::Expr(*right))?;
 
@@ -268,6 +563,20 @@ impl Evaluator {
                         return Some(right);
                     }
 
+                    let method = match operator {
+                        Operator::Sub => Some("neg"),
+                        Operator::BitNot => Some("bitNot"),
+                        _ => None,
+                    };
+
+                    if let (Object::Class(_), Some(method)) = (&right, method) {
+                        let mut instance = right.clone();
+
+                        if let Some(result) = self.call_special_method(&mut instance, method, &[]) {
+                            return Some(result);
+                        }
+                    }
+
                     return Some(Self::eval_prefix_expression(operator, &right));
                 }
 
@@ -283,12 +592,20 @@ impl Evaluator {
                         return Some(left);
                     }
 
+                    if operator == Operator::NilCoalesce && left != Object::Nil {
+                        return Some(left);
+                    }
+
                     let right = self.eval(Node::Expr(*right))?;
 
                     if is_error(&right) {
                         return Some(right);
                     }
 
+                    if let Some(result) = self.eval_overloaded_infix(operator, &left, &right) {
+                        return Some(result);
+                    }
+
                     return Some(Self::eval_infix_expression(operator, left, right));
                 }
 
@@ -306,69 +623,33 @@ impl Evaluator {
                 }
 
                 Expression::Lambda(Lambda {
-                    parameters, body, ..
+                    parameters,
+                    body,
+                    generator,
+                    ..
                 }) => {
                     return Some(Object::EvaluatedFunction(EvaluatedFunction {
                         name: String::new(),
                         parameters,
                         environment: self.environment.clone(),
                         body,
+                        generator,
                     }));
                 }
 
-                Expression::Call(Call {
-                    function,
-                    arguments,
-                    ..
-                }) => {
-                    let function = self.eval(Node::Expr(*function))?;
-
-                    if is_error(&function) {
-                        return Some(function);
-                    }
-
-                    let args = self.eval_expressions(&arguments)?;
-
-                    if args.len() == 1 && is_error(&args[0]) {
-                        return Some(args[0].clone());
-                    }
-
-                    return Some(self.eval_call_expression(&function, &args));
-                }
-
-                Expression::Index(Index { left, index }) => {
-                    let left = self.eval(Node::Expr(*left))?;
-
-                    if is_error(&left) {
-                        return Some(left);
-                    }
-
-                    let index = self.eval(Node::Expr(*index))?;
-
-                    if is_error(&index) {
-                        return Some(index);
-                    }
+                link @ (Expression::Call(_) | Expression::Index(_) | Expression::Method(_)) => {
+                    let (ControlFlow::Continue(value) | ControlFlow::Break(value)) =
+                        self.eval_link(link)?;
 
-                    return Some(Self::eval_index_expression(&left, &index));
+                    return Some(value);
                 }
 
-                Expression::Assign(Assign { to, value }) => {
-                    return self.eval_assign_expression(to, &value);
-                }
-
-                Expression::Method(Method {
-                    left,
-                    name: method,
-                    arguments,
-                    ..
+                Expression::Assign(Assign {
+                    to,
+                    operator,
+                    value,
                 }) => {
-                    let left = self.eval(Node::Expr(*left))?;
-
-                    if is_error(&left) {
-                        return Some(left);
-                    }
-
-                    return Some(self.eval_method_expression(arguments, left, &method));
+                    return self.eval_assign_expression(to, operator, &value);
                 }
 
                 Expression::Constructor(Constructor { constructable }) => {
@@ -440,7 +721,17 @@ impl Evaluator {
                 }
 
                 Expression::Scope(Scope { module, member }) => {
-                    let Some(import) = self.environment.get_import(&module) else {
+                    if let Some((Object::Enum(decl), _)) = self.environment.get(module.clone()) {
+                        return self.eval_enum_variant(&decl, *member);
+                    }
+
+                    if self.environment.get_type(&module).is_some() {
+                        return self.eval_static_member(&module, *member);
+                    }
+
+                    let import = self.environment.get_import(&module);
+
+                    let Some(import) = import.or_else(|| self.native_module(&module)) else {
                         return Some(Object::error(format!("no module named \"{module}\" found")));
                     };
 
@@ -448,7 +739,7 @@ impl Evaluator {
                         Expression::Identifier(Identifier { ref value }) => {
                             let Some((member, _)) = import.environment.get(value.clone()) else {
                                 return Some(Object::error(format!(
-                                    "member '{member}' not found in module '{module}'"
+                                    "member '{value}' not found in module '{module}'"
                                 )));
                             };
 
@@ -467,13 +758,19 @@ impl Evaluator {
                                 ));
                             };
 
-                            let Some((member, _)) = import.environment.get(member_name) else {
+                            let Some((member, _)) = import.environment.get(member_name.clone())
+                            else {
                                 return Some(Object::error(format!(
-                                    "member '{member}' not found in module '{module}'"
+                                    "member '{member_name}' not found in module '{module}'"
                                 )));
                             };
 
-                            if !matches!(member, Object::EvaluatedFunction { .. }) {
+                            if !matches!(
+                                member,
+                                Object::EvaluatedFunction { .. }
+                                    | Object::Builtin { .. }
+                                    | Object::Native(_)
+                            ) {
                                 return Some(Object::error(format!(
                                     "\"{member}\" is not callable"
                                 )));
@@ -527,6 +824,76 @@ impl Evaluator {
         None
     }
 
+    fn on_line(&mut self, line: usize) {
+        let Some(mut debugger) = self.debugger.take() else {
+            return;
+        };
+
+        if debugger.should_pause(line, self.depth + self.calls.len()) {
+            debugger.pause(&self.snapshot());
+        }
+
+        self.debugger = Some(debugger);
+    }
+
+    // Closures capture their environment, which is enclosed by a copy of the
+    // globals. The variables in between are the free ones of the function.
+    fn snapshot(&self) -> Snapshot {
+        let running = iter::once((&self.environment, self.line)).chain(
+            self.calls
+                .iter()
+                .rev()
+                .map(|caller| (&caller.environment, caller.line)),
+        );
+        let names = self
+            .calls
+            .iter()
+            .rev()
+            .map(|call| frame_name(Some(call.name.as_deref().unwrap_or("?"))))
+            .chain(iter::once(frame_name(None)));
+
+        let frames = running
+            .zip(names)
+            .map(|((environment, line), name)| {
+                let scopes = iter::successors(Some(environment), |env| env.outer.as_deref())
+                    .collect::<Vec<_>>();
+
+                // Calls also bind the function to its name, which the VM
+                // resolves without a variable.
+                let variables = |env: &Environment| {
+                    environment_variables(env).into_iter().filter(|(name, value)| {
+                        !matches!(value, Object::EvaluatedFunction(func) if func.name == *name)
+                    })
+                };
+
+                let (locals, free) = match scopes.as_slice() {
+                    [locals, free @ .., _] => (
+                        variables(locals).collect(),
+                        free.iter().flat_map(|env| variables(env)).collect(),
+                    ),
+                    _ => (Vec::new(), Vec::new()),
+                };
+
+                StackFrame {
+                    name,
+                    line,
+                    locals,
+                    free,
+                }
+            })
+            .collect();
+
+        let globals = self
+            .calls
+            .first()
+            .map_or(&self.environment, |caller| &caller.environment);
+
+        Snapshot {
+            frames,
+            globals: environment_variables(globals),
+        }
+    }
+
     fn eval_constructor_expression(&mut self, constructable: Constructable) -> Object {
         match constructable {
             Constructable::Identifier(Identifier { ref value }) => {
@@ -541,37 +908,10 @@ impl Evaluator {
                     ));
                 }
 
-                let mut members = HashMap::new();
-                for stmt in class.body {
-                    match stmt {
-                        ClassStatement::Variable(decl) => {
-                            let obj = decl
-                                .value
-                                .map_or(Object::Nil, |val| self.eval(Node::Expr(val)).unwrap());
-                            members.insert(
-                                hash_method_name(&decl.name),
-                                ClassMember::new(decl.name, obj),
-                            );
-                        }
-
-                        ClassStatement::Method(func) => {
-                            let obj = Object::EvaluatedFunction(EvaluatedFunction {
-                                name: func.name.clone(),
-                                parameters: func.parameters,
-                                environment: self.environment.clone(),
-                                body: func.body,
-                            });
-
-                            members.insert(
-                                hash_method_name(&func.name),
-                                ClassMember::new(func.name, obj),
-                            );
-                        }
-                    }
-                }
+                let members = self.eval_class_body([], class.body);
 
                 Object::Class(Class {
-                    name: value.clone(),
+                    name: class.ident,
                     members,
                 })
             }
@@ -599,46 +939,13 @@ impl Evaluator {
                     ));
                 }
 
-                let mut members = HashMap::new();
-
-                for stmt in class.body {
-                    match stmt {
-                        ClassStatement::Variable(decl) => {
-                            let obj = decl
-                                .value
-                                .map_or(Object::Nil, |val| self.eval(Node::Expr(val)).unwrap());
-
-                            members.insert(
-                                hash_method_name(&decl.name),
-                                ClassMember::new(decl.name, obj),
-                            );
-                        }
-
-                        ClassStatement::Method(func) => {
-                            let obj = Object::EvaluatedFunction(EvaluatedFunction {
-                                name: func.name.clone(),
-                                parameters: func.parameters,
-                                environment: self.environment.clone(),
-                                body: func.body,
-                            });
-
-                            members.insert(
-                                hash_method_name(&func.name),
-                                ClassMember::new(func.name, obj),
-                            );
-                        }
-                    }
-                }
-
-                for (name, value) in class.initializers.iter().zip(received_initializers.iter()) {
-                    members.insert(
-                        hash_method_name(name),
-                        ClassMember::new(name.to_string(), value.clone()),
-                    );
-                }
+                let members = self.eval_class_body(
+                    class.initializers.iter().zip(received_initializers),
+                    class.body,
+                );
 
                 Object::Class(Class {
-                    name: member,
+                    name: class.ident,
                     members,
                 })
             }
@@ -668,37 +975,10 @@ impl Evaluator {
                             ));
                         }
 
-                        let mut members = HashMap::new();
-                        for stmt in class.body {
-                            match stmt {
-                                ClassStatement::Variable(decl) => {
-                                    let obj = decl.value.map_or(Object::Nil, |val| {
-                                        self.eval(Node::Expr(val)).unwrap()
-                                    });
-                                    members.insert(
-                                        hash_method_name(&decl.name),
-                                        ClassMember::new(decl.name, obj),
-                                    );
-                                }
-
-                                ClassStatement::Method(func) => {
-                                    let obj = Object::EvaluatedFunction(EvaluatedFunction {
-                                        name: func.name.clone(),
-                                        parameters: func.parameters,
-                                        environment: self.environment.clone(),
-                                        body: func.body,
-                                    });
-
-                                    members.insert(
-                                        hash_method_name(&func.name),
-                                        ClassMember::new(func.name, obj),
-                                    );
-                                }
-                            }
-                        }
+                        let members = self.eval_class_body([], class.body);
 
                         Object::Class(Class {
-                            name: value,
+                            name: class.ident,
                             members,
                         })
                     }
@@ -729,47 +1009,13 @@ impl Evaluator {
                             ));
                         }
 
-                        let mut members = HashMap::new();
-
-                        for stmt in class.body {
-                            match stmt {
-                                ClassStatement::Variable(decl) => {
-                                    let obj = decl.value.map_or(Object::Nil, |val| {
-                                        self.eval(Node::Expr(val)).unwrap()
-                                    });
-                                    members.insert(
-                                        hash_method_name(&decl.name),
-                                        ClassMember::new(decl.name, obj),
-                                    );
-                                }
-
-                                ClassStatement::Method(func) => {
-                                    let obj = Object::EvaluatedFunction(EvaluatedFunction {
-                                        name: func.name.clone(),
-                                        parameters: func.parameters,
-                                        environment: self.environment.clone(),
-                                        body: func.body,
-                                    });
-
-                                    members.insert(
-                                        hash_method_name(&func.name),
-                                        ClassMember::new(func.name, obj),
-                                    );
-                                }
-                            }
-                        }
-
-                        for (name, value) in
-                            class.initializers.iter().zip(received_initializers.iter())
-                        {
-                            members.insert(
-                                hash_method_name(name),
-                                ClassMember::new(name.clone(), value.clone()),
-                            );
-                        }
+                        let members = self.eval_class_body(
+                            class.initializers.iter().zip(received_initializers),
+                            class.body,
+                        );
 
                         Object::Class(Class {
-                            name: member,
+                            name: class.ident,
                             members,
                         })
                     }
@@ -780,75 +1026,641 @@ impl Evaluator {
         }
     }
 
-    fn eval_method_expression(
+    // The members of a new instance, in the order they're declared: the values
+    // passed to the constructor, then the fields and methods of the body.
+    fn eval_class_body<'a>(
         &mut self,
-        arguments: Option<Vec<Expression>>,
-        left: Object,
-        method: &str,
-    ) -> Object {
-        let mut arg_objs = Vec::new();
-        if let Some(args) = &arguments {
-            for arg in args {
-                let Some(evaluated) = self.eval(Node::Expr(arg.clone())) else {
-                    return Object::error("cannot evaluate arguments".to_string());
-                };
+        initializers: impl IntoIterator<Item = (&'a Ident, Object)>,
+        body: Vec<ClassStatement>,
+    ) -> IndexMap<usize, ClassMember> {
+        let mut members = IndexMap::new();
+
+        for (name, value) in initializers {
+            members.insert(
+                hash_method_name(name),
+                ClassMember::new(name.clone(), value),
+            );
+        }
 
-                if is_error(&evaluated) {
-                    return evaluated;
+        for stmt in body {
+            let (name, obj) = match stmt {
+                ClassStatement::Variable(decl) => {
+                    let obj = decl
+                        .value
+                        .map_or(Object::Nil, |val| self.eval(Node::Expr(val)).unwrap());
+
+                    (decl.name, obj)
+                }
+                ClassStatement::Method(func) => (func.name.clone(), self.eval_class_method(func)),
+                ClassStatement::Getter(func) => {
+                    (getter_name(&func.name), self.eval_class_method(func))
                 }
+                ClassStatement::Setter(func) => {
+                    (setter_name(&func.name), self.eval_class_method(func))
+                }
+            };
 
-                arg_objs.push(evaluated);
-            }
+            // Initializers win over fields of the same name.
+            members
+                .entry(hash_method_name(&name))
+                .or_insert_with(|| ClassMember::new(name, obj));
         }
 
-        let arg_objs = arg_objs.as_slice();
-        let evaluated = left.call_method(hash_method_name(method), arguments.map(|_| arg_objs));
+        members
+    }
 
-        if let (Object::Class(_), func @ Object::EvaluatedFunction(_)) = (left, &evaluated) {
-            self.eval_call_expression(func, arg_objs)
-        } else {
-            evaluated
-        }
+    fn eval_class_method(&self, func: ClassMethod) -> Object {
+        Object::EvaluatedFunction(EvaluatedFunction {
+            name: func.name,
+            parameters: func.parameters,
+            environment: self.environment.clone(),
+            body: func.body,
+            generator: false,
+        })
     }
 
-    fn eval_for_statement(
-        &mut self,
-        iterator: &Iterable,
-        ident: &str,
-        body: &BlockStatement,
-    ) -> Option<Object> {
-        let iter_len = iterator.count();
+    fn eval_enum_variant(&mut self, decl: &Arc<Enum>, member: Expression) -> Option<Object> {
+        let (name, arguments) = match Scope::split_member(member) {
+            Ok(split) => split,
+            Err(err) => return Some(Object::error(err)),
+        };
 
-        if let Iterable::Range(obj) = &iterator {
-            if let Err(err) = validate_range(obj) {
-                return Some(Object::Error(err));
-            }
-        }
+        let args = self.eval_expressions(&arguments.unwrap_or_default())?;
 
-        if body.is_empty() {
-            return None;
+        if args.len() == 1 && is_error(&args[0]) {
+            return Some(args[0].clone());
         }
 
-        for idx in 0..iter_len {
-            let value = iterator.get(idx);
-            self.environment
-                .set(ident.to_string(), value.clone(), false);
+        Some(decl.construct(&name, args).unwrap_or_else(Object::error))
+    }
 
-            if let Some(obj) = self.eval_loop_block_statement(body) {
-                if is_error(&obj) {
-                    return Some(obj);
-                }
+    // Evaluates a call, index or member, breaking out of the whole chain it's
+    // part of when one of its links is an error or a `?.` on `nil`.
+    fn eval_link(&mut self, link: Expression) -> Option<ControlFlow<Object, Object>> {
+        let value = match link {
+            Expression::Call(Call {
+                function,
+                arguments,
+                ..
+            }) => {
+                let receiver = match function.as_ref() {
+                    Expression::Identifier(Identifier { value }) => Some(value.clone()),
+                    _ => None,
+                };
 
-                if self.loop_state.break_loop {
-                    self.loop_state = LoopState::default();
+                let function = match self.eval_chain_left(*function)? {
+                    ControlFlow::Continue(function) => function,
+                    end @ ControlFlow::Break(_) => return Some(end),
+                };
 
-                    break;
+                let args = self.eval_expressions(&arguments)?;
+
+                if args.len() == 1 && is_error(&args[0]) {
+                    return Some(ControlFlow::Break(args[0].clone()));
                 }
-            }
-        }
 
-        self.environment.delete(ident)
-    }
+                if let mut instance @ Object::Class(_) = function {
+                    let Some(ret) = self.call_special_method(&mut instance, "call", &args) else {
+                        return Some(ControlFlow::Break(Object::error(format!(
+                            "not a function: {}",
+                            instance.kind()
+                        ))));
+                    };
+
+                    if let Some(receiver) = receiver {
+                        self.update_receiver(&receiver, instance);
+                    }
+
+                    ret
+                } else {
+                    self.eval_call_expression(&function, &args)
+                }
+            }
+
+            Expression::Index(Index {
+                left,
+                index,
+                optional,
+            }) => {
+                let left = match self.eval_chain_left(*left)? {
+                    ControlFlow::Continue(left) => left,
+                    end @ ControlFlow::Break(_) => return Some(end),
+                };
+
+                if optional && left == Object::Nil {
+                    return Some(ControlFlow::Break(left));
+                }
+
+                let index = self.eval(Node::Expr(*index))?;
+
+                if is_error(&index) {
+                    return Some(ControlFlow::Break(index));
+                }
+
+                self.eval_index(left, &index)
+            }
+
+            Expression::Method(Method {
+                left,
+                name: method,
+                arguments,
+                optional,
+            }) => {
+                let receiver = match &*left {
+                    Expression::Identifier(Identifier { value }) => Some(value.clone()),
+                    _ => None,
+                };
+
+                let left = match self.eval_chain_left(*left)? {
+                    ControlFlow::Continue(left) => left,
+                    end @ ControlFlow::Break(_) => return Some(end),
+                };
+
+                if optional && left == Object::Nil {
+                    return Some(ControlFlow::Break(left));
+                }
+
+                self.eval_method_expression(arguments, left, &method, receiver.as_deref())
+            }
+
+            // The start of the chain.
+            expr => self.eval(Node::Expr(expr))?,
+        };
+
+        if let Err(err) = self.budget.check_len(&value) {
+            return Some(ControlFlow::Break(Object::error(err)));
+        }
+
+        Some(if is_error(&value) {
+            ControlFlow::Break(value)
+        } else {
+            ControlFlow::Continue(value)
+        })
+    }
+
+    // Links evaluated as the left of another count as steps of their own.
+    fn eval_chain_left(&mut self, left: Expression) -> Option<ControlFlow<Object, Object>> {
+        if matches!(
+            left,
+            Expression::Call(_) | Expression::Index(_) | Expression::Method(_)
+        ) {
+            if let Err(err) = self.budget.step() {
+                return Some(ControlFlow::Break(Object::error(err)));
+            }
+        }
+
+        self.eval_link(left)
+    }
+
+    fn eval_static_member(&mut self, class: &str, member: Expression) -> Option<Object> {
+        let (name, arguments) = match Scope::split_member(member) {
+            Ok(split) => split,
+            Err(err) => return Some(Object::error(err)),
+        };
+
+        let statics = self.environment.get_statics(class);
+
+        let Some((obj, _)) = statics.and_then(|statics| statics.get(&name)) else {
+            return Some(Object::error(format!(
+                "no static member `{name}` found in class \"{class}\""
+            )));
+        };
+
+        let Some(arguments) = arguments else {
+            return Some(obj);
+        };
+
+        let args = self.eval_expressions(&arguments)?;
+
+        if args.len() == 1 && is_error(&args[0]) {
+            return Some(args[0].clone());
+        }
+
+        Some(self.eval_call_expression(&obj, &args))
+    }
+
+    fn eval_method_expression(
+        &mut self,
+        arguments: Option<Vec<Expression>>,
+        left: Object,
+        method: &str,
+        receiver: Option<&str>,
+    ) -> Object {
+        let mut arg_objs = Vec::new();
+        if let Some(args) = &arguments {
+            for arg in args {
+                let Some(evaluated) = self.eval(Node::Expr(arg.clone())) else {
+                    return Object::error("cannot evaluate arguments".to_string());
+                };
+
+                if is_error(&evaluated) {
+                    return evaluated;
+                }
+
+                arg_objs.push(evaluated);
+            }
+        }
+
+        let arg_objs = arg_objs.as_slice();
+
+        if let Object::Class(class) = &left {
+            if let Err(err) = self.check_private(&class.name, method) {
+                return Object::error(err);
+            }
+
+            let getter = accessor(class, &getter_name(method))
+                .filter(|_| arguments.is_none() && !self.in_accessor(&class.name, method));
+
+            if let Some(getter) = getter {
+                let mut instance = left;
+                let ret = self.eval_accessor(&mut instance, &getter, method, &[]);
+
+                if let Some(receiver) = receiver {
+                    self.update_receiver(receiver, instance);
+                }
+
+                return ret;
+            }
+        }
+
+        if let (Object::Iter(iter), Some(_)) = (&left, &arguments) {
+            let budget = self.budget.clone();
+
+            if let Some(ret) =
+                budget.run_builtin(|| iter.call_method(hash_method_name(method), arg_objs, self))
+            {
+                return ret.unwrap_or_else(Object::error);
+            }
+        }
+
+        let evaluated = left.call_method(hash_method_name(method), arguments.map(|_| arg_objs));
+
+        let (
+            mut instance @ (Object::Class(_) | Object::EnumValue(_)),
+            Object::EvaluatedFunction(func),
+        ) = (left, &evaluated)
+        else {
+            return evaluated;
+        };
+
+        let ret = self.eval_bound_method(&mut instance, func, arg_objs);
+
+        if let Some(receiver) = receiver {
+            self.update_receiver(receiver, instance);
+        }
+
+        ret
+    }
+
+    fn update_receiver(&mut self, receiver: &str, instance: Object) {
+        if let Some((_, true)) = self.environment.get(receiver.to_string()) {
+            self.environment.set(receiver.to_string(), instance, true);
+        }
+    }
+
+    // Calls the special method `name` (e.g. `add` or `index`) of a class instance.
+    // Returns `None` when `instance` is not a class or doesn't define it.
+    fn call_special_method(
+        &mut self,
+        instance: &mut Object,
+        name: &str,
+        args: &[Object],
+    ) -> Option<Object> {
+        let Object::Class(Class { members, .. }) = instance else {
+            return None;
+        };
+
+        let Some(ClassMember {
+            obj: Object::EvaluatedFunction(func),
+            ..
+        }) = members.get(&hash_method_name(name)).cloned()
+        else {
+            return None;
+        };
+
+        Some(self.eval_bound_method(instance, &func, args))
+    }
+
+    fn eval_overloaded_infix(
+        &mut self,
+        operator: Operator,
+        left: &Object,
+        right: &Object,
+    ) -> Option<Object> {
+        if matches!(operator, Operator::In | Operator::NotIn) {
+            let found = self.call_special_method(
+                &mut right.clone(),
+                "contains",
+                std::slice::from_ref(left),
+            )?;
+
+            if is_error(&found) {
+                return Some(found);
+            }
+
+            return Some(Object::bool(
+                is_truthy(&found) == (operator == Operator::In),
+            ));
+        }
+
+        if !matches!(left, Object::Class(_)) {
+            return None;
+        }
+
+        let method = match operator {
+            Operator::Add => "add",
+            Operator::Sub => "sub",
+            Operator::Mul => "mul",
+            Operator::Div => "div",
+            Operator::Mod => "mod",
+            Operator::Pow => "pow",
+            Operator::FloorDiv => "floorDiv",
+            Operator::BitXor => "bitXor",
+            Operator::BitAnd => "bitAnd",
+            Operator::BitOr => "bitOr",
+            Operator::Shr => "shr",
+            Operator::Shl => "shl",
+            Operator::Eq => "eq",
+            Operator::NotEq => "ne",
+            Operator::Gt => "gt",
+            Operator::Lt => "lt",
+            Operator::GtEq => "ge",
+            Operator::LtEq => "le",
+            _ => return None,
+        };
+
+        let args = [right.clone()];
+
+        if let Some(result) = self.call_special_method(&mut left.clone(), method, &args) {
+            return Some(result);
+        }
+
+        // `!=`, `>=` and `<=` fall back to negating `eq`, `lt` and `gt`.
+        let negated = match operator {
+            Operator::NotEq => "eq",
+            Operator::GtEq => "lt",
+            Operator::LtEq => "gt",
+            _ => return None,
+        };
+
+        let result = self.call_special_method(&mut left.clone(), negated, &args)?;
+
+        if is_error(&result) {
+            return Some(result);
+        }
+
+        Some(Object::bool(!is_truthy(&result)))
+    }
+
+    // Converts class instances to the STR returned by their `toString` or `str`
+    // method, leaving everything else as is.
+    fn eval_to_string(&mut self, obj: Object) -> Object {
+        self.eval_representation(&obj, &["toString", "str"])
+            .unwrap_or(obj)
+    }
+
+    /// Like [`Object::inspect`], but uses the `inspect` or `toString` method of
+    /// class instances when they define one.
+    pub fn inspect(&mut self, obj: &Object) -> String {
+        match self.eval_representation(obj, &["inspect", "toString", "str"]) {
+            Some(Object::Str(Str { value })) => value,
+            Some(err) => err.inspect(),
+            None => obj.inspect(),
+        }
+    }
+
+    // Calls the first of `methods` defined by a class instance. An instance
+    // formatting itself from within one of these methods gets the default
+    // representation instead of recursing forever.
+    fn eval_representation(&mut self, obj: &Object, methods: &[&str]) -> Option<Object> {
+        let Object::Class(Class { name, members }) = obj else {
+            return None;
+        };
+
+        if self.representing.contains(obj) {
+            return None;
+        }
+
+        let method = methods
+            .iter()
+            .find(|method| members.contains_key(&hash_method_name(method)))?;
+
+        self.representing.push(obj.clone());
+        let repr = self.call_special_method(&mut obj.clone(), method, &[]);
+        self.representing.pop();
+
+        match repr? {
+            repr @ (Object::Str(_) | Object::Error(_)) => Some(repr),
+            other => Some(Object::error(format!(
+                "`{method}` method of class \"{name}\" must return STR, got {}",
+                other.kind()
+            ))),
+        }
+    }
+
+    fn eval_bound_method(
+        &mut self,
+        instance: &mut Object,
+        func: &EvaluatedFunction,
+        args: &[Object],
+    ) -> Object {
+        let Object::Class(Class { name, .. }) = instance else {
+            return self.eval_bound_function(instance, func, args);
+        };
+
+        self.running.push((name.clone(), None));
+        let ret = self.eval_bound_function(instance, func, args);
+        self.running.pop();
+
+        ret
+    }
+
+    fn eval_accessor(
+        &mut self,
+        instance: &mut Object,
+        func: &EvaluatedFunction,
+        property: &str,
+        args: &[Object],
+    ) -> Object {
+        let Object::Class(Class { name, .. }) = instance else {
+            return self.eval_bound_function(instance, func, args);
+        };
+
+        self.running
+            .push((name.clone(), Some(property.to_string())));
+        let ret = self.eval_bound_function(instance, func, args);
+        self.running.pop();
+
+        ret
+    }
+
+    // Private members can only be used by the methods of their class.
+    fn check_private(&self, class: &str, member: &str) -> Result<(), String> {
+        if is_private(member) && self.running.last().is_none_or(|(name, _)| name != class) {
+            return Err(format!(
+                "cannot access private member `{member}` of class \"{class}\""
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn in_accessor(&self, class: &str, property: &str) -> bool {
+        self.running.last().is_some_and(|(name, accessing)| {
+            name == class && accessing.as_deref() == Some(property)
+        })
+    }
+
+    fn eval_bound_function(
+        &mut self,
+        instance: &mut Object,
+        func: &EvaluatedFunction,
+        args: &[Object],
+    ) -> Object {
+        if func.parameters.len() != args.len() {
+            return Object::error(format!(
+                "wrong number of arguments. got: {}, want: {}",
+                args.len(),
+                func.parameters.len()
+            ));
+        }
+
+        let mut extended_env = extend_function_env(func.clone(), args);
+        extended_env.set("self".to_string(), instance.clone(), true);
+
+        // Methods capture the environment from before their enum was declared.
+        if let Object::EnumValue(EnumValue { decl, .. }) = instance {
+            extended_env.set(decl.name.clone(), Object::Enum(Arc::clone(decl)), false);
+        }
+
+        let (evaluated, environment) =
+            self.eval_function_body(&func.name, extended_env, &func.body);
+
+        if let Some((updated, _)) = environment.get("self".to_string()) {
+            *instance = updated;
+        }
+
+        if is_error(&evaluated) {
+            return evaluated;
+        }
+
+        unwrap_return_value(evaluated)
+    }
+
+    fn eval_for_statement(
+        &mut self,
+        iterator: &Iter,
+        ident: &str,
+        body: &BlockStatement,
+    ) -> Option<Object> {
+        loop {
+            let value = match iterator.next(self) {
+                Ok(Some(value)) => value,
+                Ok(None) => break,
+                Err(err) => return Some(Object::error(err)),
+            };
+
+            self.environment.set(ident.to_string(), value, false);
+
+            if let Some(obj) = self.eval_loop_block_statement(body) {
+                if is_error(&obj) {
+                    return Some(obj);
+                }
+
+                if self.loop_state.break_loop {
+                    self.loop_state = LoopState::default();
+
+                    break;
+                }
+            }
+        }
+
+        self.environment.delete(ident)
+    }
+
+    fn eval_import(
+        &mut self,
+        Import {
+            path,
+            class,
+            alias,
+            items,
+        }: Import,
+    ) -> Result<(), String> {
+        let natives = &self.natives;
+        let io = &self.io;
+        let budget = &self.budget;
+
+        let module = self
+            .modules
+            .lock()
+            .unwrap()
+            .load(&path, |modules, program| {
+                let mut evaluator =
+                    Self::new_with_natives(std::mem::take(modules), natives.clone());
+                evaluator.io = io.clone();
+                evaluator.budget = budget.clone();
+
+                if let Some(native) = evaluator.native_module(&path) {
+                    evaluator
+                        .exports
+                        .extend(native.environment.store.keys().cloned());
+                    evaluator.environment.store.extend(native.environment.store);
+                }
+
+                let evaluated = evaluator.eval(program);
+                // The module's own generators keep a copy to import from.
+                *modules = evaluator.modules.lock().unwrap().clone();
+
+                match evaluated {
+                    Some(Object::Error(Error { value })) => Err(value),
+                    _ => Ok(EvaluatedModule {
+                        name: module_name(&path),
+                        environment: evaluator.environment.exported(&evaluator.exports),
+                        class,
+                    }),
+                }
+            })?;
+
+        match items {
+            None => {
+                let name = alias.unwrap_or_else(|| module_name(&path));
+
+                self.environment.set_import(
+                    name.clone(),
+                    EvaluatedModule {
+                        name,
+                        class,
+                        ..module
+                    },
+                );
+            }
+
+            Some(ImportItems::All) => {
+                let interface = module.environment;
+
+                self.environment.store.extend(interface.store);
+                self.environment.types.extend(interface.types);
+                self.environment.statics.extend(interface.statics);
+            }
+
+            Some(ImportItems::Names(names)) => {
+                for item in names {
+                    if !self
+                        .environment
+                        .import(&module.environment, &item.name, item.binding())
+                    {
+                        return Err(format!(
+                            "module \"{}\" does not export \"{}\"",
+                            module.name, item.name
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
 
     fn eval_program(&mut self, stmts: &[Statement]) -> Option<Object> {
         let mut result = None;
@@ -868,6 +1680,74 @@ impl Evaluator {
         result
     }
 
+    // Runs the body of a called function in `environment`, which is returned
+    // as the body left it. The caller's environment waits on the call stack.
+    fn eval_function_body(
+        &mut self,
+        name: &str,
+        environment: Environment,
+        body: &BlockStatement,
+    ) -> (Object, Environment) {
+        if let Err(err) = self.enter(self.depth + self.calls.len() + 1) {
+            return (Object::error(err), environment);
+        }
+
+        let caller = CallFrame {
+            name: self.debugger.as_ref().map(|_| name.to_string()),
+            line: self.line,
+            environment: std::mem::replace(&mut self.environment, environment),
+        };
+        self.calls.push(caller);
+        let generator = self.generator.take();
+
+        let evaluated =
+            stacker::maybe_grow(RED_ZONE, STACK_SIZE, || self.eval_block_statement(body))
+                .unwrap_or(Object::Nil);
+
+        let caller = self.calls.pop().unwrap();
+        self.line = caller.line;
+        self.generator = generator;
+
+        (
+            evaluated,
+            std::mem::replace(&mut self.environment, caller.environment),
+        )
+    }
+
+    // Checks a call nesting `depth` calls deep. Calls nest on the Rust stack,
+    // so they're capped even without limits.
+    fn enter(&self, depth: usize) -> Result<(), String> {
+        self.budget.enter(depth)?;
+
+        if depth > MAX_DEPTH {
+            return Err("stack overflow".to_string());
+        }
+
+        Ok(())
+    }
+
+    // Picks up a generator where whoever resumes it is, with their debugger.
+    fn resumed(&mut self, Resume { depth, debugger }: Resume) -> Result<(), String> {
+        self.depth = depth;
+        self.debugger = debugger;
+
+        self.enter(depth)
+    }
+
+    fn yield_value(&mut self, value: Object) -> Result<(), String> {
+        let channel = self.generator.as_ref().unwrap();
+        let yielded = (Some(value), self.debugger.take());
+
+        let resume = channel
+            .yielded
+            .send(yielded)
+            .ok()
+            .and_then(|()| channel.resume.recv().ok())
+            .ok_or("generator was dropped")?;
+
+        self.resumed(resume)
+    }
+
     fn eval_block_statement(&mut self, stmts: &[Statement]) -> Option<Object> {
         let mut result = None;
 
@@ -919,29 +1799,37 @@ impl Evaluator {
                 }
             }
             Operator::Sub => match right {
-                Object::Int(Int { value }) => Object::int(-value),
+                Object::Int(Int { value }) => value.checked_neg().map_or_else(
+                    || Object::error(format!("integer overflow: -({value})")),
+                    Object::int,
+                ),
                 Object::Float(Float { value }) => Object::float(-value),
                 _ => Object::error(format!("unsupported type for negation: {}", right.kind())),
             },
+            Operator::BitNot => match right {
+                Object::Int(Int { value }) => Object::int(!value),
+                _ => Object::error(format!(
+                    "unsupported type for bitwise not: {}",
+                    right.kind()
+                )),
+            },
             _ => Object::error(format!("unknown operator: {}{}", operator, right.kind())),
         }
     }
 
     fn eval_infix_expression(operator: Operator, left: Object, right: Object) -> Object {
         match (left.clone(), right.clone()) {
-            (Object::Nil, Object::Nil) => match operator {
-                Operator::Eq => Object::TRUE,
-                Operator::NotEq => Object::FALSE,
-                _ => Object::error(format!(
-                    "unknown operator: {} {} {}",
-                    left.kind(),
-                    operator,
-                    right.kind()
-                )),
-            },
+            (Object::Nil, _) if operator == Operator::NilCoalesce => right,
+            _ if operator == Operator::NilCoalesce => left,
+            _ if matches!(operator, Operator::In | Operator::NotIn) => {
+                match contains(&right, &left) {
+                    Ok(found) => Object::bool(found == (operator == Operator::In)),
+                    Err(err) => Object::error(err),
+                }
+            }
             (Object::Nil, _) | (_, Object::Nil) => match operator {
-                Operator::Eq => Object::FALSE,
-                Operator::NotEq => Object::TRUE,
+                Operator::Eq => Object::bool(left == right),
+                Operator::NotEq => Object::bool(left != right),
                 _ => Object::error(format!(
                     "unknown operator: {} {} {}",
                     left.kind(),
@@ -969,6 +1857,16 @@ impl Evaluator {
             (Object::Float(Float { value: left }), Object::Float(Float { value: right })) => {
                 Self::eval_float_infix_expression(operator, left, right)
             }
+            (Object::Int(Int { value: left }), Object::Float(Float { value: right }))
+                if !operator.is_bitwise() =>
+            {
+                Self::eval_float_infix_expression(operator, int_to_float(left), right)
+            }
+            (Object::Float(Float { value: left }), Object::Int(Int { value: right }))
+                if !operator.is_bitwise() =>
+            {
+                Self::eval_float_infix_expression(operator, left, int_to_float(right))
+            }
             (Object::Char(Char { value: left }), Object::Char(Char { value: right })) => {
                 Self::eval_char_infix_expression(operator, left, right)
             }
@@ -991,6 +1889,18 @@ impl Evaluator {
             (Object::Str(Str { value: left }), Object::Str(Str { value: right })) => {
                 Self::eval_string_infix_expression(operator, &left, &right)
             }
+            // Instances without an `eq` method are equal when their fields are,
+            // and iterators when they're the same iterator.
+            (Object::Class(_) | Object::Iter(_), _) | (_, Object::Class(_) | Object::Iter(_))
+                if matches!(operator, Operator::Eq | Operator::NotEq) =>
+            {
+                Object::bool((left == right) == (operator == Operator::Eq))
+            }
+            (Object::EnumValue(_), Object::EnumValue(_)) => match operator {
+                Operator::Eq => native_bool_boolean_object(left == right),
+                Operator::NotEq => native_bool_boolean_object(left != right),
+                _ => Object::error(format!("unknown operator: VARIANT {operator} VARIANT")),
+            },
             _ if left.kind() != right.kind() => Object::error(format!(
                 "unsupported types for binary operation: {} {} {}",
                 left.kind(),
@@ -1008,16 +1918,20 @@ impl Evaluator {
 
     fn eval_integer_infix_expression(operator: Operator, left: isize, right: isize) -> Object {
         match operator {
-            Operator::Add => Object::int(left + right),
-            Operator::Sub => Object::int(left - right),
-            Operator::Mul => Object::int(left * right),
-            Operator::Div => Object::int(left / right),
-            Operator::Mod => Object::int(left % right),
-            Operator::BitXor => Object::int(left ^ right),
-            Operator::BitAnd => Object::int(left & right),
-            Operator::BitOr => Object::int(left | right),
-            Operator::Shr => Object::int(left >> right),
-            Operator::Shl => Object::int(left << right),
+            Operator::Add
+            | Operator::Sub
+            | Operator::Mul
+            | Operator::Div
+            | Operator::Mod
+            | Operator::FloorDiv
+            | Operator::BitXor
+            | Operator::BitAnd
+            | Operator::BitOr
+            | Operator::Shr
+            | Operator::Shl => {
+                int_op(left, &operator.to_string(), right).map_or_else(Object::error, Object::int)
+            }
+            Operator::Pow => int_pow(left, right).unwrap_or_else(Object::error),
             Operator::Lt => native_bool_boolean_object(left < right),
             Operator::Gt => native_bool_boolean_object(left > right),
             Operator::Eq => native_bool_boolean_object(left == right),
@@ -1035,6 +1949,8 @@ impl Evaluator {
             Operator::Mul => Object::float(left * right),
             Operator::Div => Object::float(left / right),
             Operator::Mod => Object::float(left % right),
+            Operator::Pow => Object::float(left.powf(right)),
+            Operator::FloorDiv => Object::float((left / right).floor()),
             Operator::Lt => native_bool_boolean_object(left < right),
             Operator::Gt => native_bool_boolean_object(left > right),
             Operator::Eq => native_bool_boolean_object((left - right).abs() < f64::EPSILON),
@@ -1161,12 +2077,19 @@ impl Evaluator {
 
                 let extended_env = extend_function_env(func.clone(), args);
 
-                let old_env = self.environment.clone();
-                self.environment = extended_env;
+                if func.generator {
+                    let evaluator = Self {
+                        environment: extended_env,
+                        io: self.io.clone(),
+                        budget: self.budget.clone(),
+                        modules: self.modules.clone(),
+                        ..Self::new_with_natives(ModuleLoader::default(), self.natives.clone())
+                    };
 
-                let evaluated = self.eval_block_statement(&func.body).unwrap_or(Object::Nil);
+                    return spawn_generator(evaluator, func.body.clone());
+                }
 
-                self.environment = old_env;
+                let (evaluated, _) = self.eval_function_body(&func.name, extended_env, &func.body);
 
                 if is_error(&evaluated) {
                     return evaluated;
@@ -1175,15 +2098,47 @@ impl Evaluator {
                 unwrap_return_value(evaluated)
             }
 
-            Object::Builtin(Builtin { func, caller, .. }) => func(
-                &(caller.clone().unwrap_or_else(|| Box::new(Object::Nil))),
-                args,
-            ),
+            Object::Builtin(Builtin { name, func, caller }) => {
+                let caller = caller.clone().unwrap_or_else(|| Box::new(Object::Nil));
+
+                if !matches!(name.as_str(), "print" | "println" | "str" | "format") {
+                    return self.budget.run_builtin(|| {
+                        self.io
+                            .call_builtin(name, args)
+                            .unwrap_or_else(|| func(&caller, args))
+                    });
+                }
+
+                let mut str_args = Vec::with_capacity(args.len());
+
+                for arg in args {
+                    let arg = self.eval_to_string(arg.clone());
+
+                    if is_error(&arg) {
+                        return arg;
+                    }
+
+                    str_args.push(arg);
+                }
+
+                self.budget.run_builtin(|| {
+                    self.io
+                        .call_builtin(name, &str_args)
+                        .unwrap_or_else(|| func(&caller, &str_args))
+                })
+            }
+
+            Object::Native(native) => self.budget.run_builtin(|| native.call(&self.io, args)),
 
             _ => Object::error(format!("not a function: {}", func.kind())),
         }
     }
 
+    fn eval_index(&mut self, mut left: Object, index: &Object) -> Object {
+        self.call_special_method(&mut left, "index", std::slice::from_ref(index))
+            .unwrap_or_else(|| Self::eval_index_expression(&left, index))
+    }
+
     fn eval_index_expression(left: &Object, index: &Object) -> Object {
         match (left, index) {
             (Object::Array(Array { elements }), Object::Int(Int { value })) => {
@@ -1219,14 +2174,19 @@ impl Evaluator {
     }
 
     fn eval_string_index_expression(string: &str, idx: isize) -> Object {
-        let max = string.len();
-        let idx = normalize_index(idx, max);
+        // Only counting from the end needs the whole string segmented.
+        let count = if idx.is_negative() {
+            grapheme_count(string)
+        } else {
+            0
+        };
+        let idx = normalize_index(idx, count);
 
-        if idx >= max {
+        let Some(grapheme) = grapheme_at(string, idx) else {
             return Object::error(format!("index out of bounds. got: {idx}"));
-        }
+        };
 
-        Object::char(string.chars().nth(idx).unwrap())
+        grapheme_object(grapheme)
     }
 
     fn eval_array_slice_expression(
@@ -1253,26 +2213,7 @@ impl Evaluator {
     }
 
     fn eval_string_slice_expression(string: &str, start: isize, end: isize, step: isize) -> Object {
-        let max = (string.len() - 1).try_into().unwrap();
-
-        if start > max || end > max || start < 0 || end < 0 || start > end {
-            return Object::error("cannot slice ARRAY using this range".to_string());
-        }
-
-        let mut value = String::new();
-
-        let mut i = start;
-        while i < end {
-            value.push(
-                string
-                    .chars()
-                    .nth(TryInto::<usize>::try_into(i).unwrap())
-                    .unwrap(),
-            );
-            i += step;
-        }
-
-        Object::Str(Str { value })
+        slice_str(string, start, end, step).map_or_else(Object::error, Object::str)
     }
 
     fn eval_dict_index_expression(pairs: &HashMap<u64, DictPair>, index: &Object) -> Object {
@@ -1321,157 +2262,343 @@ impl Evaluator {
         Some(Object::dict(obj_pairs))
     }
 
-    fn eval_assign_expression(&mut self, to: Assignable, value: &Expression) -> Option<Object> {
-        let val = self.eval(Node::Expr(value.clone()))?;
+    fn eval_assign_expression(
+        &mut self,
+        to: Assignable,
+        operator: Option<Operator>,
+        value: &Expression,
+    ) -> Option<Object> {
+        match to {
+            Assignable::Identifier(Identifier { value: name }) => {
+                let Some((current, mutable)) = self.environment.get(name.clone()) else {
+                    return Some(Object::error(format!("undefined variable {name}")));
+                };
 
-        if is_error(&val) {
-            return Some(val);
-        }
+                if !mutable {
+                    return Some(Object::error(format!("identifier is not mutable: {name}")));
+                }
 
-        match to {
-            Assignable::Identifier(Identifier { value }) => {
-                if let Some((_, mutable)) = self.environment.get(value.clone()) {
-                    if mutable {
-                        self.environment.set(value, val.clone(), mutable);
-                        Some(val)
-                    } else {
-                        Some(Object::error(format!("identifier is not mutable: {value}")))
-                    }
-                } else {
-                    Some(Object::error(format!("undefined variable {value}")))
+                let val = self.eval_assigned_value(operator, current, value)?;
+
+                if !is_error(&val) {
+                    self.environment.set(name, val.clone(), mutable);
                 }
+
+                Some(val)
             }
 
-            Assignable::Index(Index { left, index }) => {
+            Assignable::Scope(scope) => self.eval_static_assign(scope, operator, value),
+
+            Assignable::Index(Index { left, index, .. }) => {
+                let Expression::Identifier(Identifier { value: name }) = *left else {
+                    return Some(Object::error("cannot assign".to_string()));
+                };
+
                 let index = self.eval(Node::Expr(*index))?;
 
                 if is_error(&index) {
                     return Some(index);
                 }
 
-                if let Expression::Identifier(Identifier { value: index_ident }) = *left {
-                    if let Some((data, mutable)) = self.environment.get(index_ident.clone()) {
-                        if mutable {
-                            match (data.clone(), index.clone()) {
-                                (
-                                    Object::Array(Array { elements }),
-                                    Object::Int(Int { value: idx }),
-                                ) => {
-                                    let mut new_data = elements.clone();
-                                    let max = elements.len();
-                                    let idx = normalize_index(idx, max);
-                                    if idx < max {
-                                        new_data[idx] = val.clone();
-                                    }
-
-                                    self.environment.set(
-                                        index_ident,
-                                        Object::array(new_data),
-                                        true,
-                                    );
-                                }
+                let Some((mut data, mutable)) = self.environment.get(name.clone()) else {
+                    return Some(Object::error(format!("undefined variable {name}")));
+                };
 
-                                (Object::Str(Str { value }), Object::Int(Int { value: idx })) => {
-                                    let mut new_data = value.chars().collect::<Vec<_>>();
-                                    let max = value.len();
-                                    let idx = normalize_index(idx, max);
-
-                                    if let Object::Char(Char { value: ch }) = val {
-                                        if idx < max {
-                                            new_data[idx] = ch;
-                                        }
-                                        self.environment.set(
-                                            index_ident,
-                                            Object::Str(Str {
-                                                value: new_data.iter().collect(),
-                                            }),
-                                            true,
-                                        );
-                                    } else {
-                                        return Some(Object::error(format!(
-                                            "cannot assign {} to STR, expected CHAR",
-                                            val.kind()
-                                        )));
-                                    }
-                                }
+                if !mutable {
+                    return Some(Object::error(format!("identifier is not mutable: {name}")));
+                }
 
-                                (Object::Dict(Dict { pairs }), _) => {
-                                    let mut new_data = pairs;
-
-                                    let Some(hashable) = Hashable::from_object(&index) else {
-                                        return Some(Object::error(format!(
-                                            "unusable as hash key: {}",
-                                            index.kind()
-                                        )));
-                                    };
-
-                                    new_data.insert(
-                                        hashable.hash(),
-                                        DictPair {
-                                            key: hashable,
-                                            value: val.clone(),
-                                        },
-                                    );
-
-                                    self.environment
-                                        .set(index_ident, Object::dict(new_data), true);
-                                }
+                let current = if operator.is_some() {
+                    self.eval_index(data.clone(), &index)
+                } else {
+                    Object::Nil
+                };
 
-                                _ => {
-                                    return Some(Object::error(format!(
-                                        "cannot assign to index expression: {}[{}]",
-                                        data.kind(),
-                                        index.kind()
-                                    )))
-                                }
-                            }
-                            return Some(val);
-                        }
-                        return Some(Object::error(format!("identifier is not mutable: {value}")));
+                if is_error(&current) {
+                    return Some(current);
+                }
+
+                let val = self.eval_assigned_value(operator, current, value)?;
+
+                if is_error(&val) {
+                    return Some(val);
+                }
+
+                let args = [index.clone(), val.clone()];
+
+                if let Some(ret) = self.call_special_method(&mut data, "setIndex", &args) {
+                    if is_error(&ret) {
+                        return Some(ret);
                     }
-                    return Some(Object::error(format!("undefined variable {value}")));
+
+                    self.environment.set(name, data, true);
+                    return Some(val);
                 }
 
-                Some(Object::error("cannot assign".to_string()))
+                match set_index(data, &index, val.clone()) {
+                    Ok(data) => {
+                        self.environment.set(name, data, true);
+                        Some(val)
+                    }
+                    Err(err) => Some(Object::error(err)),
+                }
             }
 
             Assignable::Method(Method {
                 left, name: method, ..
             }) => {
-                if let Expression::Identifier(Identifier { value }) = *left {
-                    if let Some((data, mutable)) = self.environment.get(value.clone()) {
-                        if mutable {
-                            let mut new_obj = data.clone();
-                            let Object::Class(ast_node) = &mut new_obj else {
-                                return Some(Object::error(format!(
-                                    "cannot assign to non-class instances. got: {}",
-                                    data.kind()
-                                )));
-                            };
+                let Expression::Identifier(Identifier { value: name }) = *left else {
+                    return Some(Object::error(format!("cannot assign to method '{method}'")));
+                };
 
-                            match ast_node.members.entry(hash_method_name(&method)) {
-                                Entry::Vacant(entry) => {
-                                    entry.insert(ClassMember::new(method, val.clone()));
-                                }
-                                Entry::Occupied(mut entry) => {
-                                    entry.insert(ClassMember::new(method, val.clone()));
-                                }
-                            };
+                self.eval_member_assign(name, method, operator, value)
+            }
+        }
+    }
 
-                            self.environment.set(value, new_obj, true);
-                            return Some(val);
-                        }
-                        return Some(Object::error(format!("identifier is not mutable: {value}")));
-                    }
-                    return Some(Object::error(format!("undefined variable {value}")));
-                }
+    fn eval_member_assign(
+        &mut self,
+        name: String,
+        member: String,
+        operator: Option<Operator>,
+        value: &Expression,
+    ) -> Option<Object> {
+        let Some((mut data, mutable)) = self.environment.get(name.clone()) else {
+            return Some(Object::error(format!("undefined variable {name}")));
+        };
+
+        if !mutable {
+            return Some(Object::error(format!("identifier is not mutable: {name}")));
+        }
+
+        let Object::Class(class) = &data else {
+            return Some(Object::error(format!(
+                "cannot assign to non-class instances. got: {}",
+                data.kind()
+            )));
+        };
+
+        if let Err(err) = self.check_private(&class.name, &member) {
+            return Some(Object::error(err));
+        }
+
+        let (getter, setter) = if self.in_accessor(&class.name, &member) {
+            (None, None)
+        } else {
+            (
+                accessor(class, &getter_name(&member)),
+                accessor(class, &setter_name(&member)),
+            )
+        };
+
+        if getter.is_some() && setter.is_none() {
+            return Some(Object::error(format!(
+                "cannot assign to read-only property '{member}'"
+            )));
+        }
+
+        let field = class
+            .members
+            .get(&hash_method_name(&member))
+            .map(|field| field.obj.clone());
+
+        let current = match (operator, getter, field) {
+            (None, ..) => Object::Nil,
+            (Some(_), Some(getter), _) => self.eval_accessor(&mut data, &getter, &member, &[]),
+            (Some(_), None, Some(field)) => field,
+            (Some(_), None, None) => {
+                return Some(Object::error(format!("undefined member '{member}'")))
+            }
+        };
+
+        if is_error(&current) {
+            return Some(current);
+        }
+
+        let val = self.eval_assigned_value(operator, current, value)?;
+
+        if is_error(&val) {
+            return Some(val);
+        }
+
+        if let Some(setter) = setter {
+            let ret = self.eval_accessor(&mut data, &setter, &member, std::slice::from_ref(&val));
+
+            if is_error(&ret) {
+                return Some(ret);
+            }
+        } else if let Object::Class(Class { members, .. }) = &mut data {
+            members.insert(
+                hash_method_name(&member),
+                ClassMember::new(member, val.clone()),
+            );
+        }
+
+        self.environment.set(name, data, true);
+        Some(val)
+    }
+
+    fn eval_static_assign(
+        &mut self,
+        Scope { module, member }: Scope,
+        operator: Option<Operator>,
+        value: &Expression,
+    ) -> Option<Object> {
+        let Expression::Identifier(Identifier { value: name }) = *member else {
+            return Some(Object::error("cannot assign".to_string()));
+        };
+
+        let statics = self.environment.get_statics(&module);
 
-                Some(Object::error(format!("cannot assign to method '{method}'")))
+        let Some((statics, (current, mutable))) =
+            statics.and_then(|statics| statics.get(&name).map(|member| (statics, member)))
+        else {
+            return Some(Object::error(format!(
+                "no static member `{name}` found in class \"{module}\""
+            )));
+        };
+
+        if !mutable {
+            return Some(Object::error(format!(
+                "identifier is not mutable: {module}::{name}"
+            )));
+        }
+
+        let val = self.eval_assigned_value(operator, current, value)?;
+
+        if !is_error(&val) {
+            statics.set(name, val.clone(), mutable);
+        }
+
+        Some(val)
+    }
+
+    // Evaluates the right hand side of an assignment, combining it with the
+    // target's `current` value for compound assignments like `+=`.
+    fn eval_assigned_value(
+        &mut self,
+        operator: Option<Operator>,
+        current: Object,
+        value: &Expression,
+    ) -> Option<Object> {
+        let val = self.eval(Node::Expr(value.clone()))?;
+
+        if is_error(&val) {
+            return Some(val);
+        }
+
+        Some(match operator {
+            Some(operator) => Self::eval_infix_expression(operator, current, val),
+            None => val,
+        })
+    }
+}
+
+impl Driver for Evaluator {
+    fn resume(&mut self, generator: &Generator) -> Result<Option<Object>, String> {
+        let Generator::Evaluated(generator) = generator else {
+            return Err("cannot resume a generator created by the vm".to_string());
+        };
+
+        let (next, debugger) = {
+            let generator = generator.lock().unwrap();
+
+            if generator.done {
+                return Ok(None);
             }
+
+            let resume = Resume {
+                depth: self.depth + self.calls.len() + 1,
+                debugger: self.debugger.take(),
+            };
+
+            match generator.resume.send(resume) {
+                Ok(()) => generator.yielded.recv().unwrap_or((None, None)),
+                Err(SendError(resume)) => (None, resume.debugger),
+            }
+        };
+
+        self.debugger = debugger;
+
+        match next {
+            Some(Object::Error(Error { value })) => {
+                generator.lock().unwrap().done = true;
+                Err(value)
+            }
+            Some(obj) => Ok(Some(obj)),
+            None => {
+                generator.lock().unwrap().done = true;
+                Ok(None)
+            }
+        }
+    }
+
+    fn call_member(&mut self, instance: &mut Object, name: &str) -> Result<Object, String> {
+        let member = instance.call_method(hash_method_name(name), None);
+
+        let ret = match &member {
+            Object::EvaluatedFunction(func) => self.eval_bound_method(instance, func, &[]),
+            Object::Error(_) => member,
+            _ => self.eval_call_expression(&member, &[]),
+        };
+
+        match ret {
+            Object::Error(Error { value }) => Err(value),
+            _ => Ok(ret),
         }
     }
 }
 
+// The body of a generator runs on its own thread, which only makes progress
+// between a resume and the next `yield`.
+fn spawn_generator(mut evaluator: Evaluator, body: BlockStatement) -> Object {
+    let (resume_sender, resume) = mpsc::channel();
+    let (yielded, yielded_receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let Ok(first) = resume.recv() else {
+            return;
+        };
+
+        evaluator.generator = Some(GeneratorChannel {
+            yielded: yielded.clone(),
+            resume,
+        });
+
+        let evaluated = match evaluator.resumed(first) {
+            Ok(()) => evaluator.eval_block_statement(&body),
+            Err(err) => Some(Object::error(err)),
+        };
+
+        let debugger = evaluator.debugger.take();
+
+        if let Some(err @ Object::Error(_)) = evaluated {
+            let _ = yielded.send((Some(err), debugger));
+        } else {
+            let _ = yielded.send((None, debugger));
+        }
+    });
+
+    Object::Iter(Iter::new(IterState::Generator(Generator::Evaluated(
+        Arc::new(Mutex::new(EvaluatedGenerator {
+            resume: resume_sender,
+            yielded: yielded_receiver,
+            done: false,
+        })),
+    ))))
+}
+
+fn environment_variables(environment: &Environment) -> Vec<(String, Object)> {
+    let mut variables = environment
+        .store
+        .iter()
+        .map(|(name, (value, _))| (name.clone(), value.clone()))
+        .collect::<Vec<_>>();
+
+    variables.sort_by(|(a, _), (b, _)| a.cmp(b));
+    variables
+}
+
 fn is_truthy(obj: &Object) -> bool {
     match obj {
         Object::Nil => false,
@@ -1486,6 +2613,16 @@ fn is_truthy(obj: &Object) -> bool {
     }
 }
 
+fn accessor(class: &Class, name: &str) -> Option<EvaluatedFunction> {
+    match class.members.get(&hash_method_name(name)) {
+        Some(ClassMember {
+            obj: Object::EvaluatedFunction(func),
+            ..
+        }) => Some(func.clone()),
+        _ => None,
+    }
+}
+
 fn extend_function_env(func: EvaluatedFunction, args: &[Object]) -> Environment {
     let mut environment = Environment::new_enclosed(func.environment.clone());
 
@@ -4,8 +4,10 @@ use pretty_assertions::assert_eq;
 
 use super::{eval::Evaluator, vm::VM};
 use crate::{
+    ast::Node,
     compiler::Compiler,
     lexer::Lexer,
+    native::NativeModule,
     object::{DictPair, Hashable, Object},
     parser::Parser,
 };
@@ -17,47 +19,76 @@ struct TestCase {
 
 fn run_tests(test_cases: &[TestCase]) {
     for test_case in test_cases {
-        let mut l = Lexer::new(test_case.input);
-        let mut p = Parser::new(&mut l);
+        if !run_test(test_case.input, &test_case.expected) {
+            return;
+        }
+    }
+}
 
-        if !p.errors.is_empty() {
-            for err in p.errors {
-                println!("parser error: {err}");
-            }
+// Returns whether `input` compiled, and so ran on the VM too.
+fn run_test(input: &str, expected: &Object) -> bool {
+    run_test_with(input, expected, |_| {}, |_| {})
+}
+
+// Like `run_test`, with hooks to set up each engine before it runs `input`,
+// e.g. to register native modules.
+fn run_test_with(
+    input: &str,
+    expected: &Object,
+    setup_eval: impl Fn(&mut Evaluator),
+    setup_vm: impl Fn(&mut Compiler),
+) -> bool {
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
 
-            panic!()
+    if !p.errors.is_empty() {
+        for err in p.errors {
+            println!("parser error: {err}");
         }
 
-        let program = p.parse_program().unwrap();
+        panic!()
+    }
 
-        // Evaluator
+    let program = p.parse_program().unwrap();
 
-        let mut evaluator = Evaluator::new();
+    // Evaluator
 
-        let evaluated = evaluator.eval(program.clone()).unwrap_or(Object::Nil);
+    let mut evaluator = Evaluator::new();
+    setup_eval(&mut evaluator);
 
-        assert_eq!(test_case.expected, evaluated);
+    let evaluated = evaluator.eval(program.clone()).unwrap_or(Object::Nil);
 
-        // Compiler + VM
-        let mut comp = Compiler::new();
+    assert_eq!(*expected, evaluated, "{input}");
 
-        if let Err(err) = comp.compile(program) {
-            assert_eq!(test_case.expected, Object::error(err));
-            return;
+    // Compiler + VM
+    match run_vm(program, setup_vm) {
+        Ok(evaluated) => {
+            assert_eq!(*expected, evaluated, "{input}");
+            true
         }
+        Err(err) => {
+            assert_eq!(*expected, Object::error(err), "{input}");
+            false
+        }
+    }
+}
 
-        let byte_code = comp.bytecode();
+// Runs `program` on the VM, failing if it doesn't compile. Errors at run time
+// are returned as error objects.
+fn run_vm(program: Node, setup: impl Fn(&mut Compiler)) -> Result<Object, String> {
+    let mut comp = Compiler::new();
+    setup(&mut comp);
+    comp.compile(program)?;
 
-        let mut vm = VM::new(&byte_code);
+    let byte_code = comp.bytecode();
 
-        let evaluated = if let Err(err) = vm.run() {
-            Object::error(err)
-        } else {
-            vm.last_popped_stack_elem.unwrap_or(Object::Nil)
-        };
+    let mut vm = VM::new(&byte_code);
 
-        assert_eq!(test_case.expected, evaluated);
-    }
+    Ok(if let Err(err) = vm.run() {
+        Object::error(err)
+    } else {
+        vm.last_popped_stack_elem.unwrap_or(Object::Nil)
+    })
 }
 
 #[test]
@@ -759,6 +790,14 @@ fn test_functions() {
             input: "var fivePlusTen = fn() { 5 + 10 }; fivePlusTen()",
             expected: Object::int(15),
         },
+        TestCase {
+            input: "fn outer() { var c = 0; fn inc() { c = c + 1; return c; } inc(); return inc(); } outer()",
+            expected: Object::int(1),
+        },
+        TestCase {
+            input: "fn outer() { var xs = [1, 2]; fn() { xs[0] += 10; xs = xs.push(3); xs } } outer()()",
+            expected: Object::array(Vec::from([Object::int(11), Object::int(2), Object::int(3)])),
+        },
         TestCase {
             input: "
 var a = fn() {
@@ -1374,77 +1413,1466 @@ x
 }
 
 #[test]
-fn test_while_statement() {
+fn test_generators() {
     run_tests(&[
         TestCase {
-            input: "var i = 0;
-var j = 0;
-while (j < 10) {
-    i = i + j;
-    j = j + 1;
+            input: "
+fn* count(n) {
+    var i = 0;
+    while (i < n) {
+        yield i;
+        i = i + 1;
+    }
 }
 
-i",
+var x = 0;
+for (i in count(10)) {
+    x = x + i;
+}
+x
+",
             expected: Object::int(45),
         },
         TestCase {
-            input: "var arr = [];
-var i = 0;
-while (i < 2) {
-    arr = arr.push(i)
-    i = i + 1;
-}
+            input: "
+const evens = fn*(n) {
+    for (i in 0..n) {
+        if (i % 2 == 0) {
+            yield i;
+        }
+    }
+};
 
-arr",
-            expected: Object::array(Vec::from([Object::int(0), Object::int(1)])),
+evens(7).collect()
+",
+            expected: Object::array(Vec::from([
+                Object::int(0),
+                Object::int(2),
+                Object::int(4),
+                Object::int(6),
+            ])),
         },
         TestCase {
-            input: "var i = 10; while (i > 0) { i = i - 1 }; i",
-            expected: Object::int(0),
+            input: "
+fn* pair() {
+    yield 1;
+    yield 2;
+}
+
+var g = pair();
+[g.next(), g.next(), g.next()]
+",
+            expected: Object::array(Vec::from([Object::int(1), Object::int(2), Object::Nil])),
         },
         TestCase {
             input: "
-var i = 10;
-while (i > 0) {
-    i = i - 1;
-    if (i == 5) {
-        break;
+fn sum() {
+    const gen = fn*() { yield 3; yield 4; };
+    var total = 0;
+    for (i in gen()) {
+        total = total + i;
     }
-};
-i",
-            expected: Object::int(5),
+    return total;
+}
+
+sum()
+",
+            expected: Object::int(7),
+        },
+    ]);
+
+    // Evaluated functions capture `it` before it's assigned, so only the VM
+    // can reach a generator from inside itself.
+    let input =
+        "var it = nil; fn* g() { yield 1; yield it.next(); } it = g(); it.next(); it.next()";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+    let program = p.parse_program().unwrap();
+
+    let mut comp = Compiler::new();
+    comp.compile(program).unwrap();
+    let byte_code = comp.bytecode();
+
+    let mut vm = VM::new(&byte_code);
+    assert_eq!(vm.run(), Err("generator already running".to_string()));
+}
+
+#[test]
+fn test_iterator_adapters() {
+    run_tests(&[
+        TestCase {
+            input: "iter([1]).foo()",
+            expected: Object::error("no method found for ITER".to_string()),
+        },
+        TestCase {
+            input: "var it = iter([1]); [iter([1]) == iter([1]), it == it, it != 1]",
+            expected: Object::array(Vec::from([Object::FALSE, Object::TRUE, Object::TRUE])),
+        },
+        TestCase {
+            input: "[iter([1])]",
+            expected: Object::error("ARRAY cannot contain ITER".to_string()),
+        },
+        TestCase {
+            input: "iter(0..10).skip(2).take(3).collect()",
+            expected: Object::array(Vec::from([Object::int(2), Object::int(3), Object::int(4)])),
+        },
+        TestCase {
+            input: "iter(\"ab\").enumerate().collect()",
+            expected: Object::array(Vec::from([
+                Object::array(Vec::from([Object::int(0), Object::char('a')])),
+                Object::array(Vec::from([Object::int(1), Object::char('b')])),
+            ])),
+        },
+        TestCase {
+            input: "iter([1, 2, 3]).zip(0..2).collect()",
+            expected: Object::array(Vec::from([
+                Object::array(Vec::from([Object::int(1), Object::int(0)])),
+                Object::array(Vec::from([Object::int(2), Object::int(1)])),
+            ])),
+        },
+        TestCase {
+            input: "iter([1]).chain(2..4).collect()",
+            expected: Object::array(Vec::from([Object::int(1), Object::int(2), Object::int(3)])),
         },
         TestCase {
             input: "
-var i = 10;
-var j = 0;
-while (i > 0) {
-    i = i - 1;
-    if (i < 5) {
-        continue;
+fn* naturals() {
+    var i = 0;
+    while (true) {
+        yield i;
+        i = i + 1;
     }
-    j = i;
-};
-j",
+}
+
+naturals().skip(100).take(2).collect()
+",
+            expected: Object::array(Vec::from([Object::int(100), Object::int(101)])),
+        },
+        TestCase {
+            input: "iter(1)",
+            expected: Object::error("INT is not iterable".to_string()),
+        },
+    ]);
+}
+
+#[test]
+fn test_number_literals() {
+    run_tests(&[
+        TestCase {
+            input: "0xFF + 0o10 + 0b11",
+            expected: Object::int(266),
+        },
+        TestCase {
+            input: "0xF0 | 0b1111",
+            expected: Object::int(255),
+        },
+        TestCase {
+            input: "1_000_000 / 1_000",
+            expected: Object::int(1000),
+        },
+        TestCase {
+            input: "1e3 + 2.5e-3",
+            expected: Object::float(1000.0025),
+        },
+    ]);
+}
+
+#[test]
+fn test_string_literals() {
+    run_tests(&[
+        TestCase {
+            input: r#""\n".len()"#,
+            expected: Object::int(1),
+        },
+        TestCase {
+            input: r#"r"\n".len()"#,
+            expected: Object::int(2),
+        },
+        TestCase {
+            input: r#""\u{41}\x42\u0043" == "ABC""#,
+            expected: Object::bool(true),
+        },
+        TestCase {
+            input: "var q = \"\"\"\n    a\n      b\t{}\n    \"\"\"; q",
+            expected: Object::str("a\n  b\t{}".to_string()),
+        },
+        TestCase {
+            input: r#"f"\t{1}\"""#,
+            expected: Object::str("\t1\"".to_string()),
+        },
+    ]);
+}
+
+#[test]
+fn test_unicode_strings() {
+    run_tests(&[
+        TestCase {
+            input: r#"var naïve = "héllo"; naïve.len()"#,
             expected: Object::int(5),
         },
+        TestCase {
+            input: r#""日本語"[1]"#,
+            expected: Object::char('本'),
+        },
+        TestCase {
+            input: r#""日本語"[-1]"#,
+            expected: Object::char('語'),
+        },
+        TestCase {
+            input: r#""héllo"[1..5]"#,
+            expected: Object::str("éllo".to_string()),
+        },
+        TestCase {
+            input: r#""héllo"[0..5..2]"#,
+            expected: Object::str("hlo".to_string()),
+        },
+        TestCase {
+            input: r#""ab"[1..3]"#,
+            expected: Object::error("cannot slice STR using 1..3..1".to_string()),
+        },
+        TestCase {
+            input: r#""e\u{301}x".len()"#,
+            expected: Object::int(2),
+        },
+        TestCase {
+            input: r#""e\u{301}x".chars()"#,
+            expected: Object::array(Vec::from([
+                Object::str("e\u{301}".to_string()),
+                Object::char('x'),
+            ])),
+        },
+        TestCase {
+            input: r#"["e\u{301}x"[0], "e\u{301}x"[-1], "ae\u{301}x"[1..3]]"#,
+            expected: Object::array(Vec::from([
+                Object::str("e\u{301}".to_string()),
+                Object::char('x'),
+                Object::str("e\u{301}x".to_string()),
+            ])),
+        },
+        TestCase {
+            input: r#"var s = "e\u{301}x"; s[0] = 'a'; s"#,
+            expected: Object::str("ax".to_string()),
+        },
+        TestCase {
+            input: r#"var n = 0; for (g in "e\u{301}é") { n += 1; } n"#,
+            expected: Object::int(2),
+        },
+        TestCase {
+            input: r#""e\u{301}x".graphemes()"#,
+            expected: Object::array(Vec::from([
+                Object::str("e\u{301}".to_string()),
+                Object::str("x".to_string()),
+            ])),
+        },
     ]);
 }
 
 #[test]
-fn test_assign_expressions() {
+fn test_string_interpolation() {
     run_tests(&[
         TestCase {
-            input: "var i = 10; i = i - 1 ; i",
-            expected: Object::int(9),
+            input: r#"var name = "Ann"; f"Hello {name}, you are {29 + 1}!""#,
+            expected: Object::str("Hello Ann, you are 30!".to_string()),
         },
-        {
-            TestCase {
-                input: "var arr = [1, 1, 1, 1];
-arr[0] = 0;
-arr[0]",
-                expected: Object::int(0),
-            }
+        TestCase {
+            input: r#"f"{{{[1, 2][1]}}} {1.23456:.2} {255:#06x} {"ab":*^6}""#,
+            expected: Object::str("{2} 1.23 0x00ff **ab**".to_string()),
+        },
+        TestCase {
+            input: r#"f"{"x":b}""#,
+            expected: Object::error("cannot format STR using a radix".to_string()),
+        },
+    ]);
+}
+
+#[test]
+#[allow(clippy::literal_string_with_formatting_args)]
+fn test_format_builtin() {
+    run_tests(&[
+        TestCase {
+            input: r#"format("{:<4}|{1:>5.1}|{0}", 7, 2.25)"#,
+            expected: Object::str("7   |  2.2|7".to_string()),
+        },
+        TestCase {
+            input: r#"format("{:x} {:?}", [10, 255], {"a": 'c'})"#,
+            expected: Object::str("[a, ff] {\"a\": 'c'}".to_string()),
+        },
+        TestCase {
+            input: r#"format("{} {}", 1)"#,
+            expected: Object::error(
+                "format string references argument 1 but only 1 given".to_string(),
+            ),
+        },
+        TestCase {
+            input: r#"format("{}", 1, 2)"#,
+            expected: Object::error("argument 1 is never used in format string".to_string()),
+        },
+        TestCase {
+            input: "format(1)",
+            expected: Object::error("expected STR as format template, got INT".to_string()),
+        },
+    ]);
+}
+
+#[test]
+fn test_class_iterators() {
+    run_tests(&[
+        TestCase {
+            input: "
+class Counter(n) {
+    i = 0
+
+    next() {
+        if (self.i >= self.n) {
+            return nil;
+        }
+        self.i = self.i + 1;
+        return self.i;
+    }
+}
+
+var x = 0;
+for (i in new Counter(4)) {
+    x = x + i;
+}
+x
+",
+            expected: Object::int(10),
+        },
+        TestCase {
+            input: "
+class Wrapper(items) {
+    iter() {
+        return self.items;
+    }
+}
+
+iter(new Wrapper([1, 2])).collect()
+",
+            expected: Object::array(Vec::from([Object::int(1), Object::int(2)])),
+        },
+        TestCase {
+            input: "
+class Nope() {
+    a = 1
+}
+
+for (i in new Nope()) {}
+",
+            expected: Object::error(
+                "class \"Nope\" is not iterable. it must define an `iter` or `next` method"
+                    .to_string(),
+            ),
         },
     ]);
 }
+
+#[test]
+#[allow(clippy::literal_string_with_formatting_args)]
+fn test_operator_overloading() {
+    let class = "
+class Vec(x, y) {
+    add(other) { return new Vec(self.x + other.x, self.y + other.y); }
+    mul(k) { return new Vec(self.x * k, self.y * k); }
+    neg() { return new Vec(-self.x, -self.y); }
+    eq(other) { return self.x == other.x && self.y == other.y; }
+    lt(other) { return self.x < other.x; }
+    str() { return f\"Vec({self.x}, {self.y})\"; }
+}
+
+class Bag(items) {
+    index(i) { return self.items[i]; }
+    setIndex(i, value) {
+        var items = self.items;
+        items[i] = value;
+        self.items = items;
+    }
+    contains(item) { return item in self.items; }
+    call(n) { return self.items.len() + n; }
+}
+";
+
+    let test_cases = [
+        (
+            "var a = new Vec(1, 2); var v = a + new Vec(3, 4); str([v.x, v.y])",
+            "[4, 6]",
+        ),
+        (
+            "var a = new Vec(1, 2); var v = -(a * 3); str([v.x, v.y])",
+            "[-3, -6]",
+        ),
+        (
+            "var a = new Vec(1, 2); str([a == new Vec(1, 2), a != new Vec(1, 2)])",
+            "[true, false]",
+        ),
+        (
+            "var a = new Vec(1, 2); str([a < new Vec(2, 0), a >= new Vec(2, 0)])",
+            "[true, false]",
+        ),
+        ("str(new Vec(1, 2))", "Vec(1, 2)"),
+        ("var a = new Vec(1, 2); f\"{a}!\"", "Vec(1, 2)!"),
+        ("format(\"{:>10}\", new Vec(1, 2))", " Vec(1, 2)"),
+        (
+            "var b = new Bag([1, 2]); b[0] = 5; b[1] += 1; str(b.items)",
+            "[5, 3]",
+        ),
+        (
+            "var b = new Bag([1, 2]); str([b[1], 2 in b, 3 not in b, b(10)])",
+            "[2, true, true, 12]",
+        ),
+    ];
+
+    for (input, expected) in test_cases {
+        run_test(
+            &format!("{class}\n{input}"),
+            &Object::str(expected.to_string()),
+        );
+    }
+
+    run_tests(&[
+        TestCase {
+            input: "class A() { str() { return 1; } }; str(new A())",
+            expected: Object::error(
+                "`str` method of class \"A\" must return STR, got INT".to_string(),
+            ),
+        },
+        TestCase {
+            input: "class A() { a = 1 }; var a = new A(); a + 1",
+            expected: Object::error(
+                "unsupported types for binary operation: CLASS + INT".to_string(),
+            ),
+        },
+        TestCase {
+            input: "class A() { a = 1 }; var a = new A(); a(1)",
+            expected: Object::error("not a function: CLASS".to_string()),
+        },
+        TestCase {
+            input: "class P(x) {}; var a = new P(1); [a == a, a == new P(1), a == new P(2), a != 1, nil == a]",
+            expected: Object::array(Vec::from([
+                Object::TRUE,
+                Object::TRUE,
+                Object::FALSE,
+                Object::TRUE,
+                Object::FALSE,
+            ])),
+        },
+        TestCase {
+            input: "class P(x) {}; var ps = [new P(1)].push(new P(2)); ps[1].x",
+            expected: Object::int(2),
+        },
+    ]);
+}
+
+#[test]
+#[allow(clippy::literal_string_with_formatting_args)]
+fn test_class_string_representation() {
+    run_tests(&[
+        TestCase {
+            input: "class Point(x, y) { label = \"p\"\n norm() { self.x + self.y } }; str(new Point(1, 2))",
+            expected: Object::str("Point { x: 1, y: 2, label: \"p\" }".to_string()),
+        },
+        TestCase {
+            input: "class Rect(w, h) {}; str(new Rect(2, 1))",
+            expected: Object::str("Rect { w: 2, h: 1 }".to_string()),
+        },
+        TestCase {
+            input: "class P(x, y) { c = 'c' }; str(new P(1, [1, 2]))",
+            expected: Object::str("P { x: 1, y: [1, 2], c: 'c' }".to_string()),
+        },
+        TestCase {
+            input: "class A() { f() {} }; str(new A())",
+            expected: Object::str("A {}".to_string()),
+        },
+        TestCase {
+            input: "class A(name) { toString() { f\"A({self.name})\" } }; var a = new A(1); f\"{a}!\"",
+            expected: Object::str("A(1)!".to_string()),
+        },
+        TestCase {
+            input: "class A(name) { toString() { \"<\" + str(self) + \">\" } }; str(new A(1))",
+            expected: Object::str("<A { name: 1 }>".to_string()),
+        },
+    ]);
+
+    let test_cases = [
+        ("class A(v) { inspect() { \"A!\" } }; new A(1)", "A!"),
+        ("class A(v) { toString() { \"a\" } }; new A(1)", "a"),
+        ("class A(v) {}; new A(\"s\")", "A { v: \"s\" }"),
+        (
+            "class A(v) { inspect() { 1 } }; new A(1)",
+            "ERROR: `inspect` method of class \"A\" must return STR, got INT",
+        ),
+    ];
+
+    for (input, expected) in test_cases {
+        let mut l = Lexer::new(input);
+        let mut p = Parser::new(&mut l);
+        let program = p.parse_program().unwrap();
+
+        let mut evaluator = Evaluator::new();
+        let evaluated = evaluator.eval(program.clone()).unwrap_or(Object::Nil);
+
+        assert_eq!(expected, evaluator.inspect(&evaluated));
+
+        let mut comp = Compiler::new();
+        comp.compile(program).unwrap();
+        let byte_code = comp.bytecode();
+
+        let mut vm = VM::new(&byte_code);
+        vm.run().unwrap();
+        let evaluated = vm.last_popped_stack_elem.clone().unwrap_or(Object::Nil);
+
+        assert_eq!(expected, vm.inspect(&evaluated));
+    }
+}
+
+#[test]
+fn test_enums() {
+    let shape = "
+enum Shape {
+    Circle(r),
+    Rect(w, h),
+    Empty,
+
+    area() {
+        if (self.variant() == \"Circle\") {
+            return 3 * self.r * self.r;
+        }
+        if (self.variant() == \"Rect\") {
+            return self.w * self.h;
+        }
+        return 0;
+    }
+
+    grow(k) {
+        if (self == Shape::Empty) {
+            return self;
+        }
+        return Shape::Circle(self.r + k);
+    }
+}
+";
+
+    let test_cases = [
+        ("Shape::Circle(2).r", Object::int(2)),
+        (
+            "[Shape::Circle(2).area(), Shape::Rect(2, 3).area(), Shape::Empty.area()]",
+            Object::array(Vec::from([Object::int(12), Object::int(6), Object::int(0)])),
+        ),
+        ("Shape::Circle(1).grow(2) == Shape::Circle(3)", Object::TRUE),
+        ("Shape::Rect(1, 2) != Shape::Rect(1, 2)", Object::FALSE),
+        ("Shape::Empty == Shape::Circle(1)", Object::FALSE),
+        ("Shape::Rect(1, 2).variant()", Object::str("Rect".to_string())),
+        ("type(Shape::Empty) == type(Shape::Circle(1))", Object::TRUE),
+        (
+            "var d = {Shape::Circle(1): \"a\", Shape::Empty: \"b\"}; [d[Shape::Circle(1)], d[Shape::Empty]]",
+            Object::array(Vec::from([
+                Object::str("a".to_string()),
+                Object::str("b".to_string()),
+            ])),
+        ),
+        ("Shape::Empty in {Shape::Empty: 1}", Object::TRUE),
+        (
+            "var names = \"\"; for (s in Shape) { names = names + s.variant(); }; names",
+            Object::str("Empty".to_string()),
+        ),
+        ("if (Shape::Empty) { 1 } else { 2 }", Object::int(1)),
+        ("str(Shape::Rect(1, \"x\"))", Object::str("Shape::Rect(1, \"x\")".to_string())),
+        (
+            "Shape::Circle()",
+            Object::error("wrong number of arguments. got: 0, want: 1".to_string()),
+        ),
+        (
+            "Shape::Square(1)",
+            Object::error("no variant `Square` found in enum \"Shape\"".to_string()),
+        ),
+    ];
+
+    for (input, expected) in test_cases {
+        run_test(&format!("{shape}{input}"), &expected);
+    }
+}
+
+#[test]
+fn test_while_statement() {
+    run_tests(&[
+        TestCase {
+            input: "var i = 0;
+var j = 0;
+while (j < 10) {
+    i = i + j;
+    j = j + 1;
+}
+
+i",
+            expected: Object::int(45),
+        },
+        TestCase {
+            input: "var arr = [];
+var i = 0;
+while (i < 2) {
+    arr = arr.push(i)
+    i = i + 1;
+}
+
+arr",
+            expected: Object::array(Vec::from([Object::int(0), Object::int(1)])),
+        },
+        TestCase {
+            input: "var i = 10; while (i > 0) { i = i - 1 }; i",
+            expected: Object::int(0),
+        },
+        TestCase {
+            input: "
+var i = 10;
+while (i > 0) {
+    i = i - 1;
+    if (i == 5) {
+        break;
+    }
+};
+i",
+            expected: Object::int(5),
+        },
+        TestCase {
+            input: "
+var i = 10;
+var j = 0;
+while (i > 0) {
+    i = i - 1;
+    if (i < 5) {
+        continue;
+    }
+    j = i;
+};
+j",
+            expected: Object::int(5),
+        },
+    ]);
+}
+
+#[test]
+fn test_assign_expressions() {
+    run_tests(&[
+        TestCase {
+            input: "var i = 10; i = i - 1 ; i",
+            expected: Object::int(9),
+        },
+        {
+            TestCase {
+                input: "var arr = [1, 1, 1, 1];
+arr[0] = 0;
+arr[0]",
+                expected: Object::int(0),
+            }
+        },
+    ]);
+}
+
+#[test]
+fn test_power_and_floor_division() {
+    run_tests(&[
+        TestCase {
+            input: "2 ** 10",
+            expected: Object::int(1024),
+        },
+        TestCase {
+            input: "2 ** 3 ** 2",
+            expected: Object::int(512),
+        },
+        TestCase {
+            input: "-2 ** 2",
+            expected: Object::int(-4),
+        },
+        TestCase {
+            input: "2 ** -2",
+            expected: Object::float(0.25),
+        },
+        TestCase {
+            input: "4 ** 0.5",
+            expected: Object::float(2.0),
+        },
+        TestCase {
+            input: "2.5 ** 2",
+            expected: Object::float(6.25),
+        },
+        TestCase {
+            input: "7 // 2",
+            expected: Object::int(3),
+        },
+        TestCase {
+            input: "-7 // 2",
+            expected: Object::int(-4),
+        },
+        TestCase {
+            input: "7 // -2",
+            expected: Object::int(-4),
+        },
+        TestCase {
+            input: "7.5 // 2",
+            expected: Object::float(3.0),
+        },
+        TestCase {
+            input: "~5",
+            expected: Object::int(-6),
+        },
+        TestCase {
+            input: "var a = 3; a **= 2; a //= 4; a",
+            expected: Object::int(2),
+        },
+        TestCase {
+            input: "1 // 0",
+            expected: Object::error("division by zero".to_string()),
+        },
+        TestCase {
+            input: "1 / 0",
+            expected: Object::error("division by zero".to_string()),
+        },
+        TestCase {
+            input: "var a = 1; a %= 0; a",
+            expected: Object::error("modulo by zero".to_string()),
+        },
+        TestCase {
+            input: "1 << 64",
+            expected: Object::error("shift out of range: 1 << 64".to_string()),
+        },
+        TestCase {
+            input: "var a = 1; a >>= -1; a",
+            expected: Object::error("shift out of range: 1 >> -1".to_string()),
+        },
+        TestCase {
+            input: "9223372036854775807 + 1",
+            expected: Object::error("integer overflow: 9223372036854775807 + 1".to_string()),
+        },
+        TestCase {
+            input: "(-9223372036854775807 - 1) / -1",
+            expected: Object::error("integer overflow: -9223372036854775808 / -1".to_string()),
+        },
+        TestCase {
+            input: "2 ** 64",
+            expected: Object::error("integer overflow: 2 ** 64".to_string()),
+        },
+        TestCase {
+            input: "(-9223372036854775807 - 1) // -1",
+            expected: Object::error("integer overflow: -9223372036854775808 // -1".to_string()),
+        },
+        TestCase {
+            input: "var a = -9223372036854775807 - 1; a //= -1; a",
+            expected: Object::error("integer overflow: -9223372036854775808 // -1".to_string()),
+        },
+        TestCase {
+            input: "var a = -9223372036854775807 - 1; -a",
+            expected: Object::error("integer overflow: -(-9223372036854775808)".to_string()),
+        },
+        TestCase {
+            input: "~1.5",
+            expected: Object::error("unsupported type for bitwise not: FLOAT".to_string()),
+        },
+    ]);
+}
+
+#[test]
+fn test_mixed_numeric_arithmetic() {
+    run_tests(&[
+        TestCase {
+            input: "1 + 2.5",
+            expected: Object::float(3.5),
+        },
+        TestCase {
+            input: "2.5 - 1",
+            expected: Object::float(1.5),
+        },
+        TestCase {
+            input: "7 / 2.0",
+            expected: Object::float(3.5),
+        },
+        TestCase {
+            input: "7 % 2.5",
+            expected: Object::float(2.0),
+        },
+        TestCase {
+            input: "1 < 1.5 && 2.0 >= 2 && 1 == 1.0",
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: "var x = 1; x += 0.5; x",
+            expected: Object::float(1.5),
+        },
+        TestCase {
+            input: "1 & 2.0",
+            expected: Object::error(
+                "unsupported types for binary operation: INT & FLOAT".to_string(),
+            ),
+        },
+    ]);
+}
+
+#[test]
+fn test_numeric_conversions() {
+    run_tests(&[
+        TestCase {
+            input: "int(\" -17 \") + int(\"ff\", 16) + int(\"1_000\")",
+            expected: Object::int(1238),
+        },
+        TestCase {
+            input: "int(-3.9) + int(true)",
+            expected: Object::int(-2),
+        },
+        TestCase {
+            input: "int(\"abc\")",
+            expected: Object::error("cannot parse \"abc\" as INT".to_string()),
+        },
+        TestCase {
+            input: "int(\"12\", 2)",
+            expected: Object::error("cannot parse \"12\" as INT with radix 2".to_string()),
+        },
+        TestCase {
+            input: "int(\"12\", 40)",
+            expected: Object::error("radix must be between 2 and 36, got 40".to_string()),
+        },
+        TestCase {
+            input: "int(1e300)",
+            expected: Object::error("cannot convert 1e300 to INT".to_string()),
+        },
+        TestCase {
+            input: "[parseInt(\"12px\"), parseInt(\"0x1F\"), parseInt(\"-0b101\"), parseInt(\"z\", 36)]",
+            expected: Object::array(Vec::from([
+                Object::int(12),
+                Object::int(31),
+                Object::int(-5),
+                Object::int(35),
+            ])),
+        },
+        TestCase {
+            input: "parseInt(\"px\")",
+            expected: Object::error("cannot parse \"px\" as INT".to_string()),
+        },
+        TestCase {
+            input: "float(\"2.5\") + float(3)",
+            expected: Object::float(5.5),
+        },
+        TestCase {
+            input: "float([])",
+            expected: Object::error("cannot convert ARRAY to FLOAT".to_string()),
+        },
+        TestCase {
+            input: "str(255) + str(255, 16) + str(-10, 2) + str(1.5)",
+            expected: Object::str("255ff-10101.5".to_string()),
+        },
+        TestCase {
+            input: "[2.567.round(), 2.567.round(2), 2.5.floor(), 2.5.ceil(), (-2.5).trunc()]",
+            expected: Object::array(Vec::from([
+                Object::float(3.0),
+                Object::float(2.57),
+                Object::float(2.0),
+                Object::float(3.0),
+                Object::float(-2.0),
+            ])),
+        },
+        TestCase {
+            input: "[1.5.round(400), 1.25.round(16), 1234.5.round(-2), 1.5.round(-400)]",
+            expected: Object::array(Vec::from([
+                Object::float(1.5),
+                Object::float(1.25),
+                Object::float(1200.0),
+                Object::float(0.0),
+            ])),
+        },
+    ]);
+}
+
+#[test]
+fn test_membership_operators() {
+    run_tests(&[
+        TestCase {
+            input: "2 in [1, 2, 3]",
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: "4 not in [1, 2, 3]",
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: "\"ell\" in \"hello\"",
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: "'z' in \"hello\"",
+            expected: Object::FALSE,
+        },
+        TestCase {
+            input: "\"k\" in {\"k\": 1}",
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: "1 in {\"k\": 1}",
+            expected: Object::FALSE,
+        },
+        TestCase {
+            input: "4 in 0..10..2",
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: "5 in 0..10..2",
+            expected: Object::FALSE,
+        },
+        TestCase {
+            input: "10 in 0..10",
+            expected: Object::FALSE,
+        },
+        TestCase {
+            input: "3 in 10..0..-1",
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: "1 + 1 in [2] && 3 not in [2]",
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: "[1.0 in [1], 1 in [1.0], 2.0 in 0..3, 2.5 in 0..3]",
+            expected: Object::array(Vec::from([
+                Object::TRUE,
+                Object::TRUE,
+                Object::TRUE,
+                Object::FALSE,
+            ])),
+        },
+        TestCase {
+            input: "1 in 5",
+            expected: Object::error("INT cannot contain INT".to_string()),
+        },
+        TestCase {
+            input: "[1] in {}",
+            expected: Object::error("unusable as hash key: ARRAY".to_string()),
+        },
+    ]);
+}
+
+#[test]
+fn test_nil_safe_operators() {
+    run_tests(&[
+        TestCase {
+            input: "var a; a ?? 5",
+            expected: Object::int(5),
+        },
+        TestCase {
+            input: "false ?? 5",
+            expected: Object::FALSE,
+        },
+        TestCase {
+            input: "nil ?? nil ?? 3",
+            expected: Object::int(3),
+        },
+        TestCase {
+            input: "var a; a?.len()",
+            expected: Object::Nil,
+        },
+        TestCase {
+            input: "var a = \"abc\"; a?.len()",
+            expected: Object::int(3),
+        },
+        TestCase {
+            input: "var a = [1, 2]; a?[1]",
+            expected: Object::int(2),
+        },
+        TestCase {
+            input: "var d = {\"k\": nil}; d[\"k\"]?[0] ?? -1",
+            expected: Object::int(-1),
+        },
+        TestCase {
+            input: "var n = 0; var f = fn() { n = n + 1; n }; var a; a?.push(f()); a?[f()]; 1 ?? f(); n",
+            expected: Object::int(0),
+        },
+        TestCase {
+            input: "var len = fn(x) { x?.len() ?? 0 }; len(nil) + len([1, 2])",
+            expected: Object::int(2),
+        },
+        TestCase {
+            input: "var a; a?.b.c.d",
+            expected: Object::Nil,
+        },
+        TestCase {
+            input: "var a; [a?[0][1], a?.b().c, a?.len().x(1)[2]]",
+            expected: Object::array(Vec::from([Object::Nil, Object::Nil, Object::Nil])),
+        },
+        TestCase {
+            input: "var n = 0; var f = fn() { n = n + 1; [n] }; var a; a?.b(f()).c(f())[f()]; n",
+            expected: Object::int(0),
+        },
+        TestCase {
+            input: "var a; var f = fn(x) { [x] }; f(a?.b)[0] ?? 1",
+            expected: Object::int(1),
+        },
+    ]);
+}
+
+#[test]
+fn test_compound_assignment() {
+    run_tests(&[
+        TestCase {
+            input: "var a = 5; a += 3; a",
+            expected: Object::int(8),
+        },
+        TestCase {
+            input: "var a = 5; a -= 3; a *= 4; a",
+            expected: Object::int(8),
+        },
+        TestCase {
+            input: "var a = 1; a <<= 4; a |= 3; a ^= 1; a",
+            expected: Object::int(18),
+        },
+        TestCase {
+            input: "var s = \"foo\"; s += \"bar\"",
+            expected: Object::str("foobar".to_string()),
+        },
+        TestCase {
+            input: "var arr = [1, 2, 3]; arr[1] += 10; arr",
+            expected: Object::array(Vec::from([
+                Object::int(1),
+                Object::int(12),
+                Object::int(3),
+            ])),
+        },
+        TestCase {
+            input: "var counts = {\"a\": 1}; counts[\"a\"] += 1; counts[\"b\"] = 5; counts[\"a\"] + counts[\"b\"]",
+            expected: Object::int(7),
+        },
+        TestCase {
+            input: "var s = \"cat\"; s[0] = 'b'; s",
+            expected: Object::str("bat".to_string()),
+        },
+        TestCase {
+            input: "var f = fn() { var total = 0; for (i in 1..5) { total += i; } total }; f()",
+            expected: Object::int(10),
+        },
+        TestCase {
+            input: "var arr = [1]; arr[3] += 1",
+            expected: Object::error("index out of bounds. got: 3".to_string()),
+        },
+    ]);
+}
+
+#[test]
+fn test_interfaces() {
+    let drawable = "
+interface Drawable {
+    fn draw();
+    fn scaled(k);
+    fn describe() { \"shape \" + self.draw() }
+}
+";
+
+    let test_cases = [
+        (
+            "class C(r) implements Drawable { draw() { \"circle\" } scaled(k) { self.r * k } }; var c = new C(2); [c.scaled(3), c.describe()]",
+            Object::array(Vec::from([
+                Object::int(6),
+                Object::str("shape circle".to_string()),
+            ])),
+        ),
+        (
+            "class C() implements Drawable { draw() { \"c\" } scaled(k) { k } describe() { \"own\" } }; var c = new C(); c.describe()",
+            Object::str("own".to_string()),
+        ),
+        (
+            "class C() implements Drawable { draw() { \"c\" } scaled(k) { k } }; var c = new C(); implements(c, Drawable)",
+            Object::TRUE,
+        ),
+        (
+            "class C() { draw() { \"c\" } }; var c = new C(); implements(c, Drawable)",
+            Object::FALSE,
+        ),
+        ("implements(1, Drawable)", Object::FALSE),
+        (
+            "implements(1, 2)",
+            Object::error("expected INTERFACE as second argument to `implements`, got INT".to_string()),
+        ),
+        (
+            "class C() implements Drawable { draw() { \"c\" } }",
+            Object::error(
+                "class \"C\" does not implement method \"scaled\" of interface \"Drawable\"".to_string(),
+            ),
+        ),
+        (
+            "class C() implements Drawable { draw() { \"c\" } scaled() { 1 } }",
+            Object::error(
+                "method \"scaled\" of class \"C\" takes 0 parameters, but interface \"Drawable\" expects 1".to_string(),
+            ),
+        ),
+        (
+            "class C() implements Nope {}",
+            Object::error("no interface named \"Nope\" found".to_string()),
+        ),
+    ];
+
+    for (input, expected) in test_cases {
+        run_test(&format!("{drawable}{input}"), &expected);
+    }
+
+    // Enum values implement interfaces with their methods too.
+    let input = "
+interface Sized { size(); }
+enum Box { Full(n), Empty; size() { 1 } }
+[implements(Box::Full(2), Sized), implements(Box::Empty, Sized), implements([], Sized)]
+";
+    run_tests(&[TestCase {
+        input,
+        expected: Object::array(Vec::from([Object::TRUE, Object::TRUE, Object::FALSE])),
+    }]);
+
+    let mut l = Lexer::new("interface I { m(); }; class C() implements I {}");
+    let mut p = Parser::new(&mut l);
+    let program = p.parse_program().unwrap();
+
+    let mut comp = Compiler::new();
+    assert_eq!(
+        Err("class \"C\" does not implement method \"m\" of interface \"I\"".to_string()),
+        comp.compile(program)
+    );
+}
+
+#[test]
+fn test_static_members() {
+    run_tests(&[
+        TestCase {
+            input: "class C() { static var n = 1; }; C::n",
+            expected: Object::int(1),
+        },
+        TestCase {
+            input: "class C() { static var n = 1; }; C::n += 2; C::n = C::n * 2; C::n",
+            expected: Object::int(6),
+        },
+        TestCase {
+            input: "class C() { static const k = 2; static var m = C::k + 1; }; [C::k, C::m]",
+            expected: Object::array(Vec::from([Object::int(2), Object::int(3)])),
+        },
+        TestCase {
+            input: "class C() { static var n = 1; }; var n = 5; C::n + n",
+            expected: Object::int(6),
+        },
+        TestCase {
+            input: "class M() { static fn fact(n) { if (n < 2) { return 1; } n * M::fact(n - 1) } }; M::fact(5)",
+            expected: Object::int(120),
+        },
+        TestCase {
+            input: "class M() { static fn twice(x) { x * 2 } }; var f = fn() { M::twice(4) }; f()",
+            expected: Object::int(8),
+        },
+        TestCase {
+            input: "class P() { static var count = 0; static fn make() { P::count += 1; new P() } bump() { P::count += 10; } }; P::make(); var p = P::make(); p.bump(); P::count = P::count + 100; p.bump(); P::count",
+            expected: Object::int(122),
+        },
+        TestCase {
+            input: "class C() { static var n = 1; }; C::m",
+            expected: Object::error("no static member `m` found in class \"C\"".to_string()),
+        },
+        TestCase {
+            input: "class Point(x, y) { static fn origin() { new Point(0, 0) } }; var o = Point::origin(); o.x + o.y",
+            expected: Object::int(0),
+        },
+        TestCase {
+            input: "class C() { static var n = 0; total() { C::n + 1 } }; var c = new C(); c.total()",
+            expected: Object::int(1),
+        },
+        TestCase {
+            input: "class C() { static var n = 0; }; var c = new C(); c.n",
+            expected: Object::error("no method found for class \"C\"".to_string()),
+        },
+    ]);
+
+    for input in [
+        "class C() { static const k = 0; }; C::k = 1",
+        "class C() { static const k = 0; }; C::k += 2",
+        "class C() { static const k = 0; static fn set() { C::k = 2; } }; C::set()",
+    ] {
+        run_test(
+            input,
+            &Object::error("identifier is not mutable: C::k".to_string()),
+        );
+    }
+}
+
+#[test]
+fn test_class_accessors() {
+    let rect = "
+class Rect(_w, h) {
+    get width() { self._w }
+    set width(v) {
+        if (v < 0) { return; }
+        self._w = v;
+    }
+    get area() { self._w * self.h }
+    set h(v) { self.h = v * 2 }
+    grow(k) { self._w = self._w + k; self._w }
+    _half() { self.area / 2 }
+    half() { self._half() }
+}
+var r = new Rect(2, 3);
+";
+
+    let test_cases = [
+        ("r.area", Object::int(6)),
+        ("r.width = 5; r.area", Object::int(15)),
+        ("r.width = -1; r.width", Object::int(2)),
+        ("r.width += 2; r.width", Object::int(4)),
+        ("r.h = 4; r.h", Object::int(8)),
+        ("r.grow(1)", Object::int(3)),
+        ("r.half()", Object::int(3)),
+        (
+            "r._half()",
+            Object::error("cannot access private member `_half` of class \"Rect\"".to_string()),
+        ),
+        ("str(r)", Object::str("Rect { _w: 2, h: 3 }".to_string())),
+        (
+            "r._w",
+            Object::error("cannot access private member `_w` of class \"Rect\"".to_string()),
+        ),
+        (
+            "r._w = 1",
+            Object::error("cannot access private member `_w` of class \"Rect\"".to_string()),
+        ),
+        (
+            "r.area()",
+            Object::error("no method found for class \"Rect\"".to_string()),
+        ),
+        (
+            "r.area = 5",
+            Object::error("cannot assign to read-only property 'area'".to_string()),
+        ),
+        (
+            "r.area += 5",
+            Object::error("cannot assign to read-only property 'area'".to_string()),
+        ),
+    ];
+
+    for (input, expected) in test_cases {
+        run_test(&format!("{rect}{input}"), &expected);
+    }
+}
+
+#[test]
+fn test_imports() {
+    let root = std::env::temp_dir().join(format!("panda-imports-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("lib")).unwrap();
+
+    for (name, content) in [
+        ("main.pnd", ""),
+        (
+            "lib/shapes.pnd",
+            "import \"square\"; fn twice(x) { x * 2 } export fn area(x) { twice(square::of(x)) }",
+        ),
+        ("lib/square.pnd", "export fn of(x) { x * x }"),
+        (
+            "geo.pnd",
+            "export const PI = 3; export class Point(x) { static var count = 7; get() { self.x } } export fn mk(x) { new Point(x) }",
+        ),
+        (
+            "counter.pnd",
+            "var total = 0; export fn add(n) { total = 100; return n; } var secret = 41; export fn inc() { secret = secret + 1; return secret; }",
+        ),
+        ("a.pnd", "import \"b\";"),
+        ("b.pnd", "import \"lib/../a\";"),
+    ] {
+        std::fs::write(root.join(name), content).unwrap();
+    }
+
+    let root = root.canonicalize().unwrap();
+    let main = root.join("main.pnd");
+
+    let cycle = format!(
+        "import cycle: {} -> {} -> {}",
+        root.join("a.pnd").display(),
+        root.join("b.pnd").display(),
+        root.join("a.pnd").display()
+    );
+
+    let test_cases = [
+        ("import \"lib/shapes\" as s; s::area(3)", Object::int(18)),
+        (
+            "import { area as a } from \"lib/shapes\"; a(2)",
+            Object::int(8),
+        ),
+        ("import * from \"geo\"; Point::count + PI", Object::int(10)),
+        (
+            "import { Point as P } from \"geo\"; P::count",
+            Object::int(7),
+        ),
+        (
+            "import { Point } from \"geo\"; var p = new Point(4); p.get()",
+            Object::int(4),
+        ),
+        (
+            "import \"geo\"; var p = new geo::Point(5); p.get()",
+            Object::int(5),
+        ),
+        (
+            "import \"geo\"; import { Point as Q } from \"geo\"; type(new Q(1)) == type(new geo::Point(1))",
+            Object::bool(true),
+        ),
+        (
+            "import { Point as Q, mk } from \"geo\"; str(new Q(3)) + \" \" + str(mk(3))",
+            Object::str("Point { x: 3 } Point { x: 3 }".to_string()),
+        ),
+        (
+            "import { twice } from \"lib/shapes\"",
+            Object::error("module \"shapes\" does not export \"twice\"".to_string()),
+        ),
+        (
+            "import \"lib/shapes\" as s; s::twice(3)",
+            Object::error("member 'twice' not found in module 's'".to_string()),
+        ),
+        ("import \"counter\"; counter::add(5)", Object::int(5)),
+        ("import \"a\";", Object::error(cycle)),
+        (
+            "fn* g() { import { area } from \"lib/shapes\"; yield area(1); } g().next()",
+            Object::int(2),
+        ),
+    ];
+
+    let set_root = |comp: &mut Compiler| comp.set_root(&main).unwrap();
+
+    for (input, expected) in test_cases {
+        run_test_with(
+            input,
+            &expected,
+            |evaluator| evaluator.set_root(&main).unwrap(),
+            set_root,
+        );
+    }
+
+    // The functions of a module share its state on the vm, like those of a
+    // program share its globals.
+    let input = "import { inc } from \"counter\"; inc(); inc()";
+    let mut l = Lexer::new(input);
+    let program = Parser::new(&mut l).parse_program().unwrap();
+    assert_eq!(Ok(Object::int(43)), run_vm(program, set_root));
+}
+
+#[test]
+fn test_std_modules() {
+    let test_cases = [
+        TestCase {
+            input: r#"import { sqrt, clamp } from "std/math"; clamp(sqrt(2.25), 0, 1)"#,
+            expected: Object::int(1),
+        },
+        TestCase {
+            input: r#"import "std/math"; math::hypot(3, 4) + math::PI - math::PI"#,
+            expected: Object::float(5.0),
+        },
+        TestCase {
+            input: r#"import * from "std/string"; padLeft(upper("ab"), 4, ".") + repeat("!", 2)"#,
+            expected: Object::str("..AB!!".to_string()),
+        },
+        TestCase {
+            input: r#"import { find } from "std/string"; find("héllo", "l") + find("a", "b")"#,
+            expected: Object::int(1),
+        },
+        TestCase {
+            input: r#"import * from "std/collections"; sum(map(sort([3, 1, 2]), fn(x) { x * x }))"#,
+            expected: Object::int(14),
+        },
+        TestCase {
+            input: r#"import * from "std/collections"; reduce(filter([1, 2, 3, 4], fn(x) { x % 2 == 0 }), fn(a, x) { a * 10 + x }, 0)"#,
+            expected: Object::int(24),
+        },
+        TestCase {
+            input: r#"import * from "std/json"; stringify(parse("{\"b\": [1, -2.5e1, true, null], \"a\": \"\\u00e9\\n\"}"))"#,
+            expected: Object::str(r#"{"a":"é\n","b":[1,-25.0,true,null]}"#.to_string()),
+        },
+        TestCase {
+            input: r#"import { now, elapsed } from "std/time"; elapsed(now()) >= 0"#,
+            expected: Object::TRUE,
+        },
+        TestCase {
+            input: r#"import * from "std/test"; assertEq(1 + 1, 2); assert(false, "boom")"#,
+            expected: Object::error("boom".to_string()),
+        },
+        TestCase {
+            input: r#"import { parse } from "std/json"; parse("[1,")"#,
+            expected: Object::error("invalid JSON: unexpected end of input".to_string()),
+        },
+        TestCase {
+            input: r#"import { parse } from "std/json"; import { repeat } from "std/string"; parse(repeat("[", 200000))"#,
+            expected: Object::error("invalid JSON: nesting too deep".to_string()),
+        },
+        TestCase {
+            input: r#"import { parse } from "std/json"; import { repeat } from "std/string"; parse(repeat("[", 128) + repeat("]", 128)).len()"#,
+            expected: Object::int(1),
+        },
+        TestCase {
+            input: r#"import { sort } from "std/collections"; sort([1, "a"])"#,
+            expected: Object::error("cannot compare STR and INT".to_string()),
+        },
+        TestCase {
+            input: r#"import { helper } from "std/math""#,
+            expected: Object::error("module \"math\" does not export \"helper\"".to_string()),
+        },
+    ];
+
+    run_tests(&test_cases);
+}
+
+#[test]
+fn test_native_modules() {
+    let host = NativeModule::new("host")
+        .function("add", |args| {
+            args.expect(2)?;
+            Ok(args.get::<isize>(0)? + args.get::<isize>(1)?)
+        })
+        .function("greet", |args| {
+            Ok(format!("hi {}", args.optional::<&str>(0)?.unwrap_or("you")))
+        })
+        .function("half", |args| Ok(args.get::<f64>(0)? / 2.0));
+
+    let tests = [
+        ("host::add(1, 2) * host::half(5)", Object::float(7.5)),
+        (
+            r#"import { greet as hello } from "host"; hello() + " " + hello("bob")"#,
+            Object::str("hi you hi bob".to_string()),
+        ),
+        (
+            r#"import "host" as h; [h::add(2, 2), str(host::greet)]"#,
+            Object::array(vec![
+                Object::int(4),
+                Object::str("built-in function \"greet\"".to_string()),
+            ]),
+        ),
+        (
+            r#"import * from "host"; add(1, "2")"#,
+            Object::error("argument 2 to `add`: expected INT, got STR".to_string()),
+        ),
+        (
+            "host::add(1)",
+            Object::error("wrong number of arguments to `add`. got: 1, want: 2".to_string()),
+        ),
+        (
+            "host::sub(1, 2)",
+            Object::error("member 'sub' not found in module 'host'".to_string()),
+        ),
+    ];
+
+    let register = |comp: &mut Compiler| comp.register(&host);
+
+    for (input, expected) in tests {
+        run_test_with(
+            input,
+            &expected,
+            |evaluator| evaluator.register(host.clone()),
+            register,
+        );
+    }
+
+    let mut l = Lexer::new("guest::add(1, 2)");
+    let program = Parser::new(&mut l).parse_program().unwrap();
+
+    let mut evaluator = Evaluator::new();
+    evaluator.register(host.clone());
+    assert_eq!(
+        Some(Object::error("no module named \"guest\" found".to_string())),
+        evaluator.eval(program.clone())
+    );
+    assert_eq!(
+        Err("no module named guest found.".to_string()),
+        run_vm(program, register)
+    );
+}
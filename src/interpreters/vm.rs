@@ -1,11 +1,27 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use indexmap::IndexMap;
 
 use crate::{
     code::{self, Instructions, Opcode},
     compiler::Bytecode,
+    debugger::{frame_name, variables, DebugHook, Snapshot, StackFrame},
+    io::Io,
+    limits::Budget,
     object::{
-        builtins::BUILTINS, Array, Bool, Builtin, BuiltinFunction, Char, Closure, CompiledFunction,
-        Dict, DictPair, Error, Float, Hashable, Int, Iter, Iterable, Object, Range, Str,
+        allowed_in_array,
+        builtins::BUILTINS,
+        contains,
+        format::format_value,
+        getter_name, grapheme_at, grapheme_count, grapheme_object, hash_method_name, int_op,
+        int_pow, int_to_float, is_private,
+        iter::{CompiledGenerator, Driver, Generator, Iter, IterState},
+        set_index, setter_name, slice_str, Array, Bool, Builtin, BuiltinFunction, Char, Class,
+        ClassMember, Closure, CompiledFunction, Dict, DictPair, Enum, Error, Float, Hashable, Int,
+        NativeFunction, Object, Range, Str,
     },
 };
 
@@ -14,11 +30,21 @@ pub struct Frame {
     pub cl: Closure,
     pub ip: isize,
     pub bp: usize,
+    pub generator: Option<Arc<Mutex<CompiledGenerator>>>,
+    // The line of the statement running, if the program was compiled with
+    // line information.
+    pub line: usize,
 }
 
 impl Frame {
     pub fn new(cl: Closure, bp: usize) -> Self {
-        Self { cl, ip: -1, bp }
+        Self {
+            cl,
+            ip: -1,
+            bp,
+            generator: None,
+            line: 0,
+        }
     }
 
     pub fn instructions(&self) -> Instructions {
@@ -30,9 +56,20 @@ const STACK_SIZE: usize = 2048;
 pub const GLOBAL_SIZE: usize = 65536;
 const MAX_FRAMES: usize = 1024;
 
+// Methods called from within an instruction nest calls of `execute`, which
+// move to a new stack of `GROWN_STACK_SIZE` bytes when less than `RED_ZONE`
+// are left.
+const RED_ZONE: usize = 1024 * 1024;
+const GROWN_STACK_SIZE: usize = 8 * 1024 * 1024;
+
 #[derive(Debug)]
 pub struct VM<'a> {
     constants: &'a [Object],
+    natives: &'a [NativeFunction],
+    io: Io,
+    budget: Budget,
+    debugger: Option<Box<dyn DebugHook>>,
+    global_names: Vec<String>,
     globals: Vec<Object>,
 
     stack: Vec<Object>,
@@ -42,6 +79,13 @@ pub struct VM<'a> {
 
     frames: Vec<Frame>,
     frames_index: usize,
+
+    yielded: Option<Object>,
+    // The instances being formatted by their own `toString` or `str` method.
+    representing: Vec<Object>,
+    // The classes whose methods are running, innermost last. Accessors also
+    // record their property, which they use as a plain field.
+    running: Vec<(String, Option<String>)>,
 }
 
 impl<'a> VM<'a> {
@@ -54,6 +98,8 @@ impl<'a> VM<'a> {
             instructions: bytecode.instructions.clone(),
             num_locals: 0,
             num_parameters: 0,
+            generator: false,
+            info: None,
         };
 
         let main_closure = Closure {
@@ -67,6 +113,11 @@ impl<'a> VM<'a> {
 
         Self {
             constants: &bytecode.constants,
+            natives: &bytecode.natives,
+            io: Io::default(),
+            budget: Budget::default(),
+            debugger: None,
+            global_names: Vec::new(),
 
             stack: vec![Object::Nil; STACK_SIZE],
             sp: 0,
@@ -76,6 +127,10 @@ impl<'a> VM<'a> {
 
             frames,
             frames_index: 1,
+
+            yielded: None,
+            representing: Vec::new(),
+            running: Vec::new(),
         }
     }
 
@@ -95,12 +150,59 @@ impl<'a> VM<'a> {
         self.stack.clone()
     }
 
+    pub fn set_io(&mut self, io: Io) {
+        self.io = io;
+    }
+
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
+    }
+
+    /// Hands `debugger` the lines of a program compiled with line
+    /// information, and the globals named by `global_names` when it pauses.
+    pub fn set_debugger(&mut self, debugger: Box<dyn DebugHook>, global_names: Vec<String>) {
+        self.debugger = Some(debugger);
+        self.global_names = global_names;
+    }
+
+    pub fn take_debugger(&mut self) -> Option<Box<dyn DebugHook>> {
+        self.debugger.take()
+    }
+
     pub fn run(&mut self) -> Result<(), String> {
+        self.execute(1)
+    }
+
+    /// Calls `func` with `args`, usually once the program has run and defined
+    /// the function.
+    pub fn call(&mut self, func: Object, args: &[Object]) -> Result<Object, String> {
+        let depth = self.frames_index + 1;
+
+        self.push(func)?;
+        for arg in args {
+            self.push(arg.clone())?;
+        }
+
+        self.exec_call(args.len())?;
+
+        if self.frames_index >= depth {
+            self.execute(depth)?;
+        }
+
+        Ok(self.pop())
+    }
+
+    // Runs until the frame at `depth` returns or yields, so that generators
+    // can be resumed from within an instruction of their caller.
+    fn execute(&mut self, depth: usize) -> Result<(), String> {
         let mut ip;
         let mut ins;
         let mut op;
 
-        while self.current_frame().ip < (self.current_frame().instructions().len() - 1) as isize {
+        while self.frames_index >= depth
+            && self.current_frame().ip < self.current_frame().instructions().len().cast_signed() - 1
+        {
+            self.budget.step()?;
             self.current_frame().ip += 1;
 
             ip = self.current_frame().ip as usize;
@@ -121,6 +223,8 @@ impl<'a> VM<'a> {
                 | Opcode::Mul
                 | Opcode::Div
                 | Opcode::Mod
+                | Opcode::Pow
+                | Opcode::FloorDiv
                 | Opcode::BitXor
                 | Opcode::BitAnd
                 | Opcode::BitOr
@@ -143,9 +247,11 @@ impl<'a> VM<'a> {
                 Opcode::Nil => self.push(Object::Nil)?,
 
                 Opcode::Equal
+                | Opcode::NotEqual
                 | Opcode::GreaterThan
                 | Opcode::GreaterThanEqual
-                | Opcode::NotEqual => {
+                | Opcode::LessThan
+                | Opcode::LessThanEqual => {
                     self.execute_comparison(op)?;
                 }
 
@@ -157,13 +263,33 @@ impl<'a> VM<'a> {
                     self.execute_minus_operator()?;
                 }
 
+                Opcode::BitNot => {
+                    self.execute_bit_not_operator()?;
+                }
+
+                Opcode::In => {
+                    let container = self.pop();
+                    let item = self.pop();
+
+                    let found = match self.call_special_method(
+                        &container,
+                        "contains",
+                        std::slice::from_ref(&item),
+                    )? {
+                        Some((found, _)) => is_truthy(&found),
+                        None => contains(&container, &item)?,
+                    };
+
+                    self.push(Object::bool(found))?;
+                }
+
                 Opcode::And | Opcode::Or => {
                     self.execute_boolean_operator(op)?;
                 }
 
                 Opcode::Jump => {
                     let pos = code::read_u16(&ins, ip + 1);
-                    self.current_frame().ip = (pos - 1) as isize;
+                    self.current_frame().ip = pos.cast_signed() - 1;
                 }
 
                 Opcode::JumpNotTruthy => {
@@ -172,7 +298,17 @@ impl<'a> VM<'a> {
 
                     let condition = self.pop();
                     if !is_truthy(&condition) {
-                        self.current_frame().ip = (pos - 1) as isize;
+                        self.current_frame().ip = pos.cast_signed() - 1;
+                    }
+                }
+
+                // Leaves the value on the stack, so a short-circuited `?.` evaluates to nil.
+                Opcode::JumpNil => {
+                    let pos = code::read_u16(&ins, ip + 1);
+                    self.current_frame().ip += 2;
+
+                    if self.stack[self.sp - 1] == Object::Nil {
+                        self.current_frame().ip = pos.cast_signed() - 1;
                     }
                 }
 
@@ -182,11 +318,13 @@ impl<'a> VM<'a> {
 
                     let obj = self.pop();
 
+                    // Slots can be skipped, by declarations in branches that
+                    // didn't run for one.
                     if global_idx >= self.globals.len() {
-                        self.globals.push(obj);
-                    } else {
-                        self.globals[global_idx] = obj;
+                        self.globals.resize(global_idx + 1, Object::Nil);
                     }
+
+                    self.globals[global_idx] = obj;
                 }
 
                 Opcode::GetGlobal => {
@@ -207,10 +345,41 @@ impl<'a> VM<'a> {
                         elements.push(self.pop());
                     }
 
+                    if let Some(obj) = elements.iter().find(|obj| !allowed_in_array(obj)) {
+                        return Err(format!("ARRAY cannot contain {}", obj.kind()));
+                    }
+
                     elements.reverse();
                     self.push(Object::array(elements))?;
                 }
 
+                Opcode::Concat => {
+                    let num_parts = code::read_u16(&ins, ip + 1);
+                    self.current_frame().ip += 2;
+
+                    let parts = self.stack[self.sp - num_parts..self.sp].to_vec();
+                    self.sp -= num_parts;
+
+                    let mut out = String::new();
+                    for part in parts {
+                        out.push_str(&self.stringify(part)?.to_string());
+                    }
+
+                    self.push(Object::str(out))?;
+                }
+
+                Opcode::Format => {
+                    let const_idx = code::read_u16(&ins, ip + 1);
+                    self.current_frame().ip += 2;
+
+                    let value = self.pop();
+                    let value = self.stringify(value)?;
+                    let spec = self.constants[const_idx].to_string();
+
+                    let formatted = self.budget.run_builtin(|| format_value(&value, &spec))?;
+                    self.push(Object::str(formatted))?;
+                }
+
                 Opcode::Dict => {
                     let num_pairs = code::read_u16(&ins, ip + 1);
                     self.execute_dict_literal(num_pairs)?;
@@ -223,6 +392,22 @@ impl<'a> VM<'a> {
                     self.execute_index_expression(&left, &index)?;
                 }
 
+                Opcode::SetIndex => {
+                    let value = self.pop();
+                    let index = self.pop();
+                    let container = self.pop();
+
+                    let args = [index.clone(), value.clone()];
+
+                    let container = match self.call_special_method(&container, "setIndex", &args)? {
+                        Some((_, instance)) => instance,
+                        None => set_index(container, &index, value.clone())?,
+                    };
+
+                    self.push(value)?;
+                    self.push(container)?;
+                }
+
                 Opcode::Range => {
                     let has_step = code::read_bool(&ins, ip + 1);
                     self.current_frame().ip += 1;
@@ -241,16 +426,44 @@ impl<'a> VM<'a> {
                     let return_value = self.pop();
 
                     let frame = self.pop_frame();
-                    self.sp = frame.bp - 1;
 
-                    self.push(return_value)?;
+                    if let Some(generator) = frame.generator {
+                        generator.lock().unwrap().done = true;
+                        self.sp = frame.bp;
+                    } else {
+                        self.sp = frame.bp - 1;
+                        self.push(return_value)?;
+                    }
                 }
 
                 Opcode::Return => {
                     let frame = self.pop_frame();
-                    self.sp = frame.bp - 1;
 
-                    self.push(Object::Nil)?;
+                    if let Some(generator) = frame.generator {
+                        generator.lock().unwrap().done = true;
+                        self.sp = frame.bp;
+                    } else {
+                        self.sp = frame.bp - 1;
+                        self.push(Object::Nil)?;
+                    }
+                }
+
+                Opcode::Yield => {
+                    let value = self.pop();
+
+                    let frame = self.pop_frame();
+                    let Some(generator) = frame.generator else {
+                        return Err("cannot use `yield` outside generator functions".to_string());
+                    };
+
+                    let stack = self.stack[frame.bp..self.sp].to_vec();
+
+                    self.sp = frame.bp;
+                    self.yielded = Some(value);
+
+                    let mut generator = generator.lock().unwrap();
+                    generator.ip = frame.ip;
+                    generator.stack = stack;
                 }
 
                 Opcode::SetLocal => {
@@ -273,16 +486,20 @@ impl<'a> VM<'a> {
                 }
 
                 Opcode::GetBuiltin => {
-                    let builtin_idx = code::read_u8(&ins, ip + 1);
-                    self.current_frame().ip += 1;
+                    let builtin_idx = code::read_u16(&ins, ip + 1);
+                    self.current_frame().ip += 2;
 
-                    let (name, func) = BUILTINS[builtin_idx];
+                    // Natives registered by the host are numbered after the builtins.
+                    let builtin = match BUILTINS.get(builtin_idx) {
+                        Some(&(name, func)) => Object::Builtin(Builtin {
+                            name: name.to_owned(),
+                            func,
+                            caller: None,
+                        }),
+                        None => Object::Native(self.natives[builtin_idx - BUILTINS.len()].clone()),
+                    };
 
-                    self.push(Object::Builtin(Builtin {
-                        name: name.to_owned(),
-                        func,
-                        caller: None,
-                    }))?;
+                    self.push(builtin)?;
                 }
 
                 Opcode::Closure => {
@@ -303,6 +520,16 @@ impl<'a> VM<'a> {
                     self.push(current_closure.free[free_idx].clone())?;
                 }
 
+                // Closures hold copies of the variables they capture, so the
+                // assignment lasts until the call returns.
+                Opcode::SetFree => {
+                    let free_idx = code::read_u8(&ins, ip + 1);
+                    self.current_frame().ip += 1;
+
+                    let value = self.pop();
+                    self.current_frame().cl.free[free_idx] = value;
+                }
+
                 Opcode::CurrentClosure => {
                     let current_closure = self.current_frame().cl.clone();
                     self.push(Object::Closure(current_closure))?;
@@ -312,40 +539,75 @@ impl<'a> VM<'a> {
                     self.dup()?;
                 }
 
+                Opcode::DupTwo => {
+                    self.dup_two()?;
+                }
+
                 Opcode::Method => {
-                    let method_idx = code::read_u64(&ins, ip + 1);
-                    let has_arguments = code::read_bool(&ins, ip + 9);
-                    let num_args = code::read_u8(&ins, ip + 10);
+                    let const_idx = code::read_u16(&ins, ip + 1);
+                    let has_arguments = code::read_bool(&ins, ip + 3);
+                    let num_args = code::read_u8(&ins, ip + 4);
+                    let receiver = code::read_bool(&ins, ip + 5);
 
-                    self.current_frame().ip += 10;
+                    self.current_frame().ip += 5;
 
-                    self.exec_method_expression(num_args, method_idx, has_arguments)?;
+                    self.exec_method_expression(const_idx, num_args, has_arguments, receiver)?;
+                }
+
+                Opcode::Constructor => {
+                    let const_idx = code::read_u16(&ins, ip + 1);
+                    let num_members = code::read_u8(&ins, ip + 3);
+                    self.current_frame().ip += 3;
+
+                    self.exec_constructor(const_idx, num_members)?;
+                }
+
+                // Pushes the assigned value and the updated instance.
+                Opcode::SetMember => {
+                    let const_idx = code::read_u16(&ins, ip + 1);
+                    self.current_frame().ip += 2;
+
+                    let value = self.pop();
+                    let instance = self.pop();
+
+                    let instance = self.exec_set_member(const_idx, instance, value.clone())?;
+
+                    self.push(value)?;
+                    self.push(instance)?;
+                }
+
+                Opcode::Enum => {
+                    let const_idx = code::read_u16(&ins, ip + 1);
+                    let num_methods = code::read_u8(&ins, ip + 3);
+                    self.current_frame().ip += 3;
+
+                    self.exec_enum(const_idx, num_methods)?;
+                }
+
+                Opcode::Variant => {
+                    let const_idx = code::read_u16(&ins, ip + 1);
+                    let num_args = code::read_u8(&ins, ip + 3);
+                    self.current_frame().ip += 3;
+
+                    self.exec_variant(const_idx, num_args)?;
                 }
 
                 Opcode::Start => {
                     let iter_obj = self.pop();
 
-                    let iter = Iterable::from_object(iter_obj.clone())
+                    let iter = Iter::from_object(iter_obj.clone())
                         .ok_or_else(|| format!("{} is not iterable", iter_obj.kind()))?;
 
-                    self.push(Object::Iter(Iter {
-                        size: iter.count(),
-                        expr: iter,
-                        current: 0,
-                    }))?;
+                    self.push(Object::Iter(iter))?;
                 }
 
                 Opcode::Next => {
-                    let Object::Iter(iter) = self.pop() else {
+                    let Some(Object::Iter(iter)) = self.stack_top() else {
                         return Err("Object is not an iterator".to_string())?;
                     };
 
-                    self.push(Object::Iter(Iter {
-                        current: iter.current + 1,
-                        ..iter.clone()
-                    }))?;
-
-                    self.push(iter.expr.get(iter.current))?;
+                    let next = iter.next(self)?;
+                    self.push(next.unwrap_or(Object::Nil))?;
                 }
 
                 Opcode::JumpEnd => {
@@ -353,17 +615,28 @@ impl<'a> VM<'a> {
                     let symbol_idx = code::read_u16(&ins, ip + 3);
                     self.current_frame().ip += 4;
 
-                    let Object::Iter(iter) = self.stack_top().unwrap() else {
+                    let Some(Object::Iter(iter)) = self.stack_top() else {
                         return Err("Object is not an iterator".to_string())?;
                     };
 
-                    if iter.current >= iter.size {
+                    if iter.peek(self)?.is_none() {
                         self.pop();
-                        self.current_frame().ip = (jump_pos - 1) as isize;
-                        self.globals.remove(symbol_idx);
+                        self.current_frame().ip = jump_pos.cast_signed() - 1;
+
+                        if self.frames_index == 1 {
+                            self.globals[symbol_idx] = Object::Nil;
+                        }
                     }
                 }
 
+                Opcode::Line => {
+                    let line = code::read_u16(&ins, ip + 1);
+                    self.current_frame().ip += 2;
+
+                    self.current_frame().line = line;
+                    self.on_line(line);
+                }
+
                 Opcode::Delete => {
                     let index = code::read_u16(&ins, ip + 1);
                     self.current_frame().ip += 2;
@@ -383,6 +656,8 @@ impl<'a> VM<'a> {
             return Err("stack overflow".to_string());
         }
 
+        self.budget.check_len(&o)?;
+
         self.stack[self.sp] = o;
         self.sp += 1;
 
@@ -408,13 +683,29 @@ impl<'a> VM<'a> {
         Ok(())
     }
 
+    fn dup_two(&mut self) -> Result<(), String> {
+        if self.sp + 1 >= STACK_SIZE {
+            return Err("stack overflow".to_string());
+        }
+
+        self.stack[self.sp] = self.stack[self.sp - 2].clone();
+        self.stack[self.sp + 1] = self.stack[self.sp - 1].clone();
+        self.sp += 2;
+
+        Ok(())
+    }
+
     fn current_frame(&mut self) -> &mut Frame {
         self.frames.get_mut(self.frames_index - 1).unwrap()
     }
 
-    fn push_frame(&mut self, f: Frame) {
+    fn push_frame(&mut self, f: Frame) -> Result<(), String> {
+        self.budget.enter(self.frames_index)?;
+
         self.frames.push(f);
         self.frames_index += 1;
+
+        Ok(())
     }
 
     fn pop_frame(&mut self) -> Frame {
@@ -422,6 +713,44 @@ impl<'a> VM<'a> {
         self.frames.pop().unwrap()
     }
 
+    fn on_line(&mut self, line: usize) {
+        let Some(mut debugger) = self.debugger.take() else {
+            return;
+        };
+
+        if debugger.should_pause(line, self.frames_index - 1) {
+            debugger.pause(&self.snapshot());
+        }
+
+        self.debugger = Some(debugger);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let frames = self.frames[..self.frames_index]
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, frame)| {
+                let info = frame.cl.func.info.as_deref();
+                let name = info.map(|info| info.name.as_str());
+
+                StackFrame {
+                    name: frame_name(if i == 0 { None } else { name.or(Some("?")) }),
+                    line: frame.line,
+                    locals: info.map_or_else(Vec::new, |info| {
+                        variables(&info.locals, &self.stack[frame.bp..self.sp])
+                    }),
+                    free: info.map_or_else(Vec::new, |info| variables(&info.free, &frame.cl.free)),
+                }
+            })
+            .collect();
+
+        Snapshot {
+            frames,
+            globals: variables(&self.global_names, &self.globals),
+        }
+    }
+
     fn push_closure(&mut self, const_idx: usize, num_free: usize) -> Result<(), String> {
         let constant = self.constants[const_idx].clone();
 
@@ -465,9 +794,10 @@ impl VM<'_> {
 
     fn exec_method_expression(
         &mut self,
+        const_idx: usize,
         num_args: usize,
-        method_idx: usize,
         has_arguments: bool,
+        receiver: bool,
     ) -> Result<(), String> {
         let mut args = Vec::new();
         for _ in 0..num_args {
@@ -476,43 +806,388 @@ impl VM<'_> {
 
         args.reverse();
         let caller = self.pop();
-        let ret = caller.call_method(method_idx, has_arguments.then_some(&args));
+
+        let Object::Str(Str { value: method }) = &self.constants[const_idx] else {
+            return Err("expected a method name".to_string());
+        };
+
+        let method = method.clone();
+
+        let getter = match &caller {
+            Object::Class(class) => {
+                self.check_private(&class.name, &method)?;
+
+                accessor(class, &getter_name(&method))
+                    .filter(|_| !has_arguments && !self.in_accessor(&class.name, &method))
+            }
+            _ => None,
+        };
+
+        let (ret, instance) = match getter {
+            Some(getter) => self.call_bound(&getter, caller, &[], Some(&method))?,
+            None => self.call_method(caller, &method, has_arguments.then_some(&args))?,
+        };
+
         self.push(ret)?;
+
+        if receiver {
+            self.push(instance)?;
+        }
+
+        Ok(())
+    }
+
+    // Returns what calling `method` of `caller` returns, and `caller` as the
+    // call left it.
+    fn call_method(
+        &mut self,
+        caller: Object,
+        method: &str,
+        args: Option<&[Object]>,
+    ) -> Result<(Object, Object), String> {
+        let method_idx = hash_method_name(method);
+
+        if let (Object::Iter(iter), Some(args)) = (&caller, args) {
+            let budget = self.budget.clone();
+
+            if let Some(ret) = budget.run_builtin(|| iter.call_method(method_idx, args, self)) {
+                return Ok((ret?, caller));
+            }
+        }
+
+        match (caller.call_method(method_idx, args), &caller) {
+            (Object::Closure(method), Object::Class(_) | Object::EnumValue(_)) => {
+                self.call_bound(&method, caller, args.unwrap_or_default(), None)
+            }
+            (Object::Error(Error { value }), _) => Err(value),
+            (ret, _) => Ok((ret, caller)),
+        }
+    }
+
+    // Calls `method` with `instance` bound to `self`, as the accessor of
+    // `property` if given.
+    fn call_bound(
+        &mut self,
+        method: &Closure,
+        instance: Object,
+        args: &[Object],
+        property: Option<&str>,
+    ) -> Result<(Object, Object), String> {
+        let Object::Class(Class { name, .. }) = &instance else {
+            return self.run_bound(method, instance, args);
+        };
+
+        self.running
+            .push((name.clone(), property.map(str::to_string)));
+        let ret = self.run_bound(method, instance, args);
+        self.running.pop();
+
+        ret
+    }
+
+    // Runs `method` synchronously, so that special methods can be called from
+    // within any instruction. Returns what it returns and `self` as it left it.
+    fn run_bound(
+        &mut self,
+        method: &Closure,
+        instance: Object,
+        args: &[Object],
+    ) -> Result<(Object, Object), String> {
+        if args.len() + 1 != method.func.num_parameters {
+            return Err(format!(
+                "wrong number of arguments. got: {}, want: {}",
+                args.len(),
+                method.func.num_parameters.saturating_sub(1)
+            ));
+        }
+
+        let depth = self.frames_index + 1;
+        let bp = self.sp + 1;
+
+        self.push(Object::Closure(method.clone()))?;
+        self.push(instance)?;
+        for arg in args {
+            self.push(arg.clone())?;
+        }
+
+        self.call_closure(method, args.len() + 1)?;
+        stacker::maybe_grow(RED_ZONE, GROWN_STACK_SIZE, || self.execute(depth))?;
+
+        // Returning leaves the locals of the frame where they were, `self`
+        // among them.
+        let instance = self.stack[bp].clone();
+
+        Ok((self.pop(), instance))
+    }
+
+    // Calls the special method `name` (e.g. `add` or `index`) of a class
+    // instance. Returns `None` when `instance` is not a class or doesn't
+    // define it.
+    fn call_special_method(
+        &mut self,
+        instance: &Object,
+        name: &str,
+        args: &[Object],
+    ) -> Result<Option<(Object, Object)>, String> {
+        let Object::Class(Class { members, .. }) = instance else {
+            return Ok(None);
+        };
+
+        let Some(ClassMember {
+            obj: Object::Closure(method),
+            ..
+        }) = members.get(&hash_method_name(name))
+        else {
+            return Ok(None);
+        };
+
+        self.call_bound(&method.clone(), instance.clone(), args, None)
+            .map(Some)
+    }
+
+    // Private members can only be used by the methods of their class.
+    fn check_private(&self, class: &str, member: &str) -> Result<(), String> {
+        if is_private(member) && self.running.last().is_none_or(|(name, _)| name != class) {
+            return Err(format!(
+                "cannot access private member `{member}` of class \"{class}\""
+            ));
+        }
+
         Ok(())
     }
 
+    fn in_accessor(&self, class: &str, property: &str) -> bool {
+        self.running.last().is_some_and(|(name, accessing)| {
+            name == class && accessing.as_deref() == Some(property)
+        })
+    }
+
+    fn exec_overloaded_operation(
+        &mut self,
+        op: Opcode,
+        left: &Object,
+        right: &Object,
+    ) -> Result<Option<Object>, String> {
+        let (Object::Class(_), Some(method)) = (left, opcode_to_method(op)) else {
+            return Ok(None);
+        };
+
+        let args = [right.clone()];
+
+        if let Some((result, _)) = self.call_special_method(left, method, &args)? {
+            return Ok(Some(result));
+        }
+
+        // `!=`, `>=` and `<=` fall back to negating `eq`, `lt` and `gt`.
+        let negated = match op {
+            Opcode::NotEqual => "eq",
+            Opcode::GreaterThanEqual => "lt",
+            Opcode::LessThanEqual => "gt",
+            _ => return Ok(None),
+        };
+
+        Ok(self
+            .call_special_method(left, negated, &args)?
+            .map(|(result, _)| Object::bool(!is_truthy(&result))))
+    }
+
+    // Converts class instances to the STR returned by their `toString` or `str`
+    // method, leaving everything else as is.
+    fn stringify(&mut self, obj: Object) -> Result<Object, String> {
+        Ok(self
+            .representation(&obj, &["toString", "str"])?
+            .unwrap_or(obj))
+    }
+
+    /// Like [`Object::inspect`], but uses the `inspect` or `toString` method of
+    /// class instances when they define one.
+    pub fn inspect(&mut self, obj: &Object) -> String {
+        match self.representation(obj, &["inspect", "toString", "str"]) {
+            Ok(Some(repr)) => repr.to_string(),
+            Ok(None) => obj.inspect(),
+            Err(err) => Object::error(err).inspect(),
+        }
+    }
+
+    // Calls the first of `methods` defined by a class instance. An instance
+    // formatting itself from within one of these methods gets the default
+    // representation instead of recursing forever.
+    fn representation(&mut self, obj: &Object, methods: &[&str]) -> Result<Option<Object>, String> {
+        let Object::Class(Class { name, members }) = obj else {
+            return Ok(None);
+        };
+
+        if self.representing.contains(obj) {
+            return Ok(None);
+        }
+
+        let Some(method) = methods
+            .iter()
+            .find(|method| members.contains_key(&hash_method_name(method)))
+        else {
+            return Ok(None);
+        };
+
+        self.representing.push(obj.clone());
+        let repr = self.call_special_method(obj, method, &[]);
+        self.representing.pop();
+
+        match repr? {
+            Some((repr @ Object::Str(_), _)) => Ok(Some(repr)),
+            Some((other, _)) => Err(format!(
+                "`{method}` method of class \"{name}\" must return STR, got {}",
+                other.kind()
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn exec_constructor(&mut self, const_idx: usize, num_members: usize) -> Result<(), String> {
+        let Object::Str(Str { value: name }) = &self.constants[const_idx] else {
+            return Err("expected a class name".to_string());
+        };
+
+        // The members extend the instance beneath them, or a new one on `nil`.
+        let mut members = match &self.stack[self.sp - 2 * num_members - 1] {
+            Object::Class(prototype) => prototype.members.clone(),
+            _ => IndexMap::new(),
+        };
+        let inherited = members.len();
+
+        // Initializers come first and win over fields of the same name.
+        for pair in self.stack[self.sp - 2 * num_members..self.sp].chunks(2) {
+            let [Object::Str(Str { value: member }), value] = pair else {
+                return Err("expected a member name".to_string());
+            };
+
+            let key = hash_method_name(member);
+            if members
+                .get_index_of(&key)
+                .is_some_and(|idx| idx >= inherited)
+            {
+                continue;
+            }
+
+            members.insert(key, ClassMember::new(member.clone(), value.clone()));
+        }
+
+        self.sp -= 2 * num_members + 1;
+
+        self.push(Object::Class(Class {
+            name: name.clone(),
+            members,
+        }))
+    }
+
+    fn exec_set_member(
+        &mut self,
+        const_idx: usize,
+        instance: Object,
+        value: Object,
+    ) -> Result<Object, String> {
+        let Object::Str(Str { value: member }) = &self.constants[const_idx] else {
+            return Err("expected a member name".to_string());
+        };
+        let member = member.clone();
+
+        let Object::Class(mut class) = instance else {
+            return Err(format!(
+                "cannot assign to non-class instances. got: {}",
+                instance.kind()
+            ));
+        };
+
+        self.check_private(&class.name, &member)?;
+
+        if !self.in_accessor(&class.name, &member) {
+            if let Some(setter) = accessor(&class, &setter_name(&member)) {
+                let (_, instance) =
+                    self.call_bound(&setter, Object::Class(class), &[value], Some(&member))?;
+
+                return Ok(instance);
+            }
+
+            if accessor(&class, &getter_name(&member)).is_some() {
+                return Err(format!("cannot assign to read-only property '{member}'"));
+            }
+        }
+
+        class
+            .members
+            .insert(hash_method_name(&member), ClassMember::new(member, value));
+
+        Ok(Object::Class(class))
+    }
+
+    fn exec_enum(&mut self, const_idx: usize, num_methods: usize) -> Result<(), String> {
+        let Object::Enum(decl) = &self.constants[const_idx] else {
+            return Err("expected an enum declaration".to_string());
+        };
+
+        let mut decl = Enum::clone(decl);
+
+        for _ in 0..num_methods {
+            let method = self.pop();
+            let Object::Str(Str { value: name }) = self.pop() else {
+                return Err("expected a method name".to_string());
+            };
+
+            decl.methods
+                .insert(hash_method_name(&name), ClassMember::new(name, method));
+        }
+
+        self.push(Object::Enum(Arc::new(decl)))
+    }
+
+    fn exec_variant(&mut self, const_idx: usize, num_args: usize) -> Result<(), String> {
+        let mut values = Vec::with_capacity(num_args);
+        for _ in 0..num_args {
+            values.push(self.pop());
+        }
+
+        values.reverse();
+
+        let Object::Str(Str { value: name }) = &self.constants[const_idx] else {
+            return Err("expected a variant name".to_string());
+        };
+
+        let obj = self.pop();
+
+        let Object::Enum(decl) = &obj else {
+            return Err(format!("{} has no variants", obj.kind()));
+        };
+
+        let value = decl.construct(name, values)?;
+        self.push(value)
+    }
+
     fn exec_range(&mut self, has_step: bool) -> Result<(), String> {
+        let step = has_step.then(|| self.pop());
         let end = self.pop();
         let start = self.pop();
+
         let Object::Int(Int { value: start }) = start else {
             return Err(format!(
-                "cannot use {} as step in range. expected: INT",
+                "cannot use {} as start in range. expected: INT",
                 start.kind()
             ));
         };
         let Object::Int(Int { value: end }) = end else {
             return Err(format!(
-                "cannot use {} as step in range. expected: INT",
+                "cannot use {} as end in range. expected: INT",
                 end.kind()
             ));
         };
-        let step = if has_step {
-            if start > end {
-                -1
-            } else {
-                1
-            }
-        } else {
-            let step = self.pop();
-
-            let Object::Int(Int { value: step }) = step else {
+        let step = match step {
+            Some(Object::Int(Int { value })) => value,
+            Some(step) => {
                 return Err(format!(
                     "cannot use {} as step in range. expected: INT",
                     step.kind()
-                ));
-            };
-
-            step
+                ))
+            }
+            None if start > end => -1,
+            None => 1,
         };
         self.push(Object::Range(Range { start, end, step }))?;
 
@@ -523,6 +1198,10 @@ impl VM<'_> {
         let right = self.pop();
         let left = self.pop();
 
+        if let Some(result) = self.exec_overloaded_operation(op, &left, &right)? {
+            return self.push(result);
+        }
+
         match (&left, &right) {
             (Object::Int(Int { value: left_value }), Object::Int(Int { value: right_value })) => {
                 self.execute_binary_int_operation(op, *left_value, *right_value)
@@ -531,6 +1210,18 @@ impl VM<'_> {
                 Object::Float(Float { value: left_value }),
                 Object::Float(Float { value: right_value }),
             ) => self.execute_binary_float_operation(op, *left_value, *right_value),
+            (
+                Object::Int(Int { value: left_value }),
+                Object::Float(Float { value: right_value }),
+            ) if !is_bitwise(op) => {
+                self.execute_binary_float_operation(op, int_to_float(*left_value), *right_value)
+            }
+            (
+                Object::Float(Float { value: left_value }),
+                Object::Int(Int { value: right_value }),
+            ) if !is_bitwise(op) => {
+                self.execute_binary_float_operation(op, *left_value, int_to_float(*right_value))
+            }
             (Object::Str(Str { value: left_value }), Object::Str(Str { value: right_value })) => {
                 self.execute_binary_string_operation(op, left_value, right_value)
             }
@@ -560,21 +1251,15 @@ impl VM<'_> {
         left: isize,
         right: isize,
     ) -> Result<(), String> {
-        let value = match op {
-            Opcode::Add => left + right,
-            Opcode::Sub => left - right,
-            Opcode::Mul => left * right,
-            Opcode::Div => left / right,
-            Opcode::Mod => left % right,
-            Opcode::BitXor => left ^ right,
-            Opcode::BitAnd => left & right,
-            Opcode::BitOr => left | right,
-            Opcode::Shr => left >> right,
-            Opcode::Shl => left << right,
-            _ => return Err(format!("unknown integer operation: {op}")),
-        };
+        if op == Opcode::Pow {
+            return self.push(int_pow(left, right)?);
+        }
+
+        if opcode_to_operator(op).is_empty() {
+            return Err(format!("unknown integer operation: {op}"));
+        }
 
-        self.push(Object::int(value))
+        self.push(Object::int(int_op(left, &opcode_to_operator(op), right)?))
     }
 
     fn execute_binary_float_operation(
@@ -589,6 +1274,8 @@ impl VM<'_> {
             Opcode::Mul => left * right,
             Opcode::Div => left / right,
             Opcode::Mod => left % right,
+            Opcode::Pow => left.powf(right),
+            Opcode::FloorDiv => (left / right).floor(),
             _ => return Err(format!("unknown float operation: {op}")),
         };
 
@@ -629,6 +1316,10 @@ impl VM<'_> {
         let right = self.pop();
         let left = self.pop();
 
+        if let Some(result) = self.exec_overloaded_operation(op, &left, &right)? {
+            return self.push(result);
+        }
+
         match (&left, &right) {
             (Object::Int(Int { value: left_value }), Object::Int(Int { value: right_value })) => {
                 self.execute_int_comparison(op, *left_value, *right_value)
@@ -637,6 +1328,14 @@ impl VM<'_> {
                 Object::Float(Float { value: left_value }),
                 Object::Float(Float { value: right_value }),
             ) => self.execute_float_comparison(op, *left_value, *right_value),
+            (
+                Object::Int(Int { value: left_value }),
+                Object::Float(Float { value: right_value }),
+            ) => self.execute_float_comparison(op, int_to_float(*left_value), *right_value),
+            (
+                Object::Float(Float { value: left_value }),
+                Object::Int(Int { value: right_value }),
+            ) => self.execute_float_comparison(op, *left_value, int_to_float(*right_value)),
             (
                 Object::Char(Char { value: left_value }),
                 Object::Char(Char { value: right_value }),
@@ -677,6 +1376,8 @@ impl VM<'_> {
             Opcode::NotEqual => left != right,
             Opcode::GreaterThan => left > right,
             Opcode::GreaterThanEqual => left >= right,
+            Opcode::LessThan => left < right,
+            Opcode::LessThanEqual => left <= right,
             _ => return Err(format!("unknown operator: {op}")),
         };
 
@@ -694,6 +1395,8 @@ impl VM<'_> {
             Opcode::NotEqual => (left - right).abs() > f64::EPSILON,
             Opcode::GreaterThan => left > right,
             Opcode::GreaterThanEqual => left >= right,
+            Opcode::LessThan => left < right,
+            Opcode::LessThanEqual => left <= right,
             _ => return Err(format!("unknown operator: {op}")),
         };
 
@@ -711,6 +1414,8 @@ impl VM<'_> {
             Opcode::NotEqual => left != right,
             Opcode::GreaterThan => left > right,
             Opcode::GreaterThanEqual => left >= right,
+            Opcode::LessThan => left < right,
+            Opcode::LessThanEqual => left <= right,
             _ => return Err(format!("unknown operator: {op}")),
         };
 
@@ -730,8 +1435,14 @@ impl VM<'_> {
     fn execute_minus_operator(&mut self) -> Result<(), String> {
         let operand = self.pop();
 
-        if let Object::Int(Int { value }) = operand {
-            self.push(Object::int(-value))
+        if let Some((result, _)) = self.call_special_method(&operand, "neg", &[])? {
+            self.push(result)
+        } else if let Object::Int(Int { value }) = operand {
+            let negated = value
+                .checked_neg()
+                .ok_or_else(|| format!("integer overflow: -({value})"))?;
+
+            self.push(Object::int(negated))
         } else if let Object::Float(Float { value }) = operand {
             self.push(Object::float(-value))
         } else {
@@ -739,6 +1450,23 @@ impl VM<'_> {
         }
     }
 
+    fn execute_bit_not_operator(&mut self) -> Result<(), String> {
+        let operand = self.pop();
+
+        if let Some((result, _)) = self.call_special_method(&operand, "bitNot", &[])? {
+            return self.push(result);
+        }
+
+        let Object::Int(Int { value }) = operand else {
+            return Err(format!(
+                "unsupported type for bitwise not: {}",
+                operand.kind()
+            ));
+        };
+
+        self.push(Object::int(!value))
+    }
+
     fn execute_boolean_operator(&mut self, op: Opcode) -> Result<(), String> {
         let right = self.pop();
         let left = self.pop();
@@ -765,6 +1493,12 @@ impl VM<'_> {
     }
 
     fn execute_index_expression(&mut self, left: &Object, index: &Object) -> Result<(), String> {
+        if let Some((value, _)) =
+            self.call_special_method(left, "index", std::slice::from_ref(index))?
+        {
+            return self.push(value);
+        }
+
         match (left, index) {
             (Object::Array(Array { elements }), Object::Int(Int { value })) => {
                 self.exec_array_index_expression(elements, *value)?;
@@ -805,14 +1539,19 @@ impl VM<'_> {
     }
 
     fn exec_string_index_expression(&mut self, string: &str, idx: isize) -> Result<(), String> {
-        let max = string.len();
-        let idx = normalize_index(idx, max);
+        // Only counting from the end needs the whole string segmented.
+        let count = if idx.is_negative() {
+            grapheme_count(string)
+        } else {
+            0
+        };
+        let idx = normalize_index(idx, count);
 
-        if idx >= max {
+        let Some(grapheme) = grapheme_at(string, idx) else {
             return Err(format!("index out of bounds. got: {idx}"));
-        }
+        };
 
-        self.push(Object::char(string.chars().nth(idx).unwrap()))
+        self.push(grapheme_object(grapheme))
     }
 
     fn exec_array_slice_expression(
@@ -846,21 +1585,7 @@ impl VM<'_> {
         end: isize,
         step: isize,
     ) -> Result<(), String> {
-        let max = (string.len() - 1).try_into().unwrap();
-
-        if start > max || end > max || start < 0 || end < 0 || start > end {
-            return Err(format!("cannot slice STR using {start}..{end}..{step}"));
-        }
-
-        let mut value = String::new();
-
-        let mut i = start;
-        while i < end {
-            value.push(string.chars().nth(i.try_into().unwrap()).unwrap());
-            i += step;
-        }
-
-        self.push(Object::Str(Str { value }))
+        self.push(Object::str(slice_str(string, start, end, step)?))
     }
 
     fn exec_dict_index_expression(
@@ -883,12 +1608,36 @@ impl VM<'_> {
         match callee {
             Object::Closure(callee) => self.call_closure(&callee, num_args),
 
-            Object::Builtin(Builtin { func, caller, .. }) => self.call_builtin(
+            Object::Builtin(Builtin { name, func, caller }) => self.call_builtin(
+                &name,
                 func,
                 &(caller.unwrap_or_else(|| Box::new(Object::Nil))),
                 num_args,
             ),
 
+            Object::Native(native) => {
+                let args = &self.stack[self.sp - num_args..self.sp];
+                let result = self.budget.run_builtin(|| native.call(&self.io, args));
+
+                self.sp = self.sp - num_args - 1;
+
+                match result {
+                    Object::Error(Error { value }) => Err(value),
+                    obj => self.push(obj),
+                }
+            }
+
+            Object::Class(_) => {
+                let args = self.stack[self.sp - num_args..self.sp].to_vec();
+
+                let Some((ret, _)) = self.call_special_method(&callee, "call", &args)? else {
+                    return Err(format!("not a function: {}", callee.kind()));
+                };
+
+                self.sp = self.sp - num_args - 1;
+                self.push(ret)
+            }
+
             _ => Err(format!(
                 "calling non-function and non-builtin: {}",
                 callee.kind()
@@ -904,8 +1653,27 @@ impl VM<'_> {
             ));
         }
 
+        if cl.func.generator {
+            let mut stack = self.stack[self.sp - num_args..self.sp].to_vec();
+            stack.resize(cl.func.num_locals, Object::Nil);
+
+            self.sp = self.sp - num_args - 1;
+
+            let generator = CompiledGenerator {
+                closure: cl.clone(),
+                ip: -1,
+                stack,
+                done: false,
+                running: false,
+            };
+
+            return self.push(Object::Iter(Iter::new(IterState::Generator(
+                Generator::Compiled(Arc::new(Mutex::new(generator))),
+            ))));
+        }
+
         let frame = Frame::new(cl.clone(), self.sp - num_args);
-        self.push_frame(frame.clone());
+        self.push_frame(frame.clone())?;
 
         self.sp = frame.bp + cl.func.num_locals;
 
@@ -914,17 +1682,104 @@ impl VM<'_> {
 
     fn call_builtin(
         &mut self,
+        name: &str,
         func: BuiltinFunction,
         caller: &Object,
         num_args: usize,
     ) -> Result<(), String> {
-        let args = &self.stack[self.sp - num_args..self.sp];
+        let converted;
+        let args = if matches!(name, "print" | "println" | "str" | "format") {
+            converted = (self.sp - num_args..self.sp)
+                .map(|i| self.stringify(self.stack[i].clone()))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            &converted
+        } else {
+            &self.stack[self.sp - num_args..self.sp]
+        };
+
+        let result = self.budget.run_builtin(|| {
+            self.io
+                .call_builtin(name, args)
+                .unwrap_or_else(|| func(caller, args))
+        });
 
         self.sp = self.sp - num_args - 1;
 
-        self.push((func)(caller, args))?;
+        match result {
+            Object::Error(Error { value }) => Err(value),
+            obj => self.push(obj),
+        }
+    }
+}
 
-        Ok(())
+impl Driver for VM<'_> {
+    fn resume(&mut self, generator: &Generator) -> Result<Option<Object>, String> {
+        let Generator::Compiled(generator) = generator else {
+            return Err("cannot resume a generator created by the evaluator".to_string());
+        };
+
+        let (cl, ip, stack) = {
+            let mut generator = generator.lock().unwrap();
+
+            if generator.done {
+                return Ok(None);
+            }
+
+            if generator.running {
+                return Err("generator already running".to_string());
+            }
+
+            generator.running = true;
+
+            (
+                generator.closure.clone(),
+                generator.ip,
+                std::mem::take(&mut generator.stack),
+            )
+        };
+
+        let bp = self.sp;
+        self.yielded = None;
+
+        let resumed = stack
+            .into_iter()
+            .try_for_each(|obj| self.push(obj))
+            .and_then(|()| {
+                self.push_frame(Frame {
+                    cl,
+                    ip,
+                    bp,
+                    generator: Some(generator.clone()),
+                    line: 0,
+                })
+            })
+            .and_then(|()| self.execute(self.frames_index));
+
+        generator.lock().unwrap().running = false;
+        resumed?;
+
+        Ok(self.yielded.take())
+    }
+
+    fn call_member(&mut self, instance: &mut Object, name: &str) -> Result<Object, String> {
+        let (ret, updated) = self.call_method(instance.clone(), name, Some(&[]))?;
+        *instance = updated;
+
+        match ret {
+            Object::Error(Error { value }) => Err(value),
+            _ => Ok(ret),
+        }
+    }
+}
+
+fn accessor(class: &Class, name: &str) -> Option<Closure> {
+    match class.members.get(&hash_method_name(name)) {
+        Some(ClassMember {
+            obj: Object::Closure(closure),
+            ..
+        }) => Some(closure.clone()),
+        _ => None,
     }
 }
 
@@ -949,6 +1804,40 @@ fn normalize_index(idx: isize, max: usize) -> usize {
     usize::try_from(if idx.is_negative() { max + idx } else { idx }).unwrap()
 }
 
+const fn is_bitwise(op: Opcode) -> bool {
+    matches!(
+        op,
+        Opcode::BitXor | Opcode::BitAnd | Opcode::BitOr | Opcode::Shl | Opcode::Shr
+    )
+}
+
+// The special method a class instance on the left of the operator can define.
+const fn opcode_to_method(op: Opcode) -> Option<&'static str> {
+    let method = match op {
+        Opcode::Add => "add",
+        Opcode::Sub => "sub",
+        Opcode::Mul => "mul",
+        Opcode::Div => "div",
+        Opcode::Mod => "mod",
+        Opcode::Pow => "pow",
+        Opcode::FloorDiv => "floorDiv",
+        Opcode::BitXor => "bitXor",
+        Opcode::BitAnd => "bitAnd",
+        Opcode::BitOr => "bitOr",
+        Opcode::Shr => "shr",
+        Opcode::Shl => "shl",
+        Opcode::Equal => "eq",
+        Opcode::NotEqual => "ne",
+        Opcode::GreaterThan => "gt",
+        Opcode::LessThan => "lt",
+        Opcode::GreaterThanEqual => "ge",
+        Opcode::LessThanEqual => "le",
+        _ => return None,
+    };
+
+    Some(method)
+}
+
 fn opcode_to_operator(op: Opcode) -> String {
     let operator = match op {
         Opcode::Add => "+",
@@ -956,6 +1845,8 @@ fn opcode_to_operator(op: Opcode) -> String {
         Opcode::Mul => "*",
         Opcode::Div => "/",
         Opcode::Mod => "%",
+        Opcode::Pow => "**",
+        Opcode::FloorDiv => "//",
         Opcode::BitXor => "^",
         Opcode::BitAnd => "&",
         Opcode::BitOr => "|",
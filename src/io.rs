@@ -0,0 +1,240 @@
+use std::{
+    fmt::{self, Debug},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::object::{Int, Object, Str};
+
+/// The streams the programs an engine runs print to and read from, which are
+/// the console's unless the host replaces them. Clones share their streams.
+///
+/// ```
+/// use panda::{Engine, Interpreter, Io, OutputBuffer};
+///
+/// let out = OutputBuffer::default();
+///
+/// let mut panda = Interpreter::new(Engine::Eval);
+/// panda.set_io(Io::default().output(out.clone()).sandboxed());
+/// panda.eval_str("println(1, \"a\")").unwrap();
+///
+/// assert_eq!(out.contents(), "1 a\n");
+/// ```
+#[derive(Clone)]
+pub struct Io {
+    streams: Arc<Mutex<Streams>>,
+}
+
+struct Streams {
+    output: Box<dyn Write + Send>,
+    error: Box<dyn Write + Send>,
+    input: Box<dyn BufRead + Send>,
+    sandboxed: bool,
+    exit_code: Option<i32>,
+}
+
+impl Default for Io {
+    fn default() -> Self {
+        Self {
+            streams: Arc::new(Mutex::new(Streams {
+                output: Box::new(std::io::stdout()),
+                error: Box::new(std::io::stderr()),
+                input: Box::new(BufReader::new(std::io::stdin())),
+                sandboxed: false,
+                exit_code: None,
+            })),
+        }
+    }
+}
+
+impl Debug for Io {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Io").finish_non_exhaustive()
+    }
+}
+
+impl Io {
+    /// Replaces the stream `print` and `println` write to.
+    #[must_use]
+    pub fn output(self, output: impl Write + Send + 'static) -> Self {
+        self.lock().output = Box::new(output);
+        self
+    }
+
+    /// Replaces the stream `eprintln` writes to.
+    #[must_use]
+    pub fn error(self, error: impl Write + Send + 'static) -> Self {
+        self.lock().error = Box::new(error);
+        self
+    }
+
+    /// Replaces the stream `input` and `readLine` read from.
+    #[must_use]
+    pub fn input(self, input: impl BufRead + Send + 'static) -> Self {
+        self.lock().input = Box::new(input);
+        self
+    }
+
+    /// Makes reading from the console and using files with `std/io` an error.
+    #[must_use]
+    pub fn sandboxed(self) -> Self {
+        self.lock().sandboxed = true;
+        self
+    }
+
+    pub(crate) fn take_exit_code(&self) -> Option<i32> {
+        self.lock().exit_code.take()
+    }
+
+    /// Runs the builtins that use the console, given arguments that are
+    /// already strings where they're printed.
+    pub(crate) fn call_builtin(&self, name: &str, args: &[Object]) -> Option<Object> {
+        let result = match name {
+            "print" => self.write(false, args, false),
+            "println" => self.write(false, args, true),
+            "input" => match args {
+                [Object::Str(Str { value })] => self
+                    .write(false, &[Object::str(value.clone())], false)
+                    .and_then(|_| self.read_line())
+                    .map(|line| Object::str(line.unwrap_or_default().trim().to_string())),
+                [prompt] => Err(format!(
+                    "cannot use {} as prompt in `input`. expected STR",
+                    prompt.kind()
+                )),
+                _ => Err(format!(
+                    "wrong number of arguments. got: {}, want: 1",
+                    args.len()
+                )),
+            },
+            "exit" => match args {
+                [] => Err(self.exit(0)),
+                [Object::Int(Int { value })] => Err(self.exit(*value)),
+                [code] => Err(format!(
+                    "cannot use {} as exit code. expected INT",
+                    code.kind()
+                )),
+                _ => Err(format!(
+                    "wrong number of arguments. got: {}, want: 0 or 1",
+                    args.len()
+                )),
+            },
+            _ => return None,
+        };
+
+        Some(result.unwrap_or_else(Object::error))
+    }
+
+    pub(crate) fn write(
+        &self,
+        error: bool,
+        args: &[Object],
+        newline: bool,
+    ) -> Result<Object, String> {
+        let mut streams = self.lock();
+        let stream = if error {
+            &mut streams.error
+        } else {
+            &mut streams.output
+        };
+
+        let str_args = args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let end = if newline { "\n" } else { "" };
+
+        write!(stream, "{}{end}", str_args.join(" "))
+            .and_then(|()| stream.flush())
+            .map_err(|err| format!("cannot write output: {err}"))?;
+
+        Ok(Object::Nil)
+    }
+
+    /// Reads a line without its line ending, or `None` at the end of input.
+    pub(crate) fn read_line(&self) -> Result<Option<String>, String> {
+        let mut streams = self.lock();
+
+        if streams.sandboxed {
+            return Err("cannot read input in a sandbox".to_string());
+        }
+
+        let mut line = String::new();
+
+        match streams.input.read_line(&mut line) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(line.trim_end_matches(['\n', '\r']).to_string())),
+            Err(err) => Err(format!("cannot read input: {err}")),
+        }
+    }
+
+    pub(crate) fn read_file(&self, path: &str) -> Result<String, String> {
+        self.check_files()?;
+
+        std::fs::read_to_string(path).map_err(|err| format!("cannot read \"{path}\": {err}"))
+    }
+
+    pub(crate) fn write_file(&self, path: &str, contents: &str) -> Result<(), String> {
+        self.check_files()?;
+
+        std::fs::write(path, contents).map_err(|err| format!("cannot write \"{path}\": {err}"))
+    }
+
+    pub(crate) fn file_exists(&self, path: &str) -> Result<bool, String> {
+        self.check_files()?;
+
+        Ok(Path::new(path).exists())
+    }
+
+    fn check_files(&self) -> Result<(), String> {
+        if self.lock().sandboxed {
+            Err("cannot access files in a sandbox".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    // Records the code and returns the error that unwinds the program.
+    fn exit(&self, code: isize) -> String {
+        let code = i32::try_from(code).unwrap_or(i32::MAX);
+        self.lock().exit_code = Some(code);
+
+        format!("exit({code})")
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Streams> {
+        self.streams
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// A stream collecting what's written to it, for hosts and tests to read back.
+#[derive(Clone, Default)]
+pub struct OutputBuffer {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl OutputBuffer {
+    #[must_use]
+    pub fn contents(&self) -> String {
+        let buffer = self
+            .buffer
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+impl Write for OutputBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,25 +1,52 @@
-use crate::token::{lookup_ident, Kind, Token};
+use std::fmt::Display;
+
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+use crate::token::{lookup_ident, Kind, Span, Token};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LexError {
+    pub message: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
 
 pub struct Lexer {
     input: Vec<char>,
 
     position: usize,
     read_position: usize,
+    byte_position: usize,
+    byte_read_position: usize,
     len: usize,
 
     ch: char,
+
+    pub errors: Vec<LexError>,
 }
 
 impl Lexer {
     pub fn new(input: &str) -> Self {
+        let input = input.chars().collect::<Vec<_>>();
+
         let mut l = Self {
-            input: input.chars().collect(),
+            len: input.len(),
+            input,
 
             position: 0,
             read_position: 0,
-            len: input.len(),
+            byte_position: 0,
+            byte_read_position: 0,
 
             ch: '\0',
+
+            errors: Vec::new(),
         };
 
         l.read_char();
@@ -28,19 +55,36 @@ impl Lexer {
     }
 
     fn read_char(&mut self) {
+        self.position = self.read_position;
+        self.byte_position = self.byte_read_position;
+
         if self.read_position >= self.len {
             self.ch = '\0';
         } else {
             self.ch = self.input[self.read_position];
+            self.byte_read_position += self.ch.len_utf8();
         }
 
-        self.position = self.read_position;
         self.read_position += 1;
     }
 
     pub fn next_token(&mut self) -> Token {
         self.skip_whitespace();
 
+        let (start, byte_start) = (self.position.min(self.len), self.byte_position);
+
+        let mut tok = self.read_token();
+        tok.span = Span {
+            start,
+            end: self.position.min(self.len),
+            byte_start,
+            byte_end: self.byte_position,
+        };
+
+        tok
+    }
+
+    fn read_token(&mut self) -> Token {
         let tok = match self.ch {
             '=' => {
                 if self.peek_char() == Some('=') {
@@ -50,9 +94,9 @@ impl Lexer {
                     Token::new(Kind::Assign, self.ch.to_string())
                 }
             }
-            '%' => Token::new(Kind::Modulo, self.ch.to_string()),
-            '+' => Token::new(Kind::Plus, self.ch.to_string()),
-            '-' => Token::new(Kind::Minus, self.ch.to_string()),
+            '%' => self.read_operator("%", Kind::Modulo, Kind::ModuloAssign),
+            '+' => self.read_operator("+", Kind::Plus, Kind::PlusAssign),
+            '-' => self.read_operator("-", Kind::Minus, Kind::MinusAssign),
             '!' => {
                 if self.peek_char() == Some('=') {
                     self.read_char();
@@ -61,12 +105,26 @@ impl Lexer {
                     Token::new(Kind::Bang, self.ch.to_string())
                 }
             }
-            '/' => Token::new(Kind::Slash, self.ch.to_string()),
-            '*' => Token::new(Kind::Asterisk, self.ch.to_string()),
+            '/' => {
+                if self.peek_char() == Some('/') {
+                    self.read_char();
+                    self.read_operator("//", Kind::FloorDiv, Kind::FloorDivAssign)
+                } else {
+                    self.read_operator("/", Kind::Slash, Kind::SlashAssign)
+                }
+            }
+            '*' => {
+                if self.peek_char() == Some('*') {
+                    self.read_char();
+                    self.read_operator("**", Kind::Power, Kind::PowerAssign)
+                } else {
+                    self.read_operator("*", Kind::Asterisk, Kind::AsteriskAssign)
+                }
+            }
             '<' => {
                 if self.peek_char() == Some('<') {
                     self.read_char();
-                    Token::new(Kind::Shl, "<<".to_string())
+                    self.read_operator("<<", Kind::Shl, Kind::ShlAssign)
                 } else if self.peek_char() == Some('=') {
                     self.read_char();
                     Token::new(Kind::LtEq, "<=".to_string())
@@ -77,7 +135,7 @@ impl Lexer {
             '>' => {
                 if self.peek_char() == Some('>') {
                     self.read_char();
-                    Token::new(Kind::Shr, ">>".to_string())
+                    self.read_operator(">>", Kind::Shr, Kind::ShrAssign)
                 } else if self.peek_char() == Some('=') {
                     self.read_char();
                     Token::new(Kind::GtEq, ">=".to_string())
@@ -91,7 +149,19 @@ impl Lexer {
             ',' => Token::new(Kind::Comma, self.ch.to_string()),
             '{' => Token::new(Kind::LBrace, self.ch.to_string()),
             '}' => Token::new(Kind::RBrace, self.ch.to_string()),
-            '"' => Token::new(Kind::StrLiteral, self.read_string()),
+            '"' if self.peek_char() == Some('"') && self.peek_nth(1) == Some('"') => {
+                self.read_multiline_string()
+            }
+            '"' => {
+                let start = self.position;
+
+                let Some(raw) = self.read_string() else {
+                    self.error(start, "unterminated string literal".to_string());
+                    return Token::new(Kind::Eol, String::new());
+                };
+
+                self.string_token(start, &raw)
+            }
             '[' => Token::new(Kind::LBracket, self.ch.to_string()),
             ']' => Token::new(Kind::RBracket, self.ch.to_string()),
             ':' => {
@@ -102,8 +172,18 @@ impl Lexer {
                     Token::new(Kind::Colon, self.ch.to_string())
                 }
             }
-            '\'' => Token::new(Kind::CharLiteral, self.read_chars()),
-            '^' => Token::new(Kind::Caret, self.ch.to_string()),
+            '\'' => {
+                let start = self.position;
+
+                let Some(raw) = self.read_chars() else {
+                    self.error(start, "unterminated char literal".to_string());
+                    return Token::new(Kind::Eol, String::new());
+                };
+
+                Token::new(Kind::CharLiteral, raw)
+            }
+            '^' => self.read_operator("^", Kind::Caret, Kind::CaretAssign),
+            '~' => Token::new(Kind::Tilde, self.ch.to_string()),
             '.' => {
                 if self.peek_char() == Some('.') {
                     self.read_char();
@@ -117,7 +197,7 @@ impl Lexer {
                     self.read_char();
                     Token::new(Kind::And, "&&".to_string())
                 } else {
-                    Token::new(Kind::BitAnd, self.ch.to_string())
+                    self.read_operator("&", Kind::BitAnd, Kind::BitAndAssign)
                 }
             }
             '|' => {
@@ -125,19 +205,54 @@ impl Lexer {
                     self.read_char();
                     Token::new(Kind::Or, "||".to_string())
                 } else {
-                    Token::new(Kind::BitOr, self.ch.to_string())
+                    self.read_operator("|", Kind::BitOr, Kind::BitOrAssign)
                 }
             }
-            'a'..='z' | 'A'..='Z' | '_' => {
+            '?' => match self.peek_char() {
+                Some('?') => {
+                    self.read_char();
+                    Token::new(Kind::NilCoalesce, "??".to_string())
+                }
+                Some('.') => {
+                    self.read_char();
+                    Token::new(Kind::OptionalDot, "?.".to_string())
+                }
+                Some('[') => {
+                    self.read_char();
+                    Token::new(Kind::OptionalLBracket, "?[".to_string())
+                }
+                _ => {
+                    self.error(self.position, "unexpected character `?`".to_string());
+                    Token::new(Kind::Illegal, self.ch.to_string())
+                }
+            },
+            'r' if self.is_raw_string_start() => self.read_raw_string(),
+            'f' if self.peek_char() == Some('"') => self.read_format_string(),
+            ch if ch == '_' || is_xid_start(ch) => {
                 let ident = self.read_identifier();
+
+                if ident == "fn" && self.ch == '*' {
+                    self.read_char();
+                    return Token::new(Kind::Generator, "fn*".to_string());
+                }
+
                 return Token::new(lookup_ident(&ident), ident);
             }
             '0'..='9' => {
+                let errors = self.errors.len();
                 let (lit, num_type) = self.read_number();
+
+                if self.errors.len() > errors {
+                    return Token::new(Kind::Illegal, lit);
+                }
+
                 return Token::new(num_type, lit);
             }
             '\0' => Token::new(Kind::Eol, String::new()),
-            _ => Token::new(Kind::Illegal, self.ch.to_string()),
+            _ => {
+                self.error(self.position, format!("unexpected character `{}`", self.ch));
+                Token::new(Kind::Illegal, self.ch.to_string())
+            }
         };
 
         self.read_char();
@@ -152,16 +267,70 @@ impl Lexer {
         }
     }
 
-    fn skip_whitespace(&mut self) {
-        while let '\t' | '\n' | '\x0C' | '\r' | ' ' = self.ch {
+    // Reads the operator ending at the current character, or its compound
+    // assignment form if it is followed by `=`.
+    fn read_operator(&mut self, lit: &str, kind: Kind, assign: Kind) -> Token {
+        if self.peek_char() == Some('=') {
             self.read_char();
+            return Token::new(assign, format!("{lit}="));
+        }
+
+        Token::new(kind, lit.to_string())
+    }
+
+    fn error(&mut self, start: usize, message: String) {
+        let before = &self.input[..start.min(self.input.len())];
+
+        let line = self.line(start);
+        let col = before.len()
+            - before
+                .iter()
+                .rposition(|&ch| ch == '\n')
+                .map_or(0, |i| i + 1)
+            + 1;
+
+        self.errors.push(LexError { message, line, col });
+    }
+
+    /// The line of the character at `position`, counted from 1.
+    pub fn line(&self, position: usize) -> usize {
+        self.newlines(0, position) + 1
+    }
+
+    pub fn newlines(&self, from: usize, to: usize) -> usize {
+        let to = to.min(self.input.len());
+
+        self.input[from.min(to)..to]
+            .iter()
+            .filter(|&&ch| ch == '\n')
+            .count()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.input.get(self.read_position + n).copied()
+    }
+
+    // Comments start with `#` and run to the end of the line.
+    fn skip_whitespace(&mut self) {
+        loop {
+            while self.ch.is_whitespace() {
+                self.read_char();
+            }
+
+            if self.ch != '#' {
+                break;
+            }
+
+            while self.ch != '\n' && self.position < self.len {
+                self.read_char();
+            }
         }
     }
 
     fn read_identifier(&mut self) -> String {
         let pos = self.position;
 
-        while let 'a'..='z' | 'A'..='Z' | '_' | '0'..='9' = self.ch {
+        while is_xid_continue(self.ch) {
             self.read_char();
         }
 
@@ -171,68 +340,329 @@ impl Lexer {
     fn read_number(&mut self) -> (String, Kind) {
         let pos = self.position;
 
+        let radix = match (self.ch, self.peek_char()) {
+            ('0', Some('x')) => Some((16, "hexadecimal")),
+            ('0', Some('o')) => Some((8, "octal")),
+            ('0', Some('b')) => Some((2, "binary")),
+            _ => None,
+        };
+
+        if let Some((radix, name)) = radix {
+            self.read_char();
+            self.read_char();
+
+            let digits = self.read_digits(|ch| ch.is_ascii_alphanumeric());
+
+            if let Some(ch) = digits.chars().find(|ch| !ch.is_digit(radix)) {
+                self.error(pos, format!("invalid digit `{ch}` in {name} literal"));
+            } else if digits.is_empty() {
+                self.error(pos, format!("missing digits in {name} literal"));
+            } else if isize::from_str_radix(&digits, radix).is_err() {
+                self.error(pos, format!("{name} literal out of range for integer"));
+            }
+
+            return (self.lexeme(pos), Kind::IntLiteral);
+        }
+
         let mut num_type = Kind::IntLiteral;
 
-        loop {
-            if self.ch.is_ascii_digit() {
+        let digits = self.read_digits(|ch| ch.is_ascii_digit());
+
+        if self.ch == '.' && self.peek_char().is_some_and(|ch| ch.is_ascii_digit()) {
+            self.read_char();
+            self.read_digits(|ch| ch.is_ascii_digit());
+            num_type = Kind::FloatLiteral;
+        }
+
+        if matches!(self.ch, 'e' | 'E') {
+            self.read_char();
+
+            if matches!(self.ch, '+' | '-') {
                 self.read_char();
-            } else if self.ch == '.' && num_type == Kind::IntLiteral {
-                if self
-                    .peek_char()
-                    .and_then(|ch| if ch.is_ascii_digit() { Some(()) } else { None })
-                    .is_some()
-                {
-                    self.read_char();
-                    num_type = Kind::FloatLiteral;
-                } else {
-                    break;
-                }
-            } else {
-                break;
             }
+
+            if self.read_digits(|ch| ch.is_ascii_digit()).is_empty() {
+                self.error(pos, "missing digits in exponent".to_string());
+            }
+
+            num_type = Kind::FloatLiteral;
         }
 
-        (
-            String::from_iter(self.input[pos..self.position].to_vec()),
-            num_type,
-        )
+        let suffix = self.read_digits(is_xid_continue);
+        if !suffix.is_empty() {
+            self.error(pos, format!("invalid suffix `{suffix}` for number literal"));
+        } else if num_type == Kind::IntLiteral && digits.parse::<isize>().is_err() {
+            self.error(pos, "decimal literal out of range for integer".to_string());
+        }
+
+        (self.lexeme(pos), num_type)
+    }
+
+    // Reads digits matched by `is_digit` along with any `_` separators,
+    // returning the digits without separators.
+    fn read_digits(&mut self, is_digit: impl Fn(char) -> bool) -> String {
+        let mut digits = String::new();
+
+        while self.ch == '_' || is_digit(self.ch) {
+            if self.ch != '_' {
+                digits.push(self.ch);
+            }
+
+            self.read_char();
+        }
+
+        digits
+    }
+
+    fn lexeme(&self, pos: usize) -> String {
+        String::from_iter(self.input[pos..self.position].to_vec())
+    }
+
+    fn read_string(&mut self) -> Option<String> {
+        let pos = self.position + 1;
+
+        loop {
+            self.read_char();
+
+            match self.ch {
+                '"' => break,
+                '\0' => return None,
+                '\\' if self.peek_char().is_some() => self.read_char(),
+                _ => {}
+            }
+        }
+
+        Some(String::from_iter(self.input[pos..self.position].to_vec()))
+    }
+
+    fn string_token(&mut self, start: usize, raw: &str) -> Token {
+        let value = unescape(raw).unwrap_or_else(|err| {
+            self.error(start, err);
+            raw.to_string()
+        });
+
+        Token::new(Kind::StrLiteral, value)
+    }
+
+    // `r"..."` or `r#"..."#` with any number of `#`s.
+    fn is_raw_string_start(&self) -> bool {
+        let mut n = 0;
+
+        while self.peek_nth(n) == Some('#') {
+            n += 1;
+        }
+
+        self.peek_nth(n) == Some('"')
     }
 
-    fn read_string(&mut self) -> String {
+    fn read_raw_string(&mut self) -> Token {
+        let start = self.position;
+        let mut hashes = 0;
+
+        self.read_char();
+        while self.ch == '#' {
+            hashes += 1;
+            self.read_char();
+        }
+
         let pos = self.position + 1;
 
         loop {
             self.read_char();
 
-            if self.ch == '"' {
+            if self.ch == '\0' {
+                self.error(start, "unterminated raw string literal".to_string());
+                return Token::new(Kind::Eol, String::new());
+            }
+
+            if self.ch == '"' && (0..hashes).all(|n| self.peek_nth(n) == Some('#')) {
                 break;
             }
+        }
 
-            if self.ch == '\\' && self.peek_char() == Some('"') {
-                self.read_char();
+        let value = String::from_iter(self.input[pos..self.position].to_vec());
+
+        for _ in 0..hashes {
+            self.read_char();
+        }
+
+        Token::new(Kind::StrLiteral, value)
+    }
+
+    // Triple-quoted strings may span several lines. A line break right after the
+    // opening quotes and before the closing ones is dropped, and the indentation
+    // shared by every line (including the closing quotes) is stripped.
+    fn read_multiline_string(&mut self) -> Token {
+        let start = self.position;
+
+        self.read_char();
+        self.read_char();
+
+        let pos = self.position + 1;
+
+        loop {
+            self.read_char();
+
+            match self.ch {
+                '\0' => {
+                    self.error(start, "unterminated multi-line string literal".to_string());
+                    return Token::new(Kind::Eol, String::new());
+                }
+                '"' if self.peek_char() == Some('"') && self.peek_nth(1) == Some('"') => break,
+                '\\' if self.peek_char().is_some() => self.read_char(),
+                _ => {}
             }
         }
 
-        String::from_iter(self.input[pos..self.position].to_vec())
+        let raw = String::from_iter(self.input[pos..self.position].to_vec());
+
+        self.read_char();
+        self.read_char();
+
+        self.string_token(start, &dedent(&raw))
     }
 
-    fn read_chars(&mut self) -> String {
+    // Unlike `read_string`, quotes inside `{}` belong to the embedded expression
+    // and do not end the literal.
+    fn read_format_string(&mut self) -> Token {
+        let start = self.position;
+
+        self.read_char();
+
         let pos = self.position + 1;
+        let mut depth = 0usize;
 
         loop {
             self.read_char();
 
-            if self.ch == '\'' {
-                break;
+            let terminated = match self.ch {
+                '\0' => false,
+                '"' if depth == 0 => break,
+                '"' => self.read_string().is_some(),
+                _ => true,
+            };
+
+            if !terminated {
+                self.error(start, "unterminated format string".to_string());
+                return Token::new(Kind::Eol, String::new());
             }
 
-            if self.ch == '\\' && self.peek_char() == Some('\'') {
-                self.read_char();
+            match self.ch {
+                '{' | '}' if depth == 0 && self.peek_char() == Some(self.ch) => self.read_char(),
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                '\\' if self.peek_char() == Some('"') => self.read_char(),
+                _ => {}
             }
         }
 
-        String::from_iter(self.input[pos..self.position].to_vec())
+        Token::new(
+            Kind::FStrLiteral,
+            String::from_iter(self.input[pos..self.position].to_vec()),
+        )
+    }
+
+    fn read_chars(&mut self) -> Option<String> {
+        let pos = self.position + 1;
+
+        loop {
+            self.read_char();
+
+            match self.ch {
+                '\'' => break,
+                '\0' => return None,
+                '\\' if self.peek_char() == Some('\'') => self.read_char(),
+                _ => {}
+            }
+        }
+
+        Some(String::from_iter(self.input[pos..self.position].to_vec()))
+    }
+}
+
+fn dedent(raw: &str) -> String {
+    let raw = raw
+        .strip_prefix("\r\n")
+        .or_else(|| raw.strip_prefix('\n'))
+        .unwrap_or(raw);
+
+    let mut lines = raw.split('\n').collect::<Vec<_>>();
+
+    let indent = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| *i + 1 == lines.len() || !line.trim().is_empty())
+        .map(|(_, line)| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    if lines.len() > 1 && lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_else(|| line.trim_start()))
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Resolves the escape sequences of a string or char literal.
+pub fn unescape(raw: &str) -> Result<String, String> {
+    let mut chars = raw.chars();
+    let mut out = String::new();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some(ch @ ('\\' | '"' | '\'')) => ch,
+            Some('x') => {
+                let code = chars.by_ref().take(2).collect::<String>();
+                u8::from_str_radix(&code, 16)
+                    .ok()
+                    .filter(|_| code.len() == 2)
+                    .map(char::from)
+                    .ok_or_else(|| format!("invalid escape `\\x{code}`"))?
+            }
+            Some('u') if chars.as_str().starts_with('{') => {
+                let Some((code, rest)) = chars.as_str()[1..].split_once('}') else {
+                    return Err("unterminated unicode escape".to_string());
+                };
+
+                let escaped = u32::from_str_radix(code, 16)
+                    .ok()
+                    .filter(|_| (1..=6).contains(&code.len()))
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid unicode escape `\\u{{{code}}}`"))?;
+
+                chars = rest.chars();
+                escaped
+            }
+            Some('u') => {
+                let code = chars.by_ref().take(4).collect::<String>();
+                u32::from_str_radix(&code, 16)
+                    .ok()
+                    .filter(|_| code.len() == 4)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid unicode escape `\\u{code}`"))?
+            }
+            Some(ch) => return Err(format!("unknown escape `\\{ch}`")),
+            None => return Err("unterminated escape".to_string()),
+        };
+
+        out.push(escaped);
     }
+
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -295,6 +725,9 @@ class test() {
 
     b() {};
 }
+
+fn* g() { yield; }
+f"a{b + 1:>2}"
 "#;
 
         let test_cases = [
@@ -425,6 +858,15 @@ class test() {
             TestCase::new(Kind::RBrace, "}"),
             TestCase::new(Kind::Semicolon, ";"),
             TestCase::new(Kind::RBrace, "}"),
+            TestCase::new(Kind::Generator, "fn*"),
+            TestCase::new(Kind::Ident, "g"),
+            TestCase::new(Kind::LParen, "("),
+            TestCase::new(Kind::RParen, ")"),
+            TestCase::new(Kind::LBrace, "{"),
+            TestCase::new(Kind::Yield, "yield"),
+            TestCase::new(Kind::Semicolon, ";"),
+            TestCase::new(Kind::RBrace, "}"),
+            TestCase::new(Kind::FStrLiteral, "a{b + 1:>2}"),
             TestCase::new(Kind::Eol, ""),
         ];
 
@@ -445,4 +887,253 @@ class test() {
             );
         }
     }
+
+    #[test]
+    fn test_string_literals() {
+        let test_cases = [
+            (r#""a\tb\n""#, "a\tb\n"),
+            (
+                r#""\"q\" \\ \x41 \u{1F600} \u2022""#,
+                "\"q\" \\ A \u{1F600} \u{2022}",
+            ),
+            (r#"r"C:\new""#, r"C:\new"),
+            ("r#\"say \"hi\"\"#", "say \"hi\""),
+            (
+                "\"\"\"\n    SELECT *\n      FROM t\n    \"\"\"",
+                "SELECT *\n  FROM t",
+            ),
+            ("\"\"\"a\\tb\"\"\"", "a\tb"),
+        ];
+
+        for (input, expected_lit) in test_cases {
+            let mut l = Lexer::new(input);
+            let tok = l.next_token();
+
+            assert_eq!(tok.tok_type, Kind::StrLiteral, "input: {input}");
+            assert_eq!(tok.tok_lit, expected_lit, "input: {input}");
+            assert!(l.errors.is_empty(), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn test_number_literals() {
+        let test_cases = [
+            ("0xFF", Kind::IntLiteral),
+            ("0o755", Kind::IntLiteral),
+            ("0b1010_1010", Kind::IntLiteral),
+            ("1_000_000", Kind::IntLiteral),
+            ("1e9", Kind::FloatLiteral),
+            ("2.5e-3", Kind::FloatLiteral),
+            ("6.02E+23", Kind::FloatLiteral),
+            ("3.141_592", Kind::FloatLiteral),
+        ];
+
+        for (input, expected_type) in test_cases {
+            let mut l = Lexer::new(input);
+            let tok = l.next_token();
+
+            assert_eq!(tok.tok_type, expected_type, "input: {input}");
+            assert_eq!(tok.tok_lit, input);
+            assert!(l.errors.is_empty(), "input: {input}");
+        }
+
+        let mut l = Lexer::new("1..2");
+        assert_eq!(l.next_token().tok_lit, "1");
+        assert_eq!(l.next_token().tok_type, Kind::Range);
+    }
+
+    #[test]
+    fn test_lexer_errors() {
+        let test_cases = [
+            ("0b102", "1:1: invalid digit `2` in binary literal"),
+            ("x = 0x;", "1:5: missing digits in hexadecimal literal"),
+            ("1e+", "1:1: missing digits in exponent"),
+            ("\n  12ab", "2:3: invalid suffix `ab` for number literal"),
+            (
+                "x = 0x8000000000000000",
+                "1:5: hexadecimal literal out of range for integer",
+            ),
+            (
+                "\n9999999999999999999999",
+                "2:1: decimal literal out of range for integer",
+            ),
+            (r#""\q""#, r"1:1: unknown escape `\q`"),
+            (
+                r#""\u{110000}""#,
+                r"1:1: invalid unicode escape `\u{110000}`",
+            ),
+            (r#"a "abc"#, "1:3: unterminated string literal"),
+            (r#"r#"abc""#, "1:1: unterminated raw string literal"),
+            ("a @ b", "1:3: unexpected character `@`"),
+            (r#"println(f"{");"#, "1:9: unterminated format string"),
+            (r#"f"{x}"#, "1:1: unterminated format string"),
+            ("var c = 'a", "1:9: unterminated char literal"),
+        ];
+
+        for (input, expected) in test_cases {
+            let mut l = Lexer::new(input);
+            while l.next_token().tok_type != Kind::Eol {}
+
+            let errors = l.errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+            assert_eq!(errors, [expected], "input: {input}");
+        }
+    }
+
+    #[test]
+    fn test_comments() {
+        let input = "# a comment\nvar x = 1; # another\n#\nx #";
+
+        let test_cases = [
+            (Kind::Var, "var"),
+            (Kind::Ident, "x"),
+            (Kind::Assign, "="),
+            (Kind::IntLiteral, "1"),
+            (Kind::Semicolon, ";"),
+            (Kind::Ident, "x"),
+            (Kind::Eol, ""),
+        ];
+
+        let mut l = Lexer::new(input);
+
+        for (kind, lit) in test_cases {
+            let tok = l.next_token();
+
+            assert_eq!(tok.tok_type, kind);
+            assert_eq!(tok.tok_lit, lit);
+        }
+
+        assert!(l.errors.is_empty());
+    }
+
+    #[test]
+    fn test_unicode_spans() {
+        let input = "var café\u{3000}= \"日本\";";
+
+        let test_cases = [
+            (Kind::Var, "var", (0, 3), (0, 3)),
+            (Kind::Ident, "café", (4, 8), (4, 9)),
+            (Kind::Assign, "=", (9, 10), (12, 13)),
+            (Kind::StrLiteral, "日本", (11, 15), (14, 22)),
+            (Kind::Semicolon, ";", (15, 16), (22, 23)),
+            (Kind::Eol, "", (16, 16), (23, 23)),
+        ];
+
+        let mut l = Lexer::new(input);
+
+        for (kind, lit, (start, end), (byte_start, byte_end)) in test_cases {
+            let tok = l.next_token();
+
+            assert_eq!(tok.tok_type, kind);
+            assert_eq!(tok.tok_lit, lit);
+            assert_eq!(
+                tok.span,
+                Span {
+                    start,
+                    end,
+                    byte_start,
+                    byte_end
+                },
+                "span of {lit}"
+            );
+            assert_eq!(&input[byte_start..byte_end].replace('"', ""), lit);
+        }
+
+        assert!(l.errors.is_empty());
+    }
+
+    #[test]
+    fn test_compound_assignment_operators() {
+        let input = "+= -= *= /= %= ^= &= |= <<= >>= << >> == &&";
+
+        let test_cases = [
+            (Kind::PlusAssign, "+="),
+            (Kind::MinusAssign, "-="),
+            (Kind::AsteriskAssign, "*="),
+            (Kind::SlashAssign, "/="),
+            (Kind::ModuloAssign, "%="),
+            (Kind::CaretAssign, "^="),
+            (Kind::BitAndAssign, "&="),
+            (Kind::BitOrAssign, "|="),
+            (Kind::ShlAssign, "<<="),
+            (Kind::ShrAssign, ">>="),
+            (Kind::Shl, "<<"),
+            (Kind::Shr, ">>"),
+            (Kind::Eq, "=="),
+            (Kind::And, "&&"),
+            (Kind::Eol, ""),
+        ];
+
+        let mut l = Lexer::new(input);
+
+        for (kind, lit) in test_cases {
+            let tok = l.next_token();
+
+            assert_eq!(tok.tok_type, kind);
+            assert_eq!(tok.tok_lit, lit);
+        }
+    }
+
+    #[test]
+    fn test_nil_safe_operators() {
+        let input = "a?.b?[0] ?? c ?";
+
+        let test_cases = [
+            (Kind::Ident, "a"),
+            (Kind::OptionalDot, "?."),
+            (Kind::Ident, "b"),
+            (Kind::OptionalLBracket, "?["),
+            (Kind::IntLiteral, "0"),
+            (Kind::RBracket, "]"),
+            (Kind::NilCoalesce, "??"),
+            (Kind::Ident, "c"),
+            (Kind::Illegal, "?"),
+            (Kind::Eol, ""),
+        ];
+
+        let mut l = Lexer::new(input);
+
+        for (kind, lit) in test_cases {
+            let tok = l.next_token();
+
+            assert_eq!(tok.tok_type, kind);
+            assert_eq!(tok.tok_lit, lit);
+        }
+
+        assert_eq!(
+            l.errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["1:15: unexpected character `?`"]
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_operators() {
+        let input = "a ** b // c ~d **= //= not in * /";
+
+        let test_cases = [
+            (Kind::Ident, "a"),
+            (Kind::Power, "**"),
+            (Kind::Ident, "b"),
+            (Kind::FloorDiv, "//"),
+            (Kind::Ident, "c"),
+            (Kind::Tilde, "~"),
+            (Kind::Ident, "d"),
+            (Kind::PowerAssign, "**="),
+            (Kind::FloorDivAssign, "//="),
+            (Kind::Not, "not"),
+            (Kind::In, "in"),
+            (Kind::Asterisk, "*"),
+            (Kind::Slash, "/"),
+            (Kind::Eol, ""),
+        ];
+
+        let mut l = Lexer::new(input);
+
+        for (kind, lit) in test_cases {
+            let tok = l.next_token();
+
+            assert_eq!(tok.tok_type, kind);
+            assert_eq!(tok.tok_lit, lit);
+        }
+    }
 }
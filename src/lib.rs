@@ -0,0 +1,29 @@
+//! The panda programming language, which runs either on a tree-walking
+//! evaluator or on a bytecode VM. [`Interpreter`] is the way to embed it.
+
+pub mod analysis;
+mod ast;
+mod code;
+mod compiler;
+pub mod debug;
+mod debugger;
+mod error;
+mod interpreter;
+mod interpreters;
+mod io;
+mod lexer;
+mod limits;
+mod module;
+mod native;
+mod object;
+mod parser;
+mod token;
+
+pub use debugger::{DebugHook, Debugger, Resume, Snapshot, StackFrame};
+pub use error::{ConversionError, Error};
+pub use interpreter::{Engine, Interpreter};
+pub use io::{Io, OutputBuffer};
+pub use limits::{InterruptHandle, LimitError, Limits};
+pub use native::{Args, NativeError, NativeModule};
+pub use object::Object;
+pub use token::Span;
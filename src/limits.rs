@@ -0,0 +1,313 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Display},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::object::{Array, Dict, Object, Str};
+
+// How many steps run between two looks at the clock.
+const CLOCK_INTERVAL: u64 = 1024;
+
+// How long a sleeping builtin waits between two looks at the clock.
+const SLEEP_INTERVAL: Duration = Duration::from_millis(10);
+
+thread_local! {
+    // The budget of the builtin running on this thread. Builtins are plain
+    // functions, so this is where they find it to `reserve` against.
+    static RUNNING: RefCell<Option<Budget>> = const { RefCell::new(None) };
+}
+
+/// Bounds on the work a single run of a program may do, for hosts running
+/// code they don't trust. Nothing is limited by default.
+///
+/// ```
+/// use std::time::Duration;
+/// use panda::{Engine, Error, Interpreter, LimitError, Limits};
+///
+/// let mut panda = Interpreter::new(Engine::VM);
+/// panda.set_limits(Limits::default().steps(10_000).timeout(Duration::from_secs(1)));
+///
+/// assert!(matches!(
+///     panda.eval_str("while (true) { 1 }"),
+///     Err(Error::Limit(LimitError::Steps(10_000)))
+/// ));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Limits {
+    steps: Option<u64>,
+    depth: Option<usize>,
+    collection_len: Option<usize>,
+    timeout: Option<Duration>,
+}
+
+impl Limits {
+    /// Limits the instructions the VM, or the AST nodes the evaluator, runs.
+    #[must_use]
+    pub const fn steps(mut self, steps: u64) -> Self {
+        self.steps = Some(steps);
+        self
+    }
+
+    /// Limits how deeply function calls nest.
+    #[must_use]
+    pub const fn depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Limits the elements of arrays, the pairs of dicts and the bytes of
+    /// strings the program creates.
+    #[must_use]
+    pub const fn collection_len(mut self, len: usize) -> Self {
+        self.collection_len = Some(len);
+        self
+    }
+
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// The limit that stopped a program.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LimitError {
+    Steps(u64),
+    Depth(usize),
+    CollectionLen(usize),
+    Timeout(Duration),
+    /// An [`InterruptHandle`] stopped the program.
+    Interrupted,
+}
+
+impl Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Steps(steps) => write!(f, "exceeded the limit of {steps} steps"),
+            Self::Depth(depth) => write!(f, "exceeded the maximum call depth of {depth}"),
+            Self::CollectionLen(len) => {
+                write!(f, "exceeded the maximum collection length of {len}")
+            }
+            Self::Timeout(timeout) => write!(f, "timed out after {timeout:?}"),
+            Self::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+/// Stops the program an [`Interpreter`](crate::Interpreter) is running from
+/// another thread, or the next one it runs if it's idle.
+#[derive(Clone, Debug)]
+pub struct InterruptHandle {
+    state: Arc<State>,
+}
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.state.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The limits of a run and what it has used of them, shared by the engines
+/// and the generators they spawn.
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    limits: Limits,
+    state: Arc<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    steps: AtomicU64,
+    started: Mutex<Instant>,
+    // Set by interrupts and by the limits, which also record themselves.
+    stopped: AtomicBool,
+    exceeded: Mutex<Option<LimitError>>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            steps: AtomicU64::new(0),
+            started: Mutex::new(Instant::now()),
+            stopped: AtomicBool::new(false),
+            exceeded: Mutex::new(None),
+        }
+    }
+}
+
+impl Budget {
+    /// Replaces the limits, keeping the interrupt handles working.
+    pub(crate) fn with_limits(&self, limits: Limits) -> Self {
+        Self {
+            limits,
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    pub(crate) fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle {
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    /// Resets the budget for a new run. Interrupts that came in since the
+    /// last run still stop this one.
+    pub(crate) fn start(&self) {
+        self.state.steps.store(0, Ordering::Relaxed);
+        *lock(&self.state.started) = Instant::now();
+
+        if lock(&self.state.exceeded).take().is_some() {
+            self.state.stopped.store(false, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn step(&self) -> Result<(), String> {
+        let steps = self.state.steps.fetch_add(1, Ordering::Relaxed) + 1;
+
+        self.check_stopped()?;
+
+        match self.limits {
+            Limits {
+                steps: Some(max), ..
+            } if steps > max => Err(self.exceed(LimitError::Steps(max))),
+
+            Limits {
+                timeout: Some(timeout),
+                ..
+            } if steps.is_multiple_of(CLOCK_INTERVAL)
+                && lock(&self.state.started).elapsed() > timeout =>
+            {
+                Err(self.exceed(LimitError::Timeout(timeout)))
+            }
+
+            _ => Ok(()),
+        }
+    }
+
+    fn check_stopped(&self) -> Result<(), String> {
+        if !self.state.stopped.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let exceeded = *lock(&self.state.exceeded);
+        Err(exceeded.unwrap_or(LimitError::Interrupted).to_string())
+    }
+
+    /// Checks a call that nests `depth` calls deep.
+    pub(crate) fn enter(&self, depth: usize) -> Result<(), String> {
+        match self.limits.depth {
+            Some(max) if depth > max => Err(self.exceed(LimitError::Depth(max))),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_len(&self, obj: &Object) -> Result<(), String> {
+        match obj {
+            Object::Array(Array { elements }) => self.check_size(elements.len()),
+            Object::Dict(Dict { pairs }) => self.check_size(pairs.len()),
+            Object::Str(Str { value }) => self.check_size(value.len()),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_size(&self, len: usize) -> Result<(), String> {
+        match self.limits.collection_len {
+            Some(max) if len > max => Err(self.exceed(LimitError::CollectionLen(max))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs a builtin, or an iterator method, which can `reserve` what it
+    /// allocates against this budget.
+    pub(crate) fn run_builtin<T>(&self, builtin: impl FnOnce() -> T) -> T {
+        let outer = RUNNING.replace(Some(self.clone()));
+        let result = builtin();
+        RUNNING.set(outer);
+
+        result
+    }
+
+    // Sleeps in slices, so interrupts and the timeout cut the sleep short.
+    fn sleep(&self, duration: Duration) -> Result<(), String> {
+        let mut left = duration;
+
+        loop {
+            self.check_stopped()?;
+
+            if let Some(timeout) = self.limits.timeout {
+                if lock(&self.state.started).elapsed() > timeout {
+                    return Err(self.exceed(LimitError::Timeout(timeout)));
+                }
+            }
+
+            if left.is_zero() {
+                return Ok(());
+            }
+
+            let slice = left.min(SLEEP_INTERVAL);
+            std::thread::sleep(slice);
+            left -= slice;
+        }
+    }
+
+    /// The limit or interrupt that stopped the run, which is cleared so the
+    /// next run can go ahead.
+    pub(crate) fn take_error(&self) -> Option<LimitError> {
+        if !self.state.stopped.swap(false, Ordering::Relaxed) {
+            return None;
+        }
+
+        Some(
+            lock(&self.state.exceeded)
+                .take()
+                .unwrap_or(LimitError::Interrupted),
+        )
+    }
+
+    // Records the limit, so the steps that follow fail too, and returns the
+    // error that unwinds the program.
+    fn exceed(&self, err: LimitError) -> String {
+        lock(&self.state.exceeded).get_or_insert(err);
+        self.state.stopped.store(true, Ordering::Relaxed);
+
+        err.to_string()
+    }
+}
+
+/// Checks that a collection of `len` elements, or a string of `len` bytes,
+/// fits in the budget of the running builtin before it's allocated.
+pub fn reserve(len: usize) -> Result<(), String> {
+    RUNNING.with_borrow(|budget| {
+        budget
+            .as_ref()
+            .map_or(Ok(()), |budget| budget.check_size(len))
+    })
+}
+
+/// Sleeps for `duration`, unless the running builtin's program is stopped or
+/// times out first.
+pub fn sleep(duration: Duration) -> Result<(), String> {
+    RUNNING.with_borrow(|budget| {
+        budget.as_ref().map_or_else(
+            || {
+                std::thread::sleep(duration);
+                Ok(())
+            },
+            |budget| budget.sleep(duration),
+        )
+    })
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
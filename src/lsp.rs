@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use panda::{
+    analysis::{CompletionKind, DefinitionKind, Document, OutlineItem},
+    Span,
+};
+use serde_json::{json, Value};
+
+use crate::protocol::{read_message, write_message};
+
+// The JSON-RPC error for requests the server doesn't know of.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Serves the Language Server Protocol, analysing each document again
+/// whenever the editor changes it.
+pub fn serve(mut input: impl BufRead, output: impl Write) -> io::Result<()> {
+    let mut server = Server {
+        output,
+        documents: HashMap::new(),
+    };
+
+    while let Some(message) = read_message(&mut input)? {
+        let method = message["method"].as_str().unwrap_or_default();
+
+        if method == "exit" {
+            break;
+        }
+
+        let params = &message["params"];
+
+        // Notifications have no id, and get no response.
+        if message.get("id").is_none() {
+            server.notify(method, params)?;
+            continue;
+        }
+
+        let mut response = json!({ "jsonrpc": "2.0", "id": message["id"] });
+
+        match server.handle(method, params) {
+            Some(result) => response["result"] = result,
+            None => {
+                response["error"] = json!({
+                    "code": METHOD_NOT_FOUND,
+                    "message": format!("unknown method {method}"),
+                });
+            }
+        }
+
+        write_message(&mut server.output, &response)?;
+    }
+
+    Ok(())
+}
+
+/// A document the editor has open, as it last sent it.
+struct Open {
+    text: String,
+    document: Document,
+}
+
+struct Server<W> {
+    output: W,
+    documents: HashMap<String, Open>,
+}
+
+impl<W: Write> Server<W> {
+    fn handle(&self, method: &str, params: &Value) -> Option<Value> {
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": { "triggerCharacters": [".", ":"] },
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "panda" },
+            }),
+            "shutdown" => Value::Null,
+            "textDocument/definition" => self.at(params, |uri, open, offset| {
+                open.document
+                    .definition(offset)
+                    .map_or(Value::Null, |span| location(uri, &open.text, span))
+            }),
+            "textDocument/references" => self.at(params, |uri, open, offset| {
+                let references = open.document.references(offset);
+                let locations = references
+                    .into_iter()
+                    .map(|span| location(uri, &open.text, span));
+
+                Value::Array(locations.collect())
+            }),
+            "textDocument/hover" => self.at(params, |_, open, offset| {
+                open.document.hover(offset).map_or(Value::Null, |hover| {
+                    json!({
+                        "contents": { "kind": "markdown", "value": hover.contents },
+                        "range": range(&open.text, hover.span),
+                    })
+                })
+            }),
+            "textDocument/completion" => self.at(params, |_, open, offset| {
+                let completions = open.document.completions(offset);
+                let items = completions.into_iter().map(|completion| {
+                    json!({
+                        "label": completion.label,
+                        "kind": completion_kind(completion.kind),
+                        "detail": completion.detail,
+                    })
+                });
+
+                Value::Array(items.collect())
+            }),
+            "textDocument/documentSymbol" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+                self.documents.get(uri).map_or(Value::Null, |open| {
+                    let outline = open.document.outline();
+                    Value::Array(
+                        outline
+                            .iter()
+                            .map(|item| symbol(&open.text, item))
+                            .collect(),
+                    )
+                })
+            }
+            _ => return None,
+        };
+
+        Some(result)
+    }
+
+    fn notify(&mut self, method: &str, params: &Value) -> io::Result<()> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        let text = match method {
+            "textDocument/didOpen" => params["textDocument"]["text"].as_str(),
+            // Documents are synced whole, so the last change has all of it.
+            "textDocument/didChange" => params["contentChanges"]
+                .as_array()
+                .and_then(|changes| changes.last())
+                .and_then(|change| change["text"].as_str()),
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return self.publish(&uri, Vec::new());
+            }
+            _ => return Ok(()),
+        };
+
+        let Some(text) = text else {
+            return Ok(());
+        };
+
+        let path = uri.strip_prefix("file://").map(PathBuf::from);
+        let document = Document::new(text, path.as_deref());
+
+        let diagnostics = document
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                json!({
+                    "range": range(text, diagnostic.span),
+                    "severity": 1,
+                    "source": "panda",
+                    "message": diagnostic.message,
+                })
+            })
+            .collect();
+
+        self.documents.insert(
+            uri.clone(),
+            Open {
+                text: text.to_string(),
+                document,
+            },
+        );
+
+        self.publish(&uri, diagnostics)
+    }
+
+    fn publish(&mut self, uri: &str, diagnostics: Vec<Value>) -> io::Result<()> {
+        let mut notification = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri },
+        });
+        notification["params"]["diagnostics"] = Value::Array(diagnostics);
+
+        write_message(&mut self.output, &notification)
+    }
+
+    // Answers a request about the position of a document the editor has
+    // open, with `null` for those it doesn't.
+    fn at(&self, params: &Value, f: impl FnOnce(&str, &Open, usize) -> Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        self.documents.get(uri).map_or(Value::Null, |open| {
+            let offset = offset(&open.text, &params["position"]);
+            f(uri, open, offset)
+        })
+    }
+}
+
+// Positions count UTF-16 code units into their line, while spans count chars.
+fn offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or_default();
+    let character = position["character"].as_u64().unwrap_or_default();
+
+    let mut offset = 0;
+
+    for (i, content) in text.split('\n').enumerate() {
+        if i as u64 == line {
+            let mut units = 0;
+
+            for ch in content.chars() {
+                if units >= character {
+                    break;
+                }
+
+                units += ch.len_utf16() as u64;
+                offset += 1;
+            }
+
+            return offset;
+        }
+
+        offset += content.chars().count() + 1;
+    }
+
+    offset
+}
+
+fn position(text: &str, offset: usize) -> Value {
+    let before = text.chars().take(offset);
+    let (mut line, mut character) = (0, 0);
+
+    for ch in before {
+        if ch == '\n' {
+            line += 1;
+            character = 0;
+        } else {
+            character += ch.len_utf16();
+        }
+    }
+
+    json!({ "line": line, "character": character })
+}
+
+fn range(text: &str, span: Span) -> Value {
+    json!({ "start": position(text, span.start), "end": position(text, span.end) })
+}
+
+fn location(uri: &str, text: &str, span: Span) -> Value {
+    json!({ "uri": uri, "range": range(text, span) })
+}
+
+fn symbol(text: &str, item: &OutlineItem) -> Value {
+    let children = item.children.iter().map(|child| symbol(text, child));
+
+    json!({
+        "name": item.name,
+        "detail": item.detail,
+        "kind": symbol_kind(item.kind),
+        "range": range(text, item.extent),
+        "selectionRange": range(text, item.span),
+        "children": children.collect::<Vec<_>>(),
+    })
+}
+
+const fn symbol_kind(kind: DefinitionKind) -> u8 {
+    match kind {
+        DefinitionKind::Module => 2,
+        DefinitionKind::Class => 5,
+        DefinitionKind::Method => 6,
+        DefinitionKind::Field => 8,
+        DefinitionKind::Enum => 10,
+        DefinitionKind::Interface => 11,
+        DefinitionKind::Function => 12,
+        DefinitionKind::Variable | DefinitionKind::Parameter => 13,
+        DefinitionKind::Constant => 14,
+        DefinitionKind::Variant => 22,
+    }
+}
+
+const fn completion_kind(kind: CompletionKind) -> u8 {
+    match kind {
+        CompletionKind::Method | CompletionKind::Definition(DefinitionKind::Method) => 2,
+        CompletionKind::Builtin | CompletionKind::Definition(DefinitionKind::Function) => 3,
+        CompletionKind::Definition(DefinitionKind::Field) => 5,
+        CompletionKind::Definition(DefinitionKind::Variable | DefinitionKind::Parameter) => 6,
+        CompletionKind::Definition(DefinitionKind::Class) => 7,
+        CompletionKind::Definition(DefinitionKind::Interface) => 8,
+        CompletionKind::Definition(DefinitionKind::Module) => 9,
+        CompletionKind::Definition(DefinitionKind::Enum) => 13,
+        CompletionKind::Definition(DefinitionKind::Variant) => 20,
+        CompletionKind::Definition(DefinitionKind::Constant) => 21,
+    }
+}
@@ -1,47 +1,61 @@
-mod ast;
 mod cmd;
-mod code;
-mod compiler;
-mod interpreters;
-mod lexer;
-mod object;
-mod parser;
-mod token;
+mod dap;
+mod dbg;
+mod lsp;
+mod protocol;
 
 use std::{io::Write, process::exit};
 
 use clap::Parser;
-use cmd::{DebugOut, Engine};
-use code::instructions_to_string;
-use compiler::{symbol_table::SymbolTable, Compiler};
-use interpreters::{
-    eval::Evaluator,
-    vm::{GLOBAL_SIZE, VM},
-};
-use object::{builtins::BUILTINS, Object, DIR_ENV_VAR_NAME};
+use cmd::DebugOut;
+use panda::{debug, Engine, Error, Interpreter, Object};
 
 fn main() {
     let cli = cmd::Cli::parse();
 
     match cli.command {
         cmd::Commands::Run(run_args) => {
-            std::env::set_var(
-                DIR_ENV_VAR_NAME,
-                std::fs::canonicalize(&run_args.file_name)
-                    .unwrap()
-                    .parent()
-                    .unwrap()
-                    .to_str()
-                    .unwrap(),
-            );
-            eval_file(run_args.file_name, run_args.engine).unwrap();
+            let mut panda = Interpreter::new(run_args.engine);
+
+            match panda.eval_file(&run_args.file_name) {
+                Err(Error::Exit(code)) => exit(code),
+                Err(err) => println!("{err}"),
+                Ok(_) => {}
+            }
+        }
+
+        cmd::Commands::Dbg(dbg_args) => {
+            let source = match std::fs::read_to_string(&dbg_args.file_name) {
+                Ok(source) => source,
+                Err(err) => {
+                    println!("{err}");
+                    return;
+                }
+            };
+
+            let mut panda = Interpreter::new(dbg_args.engine);
+            panda.set_debugger(dbg::debugger(&source, &dbg_args.breakpoints));
+
+            match panda.eval_file(&dbg_args.file_name) {
+                Err(Error::Exit(code)) => exit(code),
+                Err(err) => println!("{err}"),
+                Ok(_) => {}
+            }
+        }
+
+        cmd::Commands::Dap => {
+            if let Err(err) = dap::serve(std::io::stdin().lock(), std::io::stdout()) {
+                eprintln!("{err}");
+            }
+        }
+
+        cmd::Commands::Lsp => {
+            if let Err(err) = lsp::serve(std::io::stdin().lock(), std::io::stdout().lock()) {
+                eprintln!("{err}");
+            }
         }
 
         cmd::Commands::Repl(repl_args) => {
-            std::env::set_var(
-                DIR_ENV_VAR_NAME,
-                std::env::current_dir().unwrap().to_str().unwrap(),
-            );
             start_repl(repl_args.engine).unwrap();
         }
 
@@ -53,51 +67,23 @@ fn main() {
                 std::io::stdin().read_line(&mut input).unwrap();
             }
 
-            let mut lexer = lexer::Lexer::new(&input);
-            let mut parser = parser::Parser::new(&mut lexer);
-
-            let program = parser.parse_program().map_or_else(
-                || {
-                    println!("couldn't parse. returned `None`");
-                    exit(0);
-                },
-                |prog| prog,
-            );
-
-            if !parser.errors.is_empty() {
-                println!("parser errors:");
-                for msg in &parser.errors {
-                    println!("\t{msg}");
-                }
-                return;
-            }
-
             let debug_out = match debug_args.format {
-                DebugOut::Ast => format!("{program:#?}"),
-                DebugOut::ByteCode => {
-                    let mut comp = Compiler::new();
-                    if let Err(err) = comp.compile(program) {
-                        println!("compiler error:\n\t{err}");
-                        return;
-                    };
-
-                    instructions_to_string(&comp.bytecode().instructions)
-                }
-
-                DebugOut::Stack => {
-                    let mut comp = Compiler::new();
-                    if let Err(err) = comp.compile(program) {
-                        println!("compiler error:\n\t{err}");
-                        return;
-                    };
-
-                    let byte_code = comp.bytecode();
-                    let mut machine = VM::new(&byte_code);
-                    if let Err(err) = machine.run() {
-                        println!("vm error:\n\t{err}");
+                DebugOut::Ast => debug::ast(&input),
+                DebugOut::ByteCode => debug::bytecode(&input),
+                DebugOut::Stack => debug::stack(&input).map(|(stack, err)| {
+                    if let Some(err) = err {
+                        println!("{err}");
                     }
 
-                    format!("{:#?}", machine.get_stack())
+                    stack
+                }),
+            };
+
+            let debug_out = match debug_out {
+                Ok(debug_out) => debug_out,
+                Err(err) => {
+                    println!("{err}");
+                    return;
                 }
             };
 
@@ -119,140 +105,21 @@ fn start_repl(engine: Engine) -> std::io::Result<()> {
     );
     println!("Type `exit()` to exit from the repl.");
 
-    match engine {
-        Engine::Eval => {
-            let mut evaluator = Evaluator::new();
-
-            loop {
-                print!("|> ");
-                std::io::stdout().flush()?;
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-
-                let mut lexer = lexer::Lexer::new(&input);
-                let mut parser = parser::Parser::new(&mut lexer);
-
-                let program = parser.parse_program();
-
-                if !parser.errors.is_empty() {
-                    println!("parser errors:");
-                    for msg in &parser.errors {
-                        println!("\t{msg}");
-                    }
-                    continue;
-                }
-
-                let evaluated = evaluator.eval(program.unwrap());
-
-                if let Some(evaluated) = evaluated {
-                    println!("{}", evaluated.inspect());
-                }
-            }
-        }
-
-        Engine::VM => {
-            let mut constants = Vec::new();
-            let mut globals = Vec::with_capacity(GLOBAL_SIZE);
-            let mut symbol_table = SymbolTable::new();
-
-            for (i, (name, _)) in BUILTINS.iter().enumerate() {
-                symbol_table.define_builtin(name, i);
-            }
-
-            loop {
-                print!("|> ");
-                std::io::stdout().flush()?;
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-
-                let mut lexer = lexer::Lexer::new(&input);
-                let mut parser = parser::Parser::new(&mut lexer);
-
-                let program = parser.parse_program();
-
-                if !parser.errors.is_empty() {
-                    println!("parser errors:");
-                    for msg in &parser.errors {
-                        println!("\t{msg}");
-                    }
-                    continue;
-                }
-
-                let mut comp = compiler::new_with_state(symbol_table.clone(), &constants);
-                if let Err(err) = comp.compile(program.unwrap()) {
-                    println!("compiler error:\n\t{err}");
-                    continue;
-                };
-                symbol_table = comp.get_symbol_table();
-
-                let code = comp.bytecode();
-                constants = code.constants.clone();
-
-                let mut machine = VM::new_with_global_store(&code, &globals);
-                if let Err(err) = machine.run() {
-                    println!("vm error:\n\t{err}");
-                    continue;
-                }
-
-                globals = machine.get_globals();
-
-                let stack_top = machine.last_popped_stack_elem;
-                if let Some(obj) = stack_top {
-                    println!("{}", obj.inspect());
-                }
-            }
-        }
-    }
-}
-
-fn eval_file(fname: String, engine: Engine) -> std::io::Result<()> {
-    let input = std::fs::read_to_string(fname)?;
-    let mut evalualtor = Evaluator::new();
-
-    let mut lexer = lexer::Lexer::new(&input);
-    let mut parser = parser::Parser::new(&mut lexer);
-
-    let program = parser.parse_program();
-
-    if !parser.errors.is_empty() {
-        println!("parser errors:");
-        for msg in &parser.errors {
-            println!("\t{msg}");
-        }
-        return Ok(());
-    }
-
-    match engine {
-        Engine::Eval => {
-            let evaluated = evalualtor.eval(program.unwrap());
+    let mut panda = Interpreter::new(engine);
 
-            if let Some(evaluated) = evaluated {
-                if matches!(evaluated, Object::Error { .. }) {
-                    println!("{}", evaluated.inspect());
-                }
-            }
+    loop {
+        print!("|> ");
+        std::io::stdout().flush()?;
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input)? == 0 {
+            return Ok(());
         }
 
-        Engine::VM => {
-            let mut comp = compiler::Compiler::new();
-            if let Err(err) = comp.compile(program.unwrap()) {
-                println!("compiler error:\n\t{err}");
-                return Ok(());
-            };
-
-            let bytecode = comp.bytecode();
-            let mut machine = VM::new(&bytecode);
-            if let Err(err) = machine.run() {
-                println!("vm error:\n\t{err}");
-                return Ok(());
-            }
-
-            let stack_top = machine.stack_top();
-            if let Some(top) = stack_top {
-                println!("{top}");
-            }
+        match panda.eval_str(&input) {
+            Ok(Object::Nil) => {}
+            Ok(value) => println!("{}", panda.inspect(&value)),
+            Err(Error::Exit(code)) => exit(code),
+            Err(err) => println!("{err}"),
         }
     }
-
-    Ok(())
 }
@@ -0,0 +1,268 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{ast::Node, lexer::Lexer, parser::Parser};
+
+pub const EXTENSION: &str = "pnd";
+pub const PATH_ENV_VAR_NAME: &str = "PANDA_PATH";
+
+// The sources of the standard library, which is embedded in the binary and
+// imported by name. Their native functions are registered on each engine.
+const STD_MODULES: &[(&str, &str)] = &[
+    ("std/math", include_str!("std/math.pnd")),
+    ("std/string", include_str!("std/string.pnd")),
+    ("std/collections", include_str!("std/collections.pnd")),
+    ("std/io", include_str!("std/io.pnd")),
+    ("std/time", include_str!("std/time.pnd")),
+    ("std/test", include_str!("std/test.pnd")),
+];
+
+// Finds, parses and caches the files imported by a program. Both engines
+// share it, each caching the modules it produces under their canonical path.
+#[derive(Clone, Debug)]
+pub struct ModuleLoader<T> {
+    search_path: Vec<PathBuf>,
+    cache: HashMap<PathBuf, T>,
+    // The files being loaded, outermost first. The last one is the importer
+    // relative imports are resolved against.
+    loading: Vec<PathBuf>,
+    // The modules imported by name instead of from a file.
+    embedded: HashMap<String, &'static str>,
+}
+
+impl<T: Clone> Default for ModuleLoader<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T: Clone> ModuleLoader<T> {
+    pub fn new(search_path: Vec<PathBuf>) -> Self {
+        Self {
+            search_path,
+            cache: HashMap::new(),
+            loading: Vec::new(),
+            embedded: STD_MODULES
+                .iter()
+                .map(|&(name, source)| (name.to_string(), source))
+                .collect(),
+        }
+    }
+
+    /// Makes `name` importable without a file, for modules made of the native
+    /// functions an engine registers.
+    pub fn embed(&mut self, name: &str) {
+        self.embedded.entry(name.to_string()).or_default();
+    }
+
+    /// Searches the directories listed in `PANDA_PATH` after the importer's.
+    pub fn from_env() -> Self {
+        let search_path = std::env::var_os(PATH_ENV_VAR_NAME)
+            .map(|paths| std::env::split_paths(&paths).collect())
+            .unwrap_or_default();
+
+        Self::new(search_path)
+    }
+
+    /// Sets the file of the main program, which imports are relative to.
+    pub fn set_root(&mut self, file: &Path) -> Result<(), String> {
+        let file = file
+            .canonicalize()
+            .map_err(|err| format!("cannot open \"{}\": {err}", file.display()))?;
+
+        self.loading = Vec::from([file]);
+
+        Ok(())
+    }
+
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let mut file = PathBuf::from(path);
+
+        match file.extension() {
+            None => {
+                file.set_extension(EXTENSION);
+            }
+            Some(ext) if ext != EXTENSION => {
+                return Err(format!(
+                    "cannot import \"{path}\", panda files end with `.{EXTENSION}`"
+                ));
+            }
+            Some(_) => {}
+        }
+
+        let importer_dir = self.loading.last().map_or_else(
+            || std::env::current_dir().ok(),
+            |importer| importer.parent().map(Path::to_path_buf),
+        );
+
+        importer_dir
+            .iter()
+            .chain(&self.search_path)
+            .map(|dir| dir.join(&file))
+            .find(|candidate| candidate.is_file())
+            .and_then(|found| found.canonicalize().ok())
+            .ok_or_else(|| format!("cannot find module \"{path}\""))
+    }
+
+    /// Runs the module `path` refers to with `run` unless it's cached. `run`
+    /// gets the loader back to load the imports of the module itself.
+    pub fn load(
+        &mut self,
+        path: &str,
+        run: impl FnOnce(&mut Self, Node) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let source = self.embedded.get(path).copied();
+
+        let file = match source {
+            Some(_) => PathBuf::from(path),
+            None => self.resolve(path)?,
+        };
+
+        if let Some(module) = self.cache.get(&file) {
+            return Ok(module.clone());
+        }
+
+        if let Some(start) = self.loading.iter().position(|loading| *loading == file) {
+            let chain = self.loading[start..]
+                .iter()
+                .chain([&file])
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>();
+
+            return Err(format!("import cycle: {}", chain.join(" -> ")));
+        }
+
+        let input = match source {
+            Some(source) => source.to_string(),
+            None => std::fs::read_to_string(&file)
+                .map_err(|err| format!("cannot read \"{}\": {err}", file.display()))?,
+        };
+
+        let mut lexer = Lexer::new(&input);
+        let mut parser = Parser::new(&mut lexer);
+
+        let program = parser.parse_program();
+
+        let Some(program) = program.filter(|_| parser.errors.is_empty()) else {
+            return Err(format!(
+                "could not import \"{path}\" as it had errors:\n\t{}",
+                parser.errors.join("\n\t")
+            ));
+        };
+
+        self.loading.push(file.clone());
+        let module = run(self, program);
+        self.loading.pop();
+
+        let module = module?;
+        self.cache.insert(file, module.clone());
+
+        Ok(module)
+    }
+}
+
+/// The name a module is imported as without an alias, e.g. `b` for `"a/b.pnd"`.
+pub fn module_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn write_files(dir: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("panda-{dir}-{}", std::process::id()));
+
+        for (name, content) in files {
+            let file = root.join(name);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, content).unwrap();
+        }
+
+        root.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let root = write_files(
+            "resolve",
+            &[
+                ("main.pnd", ""),
+                ("lib/a.pnd", ""),
+                ("lib/b.pnd", ""),
+                ("path/c.pnd", ""),
+            ],
+        );
+
+        let mut loader = ModuleLoader::<()>::new(Vec::from([root.join("path")]));
+        loader.set_root(&root.join("main.pnd")).unwrap();
+
+        assert_eq!(Ok(root.join("lib/a.pnd")), loader.resolve("lib/a.pnd"));
+        assert_eq!(Ok(root.join("lib/a.pnd")), loader.resolve("lib/a"));
+        assert_eq!(Ok(root.join("path/c.pnd")), loader.resolve("c"));
+        assert_eq!(
+            Err("cannot find module \"d\"".to_string()),
+            loader.resolve("d")
+        );
+        assert_eq!(
+            Err("cannot import \"a.pd\", panda files end with `.pnd`".to_string()),
+            loader.resolve("a.pd")
+        );
+
+        // Nested imports are relative to the importing file.
+        let importer = loader.resolve("lib/a").unwrap();
+        loader.loading.push(importer);
+        assert_eq!(Ok(root.join("lib/b.pnd")), loader.resolve("b"));
+    }
+
+    #[test]
+    fn test_load_caches_modules() {
+        let root = write_files("cache", &[("main.pnd", ""), ("a.pnd", "1")]);
+
+        let mut loader = ModuleLoader::new(Vec::new());
+        loader.set_root(&root.join("main.pnd")).unwrap();
+
+        let runs = Cell::new(0);
+
+        for _ in 0..2 {
+            let module = loader.load("a", |_, _| {
+                runs.set(runs.get() + 1);
+                Ok(runs.get())
+            });
+
+            assert_eq!(Ok(1), module);
+        }
+
+        assert_eq!(1, runs.get());
+    }
+
+    #[test]
+    fn test_load_detects_cycles() {
+        let root = write_files("cycle", &[("main.pnd", ""), ("a.pnd", ""), ("b.pnd", "")]);
+
+        let mut loader = ModuleLoader::<()>::new(Vec::new());
+        loader.set_root(&root.join("main.pnd")).unwrap();
+
+        let err = loader.load("a", |loader, _| {
+            loader.load("b", |loader, _| loader.load("a", |_, _| Ok(())))
+        });
+
+        assert_eq!(
+            Err(format!(
+                "import cycle: {} -> {} -> {}",
+                root.join("a.pnd").display(),
+                root.join("b.pnd").display(),
+                root.join("a.pnd").display()
+            )),
+            err
+        );
+    }
+}
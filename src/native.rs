@@ -0,0 +1,360 @@
+use std::{fmt::Display, sync::Arc};
+
+use crate::{
+    error::ConversionError,
+    io::Io,
+    object::{
+        builtins::{eprintln, exists, read_file, read_line, write_file, STD_FUNCTIONS},
+        Array, Bool, BuiltinFunction, Char, Float, Int, NativeFunction, Object, Str,
+    },
+};
+
+// Rust functions the host registers on an engine, importable by the module's
+// name (`import { greet } from "host"`) or called as `host::greet()`.
+//
+//     let host = NativeModule::new("host")
+//         .function("greet", |args| Ok(format!("hi {}", args.get::<String>(0)?)));
+#[derive(Clone, Debug)]
+pub struct NativeModule {
+    name: String,
+    functions: Vec<NativeFunction>,
+}
+
+impl NativeModule {
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            functions: Vec::new(),
+        }
+    }
+
+    /// Adds `func`, whose errors stop the script like the errors of builtins.
+    #[must_use]
+    pub fn function<F, R>(mut self, name: &str, func: F) -> Self
+    where
+        F: Fn(&Args) -> Result<R, NativeError> + Send + Sync + 'static,
+        R: Into<Object>,
+    {
+        let fn_name = name.to_string();
+
+        self.functions.push(NativeFunction {
+            name: name.to_string(),
+            func: Arc::new(move |io, args| {
+                let args = Args {
+                    function: &fn_name,
+                    values: args,
+                    io,
+                };
+
+                func(&args).map_or_else(|NativeError(err)| Object::error(err), Into::into)
+            }),
+        });
+
+        self
+    }
+
+    #[must_use]
+    pub fn builtin(mut self, name: &str, func: BuiltinFunction) -> Self {
+        self.functions.push(NativeFunction {
+            name: name.to_string(),
+            func: Arc::new(move |_, args| func(&Object::Nil, args)),
+        });
+
+        self
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn functions(&self) -> &[NativeFunction] {
+        &self.functions
+    }
+}
+
+/// The native functions of the standard library, which both engines register.
+pub fn std_modules() -> Vec<NativeModule> {
+    STD_FUNCTIONS
+        .iter()
+        .map(|(name, functions)| {
+            let module = functions
+                .iter()
+                .fold(NativeModule::new(*name), |module, &(name, func)| {
+                    module.builtin(name, func)
+                });
+
+            if *name == "std/io" {
+                module
+                    .function("readLine", read_line)
+                    .function("eprintln", eprintln)
+                    .function("readFile", read_file)
+                    .function("writeFile", write_file)
+                    .function("exists", exists)
+            } else {
+                module
+            }
+        })
+        .collect()
+}
+
+/// The error of a native function. Anything displayable converts into it, so
+/// `?` works on the errors of `Args` as well as I/O or parsing errors.
+#[derive(Debug)]
+pub struct NativeError(pub String);
+
+impl<E: Display> From<E> for NativeError {
+    fn from(err: E) -> Self {
+        Self(err.to_string())
+    }
+}
+
+pub struct Args<'a> {
+    function: &'a str,
+    values: &'a [Object],
+    io: &'a Io,
+}
+
+impl<'a> Args<'a> {
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    #[must_use]
+    pub const fn values(&self) -> &'a [Object] {
+        self.values
+    }
+
+    /// The streams of the engine calling the function.
+    #[must_use]
+    pub const fn io(&self) -> &'a Io {
+        self.io
+    }
+
+    /// # Errors
+    ///
+    /// Fails unless the function got `want` arguments.
+    pub fn expect(&self, want: usize) -> Result<(), String> {
+        if self.values.len() == want {
+            Ok(())
+        } else {
+            Err(format!(
+                "wrong number of arguments to `{}`. got: {}, want: {want}",
+                self.function,
+                self.values.len()
+            ))
+        }
+    }
+
+    /// Converts the argument at `idx`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the argument is missing or has another type.
+    pub fn get<T>(&self, idx: usize) -> Result<T, String>
+    where
+        T: TryFrom<&'a Object, Error = ConversionError>,
+    {
+        let value = self.values.get(idx).ok_or_else(|| {
+            format!(
+                "missing argument {} to `{}`. got: {}",
+                idx + 1,
+                self.function,
+                self.values.len()
+            )
+        })?;
+
+        self.convert(idx, value)
+    }
+
+    /// # Errors
+    ///
+    /// Fails if the argument has another type.
+    pub fn optional<T>(&self, idx: usize) -> Result<Option<T>, String>
+    where
+        T: TryFrom<&'a Object, Error = ConversionError>,
+    {
+        self.values
+            .get(idx)
+            .map(|value| self.convert(idx, value))
+            .transpose()
+    }
+
+    fn convert<T>(&self, idx: usize, value: &'a Object) -> Result<T, String>
+    where
+        T: TryFrom<&'a Object, Error = ConversionError>,
+    {
+        T::try_from(value)
+            .map_err(|err| format!("argument {} to `{}`: {err}", idx + 1, self.function))
+    }
+}
+
+macro_rules! object_conversions {
+    ($($ty:ty => $variant:ident, $kind:literal;)*) => {
+        $(
+            impl TryFrom<&Object> for $ty {
+                type Error = ConversionError;
+
+                fn try_from(obj: &Object) -> Result<Self, ConversionError> {
+                    match obj {
+                        Object::$variant($variant { value }) => Ok(value.clone()),
+                        _ => Err(ConversionError {
+                            expected: $kind,
+                            found: obj.kind(),
+                        }),
+                    }
+                }
+            }
+
+            impl From<$ty> for Object {
+                fn from(value: $ty) -> Self {
+                    Self::$variant($variant { value })
+                }
+            }
+        )*
+    };
+}
+
+object_conversions! {
+    isize => Int, "INT";
+    bool => Bool, "BOOLEAN";
+    String => Str, "STR";
+    char => Char, "CHAR";
+}
+
+impl TryFrom<&Object> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(obj: &Object) -> Result<Self, ConversionError> {
+        match obj {
+            Object::Float(Float { value }) => Ok(*value),
+            Object::Int(Int { value }) => Ok(crate::object::int_to_float(*value)),
+            _ => Err(ConversionError {
+                expected: "FLOAT",
+                found: obj.kind(),
+            }),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Object> for &'a str {
+    type Error = ConversionError;
+
+    fn try_from(obj: &'a Object) -> Result<Self, ConversionError> {
+        match obj {
+            Object::Str(Str { value }) => Ok(value),
+            _ => Err(ConversionError {
+                expected: "STR",
+                found: obj.kind(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<&Object> for Vec<Object> {
+    type Error = ConversionError;
+
+    fn try_from(obj: &Object) -> Result<Self, ConversionError> {
+        match obj {
+            Object::Array(Array { elements }) => Ok(elements.clone()),
+            _ => Err(ConversionError {
+                expected: "ARRAY",
+                found: obj.kind(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<&Self> for Object {
+    type Error = ConversionError;
+
+    fn try_from(obj: &Self) -> Result<Self, ConversionError> {
+        Ok(obj.clone())
+    }
+}
+
+impl From<i32> for Object {
+    fn from(value: i32) -> Self {
+        Self::int(value as isize)
+    }
+}
+
+impl From<f64> for Object {
+    fn from(value: f64) -> Self {
+        Self::float(value)
+    }
+}
+
+impl From<&str> for Object {
+    fn from(value: &str) -> Self {
+        Self::str(value.to_string())
+    }
+}
+
+impl From<Vec<Self>> for Object {
+    fn from(elements: Vec<Self>) -> Self {
+        Self::Array(Array { elements })
+    }
+}
+
+impl From<()> for Object {
+    fn from((): ()) -> Self {
+        Self::Nil
+    }
+}
+
+impl<T: Into<Self>> From<Option<T>> for Object {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Nil, Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_functions() {
+        let module = NativeModule::new("host")
+            .function("add", |args| {
+                args.expect(2)?;
+                Ok(args.get::<isize>(0)? + args.get::<isize>(1)?)
+            })
+            .function("greet", |args| {
+                let name = args.optional::<&str>(0)?.unwrap_or("you");
+                Ok(format!("hi {name}"))
+            })
+            .function("parse", |args| Ok(args.get::<&str>(0)?.parse::<isize>()?));
+
+        let [add, greet, parse] = module.functions() else {
+            panic!("expected 3 functions");
+        };
+        let io = Io::default();
+
+        assert_eq!(
+            Object::int(3),
+            add.call(&io, &[Object::int(1), Object::int(2)])
+        );
+        assert_eq!(
+            Object::error("argument 2 to `add`: expected INT, got STR".to_string()),
+            add.call(&io, &[Object::int(1), Object::from("2")])
+        );
+        assert_eq!(
+            Object::error("wrong number of arguments to `add`. got: 1, want: 2".to_string()),
+            add.call(&io, &[Object::int(1)])
+        );
+        assert_eq!(Object::from("hi you"), greet.call(&io, &[]));
+        assert_eq!(
+            Object::error("invalid digit found in string".to_string()),
+            parse.call(&io, &[Object::from("x")])
+        );
+    }
+}
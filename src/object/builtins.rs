@@ -1,12 +1,20 @@
-use std::{io::Write, process::exit};
+use crate::{ast::InterfaceDecl, io::Io};
 
 use super::{
-    allowed_in_array, intersperse, AHasher, Array, Bool, BuiltinFunction, Char, Class, Dict,
-    DictPair, Float, Hashable, Hasher, Int, Object, StdHash, Str, Type,
+    allowed_in_array, float_to_int, format, grapheme_count, grapheme_object, graphemes,
+    hash_method_name, int_to_float, intersperse,
+    iter::{Iter, IterState},
+    AHasher, Array, Bool, BuiltinFunction, Char, Class, ClassMember, Dict, DictPair, Float,
+    Hashable, Hasher, Int, Object, StdHash, Str, Type,
 };
 
+mod stdlib;
+
+pub use stdlib::{eprintln, exists, read_file, read_line, write_file, STD_FUNCTIONS};
+
 pub const BUILTINS: &[(&str, BuiltinFunction)] = &[
-    ("exit", |_, _| exit(0)),
+    // The engines run the builtins using the console with their own streams.
+    ("exit", |_, args| console("exit", args)),
     ("type", |_, args| {
         if args.len() != 1 {
             return Object::error(format!(
@@ -17,7 +25,10 @@ pub const BUILTINS: &[(&str, BuiltinFunction)] = &[
 
         get_type(&args[0])
     }),
-    ("input", |_, args| {
+    ("input", |_, args| console("input", args)),
+    ("print", |_, args| console("print", args)),
+    ("println", |_, args| console("println", args)),
+    ("iter", |_, args| {
         if args.len() != 1 {
             return Object::error(format!(
                 "wrong number of arguments. got: {}, want: 1",
@@ -25,56 +36,249 @@ pub const BUILTINS: &[(&str, BuiltinFunction)] = &[
             ));
         }
 
-        if let Object::Str(Str { value }) = &args[0] {
-            print!("{value}");
-            std::io::stdout().flush().unwrap();
+        Iter::from_object(args[0].clone()).map_or_else(
+            || Object::error(format!("{} is not iterable", args[0].kind())),
+            Object::Iter,
+        )
+    }),
+    ("format", |_, args| {
+        let Some((template, args)) = args.split_first() else {
+            return Object::error(
+                "wrong number of arguments. got: 0, want: at least 1".to_string(),
+            );
+        };
 
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input).unwrap();
+        let Object::Str(Str { value }) = template else {
+            return Object::error(format!(
+                "expected STR as format template, got {}",
+                template.kind()
+            ));
+        };
 
-            Object::Str(Str {
-                value: input.trim().to_string(),
-            })
-        } else {
-            Object::error(format!(
-                "cannot use {} as prompt in `input`. expected STR",
-                args[0].kind()
-            ))
+        format::format(value, args).map_or_else(Object::error, Object::str)
+    }),
+    ("int", |_, args| match args {
+        [Object::Str(Str { value }), radix @ ..] if radix.len() <= 1 => {
+            let radix = match get_radix(radix) {
+                Ok(radix) => radix,
+                Err(err) => return Object::error(err),
+            };
+
+            isize::from_str_radix(&value.trim().replace('_', ""), radix)
+                .map_or_else(|_| parse_error(value, "INT", radix), Object::int)
         }
+        [_, _] => Object::error(format!(
+            "int() only accepts a radix when converting STR, got {}",
+            args[0].kind()
+        )),
+        [obj] => to_int(obj),
+        _ => Object::error(format!(
+            "wrong number of arguments. got: {}, want: 1 or 2",
+            args.len()
+        )),
     }),
-    ("print", |_, args| {
-        let mut str_args = Vec::new();
-        for arg in args {
-            if let Some(s) = escape_string(&arg.to_string()) {
-                str_args.push(s);
-            } else {
-                return Object::error(
-                    "cannot print as representation contains invalid escapes".to_string(),
-                );
+    ("parseInt", |_, args| {
+        let (value, radix) = match args {
+            [Object::Str(Str { value }), radix @ ..] if radix.len() <= 1 => (value, radix),
+            [obj] | [obj, _] => return Object::error(format!("expected STR, got {}", obj.kind())),
+            _ => {
+                return Object::error(format!(
+                    "wrong number of arguments. got: {}, want: 1 or 2",
+                    args.len()
+                ))
             }
-        }
+        };
 
-        print!("{}", str_args.join(" "));
-        Object::Nil
+        let radix = match get_radix(radix) {
+            Ok(radix) => radix,
+            Err(err) => return Object::error(err),
+        };
+
+        parse_int(value, radix, args.len() == 1)
+            .map_or_else(|| parse_error(value, "INT", radix), Object::int)
     }),
-    ("println", |_, args| {
-        let mut str_args = Vec::new();
-        for arg in args {
-            if let Some(s) = escape_string(&arg.to_string()) {
-                str_args.push(s);
-            } else {
-                return Object::error(
-                    "cannot print as representation contains invalid escapes".to_string(),
-                );
-            }
+    ("float", |_, args| {
+        if args.len() != 1 {
+            return Object::error(format!(
+                "wrong number of arguments. got: {}, want: 1",
+                args.len()
+            ));
         }
 
-        println!("{}", str_args.join(" "));
-        Object::Nil
+        match &args[0] {
+            Object::Float(_) => args[0].clone(),
+            Object::Int(Int { value }) => Object::float(int_to_float(*value)),
+            Object::Bool(Bool { value }) => Object::float(f64::from(u8::from(*value))),
+            Object::Str(Str { value }) => value
+                .trim()
+                .replace('_', "")
+                .parse()
+                .map_or_else(|_| parse_error(value, "FLOAT", 10), Object::float),
+            obj => Object::error(format!("cannot convert {} to FLOAT", obj.kind())),
+        }
+    }),
+    ("str", |_, args| match args {
+        [Object::Int(Int { value }), radix] => match get_radix(std::slice::from_ref(radix)) {
+            Ok(radix) => Object::str(to_radix(*value, radix)),
+            Err(err) => Object::error(err),
+        },
+        [obj, _] => Object::error(format!(
+            "str() only accepts a radix when converting INT, got {}",
+            obj.kind()
+        )),
+        [obj] => Object::str(obj.to_string()),
+        _ => Object::error(format!(
+            "wrong number of arguments. got: {}, want: 1 or 2",
+            args.len()
+        )),
+    }),
+    ("implements", |_, args| match args {
+        [obj, Object::Interface(decl)] => Object::bool(implements(obj, decl)),
+        [_, other] => Object::error(format!(
+            "expected INTERFACE as second argument to `implements`, got {}",
+            other.kind()
+        )),
+        _ => Object::error(format!(
+            "wrong number of arguments. got: {}, want: 2",
+            args.len()
+        )),
     }),
 ];
 
+// Duck-typed: `obj` implements an interface when it has every method of it
+// (defaults included) with the same number of parameters.
+fn console(name: &str, args: &[Object]) -> Object {
+    Io::default()
+        .call_builtin(name, args)
+        .unwrap_or(Object::Nil)
+}
+
+fn implements(obj: &Object, decl: &InterfaceDecl) -> bool {
+    if !matches!(obj, Object::Class(_) | Object::EnumValue(_)) {
+        return false;
+    }
+
+    decl.methods.iter().all(|method| {
+        let key = hash_method_name(&method.name);
+        let found = match obj {
+            Object::Class(Class { members, .. }) => members.get(&key),
+            Object::EnumValue(value) => value.decl.methods.get(&key),
+            _ => None,
+        };
+
+        let arity = match found {
+            Some(ClassMember {
+                obj: Object::EvaluatedFunction(func),
+                ..
+            }) => func.parameters.len(),
+            // Compiled methods take `self` as their first parameter.
+            Some(ClassMember {
+                obj: Object::Closure(closure),
+                ..
+            }) => closure.func.num_parameters.saturating_sub(1),
+            _ => return false,
+        };
+
+        arity == method.parameters.len()
+    })
+}
+
+fn to_int(obj: &Object) -> Object {
+    match obj {
+        Object::Int(_) => obj.clone(),
+        Object::Bool(Bool { value }) => Object::int(isize::from(*value)),
+        Object::Float(Float { value }) => float_to_int(*value).map_or_else(
+            || Object::error(format!("cannot convert {value:?} to INT")),
+            Object::int,
+        ),
+        _ => Object::error(format!("cannot convert {} to INT", obj.kind())),
+    }
+}
+
+fn get_radix(args: &[Object]) -> Result<u32, String> {
+    match args {
+        [] => Ok(10),
+        [Object::Int(Int { value })] => u32::try_from(*value)
+            .ok()
+            .filter(|radix| (2..=36).contains(radix))
+            .ok_or_else(|| format!("radix must be between 2 and 36, got {value}")),
+        [obj, ..] => Err(format!("expected INT as radix, got {}", obj.kind())),
+    }
+}
+
+fn parse_error(value: &str, kind: &str, radix: u32) -> Object {
+    if radix == 10 {
+        Object::error(format!("cannot parse {value:?} as {kind}"))
+    } else {
+        Object::error(format!(
+            "cannot parse {value:?} as {kind} with radix {radix}"
+        ))
+    }
+}
+
+/// Parses the longest prefix of `value` that is an integer in `radix`, ignoring
+/// leading whitespace. `0x`, `0o` and `0b` prefixes are detected unless a radix
+/// was given.
+fn parse_int(value: &str, mut radix: u32, detect_radix: bool) -> Option<isize> {
+    let value = value.trim_start();
+    let negative = value.starts_with('-');
+    let mut digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+
+    if detect_radix {
+        for (prefix, prefix_radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+            if let Some(rest) = digits.strip_prefix(prefix) {
+                (digits, radix) = (rest, prefix_radix);
+            }
+        }
+    }
+
+    let end = digits
+        .find(|ch: char| !ch.is_digit(radix))
+        .unwrap_or(digits.len());
+
+    let sign = if negative { "-" } else { "" };
+
+    isize::from_str_radix(&format!("{sign}{}", &digits[..end]), radix).ok()
+}
+
+fn to_radix(value: isize, radix: u32) -> String {
+    let mut abs = value.unsigned_abs();
+    let mut digits = Vec::new();
+
+    loop {
+        let digit = u32::try_from(abs % radix as usize).unwrap();
+        digits.push(char::from_digit(digit, radix).unwrap());
+        abs /= radix as usize;
+
+        if abs == 0 {
+            break;
+        }
+    }
+
+    if value.is_negative() {
+        digits.push('-');
+    }
+
+    digits.iter().rev().collect()
+}
+
 fn get_type(obj: &Object) -> Object {
+    let name = match obj {
+        Object::Enum(decl) => Some(&decl.name),
+        Object::EnumValue(value) => Some(&value.decl.name),
+        _ => None,
+    };
+
+    if let Some(name) = name {
+        let mut hasher = AHasher::default();
+        name.hash(&mut hasher);
+
+        return Object::Type(Type {
+            id: usize::try_from(hasher.finish()).unwrap(),
+            lit: name.clone(),
+        });
+    }
+
     match obj {
         Object::Class(Class { name, .. }) => {
             let mut hasher = AHasher::default();
@@ -91,50 +295,6 @@ fn get_type(obj: &Object) -> Object {
     }
 }
 
-fn escape_string(s: &str) -> Option<String> {
-    let mut chars = s.chars().peekable();
-    let mut out = String::new();
-
-    while let Some(c) = chars.next() {
-        if c == '\\' {
-            match chars.peek() {
-                Some(c) => match c {
-                    'n' => {
-                        chars.next();
-                        out.push('\n');
-                    }
-                    'r' => {
-                        chars.next();
-                        out.push('\r');
-                    }
-                    't' => {
-                        chars.next();
-                        out.push('\t');
-                    }
-                    '0' => {
-                        chars.next();
-                        out.push('\0');
-                    }
-                    '\\' => {
-                        chars.next();
-                        out.push('\\');
-                    }
-                    '"' => {
-                        chars.next();
-                        out.push('"');
-                    }
-                    _ => return None,
-                },
-                None => return None,
-            }
-        } else {
-            out.push(c);
-        }
-    }
-
-    Some(out)
-}
-
 pub fn get_builtin_by_name(name: &str) -> Option<BuiltinFunction> {
     for &(func_name, func) in BUILTINS {
         if name == func_name {
@@ -235,6 +395,69 @@ pub const BUILTIN_METHODS: &[&[(&str, BuiltinFunction)]] = &[
                 Object::error(format!("expected 0 parameters. got: {}", params.len()))
             }
         }),
+        ("round", |caller, params| {
+            let Object::Float(Float { value }) = caller else {
+                return Object::error(format!("expected FLOAT, got {}", caller.kind()));
+            };
+
+            match params {
+                [] => Object::float(value.round()),
+                [Object::Int(Int { value: digits })] => {
+                    let Ok(digits) = i32::try_from(*digits) else {
+                        return Object::error(format!("cannot round to {digits} digits"));
+                    };
+                    let scale = 10_f64.powi(digits);
+                    let scaled = value * scale;
+
+                    // Past the precision of a FLOAT it's already rounded, and
+                    // the scale overflows.
+                    if !scaled.is_finite() || scaled.abs() >= 2_f64.powi(52) {
+                        return Object::float(*value);
+                    }
+
+                    if scale == 0.0 {
+                        return Object::float(0.0 * value);
+                    }
+
+                    Object::float(scaled.round() / scale)
+                }
+                [obj] => Object::error(format!("expected INT, got {}", obj.kind())),
+                _ => Object::error(format!("expected 0 or 1 parameters. got: {}", params.len())),
+            }
+        }),
+        ("floor", |caller, params| {
+            let Object::Float(Float { value }) = caller else {
+                return Object::error(format!("expected FLOAT, got {}", caller.kind()));
+            };
+
+            if params.is_empty() {
+                Object::float(value.floor())
+            } else {
+                Object::error(format!("expected 0 parameters. got: {}", params.len()))
+            }
+        }),
+        ("ceil", |caller, params| {
+            let Object::Float(Float { value }) = caller else {
+                return Object::error(format!("expected FLOAT, got {}", caller.kind()));
+            };
+
+            if params.is_empty() {
+                Object::float(value.ceil())
+            } else {
+                Object::error(format!("expected 0 parameters. got: {}", params.len()))
+            }
+        }),
+        ("trunc", |caller, params| {
+            let Object::Float(Float { value }) = caller else {
+                return Object::error(format!("expected FLOAT, got {}", caller.kind()));
+            };
+
+            if params.is_empty() {
+                Object::float(value.trunc())
+            } else {
+                Object::error(format!("expected 0 parameters. got: {}", params.len()))
+            }
+        }),
     ],
     // Str
     &[
@@ -243,7 +466,7 @@ pub const BUILTIN_METHODS: &[&[(&str, BuiltinFunction)]] = &[
                 return Object::error(format!("expected STR, got {}", caller.kind()));
             };
             if params.is_empty() {
-                Object::int(value.len() as isize)
+                Object::int(grapheme_count(value).cast_signed())
             } else {
                 Object::error(format!("expected 0 parameters. got: {}", params.len()))
             }
@@ -341,7 +564,7 @@ pub const BUILTIN_METHODS: &[&[(&str, BuiltinFunction)]] = &[
                 return Object::error(format!("expected STR, got {}", caller.kind()));
             };
             if params.is_empty() {
-                Object::array(value.chars().map(Object::char).collect())
+                Object::array(graphemes(value).into_iter().map(grapheme_object).collect())
             } else {
                 Object::error(format!("expected 0 parameters. got: {}", params.len()))
             }
@@ -370,6 +593,21 @@ pub const BUILTIN_METHODS: &[&[(&str, BuiltinFunction)]] = &[
                 Object::error(format!("expected 0 parameters. got: {}", params.len()))
             }
         }),
+        ("graphemes", |caller, params| {
+            let Object::Str(Str { value }) = caller else {
+                return Object::error(format!("expected STR, got {}", caller.kind()));
+            };
+            if params.is_empty() {
+                Object::array(
+                    graphemes(value)
+                        .into_iter()
+                        .map(|grapheme| Object::str(grapheme.to_string()))
+                        .collect(),
+                )
+            } else {
+                Object::error(format!("expected 0 parameters. got: {}", params.len()))
+            }
+        }),
     ],
     // Char
     &[
@@ -910,4 +1148,97 @@ pub const BUILTIN_METHODS: &[&[(&str, BuiltinFunction)]] = &[
             }
         }),
     ],
+    // Iter
+    &[
+        ("next", |_, _| {
+            Object::error("ITER.next must be called on the iterator directly".to_string())
+        }),
+        ("collect", |_, _| {
+            Object::error("ITER.collect must be called on the iterator directly".to_string())
+        }),
+        ("take", |caller, params| {
+            let Object::Iter(iter) = caller else {
+                return Object::error(format!("expected ITER, got {}", caller.kind()));
+            };
+            if params.len() == 1 {
+                let Object::Int(Int { value }) = params[0] else {
+                    return Object::error(format!("expected INT, got {}", params[0].kind()));
+                };
+                let Ok(remaining) = usize::try_from(value) else {
+                    return Object::error(format!("cannot take {value} elements"));
+                };
+
+                Object::Iter(Iter::new(IterState::Take {
+                    inner: iter.clone(),
+                    remaining,
+                }))
+            } else {
+                Object::error(format!("expected 1 parameters. got: {}", params.len()))
+            }
+        }),
+        ("skip", |caller, params| {
+            let Object::Iter(iter) = caller else {
+                return Object::error(format!("expected ITER, got {}", caller.kind()));
+            };
+            if params.len() == 1 {
+                let Object::Int(Int { value }) = params[0] else {
+                    return Object::error(format!("expected INT, got {}", params[0].kind()));
+                };
+                let Ok(count) = usize::try_from(value) else {
+                    return Object::error(format!("cannot skip {value} elements"));
+                };
+
+                Object::Iter(Iter::new(IterState::Skip {
+                    inner: iter.clone(),
+                    count,
+                }))
+            } else {
+                Object::error(format!("expected 1 parameters. got: {}", params.len()))
+            }
+        }),
+        ("zip", |caller, params| {
+            let Object::Iter(iter) = caller else {
+                return Object::error(format!("expected ITER, got {}", caller.kind()));
+            };
+            if params.len() == 1 {
+                let Some(other) = Iter::from_object(params[0].clone()) else {
+                    return Object::error(format!("{} is not iterable", params[0].kind()));
+                };
+
+                Object::Iter(Iter::new(IterState::Zip(iter.clone(), other)))
+            } else {
+                Object::error(format!("expected 1 parameters. got: {}", params.len()))
+            }
+        }),
+        ("enumerate", |caller, params| {
+            let Object::Iter(iter) = caller else {
+                return Object::error(format!("expected ITER, got {}", caller.kind()));
+            };
+            if params.is_empty() {
+                Object::Iter(Iter::new(IterState::Enumerate {
+                    inner: iter.clone(),
+                    index: 0,
+                }))
+            } else {
+                Object::error(format!("expected 0 parameters. got: {}", params.len()))
+            }
+        }),
+        ("chain", |caller, params| {
+            let Object::Iter(iter) = caller else {
+                return Object::error(format!("expected ITER, got {}", caller.kind()));
+            };
+            if params.len() == 1 {
+                let Some(other) = Iter::from_object(params[0].clone()) else {
+                    return Object::error(format!("{} is not iterable", params[0].kind()));
+                };
+
+                Object::Iter(Iter::new(IterState::Chain {
+                    iters: Vec::from([iter.clone(), other]),
+                    current: 0,
+                }))
+            } else {
+                Object::error(format!("expected 1 parameters. got: {}", params.len()))
+            }
+        }),
+    ],
 ];
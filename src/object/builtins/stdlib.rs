@@ -0,0 +1,600 @@
+use std::{
+    fmt::Write as _,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::super::{
+    grapheme_count, int_to_float, Array, BuiltinFunction, Dict, DictPair, Float, Hashable, Int,
+    Object, Str,
+};
+use crate::{
+    limits::{reserve, sleep},
+    native::{Args, NativeError},
+};
+
+/// The native functions of the embedded standard library modules. They're
+/// exported by their module along with the definitions in its source.
+pub const STD_FUNCTIONS: &[(&str, &[(&str, BuiltinFunction)])] = &[
+    (
+        "std/math",
+        &[
+            ("sqrt", |_, args| float_fn(args, f64::sqrt)),
+            ("cbrt", |_, args| float_fn(args, f64::cbrt)),
+            ("sin", |_, args| float_fn(args, f64::sin)),
+            ("cos", |_, args| float_fn(args, f64::cos)),
+            ("tan", |_, args| float_fn(args, f64::tan)),
+            ("asin", |_, args| float_fn(args, f64::asin)),
+            ("acos", |_, args| float_fn(args, f64::acos)),
+            ("atan", |_, args| float_fn(args, f64::atan)),
+            ("exp", |_, args| float_fn(args, f64::exp)),
+            ("ln", |_, args| float_fn(args, f64::ln)),
+            ("log2", |_, args| float_fn(args, f64::log2)),
+            ("log10", |_, args| float_fn(args, f64::log10)),
+            ("atan2", |_, args| match float_args::<2>(args) {
+                Ok([y, x]) => Object::float(y.atan2(x)),
+                Err(err) => Object::error(err),
+            }),
+            ("hypot", |_, args| match float_args::<2>(args) {
+                Ok([x, y]) => Object::float(x.hypot(y)),
+                Err(err) => Object::error(err),
+            }),
+        ],
+    ),
+    (
+        "std/string",
+        &[
+            ("upper", |_, args| str_fn(args, str::to_uppercase)),
+            ("lower", |_, args| str_fn(args, str::to_lowercase)),
+            ("trim", |_, args| str_fn(args, |s| s.trim().to_string())),
+            ("trimStart", |_, args| {
+                str_fn(args, |s| s.trim_start().to_string())
+            }),
+            ("trimEnd", |_, args| {
+                str_fn(args, |s| s.trim_end().to_string())
+            }),
+            ("startsWith", |_, args| match args {
+                [Object::Str(Str { value }), Object::Str(Str { value: prefix })] => {
+                    Object::bool(value.starts_with(prefix.as_str()))
+                }
+                _ => expected_strs(args, 2),
+            }),
+            ("endsWith", |_, args| match args {
+                [Object::Str(Str { value }), Object::Str(Str { value: suffix })] => {
+                    Object::bool(value.ends_with(suffix.as_str()))
+                }
+                _ => expected_strs(args, 2),
+            }),
+            ("find", |_, args| match args {
+                [Object::Str(Str { value }), Object::Str(Str { value: needle })] => {
+                    // Indices count grapheme clusters, like indexing a STR does.
+                    value.find(needle.as_str()).map_or(Object::int(-1), |pos| {
+                        Object::int(grapheme_count(&value[..pos]).cast_signed())
+                    })
+                }
+                _ => expected_strs(args, 2),
+            }),
+            ("replace", |_, args| match args {
+                [Object::Str(Str { value }), Object::Str(Str { value: from }), Object::Str(Str { value: to })] => {
+                    Object::str(value.replace(from.as_str(), to))
+                }
+                _ => expected_strs(args, 3),
+            }),
+            ("repeat", |_, args| match args {
+                [Object::Str(Str { value }), Object::Int(Int { value: n })] => {
+                    let Some((times, len)) = usize::try_from(*n)
+                        .ok()
+                        .and_then(|times| Some((times, value.len().checked_mul(times)?)))
+                    else {
+                        return Object::error(format!("cannot repeat a STR {n} times"));
+                    };
+
+                    match reserve(len) {
+                        Ok(()) => Object::str(value.repeat(times)),
+                        Err(err) => Object::error(err),
+                    }
+                }
+                [_, _] => Object::error(format!(
+                    "expected STR and INT, got {} and {}",
+                    args[0].kind(),
+                    args[1].kind()
+                )),
+                _ => wrong_arguments(args, 2),
+            }),
+        ],
+    ),
+    (
+        "std/collections",
+        &[
+            ("sort", |_, args| match args {
+                [Object::Array(Array { elements })] => {
+                    let mut sorted = elements.clone();
+                    let mut failed = None;
+
+                    sorted.sort_by(|a, b| {
+                        compare(a, b).unwrap_or_else(|| {
+                            failed.get_or_insert_with(|| (a.kind(), b.kind()));
+                            std::cmp::Ordering::Equal
+                        })
+                    });
+
+                    match failed {
+                        Some((a, b)) => Object::error(format!("cannot compare {a} and {b}")),
+                        None => Object::Array(Array { elements: sorted }),
+                    }
+                }
+                [obj] => Object::error(format!("expected ARRAY, got {}", obj.kind())),
+                _ => wrong_arguments(args, 1),
+            }),
+            ("reverse", |_, args| match args {
+                [Object::Array(Array { elements })] => Object::Array(Array {
+                    elements: elements.iter().rev().cloned().collect(),
+                }),
+                [obj] => Object::error(format!("expected ARRAY, got {}", obj.kind())),
+                _ => wrong_arguments(args, 1),
+            }),
+        ],
+    ),
+    ("std/io", &[]),
+    (
+        "std/time",
+        &[
+            ("now", |_, args| {
+                if !args.is_empty() {
+                    return wrong_arguments(args, 0);
+                }
+
+                let since_epoch = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+
+                Object::int(isize::try_from(since_epoch.as_millis()).unwrap_or(isize::MAX))
+            }),
+            ("sleep", |_, args| match args {
+                [Object::Int(Int { value })] => u64::try_from(*value).map_or_else(
+                    |_| Object::error(format!("cannot sleep for {value} milliseconds")),
+                    |ms| match sleep(Duration::from_millis(ms)) {
+                        Ok(()) => Object::Nil,
+                        Err(err) => Object::error(err),
+                    },
+                ),
+                [obj] => Object::error(format!("expected INT, got {}", obj.kind())),
+                _ => wrong_arguments(args, 1),
+            }),
+        ],
+    ),
+    (
+        "std/json",
+        &[
+            ("parse", |_, args| match args {
+                [Object::Str(Str { value })] => JsonParser::new(value)
+                    .parse()
+                    .unwrap_or_else(|err| Object::error(format!("invalid JSON: {err}"))),
+                _ => expected_strs(args, 1),
+            }),
+            ("stringify", |_, args| match args {
+                [value] => {
+                    let mut out = String::new();
+
+                    to_json(value, &mut out).map_or_else(Object::error, |()| Object::str(out))
+                }
+                _ => wrong_arguments(args, 1),
+            }),
+        ],
+    ),
+    (
+        "std/test",
+        &[("fail", |_, args| match args {
+            [Object::Str(Str { value })] => Object::error(value.clone()),
+            _ => expected_strs(args, 1),
+        })],
+    ),
+];
+
+// The functions of `std/io` using the streams and files of the engine, which
+// are added to the module next to `STD_FUNCTIONS`.
+pub fn read_line(args: &Args) -> Result<Object, NativeError> {
+    args.expect(0)?;
+
+    Ok(args.io().read_line()?.into())
+}
+
+pub fn eprintln(args: &Args) -> Result<Object, NativeError> {
+    Ok(args.io().write(true, args.values(), true)?)
+}
+
+pub fn read_file(args: &Args) -> Result<Object, NativeError> {
+    args.expect(1)?;
+
+    Ok(args.io().read_file(args.get(0)?)?.into())
+}
+
+pub fn write_file(args: &Args) -> Result<Object, NativeError> {
+    args.expect(2)?;
+    args.io().write_file(args.get(0)?, args.get(1)?)?;
+
+    Ok(Object::Nil)
+}
+
+pub fn exists(args: &Args) -> Result<Object, NativeError> {
+    args.expect(1)?;
+
+    Ok(args.io().file_exists(args.get(0)?)?.into())
+}
+
+fn wrong_arguments(args: &[Object], want: usize) -> Object {
+    Object::error(format!(
+        "wrong number of arguments. got: {}, want: {want}",
+        args.len()
+    ))
+}
+
+fn expected_strs(args: &[Object], want: usize) -> Object {
+    if args.len() != want {
+        return wrong_arguments(args, want);
+    }
+
+    let kinds = args.iter().map(Object::kind).collect::<Vec<_>>();
+
+    Object::error(format!("expected STR arguments, got {}", kinds.join(", ")))
+}
+
+fn float_args<const N: usize>(args: &[Object]) -> Result<[f64; N], String> {
+    let Ok(args) = <&[Object; N]>::try_from(args) else {
+        return Err(format!(
+            "wrong number of arguments. got: {}, want: {N}",
+            args.len()
+        ));
+    };
+
+    let mut values = [0.0; N];
+
+    for (value, arg) in values.iter_mut().zip(args) {
+        *value = match arg {
+            Object::Int(Int { value }) => int_to_float(*value),
+            Object::Float(Float { value }) => *value,
+            _ => return Err(format!("expected INT or FLOAT, got {}", arg.kind())),
+        };
+    }
+
+    Ok(values)
+}
+
+fn float_fn(args: &[Object], f: fn(f64) -> f64) -> Object {
+    float_args::<1>(args).map_or_else(Object::error, |[x]| Object::float(f(x)))
+}
+
+fn str_fn(args: &[Object], f: fn(&str) -> String) -> Object {
+    match args {
+        [Object::Str(Str { value })] => Object::str(f(value)),
+        _ => expected_strs(args, 1),
+    }
+}
+
+fn compare(a: &Object, b: &Object) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Object::Int(Int { value: a }), Object::Int(Int { value: b })) => Some(a.cmp(b)),
+        (Object::Str(Str { value: a }), Object::Str(Str { value: b })) => Some(a.cmp(b)),
+        (Object::Int(_) | Object::Float(_), Object::Int(_) | Object::Float(_)) => {
+            let [a, b] = float_args::<2>(&[a.clone(), b.clone()]).ok()?;
+            a.partial_cmp(&b)
+        }
+        _ => None,
+    }
+}
+
+fn to_json(value: &Object, out: &mut String) -> Result<(), String> {
+    match value {
+        Object::Nil => out.push_str("null"),
+        Object::Bool(_) | Object::Int(_) => write!(out, "{value}").unwrap(),
+        Object::Float(Float { value }) if value.is_finite() => write!(out, "{value:?}").unwrap(),
+        Object::Float(Float { value }) => return Err(format!("cannot convert {value} to JSON")),
+        Object::Str(Str { value }) => write_json_str(value, out),
+        Object::Char(ch) => write_json_str(&ch.value.to_string(), out),
+        Object::Array(Array { elements }) => {
+            out.push('[');
+
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+
+                to_json(element, out)?;
+            }
+
+            out.push(']');
+        }
+        Object::Dict(Dict { pairs }) => {
+            let mut entries = Vec::with_capacity(pairs.len());
+
+            for DictPair { key, value } in pairs.values() {
+                let Hashable::Str(key) = key else {
+                    return Err(format!(
+                        "cannot convert DICT with {} keys to JSON",
+                        key.to_object().kind()
+                    ));
+                };
+
+                entries.push((key, value));
+            }
+
+            // Dicts are unordered, so keys are sorted to keep the output stable.
+            entries.sort_by_key(|(key, _)| *key);
+
+            out.push('{');
+
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+
+                write_json_str(key, out);
+                out.push(':');
+                to_json(value, out)?;
+            }
+
+            out.push('}');
+        }
+        _ => return Err(format!("cannot convert {} to JSON", value.kind())),
+    }
+
+    Ok(())
+}
+
+fn write_json_str(value: &str, out: &mut String) {
+    out.push('"');
+
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if ch.is_control() => write!(out, "\\u{:04x}", u32::from(ch)).unwrap(),
+            ch => out.push(ch),
+        }
+    }
+
+    out.push('"');
+}
+
+// How deeply arrays and objects may nest, as each level is parsed by a
+// recursive call.
+const MAX_JSON_DEPTH: usize = 128;
+
+struct JsonParser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    const fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Object, String> {
+        let value = self.parse_value()?;
+
+        self.skip_whitespace();
+
+        if self.pos < self.input.len() {
+            return Err(format!("unexpected trailing characters at {}", self.pos));
+        }
+
+        Ok(value)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+
+        Some(ch)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|ch| matches!(ch, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.bump() {
+            Some(ch) if ch == expected => Ok(()),
+            Some(ch) => Err(format!(
+                "expected `{expected}`, got `{ch}` at {}",
+                self.pos - 1
+            )),
+            None => Err(format!("expected `{expected}`, got end of input")),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Object, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('{') => self.nested(Self::parse_object),
+            Some('[') => self.nested(Self::parse_array),
+            Some('"') => self.parse_str().map(Object::str),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some(_) => {
+                for (word, value) in [
+                    ("null", Object::Nil),
+                    ("true", Object::TRUE),
+                    ("false", Object::FALSE),
+                ] {
+                    if self.input[self.pos..].starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(value);
+                    }
+                }
+
+                Err(format!("unexpected character at {}", self.pos))
+            }
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Object, String>) -> Result<Object, String> {
+        if self.depth == MAX_JSON_DEPTH {
+            return Err("nesting too deep".to_string());
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn parse_array(&mut self) -> Result<Object, String> {
+        self.expect('[')?;
+
+        let mut elements = Vec::new();
+
+        self.skip_whitespace();
+
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Object::Array(Array { elements }));
+        }
+
+        loop {
+            elements.push(self.parse_value()?);
+
+            self.skip_whitespace();
+
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(Object::Array(Array { elements })),
+                _ => return Err(format!("expected `,` or `]` at {}", self.pos)),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Object, String> {
+        self.expect('{')?;
+
+        let mut pairs = std::collections::HashMap::new();
+
+        self.skip_whitespace();
+
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Object::Dict(Dict { pairs }));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            let key = Hashable::Str(self.parse_str()?);
+
+            self.skip_whitespace();
+            self.expect(':')?;
+
+            let value = self.parse_value()?;
+
+            pairs.insert(key.hash(), DictPair { key, value });
+
+            self.skip_whitespace();
+
+            match self.bump() {
+                Some(',') => {}
+                Some('}') => return Ok(Object::Dict(Dict { pairs })),
+                _ => return Err(format!("expected `,` or `}}` at {}", self.pos)),
+            }
+        }
+    }
+
+    fn parse_str(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut value = String::new();
+
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(value),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.parse_unicode_escape()?,
+                        _ => return Err(format!("invalid escape at {}", self.pos)),
+                    };
+
+                    value.push(escaped);
+                }
+                Some(ch) => value.push(ch),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, String> {
+        let mut code = self.parse_hex4()?;
+
+        // Characters outside the BMP are written as a surrogate pair.
+        if (0xD800..0xDC00).contains(&code) && self.input[self.pos..].starts_with("\\u") {
+            self.pos += 2;
+            let low = self.parse_hex4()?;
+            code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+        }
+
+        char::from_u32(code).ok_or_else(|| format!("invalid unicode escape at {}", self.pos))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| "unterminated unicode escape".to_string())?;
+
+        let code = u32::from_str_radix(digits, 16)
+            .map_err(|_| format!("invalid unicode escape at {}", self.pos))?;
+
+        self.pos += 4;
+
+        Ok(code)
+    }
+
+    fn parse_number(&mut self) -> Result<Object, String> {
+        let start = self.pos;
+
+        while self
+            .peek()
+            .is_some_and(|ch| ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.pos += 1;
+        }
+
+        let number = &self.input[start..self.pos];
+
+        if !number.contains(['.', 'e', 'E']) {
+            if let Ok(value) = number.parse() {
+                return Ok(Object::int(value));
+            }
+        }
+
+        number
+            .parse()
+            .map(Object::float)
+            .map_err(|_| format!("invalid number `{number}` at {start}"))
+    }
+}
@@ -0,0 +1,415 @@
+use super::{Array, Dict, Float, Int, Object, Str};
+use crate::limits::reserve;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum FormatType {
+    Display,
+    Debug,
+    Binary,
+    Octal,
+    LowerHex,
+    UpperHex,
+}
+
+#[derive(Clone, Debug)]
+struct Spec {
+    fill: char,
+    align: Option<Align>,
+    sign: bool,
+    alternate: bool,
+    zero: bool,
+    width: Option<usize>,
+    precision: Option<usize>,
+    format_type: FormatType,
+}
+
+impl Spec {
+    // `[[fill]align][+][#][0][width][.precision][type]`, as in rust's `format!`.
+    fn parse(spec: &str) -> Result<Self, String> {
+        let chars = spec.chars().collect::<Vec<_>>();
+        let mut i = 0;
+
+        let align_of = |ch: Option<&char>| match ch {
+            Some('<') => Some(Align::Left),
+            Some('^') => Some(Align::Center),
+            Some('>') => Some(Align::Right),
+            _ => None,
+        };
+
+        let (fill, align) = if let Some(align) = align_of(chars.get(1)) {
+            i += 2;
+            (chars[0], Some(align))
+        } else if let Some(align) = align_of(chars.first()) {
+            i += 1;
+            (' ', Some(align))
+        } else {
+            (' ', None)
+        };
+
+        let mut flag = |ch: char| {
+            let found = chars.get(i) == Some(&ch);
+            i += usize::from(found);
+            found
+        };
+
+        let sign = flag('+');
+        let alternate = flag('#');
+        let zero = flag('0');
+
+        let width = read_number(&chars, &mut i, spec, "width")?;
+
+        let precision = if chars.get(i) == Some(&'.') {
+            i += 1;
+
+            let Some(precision) = read_number(&chars, &mut i, spec, "precision")? else {
+                return Err(format!("missing precision in format spec \"{spec}\""));
+            };
+
+            Some(precision)
+        } else {
+            None
+        };
+
+        let format_type = match String::from_iter(&chars[i..]).as_str() {
+            "" => FormatType::Display,
+            "?" => FormatType::Debug,
+            "b" => FormatType::Binary,
+            "o" => FormatType::Octal,
+            "x" => FormatType::LowerHex,
+            "X" => FormatType::UpperHex,
+            rest => return Err(format!("unknown format type \"{rest}\"")),
+        };
+
+        Ok(Self {
+            fill,
+            align,
+            sign,
+            alternate,
+            zero,
+            width,
+            precision,
+            format_type,
+        })
+    }
+
+    const fn has_radix(&self) -> bool {
+        !matches!(self.format_type, FormatType::Display | FormatType::Debug)
+    }
+
+    // Elements of collections are formatted with everything but the padding,
+    // which applies to the collection as a whole.
+    const fn element(&self) -> Self {
+        Self {
+            fill: ' ',
+            align: None,
+            zero: false,
+            width: None,
+            ..*self
+        }
+    }
+}
+
+// Widths and precisions are capped like they are in rust's `format!`.
+fn read_number(
+    chars: &[char],
+    i: &mut usize,
+    spec: &str,
+    what: &str,
+) -> Result<Option<usize>, String> {
+    let start = *i;
+
+    while chars.get(*i).is_some_and(char::is_ascii_digit) {
+        *i += 1;
+    }
+
+    if start == *i {
+        return Ok(None);
+    }
+
+    String::from_iter(&chars[start..*i])
+        .parse::<u16>()
+        .map(|number| Some(usize::from(number)))
+        .map_err(|_| {
+            format!(
+                "{what} out of range in format spec \"{spec}\". max: {}",
+                u16::MAX
+            )
+        })
+}
+
+pub fn format_value(obj: &Object, spec: &str) -> Result<String, String> {
+    let spec = Spec::parse(spec)?;
+
+    // The padding alone takes up the whole width.
+    reserve(spec.width.unwrap_or(0) * spec.fill.len_utf8())?;
+
+    render(obj, &spec)
+}
+
+/// Formats `template` like rust's `format!`, with `{}`, `{<index>}` and
+/// `{:<spec>}` fields and `{{`/`}}` as escapes.
+pub fn format(template: &str, args: &[Object]) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+
+    let mut next_arg = 0;
+    let mut used = vec![false; args.len()];
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' | '}' if chars.peek() == Some(&ch) => {
+                chars.next();
+                out.push(ch);
+            }
+
+            '{' => {
+                let mut field = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(ch) => field.push(ch),
+                        None => return Err("unterminated `{` in format string".to_string()),
+                    }
+                }
+
+                let (position, spec) = field.split_once(':').unwrap_or((&field, ""));
+
+                let idx = if position.is_empty() {
+                    next_arg += 1;
+                    next_arg - 1
+                } else {
+                    position.trim().parse::<usize>().map_err(|_| {
+                        format!("invalid argument position \"{position}\" in format string")
+                    })?
+                };
+
+                let Some(arg) = args.get(idx) else {
+                    return Err(format!(
+                        "format string references argument {idx} but only {} given",
+                        args.len()
+                    ));
+                };
+
+                used[idx] = true;
+                out.push_str(&format_value(arg, spec)?);
+            }
+
+            '}' => return Err("unmatched `}` in format string".to_string()),
+
+            _ => out.push(ch),
+        }
+    }
+
+    if let Some(idx) = used.iter().position(|used| !used) {
+        return Err(format!("argument {idx} is never used in format string"));
+    }
+
+    Ok(out)
+}
+
+fn render(obj: &Object, spec: &Spec) -> Result<String, String> {
+    let debug = spec.format_type == FormatType::Debug;
+
+    match obj {
+        Object::Int(Int { value }) if !debug => Ok(render_int(*value, spec)),
+
+        Object::Float(Float { value }) if !debug => {
+            if spec.has_radix() {
+                return Err("cannot format FLOAT using a radix".to_string());
+            }
+
+            if let Some(precision) = spec.precision {
+                reserve(precision)?;
+            }
+
+            let digits = spec.precision.map_or_else(
+                || value.abs().to_string(),
+                |precision| format!("{:.precision$}", value.abs()),
+            );
+
+            Ok(pad_number(value.is_sign_negative(), "", &digits, spec))
+        }
+
+        Object::Str(Str { value }) if !debug => {
+            if spec.has_radix() {
+                return Err("cannot format STR using a radix".to_string());
+            }
+
+            let value = spec.precision.map_or_else(
+                || value.clone(),
+                |precision| value.chars().take(precision).collect(),
+            );
+
+            Ok(pad(&value, spec, Align::Left))
+        }
+
+        Object::Array(Array { elements }) => {
+            let element = spec.element();
+
+            let elements = elements
+                .iter()
+                .map(|obj| render(obj, &element))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(pad(
+                &format!("[{}]", elements.join(", ")),
+                spec,
+                Align::Left,
+            ))
+        }
+
+        Object::Dict(Dict { pairs }) => {
+            let element = spec.element();
+
+            let pairs = pairs
+                .values()
+                .map(|pair| {
+                    let key = pair.key.to_object();
+                    let key = if debug {
+                        key.inspect()
+                    } else {
+                        key.to_string()
+                    };
+
+                    Ok(format!("{key}: {}", render(&pair.value, &element)?))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+
+            Ok(pad(&format!("{{{}}}", pairs.join(", ")), spec, Align::Left))
+        }
+
+        _ if debug => Ok(pad(&obj.inspect(), spec, Align::Left)),
+
+        _ => {
+            if spec.has_radix() {
+                return Err(format!("cannot format {} using a radix", obj.kind()));
+            }
+
+            Ok(pad(&obj.to_string(), spec, Align::Left))
+        }
+    }
+}
+
+fn render_int(value: isize, spec: &Spec) -> String {
+    let abs = value.unsigned_abs();
+
+    let (prefix, digits) = match spec.format_type {
+        FormatType::Binary => ("0b", format!("{abs:b}")),
+        FormatType::Octal => ("0o", format!("{abs:o}")),
+        FormatType::LowerHex => ("0x", format!("{abs:x}")),
+        FormatType::UpperHex => ("0x", format!("{abs:X}")),
+        FormatType::Display | FormatType::Debug => ("", abs.to_string()),
+    };
+
+    let prefix = if spec.alternate { prefix } else { "" };
+
+    pad_number(value.is_negative(), prefix, &digits, spec)
+}
+
+fn pad_number(negative: bool, prefix: &str, digits: &str, spec: &Spec) -> String {
+    let sign = if negative {
+        "-"
+    } else if spec.sign {
+        "+"
+    } else {
+        ""
+    };
+
+    if spec.zero {
+        let len = sign.len() + prefix.len() + digits.chars().count();
+        let zeros = "0".repeat(spec.width.unwrap_or(0).saturating_sub(len));
+
+        return format!("{sign}{prefix}{zeros}{digits}");
+    }
+
+    pad(&format!("{sign}{prefix}{digits}"), spec, Align::Right)
+}
+
+fn pad(value: &str, spec: &Spec, default: Align) -> String {
+    let len = value.chars().count();
+    let Some(padding) = spec.width.and_then(|width| width.checked_sub(len)) else {
+        return value.to_string();
+    };
+
+    let (left, right) = match spec.align.unwrap_or(default) {
+        Align::Left => (0, padding),
+        Align::Center => (padding / 2, padding - padding / 2),
+        Align::Right => (padding, 0),
+    };
+
+    let fill = |n| spec.fill.to_string().repeat(n);
+
+    format!("{}{value}{}", fill(left), fill(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        let test_cases = [
+            (Object::int(42), "", "42"),
+            (Object::int(42), "5", "   42"),
+            (Object::int(42), "<5", "42   "),
+            (Object::int(42), "*^6", "**42**"),
+            (Object::int(-42), "05", "-0042"),
+            (Object::int(42), "+", "+42"),
+            (Object::int(255), "x", "ff"),
+            (Object::int(255), "#X", "0xFF"),
+            (Object::int(5), "#010b", "0b00000101"),
+            (Object::int(8), "o", "10"),
+            (Object::float(1.23456), ".2", "1.23"),
+            (Object::float(-1.5), "8.3", "  -1.500"),
+            (Object::str("hello".to_string()), ".3", "hel"),
+            (Object::str("hi".to_string()), ">4", "  hi"),
+            (Object::str("hi".to_string()), "?", "\"hi\""),
+            (Object::char('c'), "-^3", "-c-"),
+            (
+                Object::array(Vec::from([Object::int(10), Object::int(11)])),
+                "x",
+                "[a, b]",
+            ),
+            (
+                Object::array(Vec::from([Object::float(1.0), Object::float(2.26)])),
+                ">12.1",
+                "  [1.0, 2.3]",
+            ),
+        ];
+
+        for (obj, spec, expected) in test_cases {
+            assert_eq!(Ok(expected.to_string()), format_value(&obj, spec));
+        }
+    }
+
+    #[test]
+    fn test_format_spec_errors() {
+        let test_cases = [
+            (
+                ".70000",
+                "precision out of range in format spec \".70000\". max: 65535",
+            ),
+            (
+                ">10000000000",
+                "width out of range in format spec \">10000000000\". max: 65535",
+            ),
+            (".", "missing precision in format spec \".\""),
+            ("q", "unknown format type \"q\""),
+        ];
+
+        for (spec, expected) in test_cases {
+            assert_eq!(
+                Err(expected.to_string()),
+                format_value(&Object::float(1.5), spec)
+            );
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use std::{
+    fmt::{Debug, Display},
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+use super::{allowed_in_array, hash_method_name, Array, Class, Closure, Iterable, Object};
+use crate::{debugger::DebugHook, limits::reserve};
+
+/// Resumes the parts of an iterator chain that need an engine to make progress,
+/// i.e. generator frames and the `iter`/`next` methods of class instances.
+pub trait Driver {
+    fn resume(&mut self, generator: &Generator) -> Result<Option<Object>, String>;
+
+    /// Calls the zero-argument member `name` with `instance` bound as `self`,
+    /// writing any changes made to `self` back into `instance`.
+    fn call_member(&mut self, instance: &mut Object, name: &str) -> Result<Object, String>;
+}
+
+pub struct EvaluatedGenerator {
+    pub resume: Sender<Resume>,
+    pub yielded: Receiver<Yielded>,
+    pub done: bool,
+}
+
+/// What a generator yields, or `None` once it's done, and the debugger it was
+/// resumed with.
+pub type Yielded = (Option<Object>, Option<Box<dyn DebugHook>>);
+
+/// Hands a generator running on a thread of its own the depth it runs at,
+/// and the debugger, which it hands back with what it yields.
+pub struct Resume {
+    pub depth: usize,
+    pub debugger: Option<Box<dyn DebugHook>>,
+}
+
+#[derive(Debug)]
+pub struct CompiledGenerator {
+    pub closure: Closure,
+    pub ip: isize,
+    pub stack: Vec<Object>,
+    pub done: bool,
+    // Set while its frame is on the VM's stack, so it can't be resumed from
+    // inside itself.
+    pub running: bool,
+}
+
+#[derive(Clone)]
+pub enum Generator {
+    Evaluated(Arc<Mutex<EvaluatedGenerator>>),
+    Compiled(Arc<Mutex<CompiledGenerator>>),
+}
+
+impl Debug for Generator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Evaluated(_) => write!(f, "Generator::Evaluated"),
+            Self::Compiled(_) => write!(f, "Generator::Compiled"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum IterState {
+    Items { expr: Iterable, current: usize },
+    Generator(Generator),
+    Instance(Box<Object>),
+    Delegate(Iter),
+    Take { inner: Iter, remaining: usize },
+    Skip { inner: Iter, count: usize },
+    Zip(Iter, Iter),
+    Enumerate { inner: Iter, index: usize },
+    Chain { iters: Vec<Iter>, current: usize },
+    Done,
+}
+
+#[derive(Debug)]
+struct IterInner {
+    state: IterState,
+    peeked: Option<Object>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Iter {
+    inner: Arc<Mutex<IterInner>>,
+}
+
+impl PartialEq for Iter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Iter {
+    pub fn new(state: IterState) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(IterInner {
+                state,
+                peeked: None,
+            })),
+        }
+    }
+
+    pub fn from_object(obj: Object) -> Option<Self> {
+        match obj {
+            Object::Iter(iter) => Some(iter),
+            Object::Class(_) => Some(Self::new(IterState::Instance(Box::new(obj)))),
+            Object::Enum(decl) => Some(Self::new(IterState::Items {
+                expr: Iterable::Array(Array {
+                    elements: decl.unit_variants(),
+                }),
+                current: 0,
+            })),
+            _ => Iterable::from_object(obj)
+                .map(|expr| Self::new(IterState::Items { expr, current: 0 })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, IterInner> {
+        self.inner.lock().unwrap()
+    }
+
+    pub fn next(&self, driver: &mut dyn Driver) -> Result<Option<Object>, String> {
+        let peeked = self.lock().peeked.take();
+
+        if peeked.is_some() {
+            return Ok(peeked);
+        }
+
+        self.advance(driver)
+    }
+
+    pub fn peek(&self, driver: &mut dyn Driver) -> Result<Option<Object>, String> {
+        let peeked = self.lock().peeked.clone();
+
+        if peeked.is_some() {
+            return Ok(peeked);
+        }
+
+        let next = self.advance(driver)?;
+
+        let mut inner = self.lock();
+        if next.is_none() {
+            inner.state = IterState::Done;
+        }
+        inner.peeked.clone_from(&next);
+        drop(inner);
+
+        Ok(next)
+    }
+
+    /// Handles the methods of ITER that have to drive the iterator. Returns
+    /// `None` for the remaining methods, which live in `BUILTIN_METHODS`.
+    pub fn call_method(
+        &self,
+        method: usize,
+        params: &[Object],
+        driver: &mut dyn Driver,
+    ) -> Option<Result<Object, String>> {
+        if method == hash_method_name("next") {
+            if !params.is_empty() {
+                return Some(Err(format!("expected 0 parameters. got: {}", params.len())));
+            }
+
+            Some(self.next(driver).map(|next| next.unwrap_or(Object::Nil)))
+        } else if method == hash_method_name("collect") {
+            if !params.is_empty() {
+                return Some(Err(format!("expected 0 parameters. got: {}", params.len())));
+            }
+
+            Some(self.collect(driver))
+        } else {
+            None
+        }
+    }
+
+    fn collect(&self, driver: &mut dyn Driver) -> Result<Object, String> {
+        let mut elements = Vec::new();
+
+        while let Some(next) = self.next(driver)? {
+            if !allowed_in_array(&next) {
+                return Err(format!("ARRAY cannot contain {}", next.kind()));
+            }
+
+            reserve(elements.len() + 1)?;
+            elements.push(next);
+        }
+
+        Ok(Object::array(elements))
+    }
+
+    // The lock is never held while another iterator or the engine is being
+    // driven, as a generator may well touch this iterator while it runs.
+    fn advance(&self, driver: &mut dyn Driver) -> Result<Option<Object>, String> {
+        let mut inner = self.lock();
+
+        match &mut inner.state {
+            IterState::Items { expr, current } => {
+                if *current >= expr.count() {
+                    return Ok(None);
+                }
+
+                let next = expr.get(*current);
+                *current += 1;
+
+                Ok(Some(next))
+            }
+
+            IterState::Generator(generator) => {
+                let generator = generator.clone();
+                drop(inner);
+
+                driver.resume(&generator)
+            }
+
+            IterState::Instance(instance) => {
+                let mut instance = (**instance).clone();
+                drop(inner);
+
+                self.advance_instance(&mut instance, driver)
+            }
+
+            IterState::Delegate(delegate) => {
+                let delegate = delegate.clone();
+                drop(inner);
+
+                delegate.next(driver)
+            }
+
+            IterState::Take {
+                inner: taken,
+                remaining,
+            } => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+
+                *remaining -= 1;
+                let taken = taken.clone();
+                drop(inner);
+
+                taken.next(driver)
+            }
+
+            IterState::Skip {
+                inner: skipped,
+                count,
+            } => {
+                let count = std::mem::take(count);
+                let skipped = skipped.clone();
+                drop(inner);
+
+                for _ in 0..count {
+                    if skipped.next(driver)?.is_none() {
+                        return Ok(None);
+                    }
+                }
+
+                skipped.next(driver)
+            }
+
+            IterState::Zip(left, right) => {
+                let (left, right) = (left.clone(), right.clone());
+                drop(inner);
+
+                let Some(left) = left.next(driver)? else {
+                    return Ok(None);
+                };
+
+                let Some(right) = right.next(driver)? else {
+                    return Ok(None);
+                };
+
+                Ok(Some(Object::array(Vec::from([left, right]))))
+            }
+
+            IterState::Enumerate {
+                inner: enumerated, ..
+            } => {
+                let enumerated = enumerated.clone();
+                drop(inner);
+
+                let Some(next) = enumerated.next(driver)? else {
+                    return Ok(None);
+                };
+
+                let idx = match &mut self.lock().state {
+                    IterState::Enumerate { index, .. } => {
+                        *index += 1;
+                        *index - 1
+                    }
+                    _ => unreachable!(),
+                };
+
+                let idx = Object::int(isize::try_from(idx).unwrap());
+
+                Ok(Some(Object::array(Vec::from([idx, next]))))
+            }
+
+            IterState::Chain { .. } => {
+                drop(inner);
+
+                loop {
+                    let iter = match &self.lock().state {
+                        IterState::Chain { iters, current } => iters.get(*current).cloned(),
+                        _ => unreachable!(),
+                    };
+
+                    let Some(iter) = iter else {
+                        return Ok(None);
+                    };
+
+                    if let Some(next) = iter.next(driver)? {
+                        return Ok(Some(next));
+                    }
+
+                    if let IterState::Chain { current, .. } = &mut self.lock().state {
+                        *current += 1;
+                    }
+                }
+            }
+
+            IterState::Done => Ok(None),
+        }
+    }
+
+    fn advance_instance(
+        &self,
+        instance: &mut Object,
+        driver: &mut dyn Driver,
+    ) -> Result<Option<Object>, String> {
+        let Object::Class(Class { name, members }) = &instance else {
+            return Err(format!("{} is not iterable", instance.kind()));
+        };
+
+        if members.contains_key(&hash_method_name("iter")) {
+            let obj = driver.call_member(instance, "iter")?;
+
+            let Some(delegate) = Self::from_object(obj.clone()) else {
+                return Err(format!(
+                    "`iter` must return an iterable object. got: {}",
+                    obj.kind()
+                ));
+            };
+
+            self.lock().state = IterState::Delegate(delegate.clone());
+
+            return delegate.next(driver);
+        }
+
+        if !members.contains_key(&hash_method_name("next")) {
+            return Err(format!(
+                "class \"{name}\" is not iterable. it must define an `iter` or `next` method"
+            ));
+        }
+
+        let next = driver.call_member(instance, "next")?;
+
+        if next == Object::Nil {
+            self.lock().state = IterState::Done;
+            return Ok(None);
+        }
+
+        self.lock().state = IterState::Instance(Box::new(instance.clone()));
+
+        Ok(Some(next))
+    }
+}
+
+impl Display for Iter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Ok(inner) = self.inner.try_lock() else {
+            return write!(f, "<iter>");
+        };
+
+        match &inner.state {
+            IterState::Items { expr, current } => {
+                write!(
+                    f,
+                    "<iter ({current}, {}){}>",
+                    expr.count(),
+                    expr.to_object()
+                )
+            }
+            IterState::Generator(_) => write!(f, "<generator>"),
+            _ => write!(f, "<iter>"),
+        }
+    }
+}
@@ -1,21 +1,28 @@
 pub mod builtins;
+pub mod format;
+pub mod iter;
 
 use std::{
     collections::HashMap,
     fmt::Display,
     hash::{Hash as StdHash, Hasher},
+    sync::Arc,
 };
 
 use ahash::AHasher;
+use indexmap::IndexMap;
+use iter::Iter;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
-    ast::BlockStatement, code::Instructions, compiler::symbol_table::SymbolTable,
+    ast::{BlockStatement, ClassDecl, EnumVariant, InterfaceDecl},
+    code::Instructions,
     interpreters::eval::environment::Environment,
+    io::Io,
 };
 
 pub type BuiltinFunction = fn(&Object, &[Object]) -> Object;
-
-pub const DIR_ENV_VAR_NAME: &str = "STARTING_POINT_DIR";
+pub type NativeFn = dyn Fn(&Io, &[Object]) -> Object + Send + Sync;
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct DictPair {
@@ -64,6 +71,7 @@ pub struct EvaluatedFunction {
     pub parameters: Vec<String>,
     pub body: BlockStatement,
     pub environment: Environment,
+    pub generator: bool,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -73,6 +81,34 @@ pub struct Builtin {
     pub caller: Option<Box<Object>>,
 }
 
+// A function registered by the host through a `NativeModule`, which unlike a
+// `Builtin` can capture state.
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub func: Arc<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn call(&self, io: &Io, args: &[Object]) -> Object {
+        (self.func)(io, args)
+    }
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.func, &other.func)
+    }
+}
+
+impl std::fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct Array {
     pub elements: Vec<Object>,
@@ -98,7 +134,139 @@ impl ClassMember {
 #[derive(Clone, PartialEq, Debug)]
 pub struct Class {
     pub name: String,
-    pub members: HashMap<usize, ClassMember>,
+    pub members: IndexMap<usize, ClassMember>,
+}
+
+// Dumps the fields of an instance in the order they're declared, leaving out
+// its methods, e.g. `Point { x: 1, y: 2 }`.
+impl Display for Class {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields = self
+            .members
+            .values()
+            .filter(|member| {
+                !matches!(
+                    member.obj,
+                    Object::EvaluatedFunction(_) | Object::Closure(_)
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if fields.is_empty() {
+            return write!(f, "{} {{}}", self.name);
+        }
+
+        write!(
+            f,
+            "{} {{ {} }}",
+            self.name,
+            fields
+                .iter()
+                .map(|member| match &member.obj {
+                    Object::Str(_) | Object::Char(_) => {
+                        format!("{}: {}", member.name, member.obj.inspect())
+                    }
+                    obj => format!("{}: {obj}", member.name),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+    pub methods: HashMap<usize, ClassMember>,
+}
+
+impl Enum {
+    /// Creates a value of the variant `name` holding `values` as its fields.
+    pub fn construct(self: &Arc<Self>, name: &str, values: Vec<Object>) -> Result<Object, String> {
+        let Some(index) = self
+            .variants
+            .iter()
+            .position(|variant| variant.name == name)
+        else {
+            return Err(format!(
+                "no variant `{name}` found in enum \"{}\"",
+                self.name
+            ));
+        };
+
+        let fields = self.variants[index].fields.len();
+
+        if values.len() != fields {
+            return Err(format!(
+                "wrong number of arguments. got: {}, want: {fields}",
+                values.len()
+            ));
+        }
+
+        Ok(Object::EnumValue(EnumValue {
+            decl: Arc::clone(self),
+            index,
+            values,
+        }))
+    }
+
+    /// The variants without fields, which is what iterating over an enum yields.
+    pub fn unit_variants(self: &Arc<Self>) -> Vec<Object> {
+        (0..self.variants.len())
+            .filter(|&index| self.variants[index].fields.is_empty())
+            .map(|index| {
+                Object::EnumValue(EnumValue {
+                    decl: Arc::clone(self),
+                    index,
+                    values: Vec::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EnumValue {
+    pub decl: Arc<Enum>,
+    pub index: usize,
+    pub values: Vec<Object>,
+}
+
+impl EnumValue {
+    pub fn variant(&self) -> &EnumVariant {
+        &self.decl.variants[self.index]
+    }
+}
+
+// Values are compared by their variant and fields, the methods of the enum are
+// not part of a value's identity.
+impl PartialEq for EnumValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.decl.name == other.decl.name
+            && self.index == other.index
+            && self.values == other.values
+    }
+}
+
+impl Display for EnumValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}::{}", self.decl.name, self.variant().name)?;
+
+        if self.values.is_empty() {
+            return Ok(());
+        }
+
+        write!(
+            f,
+            "({})",
+            self.values
+                .iter()
+                .map(Object::inspect)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -122,6 +290,16 @@ impl Range {
     pub fn nth(&self, idx: usize) -> isize {
         self.start + (self.step * isize::try_from(idx).unwrap())
     }
+
+    pub const fn contains(&self, value: isize) -> bool {
+        let in_bounds = if self.step > 0 {
+            self.start <= value && value < self.end
+        } else {
+            self.end < value && value <= self.start
+        };
+
+        in_bounds && (value - self.start) % self.step == 0
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -134,8 +312,13 @@ pub struct EvaluatedModule {
 #[derive(Clone, PartialEq, Debug)]
 pub struct CompiledModule {
     pub name: String,
-    pub symbol_table: SymbolTable,
-    pub constants: Vec<Object>,
+    // The constant holding the body of the module, which returns a dict of
+    // its exported values, and the global the dict is kept in.
+    pub function: usize,
+    pub global: usize,
+    pub exports: Vec<String>,
+    pub types: Vec<(String, ClassDecl)>,
+    pub interfaces: Vec<(String, InterfaceDecl)>,
     pub class: bool,
 }
 
@@ -144,6 +327,17 @@ pub struct CompiledFunction {
     pub instructions: Instructions,
     pub num_locals: usize,
     pub num_parameters: usize,
+    pub generator: bool,
+    pub info: Option<Arc<FunctionInfo>>,
+}
+
+/// The names a debugger shows for a function compiled with debug information,
+/// with its locals and free variables in the order of their slots.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub locals: Vec<String>,
+    pub free: Vec<String>,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -152,13 +346,6 @@ pub struct Closure {
     pub free: Vec<Object>,
 }
 
-#[derive(Clone, PartialEq, Debug)]
-pub struct Iter {
-    pub expr: Iterable,
-    pub current: usize,
-    pub size: usize,
-}
-
 #[derive(Clone, PartialEq, Debug)]
 pub enum Object {
     Int(Int),
@@ -171,9 +358,13 @@ pub enum Object {
     Error(Error),
     EvaluatedFunction(EvaluatedFunction),
     Builtin(Builtin),
+    Native(NativeFunction),
     Array(Array),
     Dict(Dict),
     Class(Class),
+    Enum(Arc<Enum>),
+    EnumValue(EnumValue),
+    Interface(Arc<InterfaceDecl>),
     Type(Type),
     Range(Range),
     CompiledFunction(CompiledFunction),
@@ -185,39 +376,47 @@ impl Object {
     pub const FALSE: Self = Self::bool(false);
     pub const TRUE: Self = Self::bool(true);
 
+    #[must_use]
     pub const fn int(value: isize) -> Self {
         Self::Int(Int { value })
     }
 
+    #[must_use]
     pub const fn float(value: f64) -> Self {
         Self::Float(Float { value })
     }
 
+    #[must_use]
     pub const fn char(value: char) -> Self {
         Self::Char(Char { value })
     }
 
+    #[must_use]
     pub const fn bool(value: bool) -> Self {
         Self::Bool(Bool { value })
     }
 
+    #[must_use]
     pub const fn str(value: String) -> Self {
         Self::Str(Str { value })
     }
 
+    #[must_use]
     pub const fn error(value: String) -> Self {
         Self::Error(Error { value })
     }
 
+    #[must_use]
     pub const fn array(elements: Vec<Self>) -> Self {
         Self::Array(Array { elements })
     }
 
-    pub const fn dict(pairs: HashMap<u64, DictPair>) -> Self {
+    pub(crate) const fn dict(pairs: HashMap<u64, DictPair>) -> Self {
         Self::Dict(Dict { pairs })
     }
 
-    pub const fn compiled_fn(
+    #[cfg(test)]
+    pub(crate) const fn compiled_fn(
         instructions: Vec<u8>,
         num_locals: usize,
         num_parameters: usize,
@@ -226,6 +425,8 @@ impl Object {
             instructions,
             num_locals,
             num_parameters,
+            generator: false,
+            info: None,
         })
     }
 }
@@ -245,11 +446,19 @@ impl Display for Object {
 
             Self::Bool(Bool { value }) => write!(f, "{value}"),
 
-            Self::Builtin(Builtin { name, .. }) => write!(f, "built-in function \"{name}\""),
+            Self::Builtin(Builtin { name, .. }) | Self::Native(NativeFunction { name, .. }) => {
+                write!(f, "built-in function \"{name}\"")
+            }
 
             Self::Char(Char { value }) => write!(f, "{value}"),
 
-            Self::Class(Class { name, .. }) => write!(f, "<class \"{name}\">"),
+            Self::Class(class) => write!(f, "{class}"),
+
+            Self::Enum(decl) => write!(f, "<enum \"{}\">", decl.name),
+
+            Self::EnumValue(value) => write!(f, "{value}"),
+
+            Self::Interface(decl) => write!(f, "<interface \"{}\">", decl.ident),
 
             Self::Error(Error { value: message }) => write!(f, "{message}"),
 
@@ -287,13 +496,7 @@ impl Display for Object {
 
             Self::Closure(_) => write!(f, "<closure>"),
 
-            Self::Iter(Iter {
-                expr: iter,
-                current,
-                size,
-            }) => {
-                write!(f, "<iter ({current}, {size}){}>", iter.to_object())
-            }
+            Self::Iter(iter) => write!(f, "{iter}"),
         }
     }
 }
@@ -307,17 +510,21 @@ impl Object {
             | Self::Nil
             | Self::ReturnValue(_)
             | Self::Builtin(_)
+            | Self::Native(_)
             | Self::Range(_)
             | Self::Class(_)
+            | Self::Enum(_)
+            | Self::EnumValue(_)
+            | Self::Interface(_)
             | Self::EvaluatedFunction(_)
             | Self::Type(_)
             | Self::CompiledFunction(_)
             | Self::Closure(_)
             | Self::Iter(_) => self.to_string(),
 
-            Self::Char(Char { value }) => format!("'{value}'"),
+            Self::Char(Char { value }) => format!("{value:?}"),
 
-            Self::Str(Str { value }) => format!("\"{value}\""),
+            Self::Str(Str { value }) => format!("{value:?}"),
 
             Self::Error(Error { value: message }) => format!("ERROR: {message}"),
 
@@ -343,6 +550,7 @@ impl Object {
         }
     }
 
+    #[must_use]
     pub fn kind(&self) -> String {
         let out = match self {
             Self::Int(_) => "INT",
@@ -350,9 +558,12 @@ impl Object {
             Self::Bool(_) => "BOOLEAN",
             Self::Nil => "Object::Nil",
             Self::ReturnValue(_) => "RETURN_VALUE",
-            Self::Builtin(_) => "BUILTIN",
+            Self::Builtin(_) | Self::Native(_) => "BUILTIN",
             Self::Range(_) => "RANGE",
             Self::Class(_) => "CLASS",
+            Self::Enum(_) => "ENUM",
+            Self::EnumValue(_) => "VARIANT",
+            Self::Interface(_) => "INTERFACE",
             Self::EvaluatedFunction(_) => "FUNCTION",
             Self::Type(_) => "TYPE",
             Self::Char(_) => "CHAR",
@@ -368,7 +579,7 @@ impl Object {
         out.to_string()
     }
 
-    pub fn get_id(&self) -> usize {
+    pub(crate) fn get_id(&self) -> usize {
         match self {
             Self::Int(_) => 0,
             Self::Float(_) => 1,
@@ -376,27 +587,61 @@ impl Object {
             Self::Char(_) => 3,
             Self::Array(_) => 4,
             Self::Dict(_) => 5,
+            Self::Iter(_) => 6,
             _ => usize::MAX,
         }
     }
 
-    pub fn call_method(&self, method: usize, params: Option<&[Self]>) -> Self {
+    pub(crate) fn call_method(&self, method: usize, params: Option<&[Self]>) -> Self {
         match self {
             Self::Class(Class { name, members }) => members.get(&method).map_or_else(
                 || Self::error(format!("no method found for class \"{name}\"",)),
                 |class_member| class_member.obj.clone(),
             ),
 
+            Self::EnumValue(value) => {
+                if let Some(member) = value.decl.methods.get(&method) {
+                    return member.obj.clone();
+                }
+
+                let variant = value.variant();
+
+                match params {
+                    None => variant
+                        .fields
+                        .iter()
+                        .position(|field| hash_method_name(field) == method)
+                        .map_or_else(
+                            || {
+                                Self::error(format!(
+                                    "no field found for variant \"{}::{}\"",
+                                    value.decl.name, variant.name
+                                ))
+                            },
+                            |idx| value.values[idx].clone(),
+                        ),
+                    Some([]) if method == hash_method_name("variant") => {
+                        Self::str(variant.name.clone())
+                    }
+                    Some(_) => {
+                        Self::error(format!("no method found for enum \"{}\"", value.decl.name))
+                    }
+                }
+            }
+
             Self::Int(_)
             | Self::Float(_)
             | Self::Str(_)
             | Self::Char(_)
             | Self::Array(_)
-            | Self::Dict(_) => {
-                let (_, func) = builtins::BUILTIN_METHODS[self.get_id()]
+            | Self::Dict(_)
+            | Self::Iter(_) => {
+                let Some((_, func)) = builtins::BUILTIN_METHODS[self.get_id()]
                     .iter()
                     .find(|(name, _)| hash_method_name(name) == method)
-                    .unwrap();
+                else {
+                    return Self::error(format!("no method found for {}", self.kind()));
+                };
 
                 params.map_or_else(
                     || {
@@ -438,15 +683,276 @@ pub fn allowed_in_array(obj: &Object) -> bool {
             | Object::Char(_)
             | Object::Array(_)
             | Object::Dict(_)
+            | Object::EnumValue(_)
+            | Object::Class(_)
     )
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+/// Splits `string` into the grapheme clusters that `STR`s are measured,
+/// indexed and sliced by.
+pub fn graphemes(string: &str) -> Vec<&str> {
+    string.graphemes(true).collect()
+}
+
+// Outside of `\r\n`, every ASCII character is a grapheme cluster of its own.
+fn is_one_byte_per_grapheme(string: &str) -> bool {
+    string.is_ascii() && !string.contains('\r')
+}
+
+/// The number of grapheme clusters in `string`, without collecting them.
+pub fn grapheme_count(string: &str) -> usize {
+    if is_one_byte_per_grapheme(string) {
+        string.len()
+    } else {
+        string.graphemes(true).count()
+    }
+}
+
+/// The grapheme cluster at `idx`, without collecting the ones after it.
+pub fn grapheme_at(string: &str, idx: usize) -> Option<&str> {
+    if is_one_byte_per_grapheme(string) {
+        string.get(idx..=idx)
+    } else {
+        string.graphemes(true).nth(idx)
+    }
+}
+
+/// The byte offsets each grapheme cluster of `string` starts at, followed by
+/// the length of `string`, so cluster `n` is `boundaries[n]..boundaries[n + 1]`.
+pub fn grapheme_boundaries(string: &str) -> Vec<usize> {
+    string
+        .grapheme_indices(true)
+        .map(|(start, _)| start)
+        .chain(std::iter::once(string.len()))
+        .collect()
+}
+
+/// A grapheme cluster as the `CHAR` it's made of, or as a `STR` if it's made
+/// of more than one.
+pub fn grapheme_object(grapheme: &str) -> Object {
+    let mut chars = grapheme.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Object::char(ch),
+        _ => Object::str(grapheme.to_string()),
+    }
+}
+
+/// Slices `string` by grapheme cluster, the same unit used by `len` and
+/// indexing.
+pub fn slice_str(string: &str, start: isize, end: isize, step: isize) -> Result<String, String> {
+    let graphemes = graphemes(string);
+
+    let (Ok(from), Ok(to), Ok(step)) = (
+        usize::try_from(start),
+        usize::try_from(end),
+        usize::try_from(step),
+    ) else {
+        return Err(format!("cannot slice STR using {start}..{end}..{step}"));
+    };
+
+    if from > to || to > graphemes.len() || step == 0 {
+        return Err(format!("cannot slice STR using {start}..{end}..{step}"));
+    }
+
+    Ok(graphemes[from..to].iter().step_by(step).copied().collect())
+}
+
+/// Returns `container` with the element at `index` replaced by `value`.
+pub fn set_index(container: Object, index: &Object, value: Object) -> Result<Object, String> {
+    match (container, index) {
+        (Object::Array(Array { mut elements }), Object::Int(Int { value: idx })) => {
+            let idx = checked_index(*idx, elements.len())?;
+            elements[idx] = value;
+
+            Ok(Object::array(elements))
+        }
+
+        (Object::Str(Str { value: string }), Object::Int(Int { value: idx })) => {
+            let Object::Char(Char { value: ch }) = value else {
+                return Err(format!(
+                    "cannot assign {} to STR, expected CHAR",
+                    value.kind()
+                ));
+            };
+
+            let mut graphemes = graphemes(&string);
+            let idx = checked_index(*idx, graphemes.len())?;
+
+            let ch = ch.to_string();
+            graphemes[idx] = &ch;
+
+            Ok(Object::str(graphemes.concat()))
+        }
+
+        (Object::Dict(Dict { mut pairs }), _) => {
+            let Some(key) = Hashable::from_object(index) else {
+                return Err(format!("unusable as hash key: {}", index.kind()));
+            };
+
+            pairs.insert(key.hash(), DictPair { key, value });
+
+            Ok(Object::dict(pairs))
+        }
+
+        (container, _) => Err(format!(
+            "cannot assign to index expression: {}[{}]",
+            container.kind(),
+            index.kind()
+        )),
+    }
+}
+
+/// Membership test behind the `in` operator: substrings and chars of a STR,
+/// elements of an ARRAY, keys of a DICT and INTs of a RANGE.
+pub fn contains(container: &Object, item: &Object) -> Result<bool, String> {
+    match (container, item) {
+        (Object::Str(Str { value }), Object::Str(Str { value: substring })) => {
+            Ok(value.contains(substring.as_str()))
+        }
+        (Object::Str(Str { value }), Object::Char(Char { value: ch })) => Ok(value.contains(*ch)),
+        (Object::Array(Array { elements }), _) => {
+            Ok(elements.iter().any(|element| equals(element, item)))
+        }
+        (Object::Dict(Dict { pairs }), _) => {
+            let Some(key) = Hashable::from_object(item) else {
+                return Err(format!("unusable as hash key: {}", item.kind()));
+            };
+
+            Ok(pairs.contains_key(&key.hash()))
+        }
+        (Object::Range(range), Object::Int(Int { value })) => {
+            Ok(range.step != 0 && range.contains(*value))
+        }
+        (Object::Range(range), Object::Float(Float { value })) => Ok(range.step != 0
+            && value.fract() == 0.0
+            && float_to_int(*value).is_some_and(|value| range.contains(value))),
+        _ => Err(format!(
+            "{} cannot contain {}",
+            container.kind(),
+            item.kind()
+        )),
+    }
+}
+
+/// The equality of `==`, under which INTs equal the FLOATs they convert to.
+pub fn equals(left: &Object, right: &Object) -> bool {
+    match (left, right) {
+        (Object::Int(Int { value: left }), Object::Float(Float { value: right }))
+        | (Object::Float(Float { value: right }), Object::Int(Int { value: left })) => {
+            (int_to_float(*left) - right).abs() < f64::EPSILON
+        }
+        (Object::Float(Float { value: left }), Object::Float(Float { value: right })) => {
+            (left - right).abs() < f64::EPSILON
+        }
+        _ => left == right,
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub const fn int_to_float(value: isize) -> f64 {
+    value as f64
+}
+
+/// The INT a FLOAT truncates to, if it's in range.
+pub fn float_to_int(value: f64) -> Option<isize> {
+    let truncated = value.trunc();
+
+    // `isize::MAX as f64` rounds up to 2^63, which is already out of range.
+    #[allow(clippy::cast_possible_truncation)]
+    (truncated >= int_to_float(isize::MIN) && truncated < int_to_float(isize::MAX))
+        .then_some(truncated as isize)
+}
+
+/// The arithmetic and bitwise operators on INTs, failing where they would
+/// overflow, divide by zero or shift past the width of an INT.
+pub fn int_op(left: isize, operator: &str, right: isize) -> Result<isize, String> {
+    let shift = || u32::try_from(right).ok();
+
+    let value = match operator {
+        "+" => left.checked_add(right),
+        "-" => left.checked_sub(right),
+        "*" => left.checked_mul(right),
+        "/" | "%" if right == 0 => {
+            return Err(if operator == "/" {
+                "division by zero".to_string()
+            } else {
+                "modulo by zero".to_string()
+            })
+        }
+        "/" => left.checked_div(right),
+        "%" => left.checked_rem(right),
+        "//" => return floor_div(left, right),
+        "&" => Some(left & right),
+        "|" => Some(left | right),
+        "^" => Some(left ^ right),
+        "<<" | ">>" => {
+            let value = if operator == "<<" {
+                shift().and_then(|right| left.checked_shl(right))
+            } else {
+                shift().and_then(|right| left.checked_shr(right))
+            };
+
+            return value.ok_or_else(|| format!("shift out of range: {left} {operator} {right}"));
+        }
+        _ => return Err(format!("unknown operator: INT {operator} INT")),
+    };
+
+    value.ok_or_else(|| format!("integer overflow: {left} {operator} {right}"))
+}
+
+/// `INT ** INT`, which is a FLOAT for negative exponents.
+pub fn int_pow(base: isize, exp: isize) -> Result<Object, String> {
+    if exp.is_negative() {
+        let exp = i32::try_from(exp).map_err(|_| format!("exponent too large: {exp}"))?;
+
+        return Ok(Object::float(int_to_float(base).powi(exp)));
+    }
+
+    u32::try_from(exp)
+        .ok()
+        .and_then(|exp| base.checked_pow(exp))
+        .map(Object::int)
+        .ok_or_else(|| format!("integer overflow: {base} ** {exp}"))
+}
+
+/// `INT // INT`, rounding towards negative infinity.
+pub fn floor_div(left: isize, right: isize) -> Result<isize, String> {
+    if right == 0 {
+        return Err("division by zero".to_string());
+    }
+
+    let (Some(quotient), Some(remainder)) = (left.checked_div(right), left.checked_rem(right))
+    else {
+        return Err(format!("integer overflow: {left} // {right}"));
+    };
+
+    if remainder != 0 && (left < 0) != (right < 0) {
+        Ok(quotient - 1)
+    } else {
+        Ok(quotient)
+    }
+}
+
+fn checked_index(idx: isize, len: usize) -> Result<usize, String> {
+    let normalized = if idx.is_negative() {
+        len.checked_sub(idx.unsigned_abs())
+    } else {
+        Some(idx.unsigned_abs())
+    };
+
+    normalized
+        .filter(|&idx| idx < len)
+        .ok_or_else(|| format!("index out of bounds. got: {idx}"))
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub enum Hashable {
     Char(char),
     Int(isize),
     Bool(bool),
     Str(String),
+    EnumValue(EnumValue),
 }
 
 impl Hashable {
@@ -469,6 +975,18 @@ impl Hashable {
                 value.hash(&mut hasher);
                 hasher.finish()
             }
+            Self::EnumValue(value) => {
+                value.decl.name.hash(&mut hasher);
+                value.index.hash(&mut hasher);
+
+                for field in &value.values {
+                    Self::from_object(field)
+                        .map(|field| field.hash())
+                        .hash(&mut hasher);
+                }
+
+                hasher.finish()
+            }
         }
     }
 
@@ -478,6 +996,7 @@ impl Hashable {
             Self::Int(node) => Object::int(*node),
             Self::Bool(node) => Object::bool(*node),
             Self::Str(node) => Object::str(node.clone()),
+            Self::EnumValue(node) => Object::EnumValue(node.clone()),
         }
     }
 
@@ -487,6 +1006,11 @@ impl Hashable {
             Object::Int(node) => Some(Self::Int(node.value)),
             Object::Bool(node) => Some(Self::Bool(node.value)),
             Object::Str(node) => Some(Self::Str(node.value.clone())),
+            Object::EnumValue(node) => node
+                .values
+                .iter()
+                .all(|field| Self::from_object(field).is_some())
+                .then(|| Self::EnumValue(node.clone())),
             _ => None,
         }
     }
@@ -499,6 +1023,7 @@ impl Display for Hashable {
             Self::Char(value) => write!(f, "{value}"),
             Self::Int(value) => write!(f, "{value}"),
             Self::Str(value) => write!(f, "{value}"),
+            Self::EnumValue(value) => write!(f, "{value}"),
         }
     }
 }
@@ -508,7 +1033,8 @@ pub enum Iterable {
     Range(Range),
     Array(Array),
     Dict(Dict),
-    Str(Str),
+    /// Segmented once, as the iterator is built, rather than on every step.
+    Str(Str, Vec<usize>),
 }
 
 impl Iterable {
@@ -517,7 +1043,11 @@ impl Iterable {
             Object::Range(ast_node) => Some(Self::Range(ast_node)),
             Object::Array(ast_node) => Some(Self::Array(ast_node)),
             Object::Dict(ast_node) => Some(Self::Dict(ast_node)),
-            Object::Str(ast_node) => Some(Self::Str(ast_node)),
+            Object::Str(ast_node) => {
+                let boundaries = grapheme_boundaries(&ast_node.value);
+
+                Some(Self::Str(ast_node, boundaries))
+            }
             _ => None,
         }
     }
@@ -527,7 +1057,7 @@ impl Iterable {
             Self::Range(ast_node) => Object::Range(ast_node),
             Self::Array(ast_node) => Object::Array(ast_node),
             Self::Dict(ast_node) => Object::Dict(ast_node),
-            Self::Str(ast_node) => Object::Str(ast_node),
+            Self::Str(ast_node, _) => Object::Str(ast_node),
         }
     }
 
@@ -536,7 +1066,7 @@ impl Iterable {
             Self::Range(ast_node) => ast_node.len(),
             Self::Array(ast_node) => ast_node.elements.len(),
             Self::Dict(ast_node) => ast_node.pairs.len(),
-            Self::Str(ast_node) => ast_node.value.len(),
+            Self::Str(_, boundaries) => boundaries.len() - 1,
         }
     }
 
@@ -551,11 +1081,26 @@ impl Iterable {
                 .nth(idx)
                 .map(|some| some.to_object())
                 .unwrap(),
-            Self::Str(ast_node) => ast_node.value.chars().nth(idx).map(Object::char).unwrap(),
+            Self::Str(ast_node, boundaries) => {
+                grapheme_object(&ast_node.value[boundaries[idx]..boundaries[idx + 1]])
+            }
         }
     }
 }
 
+// Accessors live next to the other members, under names no identifier can take.
+pub fn getter_name(property: &str) -> String {
+    format!("get {property}")
+}
+
+pub fn setter_name(property: &str) -> String {
+    format!("set {property}")
+}
+
+pub fn is_private(member: &str) -> bool {
+    member.starts_with('_')
+}
+
 pub fn hash_method_name(method_name: &str) -> usize {
     let mut hasher = AHasher::default();
 
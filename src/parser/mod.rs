@@ -7,12 +7,14 @@ use precedence::{precedences, Precedence};
 use crate::{
     ast::{
         Assign, Assignable, BlockStatement, Call, ClassDecl, ClassMethod, ClassStatement,
-        ClassVariable, Constructable, Constructor, Declaration, Delete, Expression, ExpressionStmt,
-        For, Function, Identifier, If, Import, Index, Infix, Lambda, Lit, Literal, Method, Node,
-        Prefix, Range, Return, Scope, Statement, While,
+        ClassVariable, Constructable, Constructor, Declaration, Delete, EnumDecl, EnumVariant,
+        Expression, ExpressionStmt, For, Function, Identifier, If, Import, ImportItems, ImportName,
+        Index, Infix, InterfaceDecl, InterfaceMethod, Interpolation, InterpolationPart, Lambda,
+        Lit, Literal, Method, Node, Operator, Prefix, Range, Return, Scope, Statement, While,
+        Yield,
     },
-    lexer::Lexer,
-    token::{Kind, Token},
+    lexer::{unescape, Lexer},
+    token::{Kind, Span, Token},
 };
 
 pub struct Parser<'a> {
@@ -21,7 +23,14 @@ pub struct Parser<'a> {
     cur_tok: Token,
     peek_tok: Token,
 
+    // Whether statements are preceded by the line they start on, for
+    // debuggers, and the last position whose line was counted.
+    lines: bool,
+    line: (usize, usize),
+
     pub errors: Vec<String>,
+    /// Where each of the errors was found.
+    pub error_spans: Vec<Span>,
 }
 
 impl<'a> Parser<'a> {
@@ -30,7 +39,10 @@ impl<'a> Parser<'a> {
             lexer,
             cur_tok: Token::new(Kind::Illegal, String::new()),
             peek_tok: Token::new(Kind::Illegal, String::new()),
+            lines: false,
+            line: (0, 1),
             errors: Vec::new(),
+            error_spans: Vec::new(),
         };
 
         p.next_token();
@@ -39,9 +51,48 @@ impl<'a> Parser<'a> {
         p
     }
 
+    /// Marks where each statement starts with a `Statement::Line`.
+    pub const fn with_lines(mut self) -> Self {
+        self.lines = true;
+        self
+    }
+
+    // The line the current token is on, counted on from the last statement.
+    fn cur_line(&mut self) -> usize {
+        if !self.lines {
+            return 0;
+        }
+
+        let position = self.cur_tok.span.start;
+        let (last, line) = self.line;
+
+        let line = if position >= last {
+            line + self.lexer.newlines(last, position)
+        } else {
+            self.lexer.line(position)
+        };
+
+        self.line = (position, line);
+        line
+    }
+
+    fn push_statement(&self, statements: &mut BlockStatement, line: usize, stmt: Statement) {
+        if self.lines {
+            statements.push(Statement::Line(line));
+        }
+
+        statements.push(stmt);
+    }
+
     fn next_token(&mut self) {
         self.cur_tok = self.peek_tok.clone();
         self.peek_tok = self.lexer.next_token();
+
+        // Lexing errors are found while reading the next token.
+        for err in self.lexer.errors.drain(..) {
+            self.errors.push(err.to_string());
+            self.error_spans.push(self.peek_tok.span);
+        }
     }
 
     pub fn parse_program(&mut self) -> Option<Node> {
@@ -53,9 +104,16 @@ impl<'a> Parser<'a> {
                 break;
             }
 
-            let statement = self.parse_statement();
+            let line = self.cur_line();
+
+            let statement = if self.cur_token_is(Kind::Export) {
+                self.parse_export_statement()
+            } else {
+                self.parse_statement()
+            };
+
             if let Some(statement) = statement {
-                statements.push(statement);
+                self.push_statement(&mut statements, line, statement);
             } else {
                 return None;
             }
@@ -70,14 +128,23 @@ impl<'a> Parser<'a> {
         let stmt = match self.cur_tok.tok_type {
             Kind::Var | Kind::Const => self.parse_assign_statement(),
             Kind::Return => self.parse_return_statement(),
+            Kind::Yield => self.parse_yield_statement(),
             Kind::While => self.parse_while_statement(),
             Kind::For => self.parse_for_statement(),
             Kind::Class => self.parse_class_statement(),
+            Kind::Enum => self.parse_enum_statement(),
+            Kind::Interface => self.parse_interface_statement(),
             Kind::Import => self.parse_import_statement(),
+            Kind::Export => {
+                self.error("`export` is only allowed at the top level of a module.".to_string());
+                None
+            }
             Kind::Delete => self.parse_delete_statement(),
             Kind::Break => Some(Statement::Break),
             Kind::Continue => Some(Statement::Continue),
-            Kind::Function if self.peek_token_is(Kind::Ident) => self.parse_function_statement(),
+            Kind::Function | Kind::Generator if self.peek_token_is(Kind::Ident) => {
+                self.parse_function_statement()
+            }
             _ => self.parse_expression_statement(),
         };
 
@@ -132,6 +199,14 @@ impl<'a> Parser<'a> {
         Some(Statement::Return(Return { return_value }))
     }
 
+    fn parse_yield_statement(&mut self) -> Option<Statement> {
+        self.next_token();
+
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        Some(Statement::Yield(Yield { value }))
+    }
+
     fn parse_delete_statement(&mut self) -> Option<Statement> {
         if !self.expect_peek(Kind::Ident) {
             return None;
@@ -143,6 +218,8 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_function_statement(&mut self) -> Option<Statement> {
+        let generator = self.cur_token_is(Kind::Generator);
+
         self.next_token();
 
         if !self.cur_token_is(Kind::Ident) {
@@ -168,6 +245,7 @@ impl<'a> Parser<'a> {
             ident,
             parameters,
             body,
+            generator,
         }))
     }
 
@@ -258,16 +336,166 @@ impl<'a> Parser<'a> {
             Vec::new()
         };
 
+        // `implements` isn't a keyword, so it can still name the builtin.
+        let mut interfaces = Vec::new();
+
+        if self.peek_token_is(Kind::Ident) && self.peek_tok.tok_lit == "implements" {
+            self.next_token();
+
+            loop {
+                if !self.expect_peek(Kind::Ident) {
+                    return None;
+                }
+
+                interfaces.push(self.cur_tok.tok_lit.clone());
+
+                if !self.peek_token_is(Kind::Comma) {
+                    break;
+                }
+
+                self.next_token();
+            }
+        }
+
         if !self.expect_peek(Kind::LBrace) {
             return None;
         }
 
-        let body = self.parse_class_block();
+        let (body, statics) = self.parse_class_block()?;
 
         Some(Statement::ClassDecl(ClassDecl {
             ident,
             initializers,
+            interfaces,
             body,
+            statics,
+        }))
+    }
+
+    fn parse_interface_statement(&mut self) -> Option<Statement> {
+        if !self.expect_peek(Kind::Ident) {
+            return None;
+        }
+
+        let ident = self.cur_tok.tok_lit.clone();
+
+        if !self.expect_peek(Kind::LBrace) {
+            return None;
+        }
+
+        let mut methods = Vec::new();
+
+        self.next_token();
+
+        while !self.cur_token_is(Kind::RBrace) && !self.cur_token_is(Kind::Eol) {
+            if self.cur_token_is(Kind::Semicolon) {
+                self.next_token();
+                continue;
+            }
+
+            // Methods may be written like class methods or with a leading `fn`.
+            if self.cur_token_is(Kind::Function) && !self.expect_peek(Kind::Ident) {
+                return None;
+            }
+
+            if !self.cur_token_is(Kind::Ident) {
+                self.error(format!(
+                    "expected method in interface \"{ident}\", got {} instead.",
+                    self.cur_tok.tok_type
+                ));
+                return None;
+            }
+
+            let name = self.cur_tok.tok_lit.clone();
+
+            if !self.expect_peek(Kind::LParen) {
+                return None;
+            }
+
+            let parameters = self.parse_function_parameters()?;
+
+            let body = if self.peek_token_is(Kind::LBrace) {
+                self.next_token();
+                Some(self.parse_block_statement())
+            } else {
+                None
+            };
+
+            methods.push(InterfaceMethod {
+                name,
+                parameters,
+                body,
+            });
+
+            self.next_token();
+        }
+
+        Some(Statement::InterfaceDecl(InterfaceDecl { ident, methods }))
+    }
+
+    fn parse_enum_statement(&mut self) -> Option<Statement> {
+        if !self.expect_peek(Kind::Ident) {
+            return None;
+        }
+
+        let ident = self.cur_tok.tok_lit.clone();
+
+        if !self.expect_peek(Kind::LBrace) {
+            return None;
+        }
+
+        let mut variants = Vec::<EnumVariant>::new();
+        let mut methods = Vec::new();
+
+        self.next_token();
+
+        // Variants and methods may be separated by commas or semicolons, a
+        // member followed by a block is a method.
+        while !self.cur_token_is(Kind::RBrace) && !self.cur_token_is(Kind::Eol) {
+            if self.cur_token_is(Kind::Comma) || self.cur_token_is(Kind::Semicolon) {
+                self.next_token();
+                continue;
+            }
+
+            if !self.cur_token_is(Kind::Ident) {
+                self.error(format!(
+                    "expected variant or method in enum \"{ident}\", got {} instead.",
+                    self.cur_tok.tok_type
+                ));
+                return None;
+            }
+
+            let name = self.cur_tok.tok_lit.clone();
+
+            let fields = if self.peek_token_is(Kind::LParen) {
+                self.next_token();
+                self.parse_function_parameters()?
+            } else {
+                Vec::new()
+            };
+
+            if self.peek_token_is(Kind::LBrace) {
+                self.next_token();
+
+                methods.push(ClassMethod {
+                    name,
+                    parameters: fields,
+                    body: self.parse_block_statement(),
+                });
+            } else if variants.iter().any(|variant| variant.name == name) {
+                self.error(format!("duplicate variant `{name}` in enum \"{ident}\""));
+                return None;
+            } else {
+                variants.push(EnumVariant { name, fields });
+            }
+
+            self.next_token();
+        }
+
+        Some(Statement::EnumDecl(EnumDecl {
+            ident,
+            variants,
+            methods,
         }))
     }
 
@@ -282,16 +510,19 @@ impl<'a> Parser<'a> {
                 },
             })),
             Kind::Nil => Some(Expression::Literal(Literal { lit: Lit::Nil })),
-            Kind::Bang | Kind::Minus => self.parse_prefix_expression(),
+            Kind::Bang | Kind::Minus | Kind::Tilde => self.parse_prefix_expression(),
             Kind::LParen => self.parse_grouped_expression(),
             Kind::If => self.parse_if_expression(),
-            Kind::Function => self.parse_function_literal(),
+            Kind::Function | Kind::Generator => self.parse_function_literal(),
             Kind::StrLiteral => Some(self.parse_string_literal()),
+            Kind::FStrLiteral => self.parse_format_string(),
             Kind::CharLiteral => self.parse_char_literal(),
             Kind::LBracket => self.parse_array_literal(),
             Kind::LBrace => self.parse_dict_literal(),
             Kind::New => self.parse_constructor_expression(),
             Kind::Semicolon => return Some(Expression::Literal(Literal { lit: Lit::Nil })),
+            // Already reported by the lexer.
+            Kind::Illegal => return None,
             _ => {
                 self.no_prefix_parse_error(self.cur_tok.tok_type);
                 return None;
@@ -300,7 +531,19 @@ impl<'a> Parser<'a> {
 
         while !self.peek_token_is(Kind::Semicolon) && prec < self.peek_precedence() {
             left_exp = match self.peek_tok.clone().tok_type {
-                Kind::Assign => {
+                Kind::Assign
+                | Kind::PlusAssign
+                | Kind::MinusAssign
+                | Kind::AsteriskAssign
+                | Kind::SlashAssign
+                | Kind::ModuloAssign
+                | Kind::CaretAssign
+                | Kind::BitAndAssign
+                | Kind::BitOrAssign
+                | Kind::ShrAssign
+                | Kind::ShlAssign
+                | Kind::PowerAssign
+                | Kind::FloorDivAssign => {
                     self.next_token();
                     self.parse_assign_expression(left_exp)
                 }
@@ -309,6 +552,9 @@ impl<'a> Parser<'a> {
                 | Kind::Slash
                 | Kind::Asterisk
                 | Kind::Modulo
+                | Kind::Power
+                | Kind::FloorDiv
+                | Kind::In
                 | Kind::Eq
                 | Kind::NotEq
                 | Kind::Lt
@@ -321,19 +567,24 @@ impl<'a> Parser<'a> {
                 | Kind::Shr
                 | Kind::Shl
                 | Kind::And
-                | Kind::Or => {
+                | Kind::Or
+                | Kind::NilCoalesce => {
                     self.next_token();
                     self.parse_infix_expression(left_exp)
                 }
+                Kind::Not => {
+                    self.next_token();
+                    self.parse_not_in_expression(left_exp)
+                }
                 Kind::LParen => {
                     self.next_token();
                     self.parse_call_expression(left_exp)
                 }
-                Kind::LBracket => {
+                Kind::LBracket | Kind::OptionalLBracket => {
                     self.next_token();
                     self.parse_index_expression(left_exp)
                 }
-                Kind::Dot => {
+                Kind::Dot | Kind::OptionalDot => {
                     self.next_token();
                     self.parse_method_expression(left_exp)
                 }
@@ -358,8 +609,10 @@ impl<'a> Parser<'a> {
         self.next_token();
 
         while !self.cur_token_is(Kind::RBrace) && !self.cur_token_is(Kind::Eol) {
+            let line = self.cur_line();
+
             if let Some(stmt) = self.parse_statement() {
-                statements.push(stmt);
+                self.push_statement(&mut statements, line, stmt);
             };
             self.next_token();
         }
@@ -367,25 +620,45 @@ impl<'a> Parser<'a> {
         statements
     }
 
-    fn parse_class_block(&mut self) -> Vec<ClassStatement> {
+    fn parse_class_block(&mut self) -> Option<(Vec<ClassStatement>, Vec<Statement>)> {
         let mut statements = Vec::new();
+        let mut statics = Vec::new();
 
         self.next_token();
 
         while !self.cur_token_is(Kind::RBrace) && !self.cur_token_is(Kind::Eol) {
-            if let Some(class_stmt) = self.parse_class_member() {
+            if self.cur_token_is(Kind::Static) {
+                self.next_token();
+
+                if !matches!(
+                    self.cur_tok.tok_type,
+                    Kind::Var | Kind::Const | Kind::Function | Kind::Generator
+                ) {
+                    self.error(format!(
+                        "expected `var`, `const` or `fn` after `static`, got {} instead.",
+                        self.cur_tok.tok_type
+                    ));
+                    return None;
+                }
+
+                statics.push(self.parse_statement()?);
+            } else if let Some(class_stmt) = self.parse_class_member() {
                 statements.push(class_stmt);
             };
 
             self.next_token();
         }
 
-        statements
+        Some((statements, statics))
     }
 
     fn parse_class_member(&mut self) -> Option<ClassStatement> {
         let name = self.cur_tok.tok_lit.clone();
 
+        if matches!(name.as_str(), "get" | "set") && self.peek_token_is(Kind::Ident) {
+            return self.parse_class_accessor();
+        }
+
         if self.peek_token_is(Kind::LParen) {
             self.next_token();
             let parameters = self.parse_function_parameters()?;
@@ -419,7 +692,74 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_class_accessor(&mut self) -> Option<ClassStatement> {
+        let getter = self.cur_tok.tok_lit == "get";
+
+        self.next_token();
+        let name = self.cur_tok.tok_lit.clone();
+
+        if !self.expect_peek(Kind::LParen) {
+            return None;
+        }
+
+        let parameters = self.parse_function_parameters()?;
+
+        if !self.expect_peek(Kind::LBrace) {
+            return None;
+        }
+
+        let body = self.parse_block_statement();
+
+        if getter && !parameters.is_empty() {
+            self.error(format!("getter `{name}` cannot take parameters."));
+            return None;
+        }
+
+        if !getter && parameters.len() != 1 {
+            self.error(format!("setter `{name}` must take exactly one parameter."));
+            return None;
+        }
+
+        let method = ClassMethod {
+            name,
+            parameters,
+            body,
+        };
+
+        Some(if getter {
+            ClassStatement::Getter(method)
+        } else {
+            ClassStatement::Setter(method)
+        })
+    }
+
+    fn parse_export_statement(&mut self) -> Option<Statement> {
+        self.next_token();
+
+        let declares = match self.cur_tok.tok_type {
+            Kind::Var | Kind::Const | Kind::Class | Kind::Enum | Kind::Interface => true,
+            Kind::Function | Kind::Generator => self.peek_token_is(Kind::Ident),
+            _ => false,
+        };
+
+        if !declares {
+            self.error(format!(
+                "expected declaration after `export`, got {} instead.",
+                self.cur_tok.tok_type
+            ));
+            return None;
+        }
+
+        let stmt = self.parse_statement()?;
+
+        Some(Statement::Export(Box::new(stmt)))
+    }
+
     fn parse_import_statement(&mut self) -> Option<Statement> {
+        if self.peek_token_is(Kind::LBrace) || self.peek_token_is(Kind::Asterisk) {
+            return self.parse_import_items();
+        }
+
         let class = if self.peek_token_is(Kind::Class) {
             self.next_token();
             true
@@ -444,7 +784,77 @@ impl<'a> Parser<'a> {
             None
         };
 
-        Some(Statement::Import(Import { path, class, alias }))
+        Some(Statement::Import(Import {
+            path,
+            class,
+            alias,
+            items: None,
+        }))
+    }
+
+    fn parse_import_items(&mut self) -> Option<Statement> {
+        self.next_token();
+
+        let items = if self.cur_token_is(Kind::Asterisk) {
+            ImportItems::All
+        } else {
+            let mut names = Vec::new();
+
+            while !self.peek_token_is(Kind::RBrace) {
+                if !self.expect_peek(Kind::Ident) {
+                    return None;
+                }
+
+                let name = self.cur_tok.tok_lit.clone();
+
+                let alias = if self.peek_token_is(Kind::As) {
+                    self.next_token();
+                    if !self.expect_peek(Kind::Ident) {
+                        return None;
+                    }
+
+                    Some(self.cur_tok.tok_lit.clone())
+                } else {
+                    None
+                };
+
+                names.push(ImportName { name, alias });
+
+                if !self.peek_token_is(Kind::Comma) {
+                    break;
+                }
+
+                self.next_token();
+            }
+
+            if !self.expect_peek(Kind::RBrace) {
+                return None;
+            }
+
+            ImportItems::Names(names)
+        };
+
+        // Like `implements`, `from` isn't a keyword outside of imports.
+        if !(self.peek_token_is(Kind::Ident) && self.peek_tok.tok_lit == "from") {
+            self.error(format!(
+                "expected `from` after imported names, got {} instead.",
+                self.peek_tok.tok_type
+            ));
+            return None;
+        }
+
+        self.next_token();
+
+        if !self.expect_peek(Kind::StrLiteral) {
+            return None;
+        }
+
+        Some(Statement::Import(Import {
+            path: self.cur_tok.tok_lit.clone(),
+            class: false,
+            alias: None,
+            items: Some(items),
+        }))
     }
 }
 
@@ -460,9 +870,17 @@ impl Parser<'_> {
     fn parse_integer_literal(&mut self) -> Option<Expression> {
         let token = self.cur_tok.clone();
 
-        let Ok(value) = token.tok_lit.parse() else {
-            self.errors
-                .push(format!("couldn't parse '{}' as integer", token.tok_lit));
+        let digits = token.tok_lit.replace('_', "");
+
+        let parsed = match digits.get(..2) {
+            Some("0x") => isize::from_str_radix(&digits[2..], 16),
+            Some("0o") => isize::from_str_radix(&digits[2..], 8),
+            Some("0b") => isize::from_str_radix(&digits[2..], 2),
+            _ => digits.parse(),
+        };
+
+        let Ok(value) = parsed else {
+            self.error(format!("couldn't parse '{}' as integer", token.tok_lit));
             return None;
         };
 
@@ -474,9 +892,8 @@ impl Parser<'_> {
     fn parse_float_literal(&mut self) -> Option<Expression> {
         let token = self.cur_tok.clone();
 
-        let Ok(value) = token.tok_lit.parse() else {
-            self.errors
-                .push(format!("couldn't parse '{}' as float", token.tok_lit));
+        let Ok(value) = token.tok_lit.replace('_', "").parse() else {
+            self.error(format!("couldn't parse '{}' as float", token.tok_lit));
             return None;
         };
 
@@ -501,10 +918,19 @@ impl Parser<'_> {
     fn parse_infix_expression(&mut self, left: Expression) -> Option<Expression> {
         let token = self.cur_tok.clone();
 
-        let prec = self.cur_precedence();
+        // `**` is right associative, so `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+        let prec = if token.tok_type == Kind::Power {
+            Precedence::Prefix
+        } else {
+            self.cur_precedence()
+        };
         self.next_token();
 
-        let right = self.parse_expression(prec)?;
+        let right = if token.tok_type == Kind::In {
+            self.parse_membership_operand(prec)?
+        } else {
+            self.parse_expression(prec)?
+        };
 
         Some(Expression::Infix(Infix {
             left: Box::new(left),
@@ -513,6 +939,35 @@ impl Parser<'_> {
         }))
     }
 
+    fn parse_not_in_expression(&mut self, left: Expression) -> Option<Expression> {
+        if !self.expect_peek(Kind::In) {
+            return None;
+        }
+
+        let prec = self.cur_precedence();
+        self.next_token();
+
+        let right = self.parse_membership_operand(prec)?;
+
+        Some(Expression::Infix(Infix {
+            left: Box::new(left),
+            operator: Operator::NotIn,
+            right: Box::new(right),
+        }))
+    }
+
+    // Ranges bind looser than comparisons, but `x in 0..10` should test against the range.
+    fn parse_membership_operand(&mut self, prec: Precedence) -> Option<Expression> {
+        let right = self.parse_expression(prec)?;
+
+        if self.peek_token_is(Kind::Range) {
+            self.next_token();
+            return self.parse_range_expression(right);
+        }
+
+        Some(right)
+    }
+
     fn parse_grouped_expression(&mut self) -> Option<Expression> {
         self.next_token();
 
@@ -592,6 +1047,8 @@ impl Parser<'_> {
     }
 
     fn parse_function_literal(&mut self) -> Option<Expression> {
+        let generator = self.cur_token_is(Kind::Generator);
+
         if !self.expect_peek(Kind::LParen) {
             return None;
         }
@@ -608,6 +1065,7 @@ impl Parser<'_> {
             parameters,
             body,
             name: String::new(),
+            generator,
         }))
     }
 
@@ -655,12 +1113,101 @@ impl Parser<'_> {
         })
     }
 
+    fn parse_format_string(&mut self) -> Option<Expression> {
+        let chars = self.cur_tok.tok_lit.chars().collect::<Vec<_>>();
+
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                ch @ ('{' | '}') if chars.get(i + 1) == Some(&ch) => {
+                    text.push(ch);
+                    i += 2;
+                }
+                '}' => {
+                    self.error("unmatched `}` in format string".to_string());
+                    return None;
+                }
+                '{' => {
+                    let Some((end, colon)) = find_interpolation_end(&chars, i + 1) else {
+                        self.error("unterminated `{` in format string".to_string());
+                        return None;
+                    };
+
+                    if !text.is_empty() {
+                        let text = self.unescape(&std::mem::take(&mut text))?;
+                        parts.push(InterpolationPart::Str(text));
+                    }
+
+                    let source = String::from_iter(&chars[i + 1..colon.unwrap_or(end)]);
+                    let spec = colon.map(|colon| String::from_iter(&chars[colon + 1..end]));
+
+                    let value = self.parse_interpolated_expression(&source)?;
+
+                    parts.push(InterpolationPart::Expr { value, spec });
+                    i = end + 1;
+                }
+                ch => {
+                    text.push(ch);
+                    i += 1;
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            let text = self.unescape(&text)?;
+            parts.push(InterpolationPart::Str(text));
+        }
+
+        Some(Expression::Interpolation(Interpolation { parts }))
+    }
+
+    fn unescape(&mut self, raw: &str) -> Option<String> {
+        unescape(raw).map_or_else(
+            |err| {
+                self.error(err);
+                None
+            },
+            Some,
+        )
+    }
+
+    fn parse_interpolated_expression(&mut self, source: &str) -> Option<Expression> {
+        let mut lexer = Lexer::new(source);
+        let mut parser = Parser::new(&mut lexer);
+
+        if parser.cur_token_is(Kind::Eol) {
+            self.error("empty expression in format string".to_string());
+            return None;
+        }
+
+        let expr = parser.parse_expression(Precedence::Lowest);
+
+        if parser.errors.is_empty() && !parser.peek_token_is(Kind::Eol) {
+            parser.error(format!(
+                "unexpected {} in format string expression",
+                parser.peek_tok.tok_type
+            ));
+        }
+
+        if !parser.errors.is_empty() {
+            for err in parser.errors {
+                self.error(err);
+            }
+
+            return None;
+        }
+
+        expr
+    }
+
     fn parse_char_literal(&mut self) -> Option<Expression> {
         let token = self.cur_tok.clone();
 
         let Some(value) = parse_char(&token.tok_lit) else {
-            self.errors
-                .push(format!("cannot parse \"{}\" as char", token.tok_lit));
+            self.error(format!("cannot parse \"{}\" as char", token.tok_lit));
             return None;
         };
 
@@ -678,6 +1225,8 @@ impl Parser<'_> {
     }
 
     fn parse_index_expression(&mut self, left: Expression) -> Option<Expression> {
+        let optional = self.cur_token_is(Kind::OptionalLBracket);
+
         self.next_token();
 
         let index = self.parse_expression(Precedence::Lowest)?;
@@ -689,6 +1238,7 @@ impl Parser<'_> {
         Some(Expression::Index(Index {
             left: Box::new(left),
             index: Box::new(index),
+            optional,
         }))
     }
 
@@ -725,14 +1275,35 @@ impl Parser<'_> {
     }
 
     fn parse_assign_expression(&mut self, left: Expression) -> Option<Expression> {
+        let operator = match self.cur_tok.tok_type {
+            Kind::PlusAssign => Some(Operator::Add),
+            Kind::MinusAssign => Some(Operator::Sub),
+            Kind::AsteriskAssign => Some(Operator::Mul),
+            Kind::SlashAssign => Some(Operator::Div),
+            Kind::ModuloAssign => Some(Operator::Mod),
+            Kind::CaretAssign => Some(Operator::BitXor),
+            Kind::BitAndAssign => Some(Operator::BitAnd),
+            Kind::BitOrAssign => Some(Operator::BitOr),
+            Kind::ShrAssign => Some(Operator::Shr),
+            Kind::ShlAssign => Some(Operator::Shl),
+            Kind::PowerAssign => Some(Operator::Pow),
+            Kind::FloorDivAssign => Some(Operator::FloorDiv),
+            _ => None,
+        };
+
         self.next_token();
 
         let to = match left {
             Expression::Identifier(ast_node) => Assignable::Identifier(ast_node),
-            Expression::Method(ast_node) => Assignable::Method(ast_node),
-            Expression::Index(ast_node) => Assignable::Index(ast_node),
+            Expression::Method(ast_node) if !ast_node.optional => Assignable::Method(ast_node),
+            Expression::Index(ast_node) if !ast_node.optional => Assignable::Index(ast_node),
+            Expression::Scope(ast_node)
+                if matches!(*ast_node.member, Expression::Identifier(_)) =>
+            {
+                Assignable::Scope(ast_node)
+            }
             _ => {
-                self.errors.push(format!("{left} cannot be assigned to."));
+                self.error(format!("{left} cannot be assigned to."));
                 return None;
             }
         };
@@ -741,11 +1312,14 @@ impl Parser<'_> {
 
         Some(Expression::Assign(Assign {
             to,
+            operator,
             value: Box::new(value),
         }))
     }
 
     fn parse_method_expression(&mut self, left: Expression) -> Option<Expression> {
+        let optional = self.cur_token_is(Kind::OptionalDot);
+
         if !self.expect_peek(Kind::Ident) {
             return None;
         }
@@ -764,17 +1338,28 @@ impl Parser<'_> {
             left: Box::new(left),
             name: method,
             arguments,
+            optional,
         }))
     }
 
     fn parse_scope_expression(&mut self, left: Expression) -> Option<Expression> {
         let Expression::Identifier(Identifier { value: module }) = left else {
-            self.errors.push("expected IDENT".to_string());
+            self.error("expected IDENT".to_string());
             return None;
         };
 
-        self.next_token();
-        let member = self.parse_expression(Precedence::Lowest)?;
+        if !self.expect_peek(Kind::Ident) {
+            return None;
+        }
+
+        // Only the member and its call belong to the scope, so that in
+        // `Shape::Empty.area() == x` the method and operator apply to the result.
+        let mut member = self.parse_identifier();
+
+        if self.peek_token_is(Kind::LParen) {
+            self.next_token();
+            member = self.parse_call_expression(member)?;
+        }
 
         Some(Expression::Scope(Scope {
             module,
@@ -792,7 +1377,7 @@ impl Parser<'_> {
             Expression::Scope(ast_node) => Constructable::Scope(ast_node),
             Expression::Call(ast_node) => Constructable::Call(ast_node),
             _ => {
-                self.errors.push("cannot construct class".to_string());
+                self.error("cannot construct class".to_string());
                 return None;
             }
         };
@@ -818,8 +1403,7 @@ impl Parser<'_> {
             last = *last_start;
 
             if last_step.is_some() {
-                self.errors
-                    .push("range cannot have more than 3 parts.".to_string());
+                self.error("range cannot have more than 3 parts.".to_string());
                 return None;
             }
         }
@@ -833,47 +1417,12 @@ impl Parser<'_> {
 }
 
 fn parse_char(ch: &str) -> Option<char> {
-    let mut chars = ch.chars();
-    let c = match ch.parse() {
-        Ok(ch) => {
-            chars.next();
-            Some(ch)
-        }
-        Err(_) => match ch.len() {
-            0 => None,
-            _ => match chars.next() {
-                Some('\\') => match chars.next() {
-                    Some('x') => {
-                        let data = String::from_iter(&[chars.next()?, chars.next()?]);
-                        char::from_u32(u32::from_str_radix(&data, 16).ok()?)
-                    }
-                    Some('u') => {
-                        let data = String::from_iter(&[
-                            chars.next()?,
-                            chars.next()?,
-                            chars.next()?,
-                            chars.next()?,
-                        ]);
-                        char::from_u32(u32::from_str_radix(&data, 16).ok()?)
-                    }
-                    Some('n') => Some('\n'),
-                    Some('r') => Some('\r'),
-                    Some('t') => Some('\t'),
-                    Some('\\') => Some('\\'),
-                    Some('0') => Some('\0'),
-                    Some('\'') => Some('\''),
-                    _ => None,
-                },
-                _ => None,
-            },
-        },
-    };
+    let value = unescape(ch).ok()?;
+    let mut chars = value.chars();
 
-    if chars.next().is_some() {
-        return None;
-    }
+    let c = chars.next()?;
 
-    c
+    chars.next().is_none().then_some(c)
 }
 
 impl Parser<'_> {
@@ -905,15 +1454,62 @@ impl Parser<'_> {
 }
 
 impl Parser<'_> {
+    // Errors are about the tokens the parser is at.
+    fn error(&mut self, message: String) {
+        self.errors.push(message);
+        self.error_spans.push(Span {
+            start: self.cur_tok.span.start,
+            end: self.peek_tok.span.end,
+            byte_start: self.cur_tok.span.byte_start,
+            byte_end: self.peek_tok.span.byte_end,
+        });
+    }
+
     fn peek_error(&mut self, t: Kind) {
-        self.errors.push(format!(
+        self.error(format!(
             "expected next token to be {}, got {} instead.",
             t, self.peek_tok.tok_type
         ));
     }
 
     fn no_prefix_parse_error(&mut self, t: Kind) {
-        self.errors
-            .push(format!("no prefix parse function for {t} found"));
+        self.error(format!("no prefix parse function for {t} found"));
     }
 }
+
+// Finds the `}` closing an interpolated expression starting at `start`, along
+// with the `:` separating the expression from its format spec, if any.
+pub fn find_interpolation_end(chars: &[char], start: usize) -> Option<(usize, Option<usize>)> {
+    let mut depth = 0usize;
+    let mut colon = None;
+    let mut i = start;
+
+    while i < chars.len() {
+        match chars[i] {
+            quote @ ('"' | '\'') if colon.is_none() => {
+                i += 1;
+                while i < chars.len() && chars[i] != quote {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            '}' if depth == 0 => return Some((i, colon)),
+            '{' | '[' | '(' if colon.is_none() => depth += 1,
+            '}' | ']' | ')' if colon.is_none() => depth = depth.saturating_sub(1),
+            ':' if depth == 0 && colon.is_none() => {
+                if chars.get(i + 1) == Some(&':') {
+                    i += 1;
+                } else {
+                    colon = Some(i);
+                }
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    None
+}
@@ -4,6 +4,7 @@ use crate::token::Kind;
 pub enum Precedence {
     Lowest,
     Range,
+    Coalesce,
     Or,
     And,
     Comp,
@@ -15,6 +16,7 @@ pub enum Precedence {
     Product,
     Modulo,
     Prefix,
+    Power,
     Call,
     Index,
     Method,
@@ -24,20 +26,41 @@ pub enum Precedence {
 pub const fn precedences(t: Kind) -> Precedence {
     match t {
         Kind::Range => Precedence::Range,
+        Kind::NilCoalesce => Precedence::Coalesce,
         Kind::Or => Precedence::Or,
         Kind::And => Precedence::And,
-        Kind::Eq | Kind::NotEq | Kind::Lt | Kind::LtEq | Kind::Gt | Kind::GtEq => Precedence::Comp,
+        Kind::Eq
+        | Kind::NotEq
+        | Kind::Lt
+        | Kind::LtEq
+        | Kind::Gt
+        | Kind::GtEq
+        | Kind::In
+        | Kind::Not => Precedence::Comp,
         Kind::BitOr => Precedence::BitOr,
         Kind::Caret => Precedence::BitXor,
         Kind::BitAnd => Precedence::BitAnd,
         Kind::Shr | Kind::Shl => Precedence::Shift,
         Kind::Plus | Kind::Minus => Precedence::Sum,
-        Kind::Slash | Kind::Asterisk => Precedence::Product,
+        Kind::Slash | Kind::Asterisk | Kind::FloorDiv => Precedence::Product,
         Kind::Modulo => Precedence::Modulo,
+        Kind::Power => Precedence::Power,
         Kind::LParen => Precedence::Call,
-        Kind::LBracket => Precedence::Index,
-        Kind::Dot | Kind::Scope => Precedence::Method,
-        Kind::Assign => Precedence::Assign,
+        Kind::LBracket | Kind::OptionalLBracket => Precedence::Index,
+        Kind::Dot | Kind::OptionalDot | Kind::Scope => Precedence::Method,
+        Kind::Assign
+        | Kind::PlusAssign
+        | Kind::MinusAssign
+        | Kind::AsteriskAssign
+        | Kind::SlashAssign
+        | Kind::ModuloAssign
+        | Kind::CaretAssign
+        | Kind::BitAndAssign
+        | Kind::BitOrAssign
+        | Kind::ShrAssign
+        | Kind::ShlAssign
+        | Kind::PowerAssign
+        | Kind::FloorDivAssign => Precedence::Assign,
         _ => Precedence::Lowest,
     }
 }
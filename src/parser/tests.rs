@@ -109,6 +109,43 @@ fn test_function_statement() {
                         })),
                     }),
                 })]),
+                generator: false,
+            }),
+            statements[0]
+        );
+    } else {
+        panic!("p.parse_program() returned None")
+    }
+}
+
+#[test]
+fn test_generator_statement() {
+    let input = "fn* count() { yield 1; yield; }";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    let program = p.parse_program();
+
+    check_parser_errors(p);
+
+    if let Some(Node::Program { statements }) = program {
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            Statement::Function(Function {
+                ident: "count".to_string(),
+                parameters: Vec::new(),
+                body: Vec::from([
+                    Statement::Yield(Yield {
+                        value: Expression::Literal(Literal {
+                            lit: Lit::Int { value: 1 }
+                        }),
+                    }),
+                    Statement::Yield(Yield {
+                        value: Expression::Literal(Literal { lit: Lit::Nil }),
+                    }),
+                ]),
+                generator: true,
             }),
             statements[0]
         );
@@ -147,6 +184,7 @@ fn test_while_statement() {
                         to: Assignable::Identifier(Identifier {
                             value: "i".to_string(),
                         }),
+                        operator: None,
                         value: Box::new(Expression::Infix(Infix {
                             left: Box::new(Expression::Identifier(Identifier {
                                 value: "i".to_string(),
@@ -320,6 +358,7 @@ fn test_for_statement() {
                         to: Assignable::Identifier(Identifier {
                             value: "i".to_string(),
                         }),
+                        operator: None,
                         value: Box::new(Expression::Infix(Infix {
                             left: Box::new(Expression::Identifier(Identifier {
                                 value: "i".to_string(),
@@ -476,6 +515,8 @@ fn test_class_statement() {
             Statement::ClassDecl(ClassDecl {
                 ident: "MyClass".to_string(),
                 initializers: Vec::from(["i1".to_string(), "i2".to_string()]),
+                interfaces: Vec::new(),
+                statics: Vec::new(),
                 body: Vec::from([ClassStatement::Variable(ClassVariable {
                     name: "a".to_string(),
                     value: Some(Expression::Literal(Literal {
@@ -490,6 +531,137 @@ fn test_class_statement() {
     }
 }
 
+#[test]
+fn test_class_static_members() {
+    let input = "class C() { static var n = 1; static fn make() { n } a = 2 }";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    let program = p.parse_program();
+
+    check_parser_errors(p);
+
+    let Some(Node::Program { statements }) = program else {
+        panic!("expected a program");
+    };
+
+    let [Statement::ClassDecl(class)] = statements.as_slice() else {
+        panic!("expected a class. got: {statements:?}");
+    };
+
+    assert_eq!(1, class.body.len());
+    assert!(matches!(
+        class.static_members().as_slice(),
+        [Statement::Declaration(Declaration { name, mutable: true, .. }), Statement::Function(Function { ident, .. })]
+            if name == "C::n" && ident == "C::make"
+    ));
+
+    let mut l = Lexer::new("class C() { static x() {} }");
+    let mut p = Parser::new(&mut l);
+
+    p.parse_program();
+
+    assert_eq!(
+        p.errors,
+        ["expected `var`, `const` or `fn` after `static`, got IDENT instead."]
+    );
+}
+
+#[test]
+fn test_class_accessors() {
+    let input = "class C(w) { get area() { 1 } set area(v) { v } get(i) { i } }";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    let program = p.parse_program();
+
+    check_parser_errors(p);
+
+    let Some(Node::Program { statements }) = program else {
+        panic!("expected a program");
+    };
+
+    let [Statement::ClassDecl(class)] = statements.as_slice() else {
+        panic!("expected a class. got: {statements:?}");
+    };
+
+    assert!(matches!(
+        class.body.as_slice(),
+        [ClassStatement::Getter(getter), ClassStatement::Setter(setter), ClassStatement::Method(method)]
+            if getter.name == "area" && setter.name == "area" && method.name == "get"
+    ));
+
+    let test_cases = [
+        (
+            "class C() { get a(v) { v } }",
+            "getter `a` cannot take parameters.",
+        ),
+        (
+            "class C() { set a() { 1 } }",
+            "setter `a` must take exactly one parameter.",
+        ),
+    ];
+
+    for (input, expected) in test_cases {
+        let mut l = Lexer::new(input);
+        let mut p = Parser::new(&mut l);
+
+        p.parse_program();
+
+        assert_eq!(p.errors, [expected]);
+    }
+}
+
+#[test]
+fn test_interface_statement() {
+    let input =
+        "interface Shape { fn area(); scale(k) { k } }; class Sq(s) implements Shape, Named {}";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    let program = p.parse_program();
+
+    check_parser_errors(p);
+
+    let Some(Node::Program { statements }) = program else {
+        panic!("expected a program");
+    };
+
+    let [Statement::InterfaceDecl(interface), Statement::ClassDecl(class)] = statements.as_slice()
+    else {
+        panic!("expected an interface and a class. got: {statements:?}");
+    };
+
+    assert_eq!("Shape", interface.ident);
+    assert_eq!(
+        Vec::from([
+            InterfaceMethod {
+                name: "area".to_string(),
+                parameters: Vec::new(),
+                body: None,
+            },
+            InterfaceMethod {
+                name: "scale".to_string(),
+                parameters: Vec::from(["k".to_string()]),
+                body: Some(Vec::from([Statement::ExpressionStmt(ExpressionStmt {
+                    returns: true,
+                    expression: Expression::Identifier(Identifier {
+                        value: "k".to_string(),
+                    }),
+                })])),
+            },
+        ]),
+        interface.methods
+    );
+    assert_eq!(
+        Vec::from(["Shape".to_string(), "Named".to_string()]),
+        class.interfaces
+    );
+}
+
 struct StatementTestCase {
     input: &'static str,
     expected: Statement,
@@ -504,6 +676,7 @@ fn test_import_statement() {
                 path: "fs".to_string(),
                 alias: None,
                 class: false,
+                items: None,
             }),
         },
         StatementTestCase {
@@ -512,6 +685,7 @@ fn test_import_statement() {
                 path: "std/datetime/duration".to_string(),
                 alias: None,
                 class: false,
+                items: None,
             }),
         },
         StatementTestCase {
@@ -520,6 +694,34 @@ fn test_import_statement() {
                 path: "std/datetime/duration".to_string(),
                 alias: Some("dur".to_string()),
                 class: true,
+                items: None,
+            }),
+        },
+        StatementTestCase {
+            input: r#"import { parse, Token as Tok } from "lexer""#,
+            expected: Statement::Import(Import {
+                path: "lexer".to_string(),
+                alias: None,
+                class: false,
+                items: Some(ImportItems::Names(Vec::from([
+                    ImportName {
+                        name: "parse".to_string(),
+                        alias: None,
+                    },
+                    ImportName {
+                        name: "Token".to_string(),
+                        alias: Some("Tok".to_string()),
+                    },
+                ]))),
+            }),
+        },
+        StatementTestCase {
+            input: r#"import * from "lexer""#,
+            expected: Statement::Import(Import {
+                path: "lexer".to_string(),
+                alias: None,
+                class: false,
+                items: Some(ImportItems::All),
             }),
         },
     ];
@@ -541,6 +743,61 @@ fn test_import_statement() {
     }
 }
 
+#[test]
+fn test_export_statement() {
+    let input = "export const a = 1; export fn f() {} export class C() { static var n = 1 }";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    let program = p.parse_program();
+
+    check_parser_errors(p);
+
+    let Some(Node::Program { statements }) = program else {
+        panic!("expected a program");
+    };
+
+    let names = statements
+        .iter()
+        .map(|stmt| {
+            assert!(matches!(stmt, Statement::Export(_)), "{stmt:?}");
+            stmt.declared_names()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        Vec::from([
+            Vec::from(["a".to_string()]),
+            Vec::from(["f".to_string()]),
+            Vec::from(["C".to_string(), "C::n".to_string()]),
+        ]),
+        names
+    );
+
+    for (input, expected) in [
+        (
+            "export 1",
+            "expected declaration after `export`, got INT_LITERAL instead.",
+        ),
+        (
+            "fn f() { export var a = 1 }",
+            "`export` is only allowed at the top level of a module.",
+        ),
+        (
+            r#"import { a } "b""#,
+            "expected `from` after imported names, got STR_LITERAL instead.",
+        ),
+    ] {
+        let mut l = Lexer::new(input);
+        let mut p = Parser::new(&mut l);
+
+        p.parse_program();
+
+        assert_eq!(Some(&expected.to_string()), p.errors.first(), "{input}");
+    }
+}
+
 #[test]
 fn test_identifier_expression() {
     let input = "foo_bar123";
@@ -649,6 +906,49 @@ fn test_float_literal() {
     }
 }
 
+#[test]
+fn test_format_string_literal() {
+    let input = r#"f"{{x}} = {x + 1:>4}!""#;
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    let program = p.parse_program();
+
+    check_parser_errors(p);
+
+    if let Some(Node::Program { statements }) = program {
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            Statement::ExpressionStmt(ExpressionStmt {
+                returns: true,
+                expression: Expression::Interpolation(Interpolation {
+                    parts: Vec::from([
+                        InterpolationPart::Str("{x} = ".to_string()),
+                        InterpolationPart::Expr {
+                            value: Expression::Infix(Infix {
+                                left: Box::new(Expression::Identifier(Identifier {
+                                    value: "x".to_string()
+                                })),
+                                operator: Operator::Add,
+                                right: Box::new(Expression::Literal(Literal {
+                                    lit: Lit::Int { value: 1 }
+                                })),
+                            }),
+                            spec: Some(">4".to_string()),
+                        },
+                        InterpolationPart::Str("!".to_string()),
+                    ])
+                })
+            }),
+            statements[0]
+        );
+        assert_eq!(r#"f"{{x}} = {(x + 1):>4}!""#, statements[0].to_string());
+    } else {
+        panic!("p.parse_program() returned None")
+    }
+}
+
 #[test]
 fn test_parsing_prefix_expressions() {
     let test_cases = [
@@ -676,6 +976,18 @@ fn test_parsing_prefix_expressions() {
                 }),
             }),
         },
+        StatementTestCase {
+            input: "~15",
+            expected: Statement::ExpressionStmt(ExpressionStmt {
+                returns: true,
+                expression: Expression::Prefix(Prefix {
+                    operator: Operator::BitNot,
+                    right: Box::new(Expression::Literal(Literal {
+                        lit: Lit::Int { value: 15 },
+                    })),
+                }),
+            }),
+        },
     ];
 
     for test_case in test_cases {
@@ -711,6 +1023,12 @@ fn test_parsing_infix_expressions() {
             operator: Operator::Add,
             right_value: 4,
         },
+        InfixExpressionsTestCase {
+            input: "5 ?? 4;",
+            left_value: 5,
+            operator: Operator::NilCoalesce,
+            right_value: 4,
+        },
         InfixExpressionsTestCase {
             input: "5 - 4;",
             left_value: 5,
@@ -790,6 +1108,35 @@ fn test_parsing_infix_expressions() {
     }
 }
 
+#[test]
+fn test_operator_precedence() {
+    let test_cases = [
+        ("2 ** 3 ** 2", "(2 ** (3 ** 2))"),
+        ("-2 ** 2", "(-(2 ** 2))"),
+        ("a * b ** 2 // c", "((a * (b ** 2)) // c)"),
+        ("~a + b", "((~a) + b)"),
+        ("a + 1 in b && c", "(((a + 1) in b) && c)"),
+        ("a not in b || c", "((a not in b) || c)"),
+        ("a in 0..10..2", "(a in 0..10..2)"),
+        ("Shape::Empty == s", "(Shape::Empty == s)"),
+        (
+            "Shape::Circle(1).area() + 1",
+            "(Shape::Circle(1).area() + 1)",
+        ),
+    ];
+
+    for (input, expected) in test_cases {
+        let mut l = Lexer::new(input);
+        let mut p = Parser::new(&mut l);
+
+        let program = p.parse_program();
+
+        check_parser_errors(p);
+
+        assert_eq!(expected, program.unwrap().to_string());
+    }
+}
+
 #[test]
 fn test_if_expression() {
     let input = "if (x < y) { x }";
@@ -909,7 +1256,8 @@ fn test_lambda_expression() {
                             })),
                         })
                     })]),
-                    name: String::new()
+                    name: String::new(),
+                    generator: false,
                 })
             }),
             statements[0]
@@ -957,7 +1305,8 @@ fn test_lambda_parameter_parsing() {
                     expression: Expression::Lambda(Lambda {
                         parameters: test_case.expected_params,
                         body: Vec::new(),
-                        name: String::new()
+                        name: String::new(),
+                        generator: false,
                     })
                 }),
                 statements[0]
@@ -1049,7 +1398,8 @@ fn test_method_call_expression() {
                         right: Box::new(Expression::Literal(Literal {
                             lit: Lit::Int { value: 5 }
                         }))
-                    })]))
+                    })])),
+                    optional: false,
                 })
             }),
             statements[0]
@@ -1082,16 +1432,163 @@ fn test_method_ident_expression() {
                         }
                     })),
                     name: "length".to_string(),
-                    arguments: None
+                    arguments: None,
+                    optional: false,
+                })
+            }),
+            statements[0]
+        );
+    } else {
+        panic!("p.parse_program() returned None")
+    }
+}
+
+#[test]
+fn test_optional_chaining_expression() {
+    let input = "a?.b?[0] ?? c.d(1) || e";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    let program = p.parse_program();
+
+    check_parser_errors(p);
+
+    if let Some(Node::Program { statements }) = program {
+        assert_eq!(statements.len(), 1);
+        assert_eq!(
+            Statement::ExpressionStmt(ExpressionStmt {
+                returns: true,
+                expression: Expression::Infix(Infix {
+                    left: Box::new(Expression::Index(Index {
+                        left: Box::new(Expression::Method(Method {
+                            left: Box::new(Expression::Identifier(Identifier {
+                                value: "a".to_string()
+                            })),
+                            name: "b".to_string(),
+                            arguments: None,
+                            optional: true,
+                        })),
+                        index: Box::new(Expression::Literal(Literal {
+                            lit: Lit::Int { value: 0 }
+                        })),
+                        optional: true,
+                    })),
+                    operator: Operator::NilCoalesce,
+                    right: Box::new(Expression::Infix(Infix {
+                        left: Box::new(Expression::Method(Method {
+                            left: Box::new(Expression::Identifier(Identifier {
+                                value: "c".to_string()
+                            })),
+                            name: "d".to_string(),
+                            arguments: Some(Vec::from([Expression::Literal(Literal {
+                                lit: Lit::Int { value: 1 }
+                            })])),
+                            optional: false,
+                        })),
+                        operator: Operator::Or,
+                        right: Box::new(Expression::Identifier(Identifier {
+                            value: "e".to_string()
+                        })),
+                    })),
                 })
             }),
             statements[0]
         );
+        assert_eq!("((a?.b?[0]) ?? (c.d(1) || e))", statements[0].to_string());
     } else {
         panic!("p.parse_program() returned None")
     }
 }
 
+#[test]
+fn test_optional_chaining_cannot_be_assigned() {
+    let mut l = Lexer::new("a?.b = 1");
+    let mut p = Parser::new(&mut l);
+
+    p.parse_program();
+
+    assert_eq!(p.errors, ["a?.b cannot be assigned to."]);
+}
+
+#[test]
+fn test_error_spans() {
+    let input = "fn f() {\n  var = 1;\n}\nvar y = 0b12;";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    p.parse_program();
+
+    let found = p
+        .error_spans
+        .iter()
+        .map(|span| input.chars().skip(span.start).take(span.end - span.start))
+        .map(String::from_iter)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        p.errors,
+        [
+            "expected next token to be IDENT, got ASSIGN instead.",
+            "no prefix parse function for ASSIGN found",
+            "4:9: invalid digit `2` in binary literal"
+        ]
+    );
+    assert_eq!(found, ["var =", "= 1", "0b12"]);
+}
+
+#[test]
+fn test_enum_statement() {
+    let input = "enum Shape { Circle(r), Rect(w, h), Empty; area() { 0 } }";
+
+    let mut l = Lexer::new(input);
+    let mut p = Parser::new(&mut l);
+
+    let program = p.parse_program();
+
+    check_parser_errors(p);
+
+    let Some(Node::Program { statements }) = program else {
+        panic!("expected a program");
+    };
+
+    let [Statement::EnumDecl(decl)] = statements.as_slice() else {
+        panic!("expected an enum declaration. got: {statements:?}");
+    };
+
+    assert_eq!("Shape", decl.ident);
+    assert_eq!(
+        Vec::from([
+            EnumVariant {
+                name: "Circle".to_string(),
+                fields: Vec::from(["r".to_string()]),
+            },
+            EnumVariant {
+                name: "Rect".to_string(),
+                fields: Vec::from(["w".to_string(), "h".to_string()]),
+            },
+            EnumVariant {
+                name: "Empty".to_string(),
+                fields: Vec::new(),
+            },
+        ]),
+        decl.variants
+    );
+    assert_eq!(1, decl.methods.len());
+    assert_eq!("area", decl.methods[0].name);
+}
+
+#[test]
+fn test_enum_duplicate_variant() {
+    let mut l = Lexer::new("enum A { B, B }");
+    let mut p = Parser::new(&mut l);
+
+    p.parse_program();
+
+    assert_eq!(p.errors, ["duplicate variant `B` in enum \"A\""]);
+}
+
 #[test]
 fn test_constructor_expression() {
     let input = "var myClass = new MyClass(a, b, c);";
@@ -1404,7 +1901,8 @@ fn test_parsing_index_expressions() {
                         right: Box::new(Expression::Literal(Literal {
                             lit: Lit::Int { value: 3 }
                         }))
-                    }))
+                    })),
+                    optional: false,
                 })
             }),
             statements[0]
@@ -1727,6 +2225,7 @@ fn test_assign_expression() {
                 to: Assignable::Identifier(Identifier {
                     value: "a".to_string(),
                 }),
+                operator: None,
                 value: Box::new(Expression::Literal(Literal {
                     lit: Lit::Int { value: 45 },
                 })),
@@ -1741,7 +2240,9 @@ fn test_assign_expression() {
                     })),
                     name: "b".to_string(),
                     arguments: None,
+                    optional: false,
                 }),
+                operator: None,
                 value: Box::new(Expression::Literal(Literal {
                     lit: Lit::Int { value: 45 },
                 })),
@@ -1757,12 +2258,32 @@ fn test_assign_expression() {
                     index: Box::new(Expression::Identifier(Identifier {
                         value: "b".to_string(),
                     })),
+                    optional: false,
                 }),
+                operator: None,
                 value: Box::new(Expression::Literal(Literal {
                     lit: Lit::Int { value: 45 },
                 })),
             }),
         },
+        AssignExpressionTestCase {
+            input: "a[b] <<= 2",
+            expected: Expression::Assign(Assign {
+                to: Assignable::Index(Index {
+                    left: Box::new(Expression::Identifier(Identifier {
+                        value: "a".to_string(),
+                    })),
+                    index: Box::new(Expression::Identifier(Identifier {
+                        value: "b".to_string(),
+                    })),
+                    optional: false,
+                }),
+                operator: Some(Operator::Shl),
+                value: Box::new(Expression::Literal(Literal {
+                    lit: Lit::Int { value: 2 },
+                })),
+            }),
+        },
     ];
 
     for test_case in test_cases {
@@ -1808,7 +2329,8 @@ fn test_function_literal_with_name() {
                 value: Some(Expression::Lambda(Lambda {
                     parameters: Vec::new(),
                     body: Vec::new(),
-                    name: "myFunction".to_string()
+                    name: "myFunction".to_string(),
+                    generator: false,
                 }))
             }),
             statements[0]
@@ -0,0 +1,44 @@
+use std::io::{self, BufRead, ErrorKind, Write};
+
+use serde_json::Value;
+
+/// Reads a message framed the way the debug adapter and language server
+/// protocols frame them, returning `None` once the editor hangs up.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut len = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            len = value.trim().parse().ok();
+        }
+    }
+
+    let Some(len) = len else {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "expected a Content-Length header",
+        ));
+    };
+
+    let mut body = vec![0; len];
+    input.read_exact(&mut body)?;
+
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+pub fn write_message(output: &mut (impl Write + ?Sized), message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
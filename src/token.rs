@@ -2,15 +2,35 @@ use std::fmt::Debug;
 
 use strum::Display;
 
+/// Location of a token in the source, both as `char` offsets and as UTF-8 byte
+/// offsets. `end` and `byte_end` are exclusive.
+#[derive(Clone, Copy, Default, Hash, PartialEq, Eq, Debug)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+}
+
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub struct Token {
     pub tok_type: Kind,
     pub tok_lit: String,
+    pub span: Span,
 }
 
 impl Token {
     pub const fn new(tok_type: Kind, tok_lit: String) -> Self {
-        Self { tok_type, tok_lit }
+        Self {
+            tok_type,
+            tok_lit,
+            span: Span {
+                start: 0,
+                end: 0,
+                byte_start: 0,
+                byte_end: 0,
+            },
+        }
     }
 }
 
@@ -31,6 +51,7 @@ pub enum Kind {
     Ident,
     IntLiteral,
     StrLiteral,
+    FStrLiteral,
     CharLiteral,
     FloatLiteral,
 
@@ -42,6 +63,8 @@ pub enum Kind {
     Asterisk,
     Slash,
     Modulo,
+    Power,
+    FloorDiv,
 
     // Equality Operators
     Lt,
@@ -57,11 +80,31 @@ pub enum Kind {
     BitOr,
     Shr,
     Shl,
+    Tilde,
+
+    // Compound Assignment Operators
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
+    ModuloAssign,
+    CaretAssign,
+    BitAndAssign,
+    BitOrAssign,
+    ShrAssign,
+    ShlAssign,
+    PowerAssign,
+    FloorDivAssign,
 
     // Boolean Operators
     And,
     Or,
 
+    // Nil-safe Operators
+    NilCoalesce,
+    OptionalDot,
+    OptionalLBracket,
+
     // Rest Symbols
     Comma,
     Semicolon,
@@ -78,6 +121,7 @@ pub enum Kind {
 
     // Keywords
     Function,
+    Generator,
     Var,
     True,
     False,
@@ -89,13 +133,19 @@ pub enum Kind {
     While,
     For,
     In,
+    Not,
     Class,
+    Enum,
+    Interface,
+    Static,
     New,
     Import,
+    Export,
     As,
     Break,
     Continue,
     Delete,
+    Yield,
 }
 
 fn get_keywords(ident: &str) -> Option<Kind> {
@@ -112,13 +162,19 @@ fn get_keywords(ident: &str) -> Option<Kind> {
         "while" => Some(Kind::While),
         "for" => Some(Kind::For),
         "in" => Some(Kind::In),
+        "not" => Some(Kind::Not),
         "class" => Some(Kind::Class),
+        "enum" => Some(Kind::Enum),
+        "interface" => Some(Kind::Interface),
+        "static" => Some(Kind::Static),
         "new" => Some(Kind::New),
         "import" => Some(Kind::Import),
+        "export" => Some(Kind::Export),
         "as" => Some(Kind::As),
         "break" => Some(Kind::Break),
         "continue" => Some(Kind::Continue),
         "delete" => Some(Kind::Delete),
+        "yield" => Some(Kind::Yield),
         _ => None,
     }
 }
@@ -0,0 +1,304 @@
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde_json::{json, Value};
+
+const PROGRAM: &str = "var total = 0;
+fn add(x) {
+    var sum = total + x;
+    sum
+}
+fn outer(n) {
+    var y = add(n);
+    y * 2
+}
+total = outer(3);
+println(total);";
+
+/// An editor driving `panda dap` through a scripted session.
+struct Editor {
+    adapter: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    seq: u64,
+    // Events that came in while waiting for a response.
+    events: VecDeque<Value>,
+}
+
+impl Editor {
+    fn launch() -> Self {
+        let mut adapter = Command::new(env!("CARGO_BIN_EXE_panda"))
+            .arg("dap")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        Self {
+            input: adapter.stdin.take().unwrap(),
+            output: BufReader::new(adapter.stdout.take().unwrap()),
+            adapter,
+            seq: 0,
+            events: VecDeque::new(),
+        }
+    }
+
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.seq += 1;
+
+        let mut request = json!({ "seq": self.seq, "type": "request", "command": command });
+        request["arguments"] = arguments;
+
+        let body = request.to_string();
+
+        write!(self.input, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.input.flush().unwrap();
+
+        loop {
+            let message = self.read();
+
+            if message["type"] == "event" {
+                self.events.push_back(message);
+                continue;
+            }
+
+            assert_eq!(self.seq, message["request_seq"], "{message}");
+            assert_eq!(true, message["success"], "{message}");
+
+            return message["body"].clone();
+        }
+    }
+
+    fn event(&mut self, event: &str) -> Value {
+        loop {
+            let message = self.events.pop_front().unwrap_or_else(|| self.read());
+
+            if message["event"] == event {
+                return message["body"].clone();
+            }
+        }
+    }
+
+    /// Collects the output of the program until it exits, with its exit code.
+    fn output(&mut self) -> (String, Value) {
+        let mut output = String::new();
+
+        loop {
+            let message = self.events.pop_front().unwrap_or_else(|| self.read());
+
+            match message["event"].as_str() {
+                Some("output") => output.push_str(message["body"]["output"].as_str().unwrap()),
+                Some("exited") => return (output, message["body"]["exitCode"].clone()),
+                _ => {}
+            }
+        }
+    }
+
+    fn read(&mut self) -> Value {
+        let mut len = 0;
+
+        loop {
+            let mut header = String::new();
+            assert_ne!(0, self.output.read_line(&mut header).unwrap());
+
+            match header.trim().strip_prefix("Content-Length:") {
+                Some(value) => len = value.trim().parse().unwrap(),
+                None if header.trim().is_empty() => break,
+                None => {}
+            }
+        }
+
+        let mut body = vec![0; len];
+        self.output.read_exact(&mut body).unwrap();
+
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn stack(&mut self) -> Vec<(String, Value)> {
+        let trace = self.request("stackTrace", json!({ "threadId": 1 }));
+
+        trace["stackFrames"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|frame| {
+                (
+                    frame["name"].as_str().unwrap().to_string(),
+                    frame["line"].clone(),
+                )
+            })
+            .collect()
+    }
+
+    fn locals(&mut self) -> Vec<(String, Value)> {
+        let scopes = self.request("scopes", json!({ "frameId": 0 }));
+        let locals = &scopes["scopes"][0];
+        assert_eq!("Locals", locals["name"]);
+
+        let variables = self.request(
+            "variables",
+            json!({ "variablesReference": locals["variablesReference"] }),
+        );
+
+        variables["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|variable| variable["value"] != "nil")
+            .map(|variable| {
+                (
+                    variable["name"].as_str().unwrap().to_string(),
+                    variable["value"].clone(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn frame(name: &str, line: usize) -> (String, Value) {
+    (name.to_string(), json!(line))
+}
+
+fn variable(name: &str, value: &str) -> (String, Value) {
+    (name.to_string(), json!(value))
+}
+
+#[test]
+fn test_dap_session() {
+    for engine in ["vm", "eval"] {
+        let program = std::env::temp_dir().join(format!("panda_dap_{engine}.pnd"));
+        std::fs::write(&program, PROGRAM).unwrap();
+
+        let mut editor = Editor::launch();
+
+        let capabilities = editor.request("initialize", json!({ "adapterID": "panda" }));
+        assert_eq!(true, capabilities["supportsConfigurationDoneRequest"]);
+        editor.event("initialized");
+
+        editor.request(
+            "launch",
+            json!({ "program": program, "engine": engine, "stopOnEntry": true }),
+        );
+
+        let breakpoints = editor.request(
+            "setBreakpoints",
+            json!({ "source": { "path": program }, "breakpoints": [{ "line": 7 }] }),
+        );
+        assert_eq!(
+            json!([{ "verified": true, "line": 7 }]),
+            breakpoints["breakpoints"]
+        );
+
+        editor.request("configurationDone", json!({}));
+
+        assert_eq!("entry", editor.event("stopped")["reason"], "{engine}");
+        assert_eq!(vec![frame("main", 1)], editor.stack(), "{engine}");
+
+        let threads = editor.request("threads", json!({}));
+        assert_eq!(json!([{ "id": 1, "name": "main" }]), threads["threads"]);
+
+        editor.request("continue", json!({ "threadId": 1 }));
+        assert_eq!("breakpoint", editor.event("stopped")["reason"], "{engine}");
+        assert_eq!(
+            vec![frame("outer", 7), frame("main", 10)],
+            editor.stack(),
+            "{engine}"
+        );
+
+        editor.request("stepIn", json!({ "threadId": 1 }));
+        assert_eq!("step", editor.event("stopped")["reason"], "{engine}");
+        assert_eq!(
+            vec![frame("add", 3), frame("outer", 7), frame("main", 10)],
+            editor.stack(),
+            "{engine}"
+        );
+        assert_eq!(vec![variable("x", "3")], editor.locals(), "{engine}");
+
+        editor.request("next", json!({ "threadId": 1 }));
+        editor.event("stopped");
+        assert_eq!(frame("add", 4), editor.stack()[0], "{engine}");
+        assert!(editor.locals().contains(&variable("sum", "3")), "{engine}");
+
+        editor.request("stepOut", json!({ "threadId": 1 }));
+        editor.event("stopped");
+        assert_eq!(
+            vec![frame("outer", 8), frame("main", 10)],
+            editor.stack(),
+            "{engine}"
+        );
+
+        let result = editor.request("evaluate", json!({ "expression": "y * 10", "frameId": 0 }));
+        assert_eq!("30", result["result"], "{engine}");
+
+        editor.request("continue", json!({ "threadId": 1 }));
+        assert_eq!((String::from("6\n"), json!(0)), editor.output(), "{engine}");
+        editor.event("terminated");
+
+        editor.request("disconnect", json!({}));
+        assert!(editor.adapter.wait().unwrap().success());
+    }
+}
+
+#[test]
+fn test_dap_terminate() {
+    let program = std::env::temp_dir().join("panda_dap_terminate.pnd");
+    std::fs::write(&program, PROGRAM).unwrap();
+
+    let mut editor = Editor::launch();
+
+    editor.request("initialize", json!({ "adapterID": "panda" }));
+    editor.request("launch", json!({ "program": program, "stopOnEntry": true }));
+    editor.request("configurationDone", json!({}));
+    editor.event("stopped");
+
+    editor.request("terminate", json!({}));
+
+    let (output, code) = editor.output();
+    assert!(output.contains("interrupted"), "{output}");
+    assert_eq!(json!(1), code);
+    editor.event("terminated");
+
+    editor.request("disconnect", json!({}));
+    assert!(editor.adapter.wait().unwrap().success());
+}
+
+#[test]
+fn test_dap_breakpoints_per_source() {
+    let program = std::env::temp_dir().join("panda_dap_sources.pnd");
+    std::fs::write(&program, PROGRAM).unwrap();
+
+    let mut editor = Editor::launch();
+
+    editor.request("initialize", json!({ "adapterID": "panda" }));
+    editor.request("launch", json!({ "program": program }));
+    editor.request(
+        "setBreakpoints",
+        json!({ "source": { "path": program }, "breakpoints": [{ "line": 7 }] }),
+    );
+
+    // Setting another source's breakpoints leaves the program's alone.
+    let other = std::env::temp_dir().join("panda_dap_other.pnd");
+    let breakpoints = editor.request(
+        "setBreakpoints",
+        json!({ "source": { "path": other }, "breakpoints": [{ "line": 1 }] }),
+    );
+    assert_eq!(false, breakpoints["breakpoints"][0]["verified"]);
+    assert_eq!(
+        "breakpoints can only be set in the program being debugged",
+        breakpoints["breakpoints"][0]["message"]
+    );
+
+    editor.request("configurationDone", json!({}));
+    assert_eq!("breakpoint", editor.event("stopped")["reason"]);
+    assert_eq!(frame("outer", 7), editor.stack()[0]);
+
+    editor.request("terminate", json!({}));
+    editor.output();
+    editor.event("terminated");
+
+    editor.request("disconnect", json!({}));
+    assert!(editor.adapter.wait().unwrap().success());
+}
@@ -0,0 +1,225 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde_json::{json, Value};
+
+const URI: &str = "file:///tmp/panda_lsp.pnd";
+
+const PROGRAM: &str = "var total = 0;
+# Adds `x` to the total.
+fn add(x) {
+    total + x
+}
+var name = \"panda\";
+name.";
+
+/// An editor driving `panda lsp` through a scripted session.
+struct Editor {
+    server: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    id: u64,
+}
+
+impl Editor {
+    fn launch() -> Self {
+        let mut server = Command::new(env!("CARGO_BIN_EXE_panda"))
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        Self {
+            input: server.stdin.take().unwrap(),
+            output: BufReader::new(server.stdout.take().unwrap()),
+            server,
+            id: 0,
+        }
+    }
+
+    fn send(&mut self, message: &Value) {
+        let body = message.to_string();
+
+        write!(self.input, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.input.flush().unwrap();
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.id += 1;
+
+        let mut request = json!({ "jsonrpc": "2.0", "id": self.id, "method": method });
+        request["params"] = params;
+        self.send(&request);
+
+        loop {
+            let message = self.read();
+
+            if message["id"] == self.id {
+                return message["result"].clone();
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        let mut notification = json!({ "jsonrpc": "2.0", "method": method });
+        notification["params"] = params;
+        self.send(&notification);
+    }
+
+    fn diagnostics(&mut self) -> Vec<Value> {
+        loop {
+            let message = self.read();
+
+            if message["method"] == "textDocument/publishDiagnostics" {
+                assert_eq!(URI, message["params"]["uri"]);
+                return message["params"]["diagnostics"].as_array().unwrap().clone();
+            }
+        }
+    }
+
+    fn at(&mut self, method: &str, line: usize, character: usize) -> Value {
+        self.request(
+            method,
+            json!({
+                "textDocument": { "uri": URI },
+                "position": { "line": line, "character": character },
+                "context": { "includeDeclaration": true },
+            }),
+        )
+    }
+
+    fn read(&mut self) -> Value {
+        let mut len = 0;
+
+        loop {
+            let mut header = String::new();
+            assert_ne!(0, self.output.read_line(&mut header).unwrap());
+
+            match header.trim().strip_prefix("Content-Length:") {
+                Some(value) => len = value.trim().parse().unwrap(),
+                None if header.trim().is_empty() => break,
+                None => {}
+            }
+        }
+
+        let mut body = vec![0; len];
+        self.output.read_exact(&mut body).unwrap();
+
+        serde_json::from_slice(&body).unwrap()
+    }
+}
+
+fn range(line: usize, start: usize, end: usize) -> Value {
+    json!({
+        "start": { "line": line, "character": start },
+        "end": { "line": line, "character": end },
+    })
+}
+
+#[test]
+fn test_lsp_session() {
+    let mut editor = Editor::launch();
+
+    let capabilities = editor.request("initialize", json!({ "capabilities": {} }));
+    assert_eq!(true, capabilities["capabilities"]["hoverProvider"]);
+    editor.notify("initialized", json!({}));
+
+    editor.notify(
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": URI, "languageId": "panda", "version": 1, "text": PROGRAM } }),
+    );
+
+    let diagnostics = editor.diagnostics();
+    assert_eq!(1, diagnostics.len(), "{diagnostics:?}");
+    assert_eq!(range(6, 4, 5), diagnostics[0]["range"]);
+
+    let definition = editor.at("textDocument/definition", 3, 5);
+    assert_eq!(json!({ "uri": URI, "range": range(0, 4, 9) }), definition);
+
+    let references = editor.at("textDocument/references", 0, 6);
+    assert_eq!(
+        json!([
+            { "uri": URI, "range": range(0, 4, 9) },
+            { "uri": URI, "range": range(3, 4, 9) },
+        ]),
+        references
+    );
+
+    let hover = editor.at("textDocument/hover", 2, 4);
+    assert_eq!(
+        "```panda\nfn add(x)\n```\n\n`FUNCTION`\n\nAdds `x` to the total.",
+        hover["contents"]["value"]
+    );
+    assert_eq!(range(2, 3, 6), hover["range"]);
+
+    let completions = editor.at("textDocument/completion", 6, 5);
+    let labels = completions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert!(labels.contains(&"split"), "{labels:?}");
+
+    editor.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": "fn f() {\n    y\n}" }],
+        }),
+    );
+
+    let diagnostics = editor.diagnostics();
+    assert_eq!("undefined variable y", diagnostics[0]["message"]);
+    assert_eq!(range(1, 4, 5), diagnostics[0]["range"]);
+
+    let symbols = editor.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    assert_eq!("f", symbols[0]["name"]);
+    assert_eq!(12, symbols[0]["kind"]);
+
+    editor.request("shutdown", Value::Null);
+    editor.notify("exit", Value::Null);
+    assert!(editor.server.wait().unwrap().success());
+}
+
+#[test]
+fn test_lsp_incomplete_input() {
+    let mut editor = Editor::launch();
+
+    editor.request("initialize", json!({ "capabilities": {} }));
+    editor.notify("initialized", json!({}));
+
+    let cases = [
+        (
+            "println(f\"{",
+            "1:9: unterminated format string",
+            range(0, 8, 11),
+        ),
+        (
+            "var c = 'a",
+            "1:9: unterminated char literal",
+            range(0, 8, 10),
+        ),
+    ];
+
+    for (version, (text, message, range)) in cases.into_iter().enumerate() {
+        editor.notify(
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": URI, "languageId": "panda", "version": version, "text": text } }),
+        );
+
+        let diagnostics = editor.diagnostics();
+        assert_eq!(message, diagnostics[0]["message"], "{text}");
+        assert_eq!(range, diagnostics[0]["range"], "{text}");
+    }
+
+    editor.request("shutdown", Value::Null);
+    editor.notify("exit", Value::Null);
+    assert!(editor.server.wait().unwrap().success());
+}